@@ -0,0 +1,125 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+use taigi_translate_bot::direction::Direction;
+use taigi_translate_bot::links::{self, LinkStyle};
+use taigi_translate_bot::lookup::Lookup;
+use taigi_translate_bot::romanization::{self, Romanization};
+use taigi_translate_bot::source::{self, Source};
+use taigi_translate_bot::variant::Accent;
+
+// Pages as the sources serve them, trimmed to what the parsers read
+const TAIGITV: &str = include_str!("fixtures/taigitv.html");
+const SUTIAN: &str = include_str!("fixtures/sutian.html");
+const ITAIGI: &str = include_str!("fixtures/itaigi.json");
+const MOEDICT: &str = include_str!("fixtures/moedict.json");
+
+// Rows in the synthetic Sutian page, well past anything a real search returns
+const LARGE_ROWS: usize = 500;
+
+// A Sutian page with `rows` results in both its mobile and desktop tables
+fn large_sutian(rows: usize) -> String {
+    let mut mobile = String::new();
+    let mut desktop = String::new();
+    for idx in 1..=rows {
+        let reading = format!("khùn-{}", idx);
+        mobile.push_str(&format!(
+            "<table class=\"table d-md-none\"><tbody><tr><td>{idx}</td></tr>\
+             <tr><td><a href=\"/zh-hant/su/{idx}/\">睏{idx}</a></td></tr>\
+             <tr><td>{reading} 又唸作 khòng-{idx}</td></tr><tr><td>睡覺。</td></tr>\
+             </tbody></table>\n"
+        ));
+        desktop.push_str(&format!(
+            "<tr><td>{idx}</td><td><a href=\"/zh-hant/su/{idx}/\">睏{idx}</a></td>\
+             <td>{reading}</td><td>睡覺。</td></tr>\n"
+        ));
+    }
+    format!(
+        "<!DOCTYPE html><html><body>{}<table class=\"table d-none d-md-table\"><tbody>{}\
+         </tbody></table></body></html>",
+        mobile, desktop
+    )
+}
+
+// Every source's results from the fixtures, as a lookup would collect them
+fn merged(limit: usize) -> Lookup {
+    let mut lookup = Lookup::default();
+    let taigitv = source::parse_taigitv(TAIGITV, limit);
+    let sutian = source::parse_sutian(SUTIAN, None, Direction::MandarinToTaigi, limit);
+    let itaigi = source::parse_itaigi(ITAIGI, "電腦", limit).map(|(results, _)| results);
+    let moedict = source::parse_moedict(MOEDICT, "睏", limit).map(|(results, _)| results);
+    lookup.collect(Source::TaigiTv, (taigitv, None));
+    lookup.collect(Source::Sutian, (sutian, None));
+    lookup.collect(Source::ITaigi, (itaigi, None));
+    lookup.collect(Source::Moedict, (moedict, None));
+    lookup
+}
+
+fn parsing(c: &mut Criterion) {
+    c.bench_function("parse taigitv", |b| {
+        b.iter(|| source::parse_taigitv(black_box(TAIGITV), 3))
+    });
+    c.bench_function("parse sutian", |b| {
+        b.iter(|| {
+            let direction = Direction::MandarinToTaigi;
+            source::parse_sutian(black_box(SUTIAN), Some(Accent::Tsiang), direction, 3)
+        })
+    });
+    c.bench_function("parse itaigi", |b| {
+        b.iter(|| source::parse_itaigi(black_box(ITAIGI), "電腦", 3))
+    });
+    c.bench_function("parse moedict", |b| {
+        b.iter(|| source::parse_moedict(black_box(MOEDICT), "睏", 2))
+    });
+
+    let large = large_sutian(LARGE_ROWS);
+    c.bench_function("parse sutian, large", |b| {
+        b.iter(|| {
+            let direction = Direction::TaigiToMandarin;
+            source::parse_sutian(black_box(&large), None, direction, usize::MAX)
+        })
+    });
+}
+
+fn merging(c: &mut Criterion) {
+    c.bench_function("merge sources", |b| {
+        b.iter(|| {
+            let lookup = merged(black_box(3));
+            links::render(&lookup.results, LinkStyle::Rich)
+        })
+    });
+
+    // Every result of the large page, as with the ➕ shortcut's raised limits
+    let large = large_sutian(LARGE_ROWS);
+    let results =
+        source::parse_sutian(&large, None, Direction::MandarinToTaigi, usize::MAX).unwrap();
+    c.bench_function("merge sources, large", |b| {
+        b.iter(|| {
+            let mut lookup = merged(usize::MAX);
+            lookup.collect(Source::Sutian, (Ok(black_box(results.clone())), None));
+            links::render(&lookup.results, LinkStyle::Compact)
+        })
+    });
+}
+
+fn romanizing(c: &mut Criterion) {
+    let results = merged(usize::MAX).results;
+    c.bench_function("tailo to poj", |b| {
+        b.iter(|| romanization::to_poj(black_box("tshiú-thê tiān-náu khùn-tiong-tàu")))
+    });
+    c.bench_function("tailo to ipa", |b| {
+        b.iter(|| romanization::to_ipa(black_box("tshiú-thê tiān-náu khùn-tiong-tàu")))
+    });
+    c.bench_function("romanize results", |b| {
+        b.iter(|| {
+            results
+                .iter()
+                .map(|result| romanization::apply(black_box(result), Romanization::Poj, true))
+                .collect::<Vec<_>>()
+        })
+    });
+}
+
+criterion_group!(benches, parsing, merging, romanizing);
+criterion_main!(benches);
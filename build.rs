@@ -0,0 +1,18 @@
+// Rebuild when a migration is added or changed, since they're embedded
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+    #[cfg(feature = "grpc")]
+    grpc();
+}
+
+// Generate the gRPC service's code from its definition
+#[cfg(feature = "grpc")]
+fn grpc() {
+    println!("cargo:rerun-if-changed=proto");
+    let files =
+        protox::compile(["proto/taigi.proto"], ["proto"]).expect("invalid proto/taigi.proto");
+    tonic_build::configure()
+        .build_client(false)
+        .compile_fds(files)
+        .expect("cannot generate the gRPC code");
+}
@@ -0,0 +1,150 @@
+-- The schema as it was when migrations were introduced. Databases created
+-- before then already have these tables, hence IF NOT EXISTS.
+
+CREATE TABLE IF NOT EXISTS feedback (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    query TEXT NOT NULL,
+    source TEXT NOT NULL,
+    output TEXT NOT NULL,
+    reporter_id TEXT NOT NULL,
+    channel_id TEXT NOT NULL,
+    message_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS query_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    keyword TEXT NOT NULL,
+    variant TEXT,
+    results TEXT NOT NULL,
+    sources TEXT NOT NULL,
+    vote_targets TEXT NOT NULL,
+    complete INTEGER NOT NULL,
+    user_id TEXT NOT NULL,
+    channel_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS user_prefs (
+    user_id TEXT PRIMARY KEY,
+    romanization TEXT,
+    ipa INTEGER,
+    result_limit INTEGER,
+    locale TEXT,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    private INTEGER,
+    english INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS lookup_cache (
+    key TEXT PRIMARY KEY,
+    results TEXT NOT NULL,
+    sources TEXT NOT NULL,
+    vote_targets TEXT NOT NULL,
+    stored_at INTEGER NOT NULL
+);
+
+-- Short IDs handed out for cached lookups, see `entry_id`
+CREATE TABLE IF NOT EXISTS entries (
+    id TEXT PRIMARY KEY,
+    key TEXT NOT NULL UNIQUE,
+    keyword TEXT NOT NULL,
+    variant TEXT
+);
+
+CREATE TABLE IF NOT EXISTS blocklist (
+    kind TEXT NOT NULL,
+    id TEXT NOT NULL,
+    added_by TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (kind, id)
+);
+
+CREATE TABLE IF NOT EXISTS saved_words (
+    user_id TEXT NOT NULL,
+    keyword TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, keyword)
+);
+
+CREATE TABLE IF NOT EXISTS feed_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS guild_settings (
+    guild_id TEXT PRIMARY KEY,
+    lookup_channel TEXT,
+    locale TEXT,
+    sources TEXT,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    footnotes INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS tone_drills (
+    user_id TEXT NOT NULL,
+    tone INTEGER NOT NULL,
+    asked INTEGER NOT NULL DEFAULT 0,
+    correct INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, tone)
+);
+
+-- The latest message seen in each lookup channel
+CREATE TABLE IF NOT EXISTS channel_progress (
+    channel_id TEXT PRIMARY KEY,
+    message_id TEXT NOT NULL
+);
+
+-- Sentence breakdowns by normalized sentence and accent (`key`), one
+-- `word\thanji\tlomaji` line per word
+CREATE TABLE IF NOT EXISTS translation_memory (
+    key TEXT PRIMARY KEY,
+    sentence TEXT NOT NULL,
+    glosses TEXT NOT NULL,
+    edited_by TEXT,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS translation_memory_sentence ON translation_memory (sentence);
+
+-- Corrections suggested by users for a remembered translation, waiting
+-- for a moderator
+CREATE TABLE IF NOT EXISTS translation_corrections (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    memory_id INTEGER NOT NULL,
+    glosses TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    reviewed_by TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Which instance answers, when several share the database. A single row,
+-- held until `expires_at` (Unix seconds) unless renewed.
+CREATE TABLE IF NOT EXISTS leader_lease (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    instance TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    channel_id TEXT,
+    message_id TEXT,
+    status TEXT NOT NULL DEFAULT 'queued',
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS filtered_words (
+    guild_id TEXT NOT NULL,
+    word TEXT NOT NULL,
+    action TEXT NOT NULL,
+    added_by TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (guild_id, word)
+);
@@ -0,0 +1,8 @@
+-- The post made in the archive forum for each keyword, so it's only made once
+CREATE TABLE forum_posts (
+    forum_id TEXT NOT NULL,
+    keyword TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (forum_id, keyword)
+);
@@ -0,0 +1,2 @@
+-- The romanization a server's replies use for members who haven't picked one
+ALTER TABLE guild_settings ADD COLUMN romanization TEXT;
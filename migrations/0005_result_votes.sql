@@ -0,0 +1,11 @@
+-- 👍/👎 from the buttons under results, one per user and result of an entry.
+-- The result is kept as found, so votes follow it whatever position it is shown at.
+CREATE TABLE result_votes (
+    entry_id TEXT NOT NULL,
+    result TEXT NOT NULL,
+    source TEXT NOT NULL, -- its code, as in lookup_cache.sources
+    user_id TEXT NOT NULL,
+    good INTEGER NOT NULL,
+    voted_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (entry_id, result, user_id)
+);
@@ -0,0 +1,21 @@
+-- Servers that turned off announcements in /config
+ALTER TABLE guild_settings ADD COLUMN announcements_muted INTEGER;
+
+-- Release notes and news sent with /admin announce
+CREATE TABLE announcements (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    content TEXT NOT NULL,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- The servers an announcement goes to, picked when it is made so servers set up
+-- later don't get old news. `pending`, then `sending` while claimed by a job,
+-- and `sent`, `failed` or `skipped` (turned off since).
+CREATE TABLE announcement_deliveries (
+    announcement_id INTEGER NOT NULL,
+    guild_id TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (announcement_id, guild_id)
+);
@@ -0,0 +1,7 @@
+-- How far each user got in /tutorial, so it picks up where they left off
+CREATE TABLE tutorial_progress (
+    user_id TEXT PRIMARY KEY,
+    step INTEGER NOT NULL,
+    finished INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
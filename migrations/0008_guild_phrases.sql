@@ -0,0 +1,14 @@
+-- Phrases a server's moderators added to /phrasebook, shown after the bundled
+-- ones of the same situation
+CREATE TABLE guild_phrases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id TEXT NOT NULL,
+    situation TEXT NOT NULL,
+    hanji TEXT NOT NULL,
+    lomaji TEXT NOT NULL,
+    english TEXT NOT NULL,
+    added_by TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX guild_phrases_guild ON guild_phrases (guild_id, situation);
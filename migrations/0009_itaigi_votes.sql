@@ -0,0 +1,9 @@
+-- 👍/👎 sent to iTaigi from the buttons under its results. They all go out
+-- from the one ITAIGI_TOKEN account, so each user gets one vote per entry.
+CREATE TABLE itaigi_votes (
+    entry_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    good INTEGER NOT NULL,
+    voted_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (entry_id, user_id)
+);
@@ -0,0 +1,8 @@
+-- Where each lookup was asked and whether its answer was public, so digests
+-- only cover a server's own public lookups. Older rows count as private.
+ALTER TABLE query_history ADD COLUMN guild_id TEXT;
+ALTER TABLE query_history ADD COLUMN public INTEGER NOT NULL DEFAULT 0;
+CREATE INDEX query_history_guild ON query_history (guild_id, created_at);
+
+-- A server's own digest channel, picked with /config
+ALTER TABLE guild_settings ADD COLUMN digest_channel TEXT;
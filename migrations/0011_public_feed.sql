@@ -0,0 +1,3 @@
+-- The feed now only draws on lookups answered publicly in servers. Entries
+-- picked before could show DMs and private answers to anyone.
+DELETE FROM feed_entries;
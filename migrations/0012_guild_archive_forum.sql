@@ -0,0 +1,2 @@
+-- A server's own archive forum, picked with /config
+ALTER TABLE guild_settings ADD COLUMN archive_forum TEXT;
@@ -0,0 +1,3 @@
+-- Whether the server's answers add English meanings, for members who haven't
+-- picked in /prefs. NULL follows display.english.
+ALTER TABLE guild_settings ADD COLUMN english INTEGER;
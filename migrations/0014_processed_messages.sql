@@ -0,0 +1,10 @@
+-- Messages and passive 📖 reactions already answered, so ones delivered twice
+-- (replayed after a reconnect, fetched again as missed messages, or seen by
+-- an instance taking over) are only answered once. Reactions are keyed by
+-- the reacting user, messages by an empty one.
+CREATE TABLE processed_messages (
+    message_id TEXT NOT NULL,
+    user_id TEXT NOT NULL DEFAULT '',
+    processed_at INTEGER NOT NULL DEFAULT (CAST(strftime('%s', 'now') AS INTEGER)),
+    PRIMARY KEY (message_id, user_id)
+);
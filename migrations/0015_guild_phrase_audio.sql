@@ -0,0 +1,3 @@
+-- The word of a server's phrase whose Moedict recording its 🔊 button plays.
+-- NULL leaves the phrase without a button, as whole phrases have no recording.
+ALTER TABLE guild_phrases ADD COLUMN audio TEXT;
@@ -0,0 +1,4 @@
+-- Part of speech tags of a lookup's results, as a JSON object from result
+-- line to its tags, so lookups read back can still be filtered with --pos
+ALTER TABLE query_history ADD COLUMN pos TEXT NOT NULL DEFAULT '{}';
+ALTER TABLE lookup_cache ADD COLUMN pos TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,142 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serenity::builder::CreateMessage;
+use serenity::http::Http;
+use serenity::model::id::UserId;
+use tracing::{error, warn};
+
+use crate::config::SharedConfig;
+use crate::latency;
+use crate::source::Source;
+
+// Failure rates are taken over the requests of the last 15 minutes
+const WINDOW: Duration = Duration::from_secs(15 * 60);
+
+// How often the rates are checked against `alerts.failure_rate`
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+// Every source request in the window: when, to which source, and whether it failed
+static OUTCOMES: Mutex<VecDeque<(Instant, Source, bool)>> = Mutex::new(VecDeque::new());
+
+// Note the outcome of one source request
+pub fn record(source: Source, failed: bool) {
+    let now = Instant::now();
+    let mut outcomes = OUTCOMES.lock().unwrap();
+    while outcomes
+        .front()
+        .is_some_and(|(at, _, _)| now.duration_since(*at) > WINDOW)
+    {
+        outcomes.pop_front();
+    }
+    outcomes.push_back((now, source, failed));
+}
+
+// Requests and failures of each source in the window, in Source::ALL order
+fn rates() -> Vec<(Source, u32, u32)> {
+    let now = Instant::now();
+    let outcomes = OUTCOMES.lock().unwrap();
+    Source::ALL
+        .into_iter()
+        .map(|source| {
+            let recent = outcomes
+                .iter()
+                .filter(|(at, known, _)| *known == source && now.duration_since(*at) <= WINDOW);
+            let (requests, failures) =
+                recent.fold((0, 0), |(requests, failures), (_, _, failed)| {
+                    (requests + 1, failures + u32::from(*failed))
+                });
+            (source, requests, failures)
+        })
+        .collect()
+}
+
+// The owner of the bot application, or of the team owning it
+async fn owner(http: &Http) -> Option<UserId> {
+    match http.get_current_application_info().await {
+        Ok(info) => info
+            .team
+            .map(|team| team.owner_user_id)
+            .or(info.owner.map(|owner| owner.id)),
+        Err(why) => {
+            error!("Error fetching application info: {why:?}");
+            None
+        }
+    }
+}
+
+// Posted to the ops channel if there is one, otherwise sent to the owner by DM
+async fn send(http: &Http, config: &SharedConfig, content: String) {
+    let message = CreateMessage::new().content(content);
+    let sent = match config.get().ops_channel {
+        Some(channel) => channel.send_message(http, message).await.map(|_| ()),
+        None => match owner(http).await {
+            Some(owner) => owner.direct_message(http, message).await.map(|_| ()),
+            None => return,
+        },
+    };
+    if let Err(why) = sent {
+        error!("Error sending a source alert: {why:?}");
+    }
+}
+
+// Alert once when a source's failure rate over the window goes past
+// `alerts.failure_rate`, and again once it's back under it
+pub fn spawn(http: Arc<Http>, config: Arc<SharedConfig>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        // Sources currently alerted about, indexed like Source::ALL
+        let mut alerting = [false; Source::ALL.len()];
+        loop {
+            interval.tick().await;
+            let settings = config.get().alerts;
+            if settings.failure_rate == 0.0 {
+                alerting = [false; Source::ALL.len()];
+                continue;
+            }
+
+            let stats = latency::snapshot();
+            for (idx, (source, requests, failures)) in rates().into_iter().enumerate() {
+                let rate = if requests == 0 {
+                    0.0
+                } else {
+                    f64::from(failures) / f64::from(requests)
+                };
+                let failing = requests >= settings.min_requests && rate > settings.failure_rate;
+                if failing == alerting[idx] {
+                    continue;
+                }
+                alerting[idx] = failing;
+
+                let content = if failing {
+                    warn!(
+                        "{} failed {} of {} requests",
+                        source.name(),
+                        failures,
+                        requests
+                    );
+                    let last_error = stats[idx].1.last_error.as_ref();
+                    format!(
+                        "📉 {} failed {} of {} requests ({:.0}%) in the last {} minutes.{}",
+                        source.name(),
+                        failures,
+                        requests,
+                        rate * 100.0,
+                        WINDOW.as_secs() / 60,
+                        last_error.map_or(String::new(), |err| format!(" Latest error: {}", err))
+                    )
+                } else {
+                    format!(
+                        "📈 {} is back to {} failed of {} requests in the last {} minutes.",
+                        source.name(),
+                        failures,
+                        requests,
+                        WINDOW.as_secs() / 60
+                    )
+                };
+                send(&http, &config, content).await;
+            }
+        }
+    });
+}
@@ -0,0 +1,60 @@
+use unicode_width::UnicodeWidthStr;
+
+// Discord wraps code block lines on narrow screens, so rows are broken into
+// blocks of columns no wider than this
+const MAX_LINE_WIDTH: usize = 48;
+
+// Pad `text` with spaces to `width` terminal columns. Han characters count
+// as two columns and combining tone marks (as in Tâi-lô) as none.
+fn pad(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(text.width());
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+// Lay out rows of cells as aligned columns, e.g.
+//
+//   今天      想
+//   今仔日    想欲
+//   kin-á-ji̍t siūnn-beh
+//
+// Every row must have the same number of cells.
+pub fn columns(rows: &[Vec<&str>]) -> String {
+    let count = rows.first().map_or(0, |row| row.len());
+    let widths: Vec<usize> = (0..count)
+        .map(|col| rows.iter().map(|row| row[col].width()).max().unwrap_or(0))
+        .collect();
+
+    // Group columns into blocks that fit on one line
+    let mut blocks: Vec<(usize, usize)> = Vec::new();
+    let mut start = 0;
+    let mut line_width = 0;
+    for (col, width) in widths.iter().enumerate() {
+        if col > start && line_width + 1 + width > MAX_LINE_WIDTH {
+            blocks.push((start, col));
+            start = col;
+            line_width = 0;
+        }
+        line_width += if col == start { *width } else { 1 + width };
+    }
+    if start < count {
+        blocks.push((start, count));
+    }
+
+    blocks
+        .iter()
+        .map(|&(start, end)| {
+            rows.iter()
+                .map(|row| {
+                    (start..end)
+                        .map(|col| pad(row[col], widths[col]))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        .trim_end()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
@@ -0,0 +1,384 @@
+use std::sync::Arc;
+
+use serenity::builder::CreateActionRow;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use sqlx::SqlitePool;
+use tracing::{error, instrument};
+
+use crate::breakdown;
+use crate::config::Config;
+use crate::corpus;
+use crate::correction;
+use crate::db::{self, GuildSettings, HistoryEntry, UserPrefs};
+use crate::direction::Direction;
+use crate::etymology;
+use crate::filter::{self, Action};
+use crate::frequency;
+use crate::i18n::Locale;
+use crate::itaigi::{self, VoteTarget};
+use crate::links;
+use crate::lookup::LookupPool;
+use crate::memory;
+use crate::phrasebook;
+use crate::pinyin;
+use crate::pos;
+use crate::query::SearchOptions;
+use crate::rating;
+use crate::report;
+use crate::respond;
+use crate::romanization;
+use crate::shortcuts;
+use crate::spelling;
+use crate::source::Source;
+use crate::toneless;
+use crate::variant::Accent;
+
+// English meanings shown per answer
+const MAX_GLOSSES: usize = 3;
+
+// What the bot replies to one query, shared by channel messages and /taigi
+pub enum Answer {
+    // A sentence glossed word by word
+    Breakdown {
+        content: String,
+        // Its row in the translation memory, for suggesting corrections
+        memory_id: Option<i64>,
+    },
+    Found {
+        content: String,
+        // Source of each result line, for the report button
+        sources: Vec<Source>,
+        vote_targets: Vec<VoteTarget>,
+        // For rating the results, set for complete lookups like the permalink
+        entry_id: Option<String>,
+        // What was searched, for the shortcut buttons
+        keyword: String,
+        variant: Option<Accent>,
+    },
+    // Every source failed, or the server's filter refused the query
+    Failed(String),
+    NotFound {
+        content: String,
+        // Similar words to search instead
+        suggestions: Vec<&'static str>,
+        variant: Option<Accent>,
+    },
+}
+
+impl Answer {
+    // Vote and rating buttons, then the shortcuts and report button under the results.
+    // Reporting needs to fetch the message again later, which isn't possible
+    // for ephemeral replies.
+    pub fn components(&self, config: &Config, reportable: bool) -> Vec<CreateActionRow> {
+        if let Answer::Breakdown {
+            memory_id: Some(memory_id),
+            ..
+        } = self
+        {
+            let button = correction::button(*memory_id);
+            return vec![CreateActionRow::Buttons(vec![button])];
+        }
+        if let Answer::NotFound {
+            suggestions,
+            variant,
+            ..
+        } = self
+        {
+            return spelling::buttons(suggestions, *variant);
+        }
+        let Answer::Found {
+            sources,
+            vote_targets,
+            entry_id,
+            keyword,
+            variant,
+            ..
+        } = self
+        else {
+            return Vec::new();
+        };
+
+        // Voting goes through the configured iTaigi account
+        let mut components = Vec::new();
+        if config.itaigi_token.is_some() {
+            components.extend(itaigi::vote_buttons(vote_targets));
+        }
+        if let Some(entry_id) = entry_id {
+            components.push(rating::buttons(entry_id));
+        }
+        let mut buttons = shortcuts::buttons(keyword, *variant);
+        if reportable {
+            buttons.push(report::report_button(sources));
+        }
+        components.push(CreateActionRow::Buttons(buttons));
+        components
+    }
+}
+
+// Who asked, for history and their preferences
+pub struct Asker {
+    pub user_id: UserId,
+    pub channel_id: ChannelId,
+    pub guild_id: Option<GuildId>,
+    pub prefs: UserPrefs,
+    // Settings of the server asked in, if any
+    pub guild: GuildSettings,
+}
+
+// A user's preferences, falling back to the defaults if they can't be read.
+// Where the user hasn't picked a romanization, the server's applies, so
+// everything rendered with these prefs follows it.
+pub async fn load_prefs(db: &SqlitePool, user_id: UserId, guild_id: Option<GuildId>) -> UserPrefs {
+    let mut prefs = match db::get_prefs(db, &user_id.to_string()).await {
+        Ok(prefs) => prefs,
+        Err(why) => {
+            error!("Error loading user preferences: {why:?}");
+            UserPrefs::default()
+        }
+    };
+    if prefs.romanization.is_none() && guild_id.is_some() {
+        prefs.romanization = load_guild(db, guild_id).await.romanization;
+    }
+    prefs
+}
+
+// A server's settings, the defaults outside servers or if they can't be read
+pub async fn load_guild(db: &SqlitePool, guild_id: Option<GuildId>) -> GuildSettings {
+    let Some(guild_id) = guild_id else {
+        return GuildSettings::default();
+    };
+    match db::get_guild_settings(db, &guild_id.to_string()).await {
+        Ok(settings) => settings,
+        Err(why) => {
+            error!("Error loading server settings: {why:?}");
+            GuildSettings::default()
+        }
+    }
+}
+
+// English meanings of a keyword: the bundled phrasebook's and the corpus's
+// first, then Wiktionary's when `display.wiktionary` allows asking it
+async fn english_glosses(keyword: &str, config: &Config) -> Vec<String> {
+    let mut glosses: Vec<String> = Vec::new();
+    let local = phrasebook::english(keyword).into_iter();
+    for gloss in local.chain(corpus::english(keyword, MAX_GLOSSES)) {
+        if !glosses.iter().any(|known| known == gloss) {
+            glosses.push(gloss.to_string());
+        }
+    }
+    if !glosses.is_empty() || !config.wiktionary_glosses {
+        glosses.truncate(MAX_GLOSSES);
+        return glosses;
+    }
+    match etymology::english_glosses(keyword, MAX_GLOSSES).await {
+        Ok(glosses) => glosses,
+        Err(err) => {
+            error!("Error fetching English meanings: {}", err);
+            Vec::new()
+        }
+    }
+}
+
+#[instrument(name = "query", skip_all, fields(query = query))]
+pub async fn answer(
+    db: &SqlitePool,
+    pool: &LookupPool,
+    config: &Arc<Config>,
+    asker: &Asker,
+    query: &str,
+    options: &SearchOptions,
+) -> Answer {
+    let prefs = &asker.prefs;
+    let locale = prefs.locale.or(asker.guild.locale).unwrap_or(config.locale);
+
+    // Checked before any source is asked about the word
+    match filter::check(db, asker.guild_id, query).await {
+        Some(Action::Refuse) => return Answer::Failed(locale.filtered().to_string()),
+        Some(Action::Private) if !options.private => {
+            return Answer::Failed(locale.filtered_private().to_string());
+        }
+        _ => {}
+    }
+
+    // Pinyin/Zhuyin queries are converted to Han characters before searching
+    let converted = pinyin::to_hanzi(query);
+    let keyword = converted.unwrap_or(query);
+    let searched = locale.searched(keyword, converted.map(|_| query));
+    let direction = Direction::detect(keyword);
+    let searched = match direction {
+        Direction::MandarinToTaigi => searched,
+        Direction::TaigiToMandarin => locale.taigi_to_mandarin(&searched),
+    };
+    let searched = match options.pos {
+        Some(tag) => locale.part_of_speech(&searched, tag),
+        None => searched,
+    };
+
+    // Sentences are segmented and glossed word by word instead. The segmenter
+    // and glosses are Mandarin, so Taigi sentences are searched as they are.
+    if direction == Direction::MandarinToTaigi
+        && let Some(words) = breakdown::segment(keyword)
+    {
+        let (glosses, memory_id) =
+            memory::glosses(db, pool, config, keyword, words, options.variant).await;
+        let content = format!("{}\n{}", locale.breakdown(&searched), breakdown::render(&glosses));
+        return Answer::Breakdown {
+            content: respond::fit_message(&prefs.render(&content)),
+            memory_id,
+        };
+    }
+
+    let english = prefs.english.or(asker.guild.english).unwrap_or(config.english_glosses);
+    let (lookup, english_glosses) = tokio::join!(
+        async {
+            if options.more {
+                pool.lookup_more(keyword, options.variant, config).await
+            } else {
+                pool.lookup(keyword, options.variant, config).await
+            }
+        },
+        async {
+            if !english {
+                return Vec::new();
+            }
+            english_glosses(keyword, config).await
+        }
+    );
+
+    let history = HistoryEntry {
+        keyword,
+        variant: options.variant,
+        lookup: &lookup,
+        user_id: asker.user_id.to_string(),
+        channel_id: asker.channel_id.to_string(),
+        guild_id: asker.guild_id.map(|guild_id| guild_id.to_string()),
+        public: asker.guild_id.is_some() && !options.private,
+    };
+    if let Err(why) = db::record_query(db, &history).await {
+        error!("Error recording query history: {why:?}");
+    }
+
+    let slow: Vec<String> = lookup
+        .timings
+        .iter()
+        .filter(|(_, elapsed)| *elapsed >= config.slow_source)
+        .map(|(source, elapsed)| locale.slow_source(source.name(), elapsed.as_secs_f64()))
+        .collect();
+
+    // Servers may hide some of the sources searched and brand the rest
+    let emoji = config.emoji.for_guild(asker.guild_id);
+    let mut shown: Vec<(String, Source)> = lookup
+        .results
+        .iter()
+        .zip(&lookup.sources)
+        .filter(|(_, source)| asker.guild.shows(**source))
+        .filter(|(_, source)| options.sources.as_ref().is_none_or(|only| only.contains(source)))
+        .filter(|(result, _)| options.pos.is_none_or(|tag| pos::matches(&lookup, result, tag)))
+        .map(|(result, source)| (result.clone(), *source))
+        .collect();
+    // Results users voted up come first
+    rating::rank(&mut shown, &rating::scores(db, keyword, options.variant).await);
+    // Asked without tone marks, the results read like the query come first by tone
+    toneless::order(query, &mut shown);
+    let tones = toneless::summary(query, &shown);
+    let (mut all_results, mut result_sources): (Vec<String>, Vec<Source>) = shown
+        .into_iter()
+        .map(|(result, source)| {
+            let result = prefs.format_result(&frequency::mark(&result));
+            (emoji.apply(&result, source), source)
+        })
+        .unzip();
+    if let Some(limit) = prefs.result_limit.filter(|_| !options.more) {
+        all_results.truncate(limit as usize);
+        result_sources.truncate(limit as usize);
+    }
+    let all_results = links::render(&all_results, config.link_style);
+    let error_messages = lookup.errors;
+
+    if all_results.is_empty() {
+        return if error_messages.is_empty() {
+            not_found(locale.not_found(&searched), keyword, options.variant, locale)
+        } else {
+            Answer::Failed(locale.total_failure(&error_messages.join(", ")))
+        };
+    }
+
+    let response_message = format!(
+        "{}\n{}",
+        locale.found(all_results.len(), &searched),
+        all_results.join("\n")
+    );
+    // Kept apart from the result lines, which reports refer to by position
+    let mut notes = String::new();
+    if !english_glosses.is_empty() {
+        notes.push_str("\n\n");
+        notes.push_str(&locale.english(&english_glosses.join("; ")));
+    }
+    if let Some(tones) = tones {
+        notes.push_str("\n\n");
+        notes.push_str(&locale.tones(query, &tones));
+    }
+    if asker.guild.footnotes
+        && let Some(footnote) = romanization::footnote(&all_results, &emoji)
+    {
+        notes.push_str("\n\n");
+        notes.push_str(&footnote);
+    }
+    // Only complete lookups are kept, so only they get an ID to come back to
+    let mut entry_id = None;
+    if error_messages.is_empty() && !options.more {
+        match db::entry_id(db, keyword, options.variant).await {
+            Ok(id) => {
+                notes.push_str("\n\n");
+                notes.push_str(&locale.permalink(&id));
+                entry_id = Some(id);
+            }
+            Err(why) => error!("Error assigning entry ID: {why:?}"),
+        }
+    }
+
+    // Add error info if some sources failed
+    if !error_messages.is_empty() {
+        notes.push_str("\n\n");
+        notes.push_str(&locale.partial_failure(&error_messages.join(", ")));
+    }
+    if !slow.is_empty() {
+        notes.push('\n');
+        notes.push_str(&slow.join("\n"));
+    }
+
+    let response_message = prefs.render(&response_message);
+    let notes = prefs.render(&notes);
+    // Results past what fits give way to the notes, which are short
+    let room = respond::MAX_MESSAGE.saturating_sub(notes.chars().count());
+    let content = format!("{}{}", respond::fit(&response_message, room), notes);
+    let content = respond::fit_message(&content);
+
+    Answer::Found {
+        content,
+        sources: result_sources,
+        vote_targets: if asker.guild.shows(Source::ITaigi) {
+            lookup.vote_targets
+        } else {
+            Vec::new()
+        },
+        entry_id,
+        keyword: keyword.to_string(),
+        variant: options.variant,
+    }
+}
+
+// Nothing found, with "did you mean" suggestions if the query looks misspelt
+fn not_found(content: String, keyword: &str, variant: Option<Accent>, locale: Locale) -> Answer {
+    let suggestions = spelling::suggest(keyword);
+    let content = if suggestions.is_empty() {
+        content
+    } else {
+        format!("{}\n{}", content, locale.did_you_mean())
+    };
+    Answer::NotFound {
+        content,
+        suggestions,
+        variant,
+    }
+}
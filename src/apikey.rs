@@ -0,0 +1,90 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+// What a key may do over the HTTP API. Each scope includes the ones before
+// it: a stats key may also look words up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scope {
+    Lookup,
+    Stats,
+    Admin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 3] = [Scope::Lookup, Scope::Stats, Scope::Admin];
+
+    pub fn parse(code: &str) -> Option<Scope> {
+        Scope::ALL.into_iter().find(|scope| scope.code() == code)
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Scope::Lookup => "lookup",
+            Scope::Stats => "stats",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+// A key allowed to use the API, from the database or `widget.keys`
+#[derive(Clone, Debug)]
+pub struct ApiKey {
+    // None for keys from the config
+    pub id: Option<i64>,
+    pub label: String,
+    pub scope: Scope,
+    pub per_minute: u32,
+}
+
+impl ApiKey {
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scope >= scope
+    }
+}
+
+// A new key to hand out, e.g. `tk_9f86d081884c7d659a2feaa0c55ad015a3bf4f1b`
+pub fn generate() -> Result<String, String> {
+    let mut bytes = [0u8; 20];
+    getrandom::fill(&mut bytes).map_err(|why| format!("cannot generate a key: {}", why))?;
+    Ok(format!("tk_{}", hex(&bytes)))
+}
+
+// What the database keeps of a key
+pub fn hash(key: &str) -> String {
+    hex(&Sha256::digest(key.as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Requests made with each key in the last minute, apart from the limits on
+// Discord
+#[derive(Default)]
+pub struct Limiter {
+    keys: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl Limiter {
+    // Count a request made with the key hashed as `hash`, or say how long until
+    // it may make another when it has used up `per_minute`
+    pub fn check(&self, hash: &str, per_minute: u32) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut keys = self.keys.lock().unwrap();
+        let made = keys.entry(hash.to_string()).or_default();
+        while made.front().is_some_and(|at| now.duration_since(*at) >= WINDOW) {
+            made.pop_front();
+        }
+        if made.len() >= per_minute as usize {
+            let oldest = made.front().copied().unwrap_or(now);
+            return Err(WINDOW.saturating_sub(now.duration_since(oldest)));
+        }
+        made.push_back(now);
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::OnceLock;
+
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tracing::{error, warn};
+
+use crate::upstream;
+
+// Moedict serves the Ministry of Education recordings by heteronym ID
+const MOEDICT_AUDIO: &str = "https://1763c5ee9859e0316ed6-db85b55a6a3fbe33f09b9245992383bd.ssl.cf1.rackcdn.com";
+
+// The only recordings so far are Moedict's, read by the Ministry's speakers
+const MOEDICT_VOICE: &str = "moedict";
+
+// Opus for Discord's player, normalized so every word plays as loud
+const FFMPEG_ARGS: [&str; 14] = [
+    "-hide_banner",
+    "-loglevel",
+    "error",
+    "-i",
+    "pipe:0",
+    "-af",
+    "loudnorm=I=-16:TP=-1.5:LRA=11",
+    "-c:a",
+    "libopus",
+    "-b:a",
+    "48k",
+    "-f",
+    "ogg",
+    "pipe:1",
+];
+
+// A recording ready to be attached to a message
+pub struct Audio {
+    pub filename: String,
+    pub bytes: Vec<u8>,
+}
+
+// Where recordings are kept and what re-encodes them, both optional
+struct Pipeline {
+    cache_dir: Option<PathBuf>,
+    ffmpeg: Option<String>,
+}
+
+static PIPELINE: OnceLock<Pipeline> = OnceLock::new();
+
+// Set up the audio cache and re-encoding, once at startup
+pub fn init(cache_dir: Option<String>, ffmpeg: Option<String>) {
+    let pipeline = Pipeline {
+        cache_dir: cache_dir.map(PathBuf::from),
+        ffmpeg,
+    };
+    if PIPELINE.set(pipeline).is_err() {
+        warn!("The audio pipeline was already set up, keeping its settings");
+    }
+}
+
+// A word's recordings are kept in their own directory per voice, under the
+// name they were attached with: `<cache>/moedict/<word>/<file>.ogg`
+fn cache_path(voice: &str, keyword: &str) -> Option<PathBuf> {
+    let cache_dir = PIPELINE.get()?.cache_dir.as_ref()?;
+    Some(cache_dir.join(voice).join(dir_name(keyword)?))
+}
+
+// A keyword as a directory name. Percent-encoding leaves dots alone, so
+// they're escaped too, or `..` would lead out of the cache.
+fn dir_name(keyword: &str) -> Option<String> {
+    (!keyword.is_empty()).then(|| urlencoding::encode(keyword).replace('.', "%2E"))
+}
+
+async fn cached(dir: &Path) -> Option<Audio> {
+    let mut entries = tokio::fs::read_dir(dir).await.ok()?;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let filename = entry.file_name().to_string_lossy().to_string();
+        if !filename.ends_with(".ogg") {
+            continue;
+        }
+        match tokio::fs::read(entry.path()).await {
+            Ok(bytes) => return Some(Audio { filename, bytes }),
+            Err(why) => error!("Error reading cached audio {}: {why:?}", filename),
+        }
+    }
+    None
+}
+
+// Written next to its final name first, so a crash never leaves half a file
+// to be served
+async fn store(dir: &Path, audio: &Audio) -> std::io::Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+    let partial = dir.join(format!("{}.partial", audio.filename));
+    tokio::fs::write(&partial, &audio.bytes).await?;
+    tokio::fs::rename(&partial, dir.join(&audio.filename)).await
+}
+
+// Run a recording through ffmpeg, or why it couldn't be
+async fn reencode(ffmpeg: &str, bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut child = tokio::process::Command::new(ffmpeg)
+        .args(FFMPEG_ARGS)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|why| format!("cannot run {}: {}", ffmpeg, why))?;
+    // Fed from a task of its own, ffmpeg stops reading while its output is
+    // waiting to be read
+    let mut stdin = child.stdin.take().ok_or("no stdin")?;
+    let input = bytes.to_vec();
+    let feed = tokio::spawn(async move { stdin.write_all(&input).await });
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|why| format!("ffmpeg failed: {}", why))?;
+    let _ = feed.await;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(format!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+// The recording of the first heteronym of a word on Moedict, if it has one.
+// Served from the cache when it was fetched before.
+pub async fn moedict(keyword: &str) -> Result<Option<Audio>, String> {
+    let dir = cache_path(MOEDICT_VOICE, keyword);
+    if let Some(dir) = &dir
+        && let Some(audio) = cached(dir).await
+    {
+        return Ok(Some(audio));
+    }
+    let Some(mut audio) = fetch_moedict(keyword).await? else {
+        return Ok(None);
+    };
+
+    let mut finished = true;
+    if let Some(ffmpeg) = PIPELINE.get().and_then(|pipeline| pipeline.ffmpeg.as_deref()) {
+        match reencode(ffmpeg, &audio.bytes).await {
+            Ok(bytes) => audio.bytes = bytes,
+            // The original still plays, only louder or quieter. It isn't
+            // cached, so the next request tries again.
+            Err(err) => {
+                error!("Error re-encoding {}: {}", audio.filename, err);
+                finished = false;
+            }
+        }
+    }
+    if finished
+        && let Some(dir) = &dir
+        && let Err(why) = store(dir, &audio).await
+    {
+        error!("Error caching audio for {}: {why:?}", keyword);
+    }
+    Ok(Some(audio))
+}
+
+async fn fetch_moedict(keyword: &str) -> Result<Option<Audio>, String> {
+    let url = format!("https://www.moedict.tw/t/{}.json", urlencoding::encode(keyword));
+    let response = upstream::client()
+        .get(&url)
+        .send()
+        .await
+        .map_err(|_| "Error fetching from Moedict".to_string())?;
+    if !response.status().is_success() {
+        return Ok(None);
+    }
+    let response_text = upstream::text(response)
+        .await
+        .map_err(|_| "Error reading response from Moedict".to_string())?;
+    let json: Value = serde_json::from_str(&response_text)
+        .map_err(|_| "Error parsing JSON from Moedict".to_string())?;
+
+    let Some(id) = json
+        .get("h")
+        .and_then(|v| v.as_array())
+        .and_then(|heteronyms| heteronyms.iter().find_map(|h| h.get("_")?.as_str()))
+    else {
+        return Ok(None);
+    };
+
+    let response = upstream::client()
+        .get(format!("{}/{}.ogg", MOEDICT_AUDIO, id))
+        .send()
+        .await
+        .map_err(|_| "Error fetching audio from Moedict".to_string())?
+        .error_for_status()
+        .map_err(|_| "Moedict has no recording for this word".to_string())?;
+    let bytes = upstream::bytes(response)
+        .await
+        .map_err(|_| "Error reading audio from Moedict".to_string())?;
+
+    Ok(Some(Audio {
+        filename: format!("{}.ogg", id),
+        bytes,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dir_names_stay_in_the_cache() {
+        assert_eq!(dir_name("食飯").as_deref(), Some("%E9%A3%9F%E9%A3%AF"));
+        assert_eq!(dir_name("..").as_deref(), Some("%2E%2E"));
+        assert_eq!(dir_name("../etc").as_deref(), Some("%2E%2E%2Fetc"));
+        assert_eq!(dir_name("%2E").as_deref(), Some("%252E"));
+        assert_eq!(dir_name(""), None);
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use sqlx::SqlitePool;
+use sqlx::sqlite::SqliteConnectOptions;
+
+// Every SQLite database file starts with this
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+// A gzipped copy of the whole database and a file name for it. VACUUM INTO
+// writes a consistent snapshot even while lookups keep writing. The snapshot
+// goes in a temporary directory only the bot can read (0700 on Unix), removed
+// when done.
+pub async fn create(db: &SqlitePool) -> Result<(String, Vec<u8>), String> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let dir = tempfile::Builder::new()
+        .prefix("taigi-backup-")
+        .tempdir()
+        .map_err(|why| format!("cannot create a temporary directory: {}", why))?;
+    let snapshot = dir.path().join("snapshot.db");
+    sqlx::query("VACUUM INTO ?")
+        .bind(snapshot.to_string_lossy().into_owned())
+        .execute(db)
+        .await
+        .map_err(|why| format!("cannot snapshot the database: {}", why))?;
+
+    let compressed = fs::read(&snapshot).and_then(|bytes| {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&bytes)?;
+        encoder.finish()
+    });
+    // Removes the snapshot with the directory
+    drop(dir);
+    let compressed = compressed.map_err(|why| format!("cannot compress the snapshot: {}", why))?;
+    Ok((format!("taigi-backup-{}.db.gz", stamp), compressed))
+}
+
+// Replace the database at `database_url` with a backup from `create` (or an
+// uncompressed database file), before it's opened. The current file and its
+// journal are kept next to it with a `.before-restore` suffix.
+pub fn restore(archive: &Path, database_url: &str) -> Result<PathBuf, String> {
+    let bytes =
+        fs::read(archive).map_err(|why| format!("cannot read {}: {}", archive.display(), why))?;
+    let bytes = if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(bytes.as_slice())
+            .read_to_end(&mut decompressed)
+            .map_err(|why| format!("cannot decompress {}: {}", archive.display(), why))?;
+        decompressed
+    } else {
+        bytes
+    };
+    if !bytes.starts_with(SQLITE_HEADER) {
+        return Err(format!("{} is not a database backup", archive.display()));
+    }
+
+    let options = SqliteConnectOptions::from_str(database_url)
+        .map_err(|why| format!("cannot read database.url: {}", why))?;
+    let target = options.get_filename().to_path_buf();
+    for suffix in ["", "-wal", "-shm", "-journal"] {
+        let current = PathBuf::from(format!("{}{}", target.display(), suffix));
+        if current.exists() {
+            let kept = PathBuf::from(format!("{}.before-restore", current.display()));
+            fs::rename(&current, &kept)
+                .map_err(|why| format!("cannot move {} aside: {}", current.display(), why))?;
+        }
+    }
+    fs::write(&target, bytes)
+        .map_err(|why| format!("cannot write {}: {}", target.display(), why))?;
+    Ok(target)
+}
@@ -0,0 +1,83 @@
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+use serenity::model::id::{GuildId, UserId};
+use sqlx::SqlitePool;
+
+use crate::db;
+
+// Users and guilds the bot ignores entirely, managed with /admin and kept
+// in the database. Checked on every event, so it's held in memory.
+#[derive(Default)]
+pub struct Blocklist {
+    users: RwLock<HashSet<UserId>>,
+    guilds: RwLock<HashSet<GuildId>>,
+}
+
+// What a blocklist entry refers to, as stored in the `kind` column
+#[derive(Clone, Copy)]
+pub enum Kind {
+    User,
+    Guild,
+}
+
+impl Kind {
+    pub fn code(self) -> &'static str {
+        match self {
+            Kind::User => "user",
+            Kind::Guild => "guild",
+        }
+    }
+}
+
+impl Blocklist {
+    pub async fn load(pool: &SqlitePool) -> Result<Blocklist, sqlx::Error> {
+        let blocklist = Blocklist::default();
+        for (kind, id) in db::blocked(pool).await? {
+            match kind.as_str() {
+                "user" => blocklist.users.write().unwrap().insert(UserId::new(id)),
+                "guild" => blocklist.guilds.write().unwrap().insert(GuildId::new(id)),
+                _ => false,
+            };
+        }
+        Ok(blocklist)
+    }
+
+    pub fn is_blocked(&self, user_id: UserId, guild_id: Option<GuildId>) -> bool {
+        self.users.read().unwrap().contains(&user_id)
+            || guild_id.is_some_and(|guild_id| self.guilds.read().unwrap().contains(&guild_id))
+    }
+
+    // Returns false if the entry was already there
+    pub async fn add(
+        &self,
+        pool: &SqlitePool,
+        kind: Kind,
+        id: u64,
+        added_by: UserId,
+    ) -> Result<bool, sqlx::Error> {
+        db::block(pool, kind.code(), id, &added_by.to_string()).await?;
+        Ok(match kind {
+            Kind::User => self.users.write().unwrap().insert(UserId::new(id)),
+            Kind::Guild => self.guilds.write().unwrap().insert(GuildId::new(id)),
+        })
+    }
+
+    // Returns false if there was no such entry
+    pub async fn remove(&self, pool: &SqlitePool, kind: Kind, id: u64) -> Result<bool, sqlx::Error> {
+        db::unblock(pool, kind.code(), id).await?;
+        Ok(match kind {
+            Kind::User => self.users.write().unwrap().remove(&UserId::new(id)),
+            Kind::Guild => self.guilds.write().unwrap().remove(&GuildId::new(id)),
+        })
+    }
+
+    pub fn list(&self, kind: Kind) -> Vec<u64> {
+        let mut ids: Vec<u64> = match kind {
+            Kind::User => self.users.read().unwrap().iter().map(|id| id.get()).collect(),
+            Kind::Guild => self.guilds.read().unwrap().iter().map(|id| id.get()).collect(),
+        };
+        ids.sort_unstable();
+        ids
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::{Arc, OnceLock};
+
+use jieba_rs::Jieba;
+
+use crate::align;
+use crate::config::Config;
+use crate::lookup::LookupPool;
+use crate::variant::Accent;
+
+// Shorter queries are looked up as a single word
+const MIN_PHRASE_CHARS: usize = 5;
+// Keep the number of lookups (and the reply) bounded for pasted paragraphs
+const MAX_WORDS: usize = 12;
+
+// The Taigi equivalent found for one word of a segmented phrase
+pub struct Gloss {
+    pub word: String,
+    pub hanji: Option<String>,
+    pub lomaji: Option<String>,
+    // False if a source failed while the word was looked up, so a better
+    // gloss may exist
+    pub complete: bool,
+}
+
+fn jieba() -> &'static Jieba {
+    static JIEBA: OnceLock<Jieba> = OnceLock::new();
+    JIEBA.get_or_init(Jieba::new)
+}
+
+pub fn is_han(c: char) -> bool {
+    matches!(c, '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}')
+}
+
+pub fn has_han(word: &str) -> bool {
+    word.chars().any(is_han)
+}
+
+// Every token of a passage in order, punctuation and spaces included, so it
+// can be annotated in place
+pub fn tokens(text: &str) -> Vec<String> {
+    jieba()
+        .cut(text, true)
+        .into_iter()
+        .map(|token| token.word.to_string())
+        .collect()
+}
+
+// Split a Mandarin sentence into words. Returns None for queries that should
+// be searched as-is: short ones, ones with Latin text, or single words.
+pub fn segment(query: &str) -> Option<Vec<String>> {
+    if query.chars().filter(|&c| is_han(c)).count() < MIN_PHRASE_CHARS
+        || query.chars().any(|c| c.is_ascii_alphanumeric())
+    {
+        return None;
+    }
+
+    let mut words: Vec<String> = Vec::new();
+    for token in jieba().cut(query, true) {
+        // Punctuation and spaces come out as tokens of their own
+        if token.word.chars().any(is_han) {
+            words.push(token.word.to_string());
+        }
+    }
+
+    if words.len() < 2 {
+        return None;
+    }
+    words.truncate(MAX_WORDS);
+    Some(words)
+}
+
+// Pull the headword and its bracketed pronunciation out of a formatted
+// result, e.g. `📚 食飯 [tsia̍h-pn̄g] - …` or `🏷️ 吃飯 → 食飯 [tsia̍h-pn̄g] …`
+pub fn headword(result: &str) -> Option<(&str, Option<&str>)> {
+    // Drop the leading emoji and the trailing ` - [Source](url)` link
+    let (_, rest) = result.split_once(' ')?;
+    let entry = rest.split(" - [").next()?;
+
+    let (head, lomaji) = match entry.split_once(" [") {
+        Some((head, rest)) => (head, rest.split_once(']').map(|(lomaji, _)| lomaji)),
+        None => (entry.split(' ').next()?, None),
+    };
+    let hanji = match head.split_once(" → ") {
+        Some((_, taigi)) => taigi,
+        None => head,
+    };
+    let lomaji = lomaji.filter(|lomaji| !lomaji.is_empty() && *lomaji != "N/A");
+
+    if hanji.is_empty() || hanji == "N/A" {
+        None
+    } else {
+        Some((hanji, lomaji))
+    }
+}
+
+// The definition part of a Moedict result:
+// `📖 term [pron] (pos) definition - [萌典](…)`
+pub fn definition(result: &str) -> Option<&str> {
+    let (_, entry) = result.split_once(' ')?;
+    let entry = entry.split(" - [").next()?;
+    let rest = match entry.split_once("] ") {
+        Some((_, rest)) => rest,
+        None => entry.split_once(' ')?.1,
+    };
+    let rest = match rest.strip_prefix('(') {
+        Some(rest) => rest.split_once(") ")?.1,
+        None => rest,
+    };
+    Some(rest.trim()).filter(|rest| !rest.is_empty())
+}
+
+// Look every distinct word up through the pool and keep the first usable
+// result as its gloss
+pub async fn gloss(
+    words: Vec<String>,
+    variant: Option<Accent>,
+    pool: &LookupPool,
+    config: &Arc<Config>,
+) -> Vec<Gloss> {
+    let mut distinct: Vec<String> = Vec::new();
+    for word in &words {
+        if !distinct.contains(word) {
+            distinct.push(word.clone());
+        }
+    }
+    let lookups = pool.lookup_all(&distinct, variant, config).await;
+
+    words
+        .into_iter()
+        .map(|word| {
+            let lookup = distinct
+                .iter()
+                .position(|distinct| *distinct == word)
+                .map(|idx| &lookups[idx]);
+            let found = lookup
+                .and_then(|lookup| lookup.results.iter().find_map(|result| headword(result)));
+            Gloss {
+                word,
+                hanji: found.map(|(hanji, _)| hanji.to_string()),
+                lomaji: found.and_then(|(_, lomaji)| lomaji).map(str::to_string),
+                complete: lookup.is_some_and(|lookup| lookup.is_complete()),
+            }
+        })
+        .collect()
+}
+
+// Three aligned lines in a code block: the Mandarin words, their Taigi
+// Hanji and their Tâi-lô, with `？` where no gloss was found
+pub fn render(glosses: &[Gloss]) -> String {
+    let rows: Vec<Vec<&str>> = vec![
+        glosses.iter().map(|gloss| gloss.word.as_str()).collect(),
+        glosses
+            .iter()
+            .map(|gloss| gloss.hanji.as_deref().unwrap_or("？"))
+            .collect(),
+        glosses
+            .iter()
+            .map(|gloss| gloss.lomaji.as_deref().unwrap_or("?"))
+            .collect(),
+    ];
+    format!("```\n{}\n```", align::columns(&rows))
+}
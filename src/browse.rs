@@ -0,0 +1,203 @@
+use std::sync::OnceLock;
+
+use serenity::builder::{CreateActionRow, CreateButton};
+use serenity::model::application::{ButtonStyle, ComponentInteraction};
+use sqlx::SqlitePool;
+
+use crate::context::Context;
+use crate::corpus;
+use crate::respond::{self, Mode, Reply};
+
+const BUNDLED: &str = include_str!("../data/categories.txt");
+
+const PREFIX: &str = "browse:";
+
+const PAGE_SIZE: usize = 15;
+
+// Categories named in the overview, which a corpus could have hundreds of
+const MAX_LISTED: usize = 40;
+
+pub struct Entry {
+    pub hanji: String,
+    pub lomaji: String,
+    pub mandarin: String,
+}
+
+// A theme of the vocabulary, like food or family
+pub struct Category {
+    // Short English name to ask for it by, and its Hanji name
+    pub code: String,
+    pub name: String,
+    pub entries: Vec<Entry>,
+}
+
+impl Category {
+    fn is(&self, wanted: &str) -> bool {
+        self.code.eq_ignore_ascii_case(wanted) || self.name == wanted
+    }
+
+    fn add(&mut self, entry: Entry) {
+        if !self.entries.iter().any(|known| known.hanji == entry.hanji) {
+            self.entries.push(entry);
+        }
+    }
+
+    // `食物 (food)`, or just the name of a category from the corpus
+    fn title(&self) -> String {
+        if self.code == self.name {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, self.code)
+        }
+    }
+
+    fn pages(&self) -> usize {
+        self.entries.len().div_ceil(PAGE_SIZE).max(1)
+    }
+}
+
+// The bundled categories, then corpus rows with a category column added to
+// the one of the same code or name, or to a category of their own
+pub fn categories() -> &'static [Category] {
+    static CATEGORIES: OnceLock<Vec<Category>> = OnceLock::new();
+    CATEGORIES.get_or_init(|| {
+        let mut categories: Vec<Category> = Vec::new();
+        for line in BUNDLED.lines().filter(|line| !line.starts_with('#')) {
+            if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                let (code, name) = header.split_once(' ').unwrap_or((header, header));
+                categories.push(Category {
+                    code: code.to_string(),
+                    name: name.to_string(),
+                    entries: Vec::new(),
+                });
+                continue;
+            }
+            let mut fields = line.split('\t');
+            let (Some(hanji), Some(lomaji), Some(category)) =
+                (fields.next(), fields.next(), categories.last_mut())
+            else {
+                continue;
+            };
+            category.add(Entry {
+                hanji: hanji.to_string(),
+                lomaji: lomaji.to_string(),
+                mandarin: fields.next().unwrap_or_default().to_string(),
+            });
+        }
+
+        let themed = corpus::sentences().iter().filter(|sentence| !sentence.category.is_empty());
+        for sentence in themed {
+            for wanted in sentence.category.split([';', ',', '、', '/']).map(str::trim) {
+                if wanted.is_empty() {
+                    continue;
+                }
+                let idx = match categories.iter().position(|category| category.is(wanted)) {
+                    Some(idx) => idx,
+                    None => {
+                        categories.push(Category {
+                            code: wanted.to_string(),
+                            name: wanted.to_string(),
+                            entries: Vec::new(),
+                        });
+                        categories.len() - 1
+                    }
+                };
+                categories[idx].add(Entry {
+                    hanji: sentence.hanji.clone(),
+                    lomaji: sentence.lomaji.clone(),
+                    mandarin: sentence.mandarin.clone(),
+                });
+            }
+        }
+        categories
+    })
+}
+
+// Every category with its size, for /browse without a known category
+pub fn overview() -> String {
+    let mut lines: Vec<String> = categories()
+        .iter()
+        .take(MAX_LISTED)
+        .map(|category| {
+            let label = if category.code == category.name {
+                format!("`{}`", category.code)
+            } else {
+                format!("`{}` {}", category.code, category.name)
+            };
+            format!("{} ({} words)", label, category.entries.len())
+        })
+        .collect();
+    if categories().len() > MAX_LISTED {
+        lines.push(format!("… and {} more", categories().len() - MAX_LISTED));
+    }
+    format!("🗂️ Categories to browse, e.g. `/browse category:food`:\n{}", lines.join("\n"))
+}
+
+// The first page of a category, None if there is no such category
+pub fn start(wanted: &str) -> Option<Reply> {
+    let wanted = wanted.trim();
+    let idx = categories().iter().position(|category| category.is(wanted))?;
+    Some(page(idx, 0))
+}
+
+// One page of a category's words with buttons to the others. Custom IDs are
+// `browse:<category>:<page>`, the category by position so any name fits.
+fn page(idx: usize, page: usize) -> Reply {
+    let category = &categories()[idx];
+    let pages = category.pages();
+    let page = page.min(pages - 1);
+    let lines: Vec<String> = category
+        .entries
+        .iter()
+        .skip(page * PAGE_SIZE)
+        .take(PAGE_SIZE)
+        .map(|entry| {
+            if entry.mandarin.is_empty() {
+                format!("{} {}", entry.hanji, entry.lomaji)
+            } else {
+                format!("{} {} — {}", entry.hanji, entry.lomaji, entry.mandarin)
+            }
+        })
+        .collect();
+    let content = format!(
+        "🗂️ **{}** ({}/{})\n{}",
+        category.title(),
+        page + 1,
+        pages,
+        lines.join("\n")
+    );
+    let buttons = vec![
+        CreateButton::new(format!("{}{}:{}", PREFIX, idx, page.saturating_sub(1)))
+            .style(ButtonStyle::Secondary)
+            .label("◀ Back")
+            .disabled(page == 0),
+        CreateButton::new(format!("{}{}:{}", PREFIX, idx, page + 1))
+            .style(ButtonStyle::Secondary)
+            .label("Next ▶")
+            .disabled(page + 1 >= pages),
+    ];
+    Reply {
+        content,
+        components: Some(vec![CreateActionRow::Buttons(buttons)]),
+        files: Vec::new(),
+    }
+}
+
+// Handle the page buttons. Returns false for other components.
+pub async fn handle(ctx: &Context, db: &SqlitePool, component: &ComponentInteraction) -> bool {
+    let Some(rest) = component.data.custom_id.strip_prefix(PREFIX) else {
+        return false;
+    };
+    let reply = async {
+        let target = rest.split_once(':').and_then(|(idx, page)| {
+            let idx = idx.parse::<usize>().ok().filter(|idx| *idx < categories().len())?;
+            Some((idx, page.parse::<usize>().ok()?))
+        });
+        let Some((idx, number)) = target else {
+            return Reply::from("This list is no longer available.".to_string());
+        };
+        page(idx, number)
+    };
+    respond::component(ctx, db, component, Mode::Update, reply).await;
+    true
+}
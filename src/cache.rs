@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::db;
+use crate::lookup::Lookup;
+
+struct CacheEntry {
+    lookup: Lookup,
+    stored_at: Instant,
+}
+
+// In-memory cache of complete lookups, keyed by `lookup::cache_key`,
+// optionally written through to the database so it survives restarts
+pub struct ResultCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    ttl: Duration,
+    store: Option<SqlitePool>,
+}
+
+impl ResultCache {
+    pub fn new(ttl: Duration) -> Self {
+        ResultCache {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            store: None,
+        }
+    }
+
+    pub fn persistent(mut self, pool: SqlitePool) -> Self {
+        self.store = Some(pool);
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<Lookup> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => Some(entry.lookup.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+        if let Some(pool) = self.store.clone() {
+            tokio::spawn(async move {
+                if let Err(why) = db::clear_cached(&pool).await {
+                    error!("Error clearing the stored cache: {why:?}");
+                }
+            });
+        }
+    }
+
+    pub fn insert(&self, key: String, lookup: Lookup) {
+        // Written in the background so replies don't wait on the database
+        if let Some(pool) = self.store.clone() {
+            let (key, lookup) = (key.clone(), lookup.clone());
+            tokio::spawn(async move {
+                if let Err(why) = db::save_cached(&pool, &key, &lookup).await {
+                    error!("Error storing cached lookup: {why:?}");
+                }
+            });
+        }
+        self.insert_aged(key, lookup, Duration::ZERO);
+    }
+
+    fn insert_aged(&self, key: String, lookup: Lookup, age: Duration) {
+        let stored_at = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key, CacheEntry { lookup, stored_at });
+    }
+
+    // Reload the lookups stored before a restart that haven't expired yet
+    pub async fn restore(&self) -> Result<usize, sqlx::Error> {
+        let Some(pool) = &self.store else {
+            return Ok(0);
+        };
+        let stored = db::load_cached(pool, self.ttl).await?;
+        let count = stored.len();
+        for (key, lookup, age) in stored {
+            self.insert_aged(key, lookup, age);
+        }
+        Ok(count)
+    }
+
+    // Pre-fill the cache with the stored results of the most popular
+    // keywords, so the first queries after a restart don't hit the network.
+    // Keywords already restored keep their (newer) cached lookup.
+    pub async fn warm_up(&self, pool: &SqlitePool, limit: u32) -> Result<usize, sqlx::Error> {
+        let queries = db::top_queries(pool, limit, self.ttl).await?;
+        let mut count = 0;
+        for (key, lookup, age) in queries {
+            if !self.entries.lock().unwrap().contains_key(&key) {
+                self.insert_aged(key, lookup, age);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    // Drop expired lookups from memory and the database
+    pub async fn compact(&self) -> Result<usize, sqlx::Error> {
+        let mut removed = {
+            let mut entries = self.entries.lock().unwrap();
+            let before = entries.len();
+            entries.retain(|_, entry| entry.stored_at.elapsed() < self.ttl);
+            before - entries.len()
+        };
+        if let Some(pool) = &self.store {
+            // The database holds every cached lookup, so its count covers memory too
+            removed = db::compact_cached(pool, self.ttl).await? as usize;
+        }
+        Ok(removed)
+    }
+}
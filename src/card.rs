@@ -0,0 +1,194 @@
+use std::io::Cursor;
+
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont, point};
+use image::{ImageFormat, Rgba, RgbaImage};
+use qrcode::{Color, QrCode};
+
+use crate::keyboard::Sheet;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 420;
+const MARGIN: f32 = 40.0;
+// Side of the QR code in the bottom right corner
+const QR_SIZE: u32 = 180;
+
+const BACKGROUND: Rgba<u8> = Rgba([250, 247, 240, 255]);
+const INK: Rgba<u8> = Rgba([40, 40, 40, 255]);
+const ACCENT: Rgba<u8> = Rgba([176, 48, 48, 255]);
+const MUTED: Rgba<u8> = Rgba([110, 110, 110, 255]);
+
+// What goes on a share card
+pub struct Card {
+    // The word as it was searched
+    pub word: String,
+    pub hanji: String,
+    pub lomaji: Option<String>,
+    pub gloss: Option<String>,
+    // Encoded as a QR code
+    pub url: Option<String>,
+}
+
+// Draw one line of text with its baseline at `y`, cut to fit `max_width`
+fn draw_text(
+    image: &mut RgbaImage,
+    font: &FontVec,
+    size: f32,
+    (x, y): (f32, f32),
+    max_width: f32,
+    color: Rgba<u8>,
+    text: &str,
+) {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut caret = x;
+    for c in text.chars() {
+        let glyph_id = scaled.glyph_id(c);
+        let advance = scaled.h_advance(glyph_id);
+        if caret + advance > x + max_width {
+            break;
+        }
+        let glyph = glyph_id.with_scale_and_position(PxScale::from(size), point(caret, y));
+        if let Some(outline) = font.outline_glyph(glyph) {
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i32 + gx as i32;
+                let py = bounds.min.y as i32 + gy as i32;
+                if px < 0 || py < 0 || px >= image.width() as i32 || py >= image.height() as i32
+                {
+                    return;
+                }
+                let pixel = image.get_pixel_mut(px as u32, py as u32);
+                for channel in 0..3 {
+                    let blended = f32::from(pixel[channel]) * (1.0 - coverage)
+                        + f32::from(color[channel]) * coverage;
+                    pixel[channel] = blended.round() as u8;
+                }
+            });
+        }
+        caret += advance;
+    }
+}
+
+// Split `text` into lines no wider than `max_width`, at spaces
+fn wrap(font: &FontVec, size: f32, text: &str, max_width: f32) -> Vec<String> {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let width = |text: &str| -> f32 {
+        text.chars().map(|c| scaled.h_advance(scaled.glyph_id(c))).sum()
+    };
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if width(&candidate) > max_width && !line.is_empty() {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+fn draw_qr(image: &mut RgbaImage, url: &str) -> Result<(), String> {
+    let code = QrCode::new(url.as_bytes()).map_err(|_| "The link is too long for a QR code")?;
+    let modules = code.width() as u32;
+    // Keep a quiet zone of two modules around the code
+    let module = (QR_SIZE / (modules + 4)).max(1);
+    let left = WIDTH - MARGIN as u32 - module * (modules + 4);
+    let top = HEIGHT - MARGIN as u32 - module * (modules + 4);
+
+    for y in 0..module * (modules + 4) {
+        for x in 0..module * (modules + 4) {
+            image.put_pixel(left + x, top + y, Rgba([255, 255, 255, 255]));
+        }
+    }
+    for (idx, color) in code.to_colors().iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let column = idx as u32 % modules + 2;
+        let row = idx as u32 / modules + 2;
+        for y in 0..module {
+            for x in 0..module {
+                image.put_pixel(left + column * module + x, top + row * module + y, INK);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Render a card as PNG. The font needs CJK glyphs for the Hanji to show.
+pub fn render(card: &Card, font_data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let font = FontVec::try_from_vec(font_data).map_err(|_| "The card font can't be read")?;
+    let mut image = RgbaImage::from_pixel(WIDTH, HEIGHT, BACKGROUND);
+
+    let text_width = WIDTH as f32 - 2.0 * MARGIN - QR_SIZE as f32;
+    draw_text(&mut image, &font, 96.0, (MARGIN, 150.0), text_width, INK, &card.hanji);
+    if let Some(lomaji) = &card.lomaji {
+        draw_text(&mut image, &font, 44.0, (MARGIN, 215.0), text_width, ACCENT, lomaji);
+    }
+    if card.word != card.hanji {
+        let word = format!("華語：{}", card.word);
+        draw_text(&mut image, &font, 30.0, (MARGIN, 280.0), text_width, MUTED, &word);
+    }
+    if let Some(gloss) = &card.gloss {
+        draw_text(&mut image, &font, 26.0, (MARGIN, 330.0), text_width, INK, gloss);
+    }
+    let footer = (MARGIN, HEIGHT as f32 - MARGIN);
+    draw_text(&mut image, &font, 20.0, footer, text_width, MUTED, "taigi-translate-bot");
+    if let Some(url) = &card.url {
+        draw_qr(&mut image, url)?;
+    }
+
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|_| "Error encoding the card".to_string())?;
+    Ok(png.into_inner())
+}
+
+// A4 at 150 dpi, so the sheet prints at its natural size
+const SHEET_WIDTH: u32 = 1240;
+const SHEET_MARGIN: f32 = 70.0;
+
+// Render a printable cheat sheet as PNG, as tall as its content needs
+pub fn render_sheet(sheet: &Sheet, font_data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let font = FontVec::try_from_vec(font_data).map_err(|_| "The card font can't be read")?;
+    let text_width = SHEET_WIDTH as f32 - 2.0 * SHEET_MARGIN;
+
+    // Lay the text out first to know the height: (size, color, text, baseline)
+    let mut layout = Vec::new();
+    let mut y = SHEET_MARGIN + 48.0;
+    layout.push((48.0, INK, sheet.title.to_string(), y));
+    for (name, lines) in &sheet.sections {
+        y += 30.0;
+        if !name.is_empty() {
+            y += 40.0;
+            layout.push((32.0, ACCENT, name.to_string(), y));
+        }
+        for line in lines {
+            for wrapped in wrap(&font, 24.0, line, text_width) {
+                y += 36.0;
+                layout.push((24.0, INK, wrapped, y));
+            }
+        }
+    }
+    y += 50.0;
+    layout.push((20.0, MUTED, "taigi-translate-bot".to_string(), y));
+
+    let height = (y + SHEET_MARGIN) as u32;
+    let mut image = RgbaImage::from_pixel(SHEET_WIDTH, height, BACKGROUND);
+    for (size, color, text, baseline) in &layout {
+        let position = (SHEET_MARGIN, *baseline);
+        draw_text(&mut image, &font, *size, position, text_width, *color, text);
+    }
+
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|_| "Error encoding the cheat sheet".to_string())?;
+    Ok(png.into_inner())
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serenity::model::id::ChannelId;
+use tracing::{info, warn};
+
+use crate::opslog::{self, Kind};
+
+// Sends failing in a row before a channel stops getting results
+const THRESHOLD: u32 = 5;
+// How long the first pause lasts. Each failed retry doubles it, up to MAX_PAUSE.
+const FIRST_PAUSE: Duration = Duration::from_secs(60);
+const MAX_PAUSE: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Default)]
+struct State {
+    failures: u32,
+    // While paused, results aren't posted until this passes
+    paused_until: Option<Instant>,
+    pause: Duration,
+    // Whether the one send tried after a pause is under way
+    trying: bool,
+}
+
+// A circuit breaker on posting results, per channel. When sends keep failing
+// (the bot lost its permissions, or Discord is rate limiting it), queries are
+// still looked up, filling the cache and history, but the results aren't
+// posted until a pause has passed. Then one send is tried again: if it goes
+// through, posting resumes, otherwise the pause doubles.
+#[derive(Default)]
+pub struct Circuit {
+    channels: Mutex<HashMap<ChannelId, State>>,
+}
+
+impl Circuit {
+    // Whether results may be posted in `channel` now. Once a pause has passed,
+    // only the first caller is let through to try, under the same lock, and
+    // the pause starts over in case its send is never recorded.
+    pub fn allows(&self, channel: ChannelId) -> bool {
+        let mut channels = self.channels.lock().unwrap();
+        let Some(state) = channels.get_mut(&channel) else {
+            return true;
+        };
+        let now = Instant::now();
+        match state.paused_until {
+            None => true,
+            Some(until) if now >= until => {
+                state.trying = true;
+                state.paused_until = Some(now + state.pause);
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    // Note how a send to `channel` went
+    pub fn record<E: std::fmt::Debug>(&self, channel: ChannelId, result: Result<(), E>) {
+        let mut channels = self.channels.lock().unwrap();
+        let why = match result {
+            Ok(()) => {
+                if channels
+                    .remove(&channel)
+                    .is_some_and(|state| state.paused_until.is_some())
+                {
+                    info!("Posting in {} again", channel);
+                    opslog::report(Kind::SendFailure, format!("<#{}>: posting again", channel));
+                }
+                return;
+            }
+            Err(why) => why,
+        };
+
+        let state = channels.entry(channel).or_default();
+        state.failures += 1;
+        let retried = std::mem::take(&mut state.trying);
+        // Sends that were under way when the pause began don't extend it
+        if state.paused_until.is_some() && !retried {
+            return;
+        }
+        if state.failures < THRESHOLD && !retried {
+            return;
+        }
+        state.pause = if retried {
+            (state.pause * 2).min(MAX_PAUSE)
+        } else {
+            FIRST_PAUSE
+        };
+        state.paused_until = Some(Instant::now() + state.pause);
+        warn!(
+            "Pausing results in {} for {}s after {} failed sends: {why:?}",
+            channel,
+            state.pause.as_secs(),
+            state.failures
+        );
+        opslog::report(
+            Kind::SendFailure,
+            format!(
+                "<#{}>: {} sends failed, results paused for {} min ({:?})",
+                channel,
+                state.failures,
+                state.pause.as_secs() / 60,
+                why
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fail(circuit: &Circuit, channel: ChannelId) {
+        circuit.record(channel, Err("Missing Permissions"));
+    }
+
+    #[test]
+    fn pauses_after_failures_in_a_row() {
+        let circuit = Circuit::default();
+        let channel = ChannelId::new(1);
+        for _ in 0..THRESHOLD - 1 {
+            fail(&circuit, channel);
+        }
+        assert!(circuit.allows(channel));
+        fail(&circuit, channel);
+        assert!(!circuit.allows(channel));
+        assert!(circuit.allows(ChannelId::new(2)));
+    }
+
+    #[test]
+    fn only_one_send_is_tried_after_a_pause() {
+        let circuit = Circuit::default();
+        let channel = ChannelId::new(1);
+        for _ in 0..THRESHOLD {
+            fail(&circuit, channel);
+        }
+        let ended = Instant::now() - Duration::from_secs(1);
+        circuit.channels.lock().unwrap().get_mut(&channel).unwrap().paused_until = Some(ended);
+        assert!(circuit.allows(channel));
+        assert!(!circuit.allows(channel));
+
+        // A failed try doubles the pause, a good one resumes posting
+        fail(&circuit, channel);
+        assert_eq!(circuit.channels.lock().unwrap()[&channel].pause, FIRST_PAUSE * 2);
+        circuit.record::<&str>(channel, Ok(()));
+        assert!(circuit.allows(channel));
+    }
+
+    #[test]
+    fn late_failures_leave_the_pause_alone() {
+        let circuit = Circuit::default();
+        let channel = ChannelId::new(1);
+        for _ in 0..THRESHOLD + 3 {
+            fail(&circuit, channel);
+        }
+        assert_eq!(circuit.channels.lock().unwrap()[&channel].pause, FIRST_PAUSE);
+    }
+}
@@ -0,0 +1,620 @@
+use serenity::builder::{CreateAttachment, CreateCommand, CreateCommandOption, CreateMessage};
+use serenity::model::Permissions;
+use serenity::model::application::{
+    CommandInteraction, CommandOptionType, ResolvedOption, ResolvedValue,
+};
+use serenity::model::id::UserId;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::apikey::{self, Scope};
+use crate::backup;
+use crate::blocklist::{Blocklist, Kind};
+use crate::breakdown;
+use crate::cache::ResultCache;
+use crate::config::SharedConfig;
+use crate::context::Context;
+use crate::db;
+use crate::export;
+use crate::jobs::{JobKind, JobQueue};
+use crate::memory;
+
+// Hidden from everyone but server administrators, and in DMs. The bot owner
+// check in `run` still applies.
+pub fn register() -> CreateCommand {
+    CreateCommand::new("admin")
+        .description("Bot owner tools")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "reload",
+            "Re-read config.toml and apply the settings that can change at runtime",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "backup",
+            "Get a compressed copy of the database by DM",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "export",
+            "Get every answered word as a static HTML/JSON glossary site by DM",
+        ))
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "announce",
+                "Post release notes or news in every server's lookup channel",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "message",
+                    "What to announce, \\n for a line break",
+                )
+                .max_length(MAX_ANNOUNCEMENT)
+                .required(true),
+            ),
+        )
+        .add_option(blocklist_group(
+            "ignore",
+            "Users the bot ignores",
+            CommandOptionType::User,
+            "user",
+        ))
+        .add_option(blocklist_group(
+            "block",
+            "Servers the bot ignores",
+            CommandOptionType::String,
+            "guild",
+        ))
+        .add_option(memory_group())
+        .add_option(key_group())
+}
+
+// Leaves room for the 📣 and the note on turning announcements off
+const MAX_ANNOUNCEMENT: u16 = 1800;
+
+// HTTP API keys: `issue`, `revoke`, `list`
+fn key_group() -> CreateCommandOption {
+    let scope = Scope::ALL.into_iter().fold(
+        CreateCommandOption::new(CommandOptionType::String, "scope", "What the key may do")
+            .required(true),
+        |option, scope| option.add_string_choice(scope.code(), scope.code()),
+    );
+    CreateCommandOption::new(CommandOptionType::SubCommandGroup, "key", "HTTP API keys")
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "issue", "Issue a new key")
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "label",
+                        "Who the key is for",
+                    )
+                    .required(true),
+                )
+                .add_sub_option(scope)
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "per_minute",
+                        "Requests allowed a minute",
+                    )
+                    .min_int_value(1)
+                    .max_int_value(10_000)
+                    .required(true),
+                ),
+        )
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "revoke", "Revoke a key")
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "id",
+                        "The key's ID from `list`",
+                    )
+                    .required(true),
+                ),
+        )
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "Show the keys in use",
+        ))
+}
+
+// Stored sentence translations: `show`, `edit` one word's gloss, `purge`
+fn memory_group() -> CreateCommandOption {
+    let sentence = |required: bool| {
+        CreateCommandOption::new(CommandOptionType::String, "sentence", "The sentence as asked")
+            .required(required)
+    };
+    let text = |name: &str, description: &str| {
+        CreateCommandOption::new(CommandOptionType::String, name, description).required(true)
+    };
+    CreateCommandOption::new(
+        CommandOptionType::SubCommandGroup,
+        "memory",
+        "Stored sentence translations",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(CommandOptionType::SubCommand, "show", "Show a stored translation")
+            .add_sub_option(sentence(true)),
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "edit",
+            "Change the gloss stored for one word of a sentence",
+        )
+        .add_sub_option(sentence(true))
+        .add_sub_option(text("word", "The word as segmented"))
+        .add_sub_option(text("hanji", "Its Taigi Hanji"))
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "lomaji",
+            "Its Tâi-lô",
+        )),
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "purge",
+            "Drop a stored translation, or every one without a sentence",
+        )
+        .add_sub_option(sentence(false)),
+    )
+}
+
+// `add`/`remove`/`list` subcommands for one kind of blocklist entry
+fn blocklist_group(
+    name: &str,
+    description: &str,
+    kind: CommandOptionType,
+    target: &str,
+) -> CreateCommandOption {
+    let target_option = || {
+        CreateCommandOption::new(kind, target, format!("The {} (ID for servers)", target))
+            .required(true)
+    };
+    CreateCommandOption::new(CommandOptionType::SubCommandGroup, name, description)
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Add an entry")
+                .add_sub_option(target_option()),
+        )
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove an entry")
+                .add_sub_option(target_option()),
+        )
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "Show all entries",
+        ))
+}
+
+// Only the owner of the bot application (or a member of its team) may use /admin
+pub async fn is_owner(ctx: &Context, user_id: UserId) -> bool {
+    match ctx.http.get_current_application_info().await {
+        Ok(info) => {
+            info.owner.is_some_and(|owner| owner.id == user_id)
+                || info
+                    .team
+                    .is_some_and(|team| team.members.iter().any(|member| member.user.id == user_id))
+        }
+        Err(why) => {
+            error!("Error fetching application info: {why:?}");
+            false
+        }
+    }
+}
+
+pub async fn run(
+    ctx: &Context,
+    command: &CommandInteraction,
+    config: &SharedConfig,
+    cache: &ResultCache,
+    db: &SqlitePool,
+    blocklist: &Blocklist,
+    jobs: &JobQueue,
+) -> String {
+    let user_id = command.user.id;
+    if !is_owner(ctx, user_id).await {
+        return "Only the bot owner can use /admin.".to_string();
+    }
+
+    let options = command.data.options();
+    let Some(option) = options.first() else {
+        return "Unknown /admin subcommand.".to_string();
+    };
+    match (option.name, &option.value) {
+        ("reload", _) => reload(config, cache),
+        ("backup", _) => send_backup(ctx, user_id, db).await,
+        ("export", _) => send_export(ctx, user_id, db).await,
+        ("announce", ResolvedValue::SubCommand(options)) => {
+            announce(options, user_id, db, jobs).await
+        }
+        ("ignore", ResolvedValue::SubCommandGroup(options)) => {
+            manage_blocklist(options, Kind::User, user_id, db, blocklist).await
+        }
+        ("block", ResolvedValue::SubCommandGroup(options)) => {
+            manage_blocklist(options, Kind::Guild, user_id, db, blocklist).await
+        }
+        ("memory", ResolvedValue::SubCommandGroup(options)) => {
+            manage_memory(options, user_id, db).await
+        }
+        ("key", ResolvedValue::SubCommandGroup(options)) => manage_keys(options, user_id, db).await,
+        _ => "Unknown /admin subcommand.".to_string(),
+    }
+}
+
+// Discord refuses larger attachments from bots
+const MAX_ATTACHMENT: usize = 10 * 1024 * 1024;
+
+// Sent by DM rather than in the channel, since it holds everyone's history
+async fn send_backup(ctx: &Context, user_id: UserId, db: &SqlitePool) -> String {
+    let (filename, bytes) = match backup::create(db).await {
+        Ok(backup) => backup,
+        Err(err) => {
+            error!("Error backing up the database: {}", err);
+            return "Could not back up the database.".to_string();
+        }
+    };
+    let size = bytes.len();
+    if size > MAX_ATTACHMENT {
+        return format!(
+            "The backup is {} MB, too large to send. Copy the database file from the host instead.",
+            size / (1024 * 1024)
+        );
+    }
+
+    let message = CreateMessage::new()
+        .content("💾 Database backup. Restore it with `--restore <file>` at startup.")
+        .add_file(CreateAttachment::bytes(bytes, filename));
+    match user_id.direct_message(ctx, message).await {
+        Ok(_) => format!("💾 Sent the backup ({} KB) by DM.", size.div_ceil(1024)),
+        Err(why) => {
+            error!("Error sending the backup: {why:?}");
+            "Could not send the backup, are DMs from this server allowed?".to_string()
+        }
+    }
+}
+
+// The glossary site, also by DM so it can be looked over before publishing
+async fn send_export(ctx: &Context, user_id: UserId, db: &SqlitePool) -> String {
+    let prefs = answer::load_prefs(db, user_id, None).await;
+    let (filename, bytes, count) = match export::site(db, &prefs).await {
+        Ok(export) => export,
+        Err(err) => {
+            error!("Error exporting the glossary: {}", err);
+            return "Could not export the glossary.".to_string();
+        }
+    };
+    let size = bytes.len();
+    if size > MAX_ATTACHMENT {
+        return format!("The export is {} MB, too large to send.", size / (1024 * 1024));
+    }
+
+    let message = CreateMessage::new()
+        .content(format!(
+            "🌐 Glossary of {} words. Unpack it and publish the `glossary` folder as a static site.",
+            count
+        ))
+        .add_file(CreateAttachment::bytes(bytes, filename));
+    match user_id.direct_message(ctx, message).await {
+        Ok(_) => format!("🌐 Sent the glossary ({} words) by DM.", count),
+        Err(why) => {
+            error!("Error sending the glossary: {why:?}");
+            "Could not send the glossary, are DMs from this server allowed?".to_string()
+        }
+    }
+}
+
+// Stored with the servers it goes to, then sent in the background, reporting
+// progress by DM
+async fn announce(
+    options: &[ResolvedOption<'_>],
+    user_id: UserId,
+    db: &SqlitePool,
+    jobs: &JobQueue,
+) -> String {
+    let Some(ResolvedValue::String(message)) = options.first().map(|option| &option.value) else {
+        return "Please give the message.".to_string();
+    };
+    let message = message.replace("\\n", "\n");
+    let message = message.trim();
+    if message.is_empty() {
+        return "Please give the message.".to_string();
+    }
+
+    let (id, recipients) = match db::add_announcement(db, message, &user_id.to_string()).await {
+        Ok(added) => added,
+        Err(why) => {
+            error!("Error storing announcement: {why:?}");
+            return "Could not store the announcement.".to_string();
+        }
+    };
+    if recipients == 0 {
+        return "📣 No server has a lookup channel and announcements on, nothing to send."
+            .to_string();
+    }
+    match jobs.submit(JobKind::Announcements, user_id).await {
+        Ok(()) => format!(
+            "📣 Announcement {} queued for {} servers, I'll DM you the progress.",
+            id, recipients
+        ),
+        Err(err) => err,
+    }
+}
+
+async fn manage_blocklist(
+    options: &[ResolvedOption<'_>],
+    kind: Kind,
+    user_id: UserId,
+    db: &SqlitePool,
+    blocklist: &Blocklist,
+) -> String {
+    let Some(subcommand) = options.first() else {
+        return "Unknown /admin subcommand.".to_string();
+    };
+    let mention = |id: u64| match kind {
+        Kind::User => format!("<@{}>", id),
+        Kind::Guild => format!("server {}", id),
+    };
+
+    if subcommand.name == "list" {
+        let ids = blocklist.list(kind);
+        if ids.is_empty() {
+            return format!("🚫 No {}s are blocked.", kind.code());
+        }
+        let entries: Vec<String> = ids.into_iter().map(mention).collect();
+        return format!("🚫 Blocked {}s:\n{}", kind.code(), entries.join("\n"));
+    }
+
+    let ResolvedValue::SubCommand(target) = &subcommand.value else {
+        return "Unknown /admin subcommand.".to_string();
+    };
+    let id = match target.first().map(|option| &option.value) {
+        Some(ResolvedValue::User(user, _)) => Some(user.id.get()),
+        Some(ResolvedValue::String(id)) => id.trim().parse().ok().filter(|&id| id != 0),
+        _ => None,
+    };
+    let Some(id) = id else {
+        return format!("Please give a valid {} ID.", kind.code());
+    };
+
+    let result = match subcommand.name {
+        "add" => blocklist.add(db, kind, id, user_id).await.map(|added| {
+            if added {
+                format!("🚫 Now ignoring {}.", mention(id))
+            } else {
+                format!("{} was already blocked.", mention(id))
+            }
+        }),
+        "remove" => blocklist.remove(db, kind, id).await.map(|removed| {
+            if removed {
+                format!("✅ No longer ignoring {}.", mention(id))
+            } else {
+                format!("{} wasn't blocked.", mention(id))
+            }
+        }),
+        _ => return "Unknown /admin subcommand.".to_string(),
+    };
+
+    result.unwrap_or_else(|why| {
+        error!("Error updating the blocklist: {why:?}");
+        "Could not update the blocklist.".to_string()
+    })
+}
+
+async fn manage_memory(options: &[ResolvedOption<'_>], user_id: UserId, db: &SqlitePool) -> String {
+    let Some(subcommand) = options.first() else {
+        return "Unknown /admin subcommand.".to_string();
+    };
+    let ResolvedValue::SubCommand(options) = &subcommand.value else {
+        return "Unknown /admin subcommand.".to_string();
+    };
+    let text = |name: &str| {
+        options.iter().find(|option| option.name == name).and_then(|option| {
+            match option.value {
+                ResolvedValue::String(value) => Some(value.trim()),
+                _ => None,
+            }
+        })
+    };
+    let sentence = text("sentence").map(memory::normalize);
+
+    if subcommand.name == "purge" {
+        return match db::forget_translations(db, sentence.as_deref()).await {
+            Ok(0) => "Nothing was stored for that.".to_string(),
+            Ok(count) => format!("🗑️ Dropped {} stored translation(s).", count),
+            Err(why) => {
+                error!("Error purging the translation memory: {why:?}");
+                "Could not purge the translation memory.".to_string()
+            }
+        };
+    }
+
+    let Some(sentence) = sentence else {
+        return "Please give the sentence.".to_string();
+    };
+    let stored = match db::sentence_translations(db, &sentence).await {
+        Ok(stored) if stored.is_empty() => {
+            return format!("Nothing is stored for \"{}\".", sentence);
+        }
+        Ok(stored) => stored,
+        Err(why) => {
+            error!("Error loading the translation memory: {why:?}");
+            return "Could not load the translation memory.".to_string();
+        }
+    };
+
+    if subcommand.name == "edit" {
+        let (Some(word), Some(hanji)) = (text("word"), text("hanji")) else {
+            return "Please give the word and its Hanji.".to_string();
+        };
+        let lomaji = text("lomaji").filter(|lomaji| !lomaji.is_empty());
+        // Every accent's translation gets the same fix
+        for (key, glosses) in &stored {
+            let mut glosses = memory::decode(glosses);
+            let Some(gloss) = glosses.iter_mut().find(|gloss| gloss.word == word) else {
+                return format!("\"{}\" isn't a word of \"{}\".", word, sentence);
+            };
+            gloss.hanji = Some(hanji.to_string());
+            gloss.lomaji = lomaji.map(str::to_string);
+            let encoded = memory::encode(&glosses);
+            if let Err(why) = db::edit_translation(db, key, &encoded, &user_id.to_string()).await {
+                error!("Error editing the translation memory: {why:?}");
+                return "Could not edit the translation.".to_string();
+            }
+        }
+    }
+
+    let sections: Vec<String> = match db::sentence_translations(db, &sentence).await {
+        Ok(stored) => stored
+            .iter()
+            .map(|(key, glosses)| {
+                // Keys carry the accent after the sentence
+                let accent = key.split_once('|').map_or("Default accent", |(_, accent)| accent);
+                format!("{}\n{}", accent, breakdown::render(&memory::decode(glosses)))
+            })
+            .collect(),
+        Err(why) => {
+            error!("Error loading the translation memory: {why:?}");
+            return "Could not load the translation memory.".to_string();
+        }
+    };
+    let header = if subcommand.name == "edit" { "✏️ Updated:" } else { "💾 Stored:" };
+    format!("{}\n{}", header, sections.join("\n"))
+}
+
+async fn manage_keys(options: &[ResolvedOption<'_>], user_id: UserId, db: &SqlitePool) -> String {
+    let Some(subcommand) = options.first() else {
+        return "Unknown /admin subcommand.".to_string();
+    };
+    let ResolvedValue::SubCommand(options) = &subcommand.value else {
+        return "Unknown /admin subcommand.".to_string();
+    };
+    let value = |name: &str| {
+        options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| &option.value)
+    };
+
+    match subcommand.name {
+        "issue" => {
+            let (Some(ResolvedValue::String(label)), Some(ResolvedValue::String(scope))) =
+                (value("label"), value("scope"))
+            else {
+                return "Please give a label and a scope.".to_string();
+            };
+            let Some(scope) = Scope::parse(scope) else {
+                return "Unknown scope.".to_string();
+            };
+            let per_minute = match value("per_minute") {
+                Some(ResolvedValue::Integer(per_minute)) => (*per_minute).max(1) as u32,
+                _ => return "Please give how many requests a minute are allowed.".to_string(),
+            };
+            let key = match apikey::generate() {
+                Ok(key) => key,
+                Err(err) => {
+                    error!("Error issuing an API key: {}", err);
+                    return "Could not issue a key.".to_string();
+                }
+            };
+            let hash = apikey::hash(&key);
+            let user = user_id.to_string();
+            match db::add_api_key(db, &hash, label.trim(), scope, per_minute, &user).await {
+                Ok(id) => format!(
+                    "🔑 Key {} for {} ({}, {}/min):\n`{}`\nIt won't be shown again. Send it \
+                     as the `X-Api-Key` header.",
+                    id,
+                    label.trim(),
+                    scope.code(),
+                    per_minute,
+                    key
+                ),
+                Err(why) => {
+                    error!("Error saving an API key: {why:?}");
+                    "Could not issue a key.".to_string()
+                }
+            }
+        }
+        "revoke" => {
+            let Some(ResolvedValue::Integer(id)) = value("id") else {
+                return "Please give the key's ID.".to_string();
+            };
+            match db::revoke_api_key(db, *id).await {
+                Ok(true) => format!("🔒 Revoked key {}.", id),
+                Ok(false) => format!("There is no key {} in use.", id),
+                Err(why) => {
+                    error!("Error revoking an API key: {why:?}");
+                    "Could not revoke the key.".to_string()
+                }
+            }
+        }
+        "list" => match db::api_keys(db).await {
+            Ok(keys) if keys.is_empty() => "🔑 No keys are in use.".to_string(),
+            Ok(keys) => {
+                let lines: Vec<String> = keys
+                    .iter()
+                    .map(|key| {
+                        format!(
+                            "{}. {} ({}, {}/min)",
+                            key.id.unwrap_or_default(),
+                            key.label,
+                            key.scope.code(),
+                            key.per_minute
+                        )
+                    })
+                    .collect();
+                format!("🔑 Keys in use:\n{}", lines.join("\n"))
+            }
+            Err(why) => {
+                error!("Error loading the API keys: {why:?}");
+                "Could not load the keys.".to_string()
+            }
+        },
+        _ => "Unknown /admin subcommand.".to_string(),
+    }
+}
+
+// Shared by `/admin reload` and SIGHUP. Loading reads files and runs
+// `ffmpeg -version`, which would hold up a runtime thread otherwise.
+pub fn reload(config: &SharedConfig, cache: &ResultCache) -> String {
+    let report = match tokio::task::block_in_place(|| config.reload()) {
+        Ok(report) => report,
+        Err(errors) => {
+            return format!(
+                "❌ The configuration has problems, keeping the current settings:\n{}",
+                errors
+                    .iter()
+                    .map(|error| format!("- {}", error))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
+    };
+
+    if report.invalidates_cache() {
+        cache.clear();
+    }
+
+    if report.applied.is_empty() && report.restart_required.is_empty() {
+        return "🔄 Reloaded, nothing changed.".to_string();
+    }
+
+    let mut lines = vec!["🔄 Reloaded the configuration.".to_string()];
+    if !report.applied.is_empty() {
+        lines.push(format!("Applied: {}", report.applied.join(", ")));
+    }
+    if !report.restart_required.is_empty() {
+        lines.push(format!(
+            "Needs a restart to take effect: {}",
+            report.restart_required.join(", ")
+        ));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+use serenity::builder::{
+    CreateAttachment, CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CreateInteractionResponseMessage, EditInteractionResponse,
+};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+use tokio::task::JoinSet;
+use tracing::error;
+
+use crate::answer;
+use crate::commands::chars;
+use crate::breakdown::{self, Gloss};
+use crate::config::Config;
+use crate::context::Context;
+use crate::filter::{self, Action};
+use crate::db::UserPrefs;
+use crate::lookup::LookupPool;
+use crate::reading::{self, Kind};
+use crate::respond;
+use crate::text::escape;
+
+// Discord allows up to 6000 characters, but passages cost a lookup per word
+pub const MAX_TEXT: u16 = 1000;
+// Words past this many are left without a reading
+pub const MAX_WORDS: usize = 40;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("annotate")
+        .description("Add Taigi readings to every word of a passage")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "text", "Passage to annotate")
+                .max_length(MAX_TEXT)
+                .required(true),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "html",
+            "Attach an HTML file with <ruby> readings for class materials",
+        ))
+        .add_option(chars::reading_option())
+}
+
+// Swap each gloss's reading for Moedict's literary or colloquial one where it
+// marks one, returning how many were swapped. Each word is asked once, through
+// the lookup pool, so repeated words share a request and the cache.
+async fn prefer_reading(pool: &LookupPool, glosses: &mut [Gloss], kind: Kind) -> usize {
+    let mut words: Vec<String> = Vec::new();
+    for gloss in glosses.iter() {
+        if !words.contains(&gloss.word) {
+            words.push(gloss.word.clone());
+        }
+    }
+    let mut tasks = JoinSet::new();
+    for word in words {
+        let pool = pool.clone();
+        tasks.spawn(async move {
+            let picked = reading::pick(&pool, &word, kind).await;
+            (word, picked)
+        });
+    }
+    let mut swapped = 0;
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((word, Ok(Some(lomaji)))) => {
+                for gloss in glosses.iter_mut().filter(|gloss| gloss.word == word) {
+                    gloss.lomaji = Some(lomaji.clone());
+                    swapped += 1;
+                }
+            }
+            Ok((_, Ok(None))) => {}
+            Ok((word, Err(err))) => error!("Error fetching readings of {}: {}", word, err),
+            Err(why) => error!("Reading lookup panicked: {why:?}"),
+        }
+    }
+    swapped
+}
+
+// Each token followed by its reading in brackets, e.g. `食飯(tsia̍h-pn̄g)`
+fn inline(tokens: &[String], glosses: &[Gloss]) -> String {
+    tokens
+        .iter()
+        .map(|token| {
+            let lomaji = glosses
+                .iter()
+                .find(|gloss| gloss.word == *token)
+                .and_then(|gloss| gloss.lomaji.as_deref());
+            match lomaji {
+                Some(lomaji) => format!("{}({})", token, lomaji),
+                None => token.clone(),
+            }
+        })
+        .collect()
+}
+
+// A standalone page with the readings as <ruby> over each word. Where the
+// Taigi Hanji differ from the passage they show up on hover.
+fn html(tokens: &[String], glosses: &[Gloss], prefs: &UserPrefs) -> String {
+    let mut body = String::new();
+    for token in tokens {
+        let gloss = glosses.iter().find(|gloss| gloss.word == *token);
+        match gloss.and_then(|gloss| gloss.lomaji.as_deref().map(|lomaji| (gloss, lomaji))) {
+            Some((gloss, lomaji)) => {
+                let title = match &gloss.hanji {
+                    Some(hanji) if hanji != token => format!(" title=\"{}\"", escape(hanji)),
+                    _ => String::new(),
+                };
+                body.push_str(&format!(
+                    "<ruby{}>{}<rt>{}</rt></ruby>",
+                    title,
+                    escape(token),
+                    escape(&prefs.render(lomaji))
+                ));
+            }
+            None => body.push_str(&escape(token).replace('\n', "<br>\n")),
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"nan-Hant-TW\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>台語標音</title>\n<style>body {{ font-size: 1.6em; line-height: 2.4; }} \
+         rt {{ font-size: 0.5em; }}</style>\n</head>\n<body>\n<p>{}</p>\n</body>\n</html>\n",
+        body
+    )
+}
+
+pub async fn run(
+    ctx: &Context,
+    command: &CommandInteraction,
+    config: &Arc<Config>,
+    pool: &LookupPool,
+    db: &SqlitePool,
+) {
+    let mut text = "";
+    let mut want_html = false;
+    let mut only = None;
+    for option in command.data.options() {
+        match (option.name, option.value) {
+            ("text", ResolvedValue::String(value)) => text = value.trim(),
+            ("html", ResolvedValue::Boolean(value)) => want_html = value,
+            ("reading", ResolvedValue::String(value)) => only = Kind::parse(value),
+            _ => {}
+        }
+    }
+
+    // The readings are public, so privately answered words are refused too
+    let prefs = answer::load_prefs(db, command.user.id, command.guild_id).await;
+    let locale = prefs.locale.unwrap_or(config.locale);
+    let refusal = match filter::check(db, command.guild_id, text).await {
+        Some(Action::Refuse) => Some(locale.filtered()),
+        Some(Action::Private) => Some(locale.filtered_private()),
+        None => None,
+    };
+    if let Some(refusal) = refusal {
+        let data = CreateInteractionResponseMessage::new()
+            .content(refusal)
+            .ephemeral(true);
+        if let Err(why) = command
+            .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+            .await
+        {
+            error!("Cannot respond to /annotate: {why:?}");
+        }
+        return;
+    }
+
+    // Lookups can take longer than the 3 seconds Discord waits for a response
+    let defer = CreateInteractionResponseMessage::new();
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Defer(defer))
+        .await
+    {
+        error!("Cannot defer /annotate: {why:?}");
+        return;
+    }
+
+    let tokens = breakdown::tokens(text);
+    let mut words: Vec<String> = Vec::new();
+    for token in &tokens {
+        if breakdown::has_han(token) && !words.contains(token) {
+            words.push(token.clone());
+        }
+    }
+    let skipped = words.len().saturating_sub(MAX_WORDS);
+    words.truncate(MAX_WORDS);
+
+    let mut glosses = breakdown::gloss(words, None, pool, config).await;
+
+    let mut note = String::new();
+    if let Some(kind) = only {
+        let swapped = prefer_reading(pool, &mut glosses, kind).await;
+        note = format!("\n🗣️ {} {} marked by Moedict.", swapped, kind.label());
+    }
+    if skipped > 0 {
+        note.push_str(&format!("\n⚠️ {} more words were left without a reading.", skipped));
+    }
+
+    let edit = if want_html {
+        let page = html(&tokens, &glosses, &prefs);
+        EditInteractionResponse::new()
+            .content(format!("📝 Annotated passage attached.{}", note))
+            .new_attachment(CreateAttachment::bytes(page.into_bytes(), "annotated.html"))
+    } else {
+        let content = format!("📝 {}{}", inline(&tokens, &glosses), note);
+        EditInteractionResponse::new().content(respond::fit_message(&prefs.render(&content)))
+    };
+    if let Err(why) = command.edit_response(&ctx.http, edit).await {
+        error!("Cannot respond to /annotate: {why:?}");
+    }
+}
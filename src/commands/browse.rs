@@ -0,0 +1,39 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+
+use crate::browse;
+use crate::context::Context;
+use crate::respond::{self, Reply};
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("browse")
+        .description("Taigi vocabulary by theme: food, family, weather…")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "category",
+                "e.g. food, family or weather; leave out to see them all",
+            )
+            .max_length(50),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    let mut category = "";
+    for option in command.data.options() {
+        if let ("category", ResolvedValue::String(value)) = (option.name, option.value) {
+            category = value;
+        }
+    }
+
+    let reply = async {
+        if category.trim().is_empty() {
+            return Reply::from(browse::overview());
+        }
+        browse::start(category).unwrap_or_else(|| {
+            Reply::from(format!("There's no category \"{}\".\n{}", category, browse::overview()))
+        })
+    };
+    respond::command(ctx, db, command, reply).await;
+}
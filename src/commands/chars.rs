@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use serenity::model::id::GuildId;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::config::Config;
+use crate::context::Context;
+use crate::db::UserPrefs;
+use crate::direction::Direction;
+use crate::filter::{self, Action};
+use crate::links::{self, LinkStyle};
+use crate::lookup::LookupPool;
+use crate::reading::{self, Kind};
+use crate::related;
+use crate::respond;
+use crate::source::{self, Source};
+use crate::variant::Reading;
+
+// Characters explained per reply, to stay under the message limit
+const MAX_CHARS: usize = 6;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("chars")
+        .description("Explain each Hanji of a word: readings, radical and compounds")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "word", "Word or characters")
+                .required(true),
+        )
+        .add_option(reading_option())
+}
+
+// `reading: wenyan|colloquial`, shared with /annotate
+pub fn reading_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::String,
+        "reading",
+        "Only literary (文讀) or colloquial (白讀) readings",
+    )
+    .add_string_choice("文讀 literary", Kind::Literary.code())
+    .add_string_choice("白讀 colloquial", Kind::Colloquial.code())
+}
+
+// What Moedict knows of a single character: its Taigi readings with their
+// 文/白 marks, and its radical and stroke count from the Mandarin dictionary
+struct Character {
+    readings: Vec<Reading>,
+    radical: Option<String>,
+    strokes: Option<u64>,
+}
+
+async fn moedict(pool: &LookupPool, character: char) -> Result<Character, String> {
+    let single = character.to_string();
+    let (readings, mandarin) = tokio::join!(
+        reading::moedict(pool, &single),
+        pool.moedict_json("a", &single)
+    );
+    let mandarin = mandarin.unwrap_or_else(|err| {
+        error!("Error fetching the radical of {}: {}", character, err);
+        None
+    });
+    Ok(Character {
+        readings: readings?,
+        radical: mandarin
+            .as_ref()
+            .and_then(|json| json.get("r")?.as_str())
+            .map(|radical| radical.replace(['`', '~'], "")),
+        strokes: mandarin.as_ref().and_then(|json| json.get("c")?.as_u64()),
+    })
+}
+
+// Compounds the server's filtered words don't hide; the reply is public
+async fn compounds(db: &SqlitePool, guild_id: Option<GuildId>, single: &str) -> Vec<String> {
+    let mut shown = Vec::new();
+    for word in related::words(db, single).await {
+        if filter::check(db, guild_id, &word).await.is_none() {
+            shown.push(word);
+        }
+    }
+    shown
+}
+
+// `**人** 部首 人 · 2 畫`, then the readings, Sutian's entry and compounds
+async fn explain(
+    pool: &LookupPool,
+    db: &SqlitePool,
+    guild_id: Option<GuildId>,
+    character: char,
+    prefs: &UserPrefs,
+    link_style: LinkStyle,
+    only: Option<Kind>,
+) -> String {
+    let single = character.to_string();
+    let sutian = pool.fetch(
+        format!("sutian-char:{}", single),
+        Source::Sutian,
+        source::search_sutian(&single, None, Direction::TaigiToMandarin, 1),
+    );
+    let (moedict, sutian, compounds) = tokio::join!(
+        moedict(pool, character),
+        sutian,
+        compounds(db, guild_id, &single)
+    );
+
+    let mut lines = Vec::new();
+    let mut heading = format!("**{}**", character);
+    match moedict {
+        Ok(moedict) => {
+            if let Some(radical) = &moedict.radical {
+                heading.push_str(&format!(" 部首 {}", radical));
+            }
+            if let Some(strokes) = moedict.strokes {
+                heading.push_str(&format!(" · {} 畫", strokes));
+            }
+            lines.push(heading);
+            let readings: Vec<String> = moedict
+                .readings
+                .iter()
+                .filter(|reading| only.is_none_or(|kind| reading.kind() == Some(kind)))
+                .map(|reading| match reading.mark() {
+                    Some(label) => format!("{} {}", label, reading.text),
+                    None => reading.text.clone(),
+                })
+                .collect();
+            if !readings.is_empty() {
+                lines.push(format!("🗣️ {}", readings.join(" · ")));
+            } else if let Some(kind) = only {
+                lines.push(format!("🗣️ No {} marked", kind.label()));
+            }
+        }
+        Err(err) => {
+            lines.push(heading);
+            lines.push(format!("⚠️ {}", err));
+        }
+    }
+    match sutian {
+        Ok(results) => {
+            let results: Vec<String> = results
+                .iter()
+                .map(|result| prefs.format_result(result))
+                .collect();
+            lines.extend(links::render(&results, link_style));
+        }
+        Err(err) => error!("Error searching Sutian for {}: {}", character, err),
+    }
+    if !compounds.is_empty() {
+        lines.push(format!("🧩 {}", compounds.join(" · ")));
+    }
+    lines.join("\n")
+}
+
+// Each character takes a few requests, made through the lookup pool and
+// cached like lookups
+pub async fn run(
+    ctx: &Context,
+    command: &CommandInteraction,
+    config: &Arc<Config>,
+    pool: &LookupPool,
+    db: &SqlitePool,
+) {
+    let mut word = String::new();
+    let mut only = None;
+    for option in command.data.options() {
+        match (option.name, option.value) {
+            ("word", ResolvedValue::String(value)) => word = value.trim().to_string(),
+            ("reading", ResolvedValue::String(value)) => only = Kind::parse(value),
+            _ => {}
+        }
+    }
+
+    let is_han = |c: &char| ('\u{4E00}'..='\u{9FFF}').contains(c);
+    let mut characters: Vec<char> = Vec::new();
+    for character in word.chars().filter(is_han) {
+        if !characters.contains(&character) {
+            characters.push(character);
+        }
+    }
+
+    let prefs = answer::load_prefs(db, command.user.id, command.guild_id).await;
+    let explanations = async {
+        if characters.is_empty() {
+            return "❌ No Hanji to explain, try e.g. `/chars 蘋果`.".to_string();
+        }
+        let mut explanations = Vec::new();
+        for &character in characters.iter().take(MAX_CHARS) {
+            explanations.push(
+                explain(pool, db, command.guild_id, character, &prefs, config.link_style, only)
+                    .await,
+            );
+        }
+        let mut content = explanations.join("\n\n");
+        if characters.len() > MAX_CHARS {
+            content.push_str(&format!("\n\n…and {} more.", characters.len() - MAX_CHARS));
+        }
+        content
+    };
+
+    // The server's filtered words apply to /chars like lookups
+    match filter::check(db, command.guild_id, &word).await {
+        None => respond::public(ctx, db, command, explanations).await,
+        Some(action) => {
+            let guild = answer::load_guild(db, command.guild_id).await;
+            let locale = prefs.locale.or(guild.locale).unwrap_or(config.locale);
+            match action {
+                Action::Refuse => {
+                    let refusal = async { locale.filtered().to_string() };
+                    respond::command(ctx, db, command, refusal).await
+                }
+                Action::Private => respond::command(ctx, db, command, explanations).await,
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use serenity::builder::{
+    CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CreateInteractionResponseMessage, EditInteractionResponse,
+};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::align;
+use crate::answer;
+use crate::breakdown;
+use crate::config::Config;
+use crate::context::Context;
+use crate::filter::{self, Action};
+use crate::lookup::{Lookup, LookupPool};
+use crate::source::Source;
+
+// More columns than this don't fit side by side, even split into blocks
+const MAX_WORDS: usize = 5;
+// Moedict definitions are cut to keep the columns narrow
+const GLOSS_CHARS: usize = 12;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("compare")
+        .description("Compare the Taigi of two or more words side by side")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "words",
+                "Words separated by spaces, e.g. 蘋果 林檎",
+            )
+            .required(true),
+        )
+}
+
+// One column of the table: word, Taigi Hanji, romanization, gloss, sources
+fn column(word: &str, lookup: &Lookup, romanize: impl Fn(&str) -> String) -> Vec<String> {
+    let headword = lookup.results.iter().find_map(|result| breakdown::headword(result));
+    let gloss = lookup
+        .results
+        .iter()
+        .zip(&lookup.sources)
+        .filter(|(_, source)| **source == Source::Moedict)
+        .find_map(|(result, _)| breakdown::definition(result))
+        .map(|gloss| gloss.chars().take(GLOSS_CHARS).collect::<String>());
+    let codes: String = Source::ALL
+        .into_iter()
+        .filter(|source| lookup.sources.contains(source))
+        .map(Source::code)
+        .collect();
+
+    vec![
+        word.to_string(),
+        headword.map_or("？".to_string(), |(hanji, _)| hanji.to_string()),
+        headword
+            .and_then(|(_, lomaji)| lomaji)
+            .map_or("?".to_string(), romanize),
+        gloss.unwrap_or_else(|| "—".to_string()),
+        if codes.is_empty() { "—".to_string() } else { codes },
+    ]
+}
+
+pub async fn run(
+    ctx: &Context,
+    command: &CommandInteraction,
+    config: &Arc<Config>,
+    pool: &LookupPool,
+    db: &SqlitePool,
+) {
+    let words: Vec<String> = command
+        .data
+        .options()
+        .into_iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::String(value) if option.name == "words" => Some(value),
+            _ => None,
+        })
+        .unwrap_or_default()
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '，' || c == '、')
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let prefs = answer::load_prefs(db, command.user.id, command.guild_id).await;
+    let locale = prefs.locale.unwrap_or(config.locale);
+    // The table is public, so privately answered words are refused too
+    let problem = if words.len() < 2 || words.len() > MAX_WORDS {
+        Some(format!(
+            "Please give between 2 and {} words separated by spaces.",
+            MAX_WORDS
+        ))
+    } else {
+        match filter::check(db, command.guild_id, &words.join(" ")).await {
+            Some(Action::Refuse) => Some(locale.filtered().to_string()),
+            Some(Action::Private) => Some(locale.filtered_private().to_string()),
+            None => None,
+        }
+    };
+    if let Some(problem) = problem {
+        let data = CreateInteractionResponseMessage::new()
+            .content(problem)
+            .ephemeral(true);
+        if let Err(why) = command
+            .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+            .await
+        {
+            error!("Cannot respond to /compare: {why:?}");
+        }
+        return;
+    }
+
+    // Lookups can take longer than the 3 seconds Discord waits for a response
+    let defer = CreateInteractionResponseMessage::new();
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Defer(defer))
+        .await
+    {
+        error!("Cannot defer /compare: {why:?}");
+        return;
+    }
+
+    let lookups = pool.lookup_all(&words, None, config).await;
+    let columns: Vec<Vec<String>> = words
+        .iter()
+        .zip(&lookups)
+        .map(|(word, lookup)| column(word, lookup, |lomaji| prefs.render(lomaji)))
+        .collect();
+
+    // The table is built column by column but laid out by rows
+    let labels = ["", "台語", "Lô-jī", "釋義", "Sources"];
+    let rows: Vec<Vec<&str>> = labels
+        .iter()
+        .enumerate()
+        .map(|(idx, label)| {
+            std::iter::once(*label)
+                .chain(columns.iter().map(|column| column[idx].as_str()))
+                .collect()
+        })
+        .collect();
+
+    let legend: Vec<String> = Source::available()
+        .map(|source| format!("{} = {}", source.code(), source.name()))
+        .collect();
+    let content = format!(
+        "⚖️ {}\n```\n{}\n```\n{}",
+        words.join(" / "),
+        align::columns(&rows),
+        legend.join(", ")
+    );
+
+    let edit = EditInteractionResponse::new().content(content);
+    if let Err(why) = command.edit_response(&ctx.http, edit).await {
+        error!("Cannot respond to /compare: {why:?}");
+    }
+}
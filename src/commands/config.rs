@@ -0,0 +1,108 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::Permissions;
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use serenity::model::channel::ChannelType;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::context::Context;
+use crate::db;
+use crate::onboarding;
+use crate::preflight::{self, Purpose};
+use crate::respond::{self, Reply};
+
+// Only members who can manage the server see the command; Discord enforces
+// this and server admins can adjust it under Integrations. The channels that
+// don't fit in the wizard's menus are options.
+pub fn register() -> CreateCommand {
+    CreateCommand::new("config")
+        .description("Change the bot's settings for this server")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Channel,
+                "digest_channel",
+                "Channel for a daily digest of this server's public lookups",
+            )
+            .channel_types(vec![ChannelType::Text]),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Channel,
+                "archive_forum",
+                "Forum getting a post for each word looked up publicly in this server",
+            )
+            .channel_types(vec![ChannelType::Forum]),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "english",
+            "Add English meanings to answers, for members who haven't picked in /prefs",
+        ))
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "turn_off", "Stop posting")
+                .add_string_choice("Daily digest", "digest")
+                .add_string_choice("Archive forum", "archive"),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    let reply = async {
+        let Some(guild_id) = command.guild_id else {
+            return Reply::from("/config only works in servers.".to_string());
+        };
+        let mut settings = answer::load_guild(db, Some(guild_id)).await;
+        let mut changed = false;
+        let mut picked = Vec::new();
+        for option in command.data.options() {
+            match (option.name, option.value) {
+                ("digest_channel", ResolvedValue::Channel(channel)) => {
+                    settings.digest_channel = Some(channel.id);
+                    picked.push((channel.id, Purpose::Digest));
+                    changed = true;
+                }
+                ("archive_forum", ResolvedValue::Channel(channel)) => {
+                    settings.archive_forum = Some(channel.id);
+                    picked.push((channel.id, Purpose::Archive));
+                    changed = true;
+                }
+                ("english", ResolvedValue::Boolean(english)) => {
+                    settings.english = Some(english);
+                    changed = true;
+                }
+                ("turn_off", ResolvedValue::String("digest")) => {
+                    settings.digest_channel = None;
+                    changed = true;
+                }
+                ("turn_off", ResolvedValue::String("archive")) => {
+                    settings.archive_forum = None;
+                    changed = true;
+                }
+                _ => {}
+            }
+        }
+        if changed
+            && let Err(why) = db::save_guild_settings(db, &guild_id.to_string(), &settings).await
+        {
+            error!("Error saving server settings: {why:?}");
+            return Reply::from("Could not save the server settings.".to_string());
+        }
+
+        // Say right away if the bot can't post in the channels picked
+        let mut content = onboarding::summary(&settings);
+        let bot = ctx.cache.current_user().id;
+        for (channel, purpose) in picked {
+            if let Some(problem) = preflight::problem(&ctx.http, channel, bot, purpose).await {
+                content.push_str(&format!("\n⚠️ {}", problem));
+            }
+        }
+        Reply {
+            content,
+            components: Some(onboarding::components(guild_id, true)),
+            files: Vec::new(),
+        }
+    };
+    respond::command(ctx, db, command, reply).await;
+}
@@ -0,0 +1,54 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandOptionType, ResolvedOption, ResolvedValue};
+
+use crate::itaigi;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("contribute")
+        .description("Suggest a new Taigi translation to iTaigi")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "word", "Mandarin word to translate")
+                .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "hanji", "Taigi in Hanji")
+                .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "lomaji", "Pronunciation in Tâi-lô")
+                .required(true),
+        )
+}
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    itaigi_token: Option<&str>,
+    contributor: &str,
+) -> String {
+    let Some(token) = itaigi_token else {
+        return "iTaigi contributions are not configured on this bot.".to_string();
+    };
+
+    let option = |name: &str| {
+        options.iter().find_map(|option| match option.value {
+            ResolvedValue::String(value) if option.name == name => Some(value.trim()),
+            _ => None,
+        })
+    };
+
+    let (Some(word), Some(hanji), Some(lomaji)) = (option("word"), option("hanji"), option("lomaji"))
+    else {
+        return "Please provide the word, its Hanji and its Tâi-lô.".to_string();
+    };
+
+    match itaigi::contribute(token, word, hanji, lomaji, contributor).await {
+        Ok(()) => format!(
+            "🏷️ Sent {} → {} [{}] to iTaigi, thanks for contributing! - [iTaigi 愛台語](https://itaigi.tw/k/{})",
+            word,
+            hanji,
+            lomaji,
+            urlencoding::encode(word)
+        ),
+        Err(err) => format!("Could not contribute to iTaigi: {}", err),
+    }
+}
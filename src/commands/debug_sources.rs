@@ -0,0 +1,147 @@
+use std::future::Future;
+use std::time::Instant;
+
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::Permissions;
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+
+use crate::commands::admin;
+use crate::config::Config;
+use crate::context::Context;
+use crate::direction::Direction;
+use crate::places;
+use crate::plugin;
+use crate::query;
+use crate::respond;
+use crate::source::{self, Source};
+
+// For moderators and the bot owner: Discord only shows the command to
+// members who can manage messages. Every source is searched directly, even
+// disabled ones, without the cache or the latency stats.
+pub fn register() -> CreateCommand {
+    CreateCommand::new("debug-sources")
+        .description("Search every source for a keyword and show what each one parsed")
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "keyword", "Word to search for")
+                .required(true),
+        )
+}
+
+// Characters of the message the title may take, so the sources keep theirs
+const MAX_TITLE: usize = 200;
+
+// Run one source search, timing it
+async fn timed(
+    search: impl Future<Output = Result<Vec<String>, String>>,
+) -> (Result<Vec<String>, String>, u128) {
+    let started = Instant::now();
+    let result = search.await;
+    (result, started.elapsed().as_millis())
+}
+
+// A source's count and time, then its first result as parsed, in at most
+// `room` characters
+fn section(
+    source: Source,
+    enabled: bool,
+    (result, millis): (Result<Vec<String>, String>, u128),
+    room: usize,
+) -> String {
+    let disabled = if enabled { "" } else { " (disabled)" };
+    match result {
+        Ok(results) => {
+            let header = format!(
+                "**{}**{}: {} results in {} ms\n```\n",
+                source.name(),
+                disabled,
+                results.len(),
+                millis
+            );
+            let first = results.first().map_or("(nothing)".to_string(), |first| {
+                first.replace("```", "'''")
+            });
+            // The header and the closing fence take their part of the room
+            let room = room.saturating_sub(header.chars().count() + 4);
+            format!("{}{}\n```", header, respond::fit(&first, room))
+        }
+        Err(err) => respond::fit(
+            &format!("**{}**{}: failed in {} ms: {}", source.name(), disabled, millis, err),
+            room,
+        ),
+    }
+}
+
+async fn reply(ctx: &Context, command: &CommandInteraction, config: &Config) -> String {
+    let allowed = match &command.member {
+        Some(member) => member
+            .permissions
+            .is_some_and(|permissions| permissions.manage_messages()),
+        None => false,
+    };
+    if !allowed && !admin::is_owner(ctx, command.user.id).await {
+        return "Only moderators and the bot owner can use /debug-sources.".to_string();
+    }
+
+    let keyword = command
+        .data
+        .options()
+        .into_iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::String(value) if option.name == "keyword" => {
+                Some(query::normalize(value))
+            }
+            _ => None,
+        })
+        .unwrap_or_default();
+    if keyword.is_empty() {
+        return "Please give a keyword to search for.".to_string();
+    }
+
+    let limits = &config.limits;
+    let direction = Direction::detect(&keyword);
+    let sutian = source::search_sutian(&keyword, None, direction, limits.sutian);
+    let (taigitv, sutian, itaigi, moedict, plugins, places) = tokio::join!(
+        timed(source::search_taigitv(&keyword, limits.taigitv)),
+        timed(sutian),
+        timed(async {
+            source::search_itaigi(&keyword, limits.itaigi)
+                .await
+                .map(|(results, _)| results)
+        }),
+        timed(async {
+            source::search_moedict(&keyword, limits.moedict)
+                .await
+                .map(|(results, _)| results)
+        }),
+        timed(plugin::search(&keyword, limits.plugins)),
+        timed(places::search(&keyword)),
+    );
+
+    let sources = &config.sources;
+    let mut searched = vec![
+        (Source::TaigiTv, sources.taigitv, taigitv),
+        (Source::Sutian, sources.sutian, sutian),
+        (Source::ITaigi, sources.itaigi, itaigi),
+        (Source::Moedict, sources.moedict, moedict),
+    ];
+    if plugin::any() {
+        searched.push((Source::Plugins, sources.plugins, plugins));
+    }
+    searched.push((Source::Places, sources.places, places));
+
+    // Every source gets an equal share of one message, after the title
+    let title = respond::fit(&format!("🔬 {}", keyword), MAX_TITLE);
+    let room = (respond::MAX_MESSAGE.saturating_sub(title.chars().count()) / searched.len())
+        .saturating_sub(1);
+    let sections: Vec<String> = searched
+        .into_iter()
+        .map(|(source, enabled, result)| section(source, enabled, result, room))
+        .collect();
+    format!("{}\n{}", title, sections.join("\n"))
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, config: &Config, db: &SqlitePool) {
+    respond::command(ctx, db, command, reply(ctx, command, config)).await;
+}
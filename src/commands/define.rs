@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use serenity::builder::{
+    CreateAttachment, CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CreateInteractionResponseMessage, EditInteractionResponse,
+};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer::{self, Answer, Asker};
+#[cfg(feature = "cards")]
+use crate::breakdown;
+#[cfg(feature = "cards")]
+use crate::card::{self, Card};
+use crate::config::Config;
+use crate::context::Context;
+use crate::etymology;
+use crate::lookup::LookupPool;
+use crate::query::SearchOptions;
+use crate::related;
+use crate::respond;
+use crate::source::Source;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("define")
+        .description("Detailed entry for a word, with its origin")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "word", "Word to look up")
+                .required(true),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "card",
+            "Attach an image card for sharing",
+        ))
+}
+
+// The URL of a formatted result's `[Source](url)` link
+fn link(result: &str) -> Option<&str> {
+    let url = result.rsplit_once("](")?.1.strip_suffix(')')?;
+    // Compact links are wrapped in <...>
+    Some(url.trim_start_matches('<').trim_end_matches('>'))
+}
+
+// Origin notes from Sutian's 來源 field and Wiktionary, for loanwords mostly
+async fn origin(word: &str, sutian_url: Option<&str>) -> Vec<String> {
+    let (sutian, wiktionary) = tokio::join!(
+        async {
+            match sutian_url {
+                Some(url) => etymology::sutian_origin(url).await,
+                None => Ok(None),
+            }
+        },
+        etymology::wiktionary(word)
+    );
+
+    let mut lines = Vec::new();
+    match sutian {
+        Ok(Some(origin)) => lines.push(format!("📜 來源: {}", origin)),
+        Ok(None) => {}
+        Err(err) => error!("Error fetching Sutian origin: {err}"),
+    }
+    match wiktionary {
+        Ok(Some(etymology)) => lines.push(format!(
+            "📜 Wiktionary: {} - <https://en.wiktionary.org/wiki/{}#Chinese>",
+            etymology,
+            urlencoding::encode(word)
+        )),
+        Ok(None) => {}
+        Err(err) => error!("Error fetching Wiktionary etymology: {err}"),
+    }
+    lines
+}
+
+// A PNG card of the first result, with a Moedict gloss if there is one
+#[cfg(feature = "cards")]
+async fn share_card(
+    word: &str,
+    content: &str,
+    sources: &[Source],
+    config: &Config,
+) -> Result<CreateAttachment, String> {
+    let font = config
+        .card_font
+        .clone()
+        .ok_or("Share cards are disabled on this bot")?;
+
+    // Result lines follow the header line
+    let lines: Vec<&str> = content.lines().skip(1).take(sources.len()).collect();
+    let (first, headword) = lines
+        .iter()
+        .find_map(|line| Some((*line, breakdown::headword(line)?)))
+        .ok_or("No headword to put on the card")?;
+    let gloss = lines
+        .iter()
+        .zip(sources)
+        .filter(|(_, source)| **source == Source::Moedict)
+        .find_map(|(line, _)| breakdown::definition(line));
+
+    let card = Card {
+        word: word.to_string(),
+        hanji: headword.0.to_string(),
+        lomaji: headword.1.map(str::to_string),
+        gloss: gloss.map(str::to_string),
+        url: link(first).map(str::to_string),
+    };
+    // Fonts with CJK glyphs are large, and rendering is CPU bound
+    let png = tokio::task::spawn_blocking(move || {
+        let font_data = std::fs::read(&font).map_err(|_| format!("Cannot read {}", font))?;
+        card::render(&card, font_data)
+    })
+    .await
+    .map_err(|_| "Rendering the card failed".to_string())??;
+
+    Ok(CreateAttachment::bytes(png, format!("{}.png", word)))
+}
+
+#[cfg(not(feature = "cards"))]
+async fn share_card(
+    _word: &str,
+    _content: &str,
+    _sources: &[Source],
+    _config: &Config,
+) -> Result<CreateAttachment, String> {
+    Err("Share cards aren't built into this bot".to_string())
+}
+
+pub async fn run(
+    ctx: &Context,
+    command: &CommandInteraction,
+    config: &Arc<Config>,
+    pool: &LookupPool,
+    db: &SqlitePool,
+) {
+    let mut word = "";
+    let mut want_card = false;
+    for option in command.data.options() {
+        match (option.name, option.value) {
+            ("word", ResolvedValue::String(value)) => word = value.trim(),
+            ("card", ResolvedValue::Boolean(value)) => want_card = value,
+            _ => {}
+        }
+    }
+
+    // Lookups can take longer than the 3 seconds Discord waits for a response
+    let defer = CreateInteractionResponseMessage::new();
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Defer(defer))
+        .await
+    {
+        error!("Cannot defer /define: {why:?}");
+        return;
+    }
+
+    let asker = Asker {
+        user_id: command.user.id,
+        channel_id: command.channel_id,
+        guild_id: command.guild_id,
+        prefs: answer::load_prefs(db, command.user.id, command.guild_id).await,
+        guild: answer::load_guild(db, command.guild_id).await,
+    };
+    let answer = answer::answer(db, pool, config, &asker, word, &SearchOptions::default()).await;
+    let mut components = answer.components(config, true);
+
+    let mut attachment = None;
+    let content = match answer {
+        Answer::Found {
+            content, sources, ..
+        } => {
+            let mut card_error = None;
+            if want_card {
+                match share_card(word, &content, &sources, config).await {
+                    Ok(card) => attachment = Some(card),
+                    Err(err) => card_error = Some(err),
+                }
+            }
+            // Result lines follow the header line
+            let sutian_url = content
+                .lines()
+                .skip(1)
+                .zip(&sources)
+                .find(|(_, source)| **source == Source::Sutian)
+                .and_then(|(line, _)| link(line));
+            let mut origin = origin(word, sutian_url).await;
+            if let Some(err) = card_error {
+                origin.push(format!("⚠️ No share card: {}", err));
+            }
+            // Messages take at most 5 rows of buttons
+            let related = related::words(db, word).await;
+            if let Some(row) = related::buttons(&related).filter(|_| components.len() < 5) {
+                origin.push(format!("🔗 Related: {}", related.join(" · ")));
+                components.push(row);
+            }
+            if origin.is_empty() {
+                content
+            } else {
+                format!("{}\n\n{}", content, origin.join("\n"))
+            }
+        }
+        Answer::Breakdown { content, .. }
+        | Answer::Failed(content)
+        | Answer::NotFound { content, .. } => content,
+    };
+
+    let content = respond::fit_message(&content);
+    let mut edit = EditInteractionResponse::new()
+        .content(content)
+        .components(components);
+    if let Some(card) = attachment {
+        edit = edit.new_attachment(card);
+    }
+    if let Err(why) = command.edit_response(&ctx.http, edit).await {
+        error!("Cannot respond to /define: {why:?}");
+    }
+}
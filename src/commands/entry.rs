@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use serenity::builder::{
+    CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::config::Config;
+use crate::context::Context;
+use crate::db;
+use crate::links;
+use crate::respond;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("entry")
+        .description("Show a looked up entry again by its ID")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "id", "ID under the results, e.g. tg#4f2a")
+                .required(true),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "private",
+            "Only show the entry to you",
+        ))
+}
+
+// Replays the stored results without searching the sources again
+pub async fn run(ctx: &Context, command: &CommandInteraction, config: &Arc<Config>, db: &SqlitePool) {
+    let mut id = "";
+    let mut private = None;
+    for option in command.data.options() {
+        match (option.name, option.value) {
+            ("id", ResolvedValue::String(value)) => id = value.trim(),
+            ("private", ResolvedValue::Boolean(value)) => private = Some(value),
+            _ => {}
+        }
+    }
+    let id = id.trim_start_matches("tg#").to_lowercase();
+
+    let prefs = answer::load_prefs(db, command.user.id, command.guild_id).await;
+    let guild = answer::load_guild(db, command.guild_id).await;
+    let locale = prefs.locale.or(guild.locale).unwrap_or(config.locale);
+    let private = private.or(prefs.private).unwrap_or(false);
+
+    let content = match db::entry(db, &id).await {
+        Ok(Some((keyword, lookup))) => {
+            let emoji = config.emoji.for_guild(command.guild_id);
+            let results: Vec<String> = lookup
+                .results
+                .iter()
+                .zip(&lookup.sources)
+                .filter(|(_, source)| guild.shows(**source))
+                .map(|(result, source)| emoji.apply(&prefs.format_result(result), *source))
+                .collect();
+            let searched = locale.searched(&keyword, None);
+            if results.is_empty() {
+                locale.not_found(&searched)
+            } else {
+                format!(
+                    "{}\n{}",
+                    locale.entry(&id, &searched),
+                    links::render(&results, config.link_style).join("\n")
+                )
+            }
+        }
+        Ok(None) => locale.unknown_entry(&id),
+        Err(why) => {
+            error!("Error loading entry {}: {why:?}", id);
+            "Could not load the entry.".to_string()
+        }
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .content(respond::fit_message(&prefs.render(&content)))
+        .ephemeral(private);
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        error!("Cannot respond to /entry: {why:?}");
+    }
+}
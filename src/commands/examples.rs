@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use serenity::builder::{
+    CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CreateInteractionResponseMessage, EditInteractionResponse,
+};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::config::Config;
+use crate::context::Context;
+use crate::corpus::{self, Sentence};
+use crate::respond;
+use crate::source;
+
+// Sentences shown per reply, dictionary ones first
+const MAX_EXAMPLES: usize = 6;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("examples")
+        .description("Example sentences using a word")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "keyword", "Taigi or Mandarin word")
+                .required(true),
+        )
+}
+
+// `你食飽未？ *Lí tsia̍h-pá buē?* (你吃飽了沒？)`
+fn line(sentence: &Sentence, origin: &str) -> String {
+    let mut line = format!("• {}", sentence.hanji);
+    if !sentence.lomaji.is_empty() {
+        line.push_str(&format!(" *{}*", sentence.lomaji));
+    }
+    if !sentence.mandarin.is_empty() {
+        line.push_str(&format!(" ({})", sentence.mandarin));
+    }
+    line.push_str(&format!(" — {}", origin));
+    line
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, config: &Arc<Config>, db: &SqlitePool) {
+    let mut keyword = "";
+    for option in command.data.options() {
+        if let ("keyword", ResolvedValue::String(value)) = (option.name, option.value) {
+            keyword = value.trim();
+        }
+    }
+
+    // Moedict can take longer than the 3 seconds Discord waits for a response
+    let defer = CreateInteractionResponseMessage::new();
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Defer(defer))
+        .await
+    {
+        error!("Cannot defer /examples: {why:?}");
+        return;
+    }
+
+    let prefs = answer::load_prefs(db, command.user.id, command.guild_id).await;
+    let guild = answer::load_guild(db, command.guild_id).await;
+    let locale = prefs.locale.or(guild.locale).unwrap_or(config.locale);
+
+    let dictionary = match source::moedict_examples(keyword, MAX_EXAMPLES).await {
+        Ok(sentences) => sentences,
+        Err(err) => {
+            error!("Error fetching Moedict examples: {}", err);
+            Vec::new()
+        }
+    };
+    let mut lines: Vec<String> = dictionary
+        .iter()
+        .map(|sentence| line(sentence, "萌典"))
+        .collect();
+    // The corpus fills in for words the dictionary has no examples for
+    for sentence in corpus::search(keyword, MAX_EXAMPLES - lines.len()) {
+        if !dictionary.iter().any(|known| known.hanji == sentence.hanji) {
+            lines.push(line(sentence, "例句語料庫"));
+        }
+    }
+
+    let searched = locale.searched(keyword, None);
+    let content = if lines.is_empty() {
+        locale.no_examples(&searched)
+    } else {
+        format!("{}\n{}", locale.examples(&searched), lines.join("\n"))
+    };
+    let content = respond::fit_message(&prefs.render(&content));
+    let edit = EditInteractionResponse::new().content(content);
+    if let Err(why) = command.edit_response(&ctx.http, edit).await {
+        error!("Cannot respond to /examples: {why:?}");
+    }
+}
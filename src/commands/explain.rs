@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use serenity::builder::{
+    CreateCommand, CreateInteractionResponse, CreateInteractionResponseMessage,
+    EditInteractionResponse,
+};
+use serenity::model::application::{CommandInteraction, CommandType, ResolvedTarget};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer::{self, Answer, Asker};
+use crate::config::Config;
+use crate::context::Context;
+use crate::lookup::LookupPool;
+use crate::passive;
+use crate::query::SearchOptions;
+use crate::respond;
+
+pub const NAME: &str = "Explain Taigi words";
+
+// Listed under Apps on a message, like the 📖 passive channels leave on ones
+// with Taigi words
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME).kind(CommandType::Message)
+}
+
+// The Taigi words of a message, explained only to whoever asked
+pub async fn run(
+    ctx: &Context,
+    command: &CommandInteraction,
+    config: &Arc<Config>,
+    pool: &LookupPool,
+    db: &SqlitePool,
+) {
+    let words = match command.data.target() {
+        Some(ResolvedTarget::Message(message)) => passive::detect(&message.content),
+        _ => Vec::new(),
+    };
+
+    // Lookups can take longer than the 3 seconds Discord waits for a response
+    let defer = CreateInteractionResponseMessage::new().ephemeral(true);
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Defer(defer))
+        .await
+    {
+        error!("Cannot defer {}: {why:?}", NAME);
+        return;
+    }
+
+    let asker = Asker {
+        user_id: command.user.id,
+        channel_id: command.channel_id,
+        guild_id: command.guild_id,
+        prefs: answer::load_prefs(db, command.user.id, command.guild_id).await,
+        guild: answer::load_guild(db, command.guild_id).await,
+    };
+    // Only the asker sees it, so words a server only answers privately are fine
+    let options = SearchOptions {
+        private: true,
+        ..SearchOptions::default()
+    };
+    let mut sections = Vec::new();
+    for word in words {
+        match answer::answer(db, pool, config, &asker, &word, &options).await {
+            Answer::Breakdown { content, .. }
+            | Answer::Found { content, .. }
+            | Answer::Failed(content)
+            | Answer::NotFound { content, .. } => sections.push(content),
+        }
+    }
+    let content = if sections.is_empty() {
+        "No Taigi words found in this message.".to_string()
+    } else {
+        respond::fit_message(&sections.join("\n\n"))
+    };
+
+    let edit = EditInteractionResponse::new().content(content);
+    if let Err(why) = command.edit_response(&ctx.http, edit).await {
+        error!("Cannot respond to {}: {why:?}", NAME);
+    }
+}
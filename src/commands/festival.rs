@@ -0,0 +1,56 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+
+use crate::context::Context;
+use crate::festival;
+use crate::respond;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("festival")
+        .description("A festival's Taigi name and the words that go with it")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "festival",
+                "A festival like 中秋節 or mid-autumn, or a date like 9/25; the next one if unset",
+            )
+            .max_length(50),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    let mut wanted = "";
+    for option in command.data.options() {
+        if let ("festival", ResolvedValue::String(value)) = (option.name, option.value) {
+            wanted = value.trim();
+        }
+    }
+
+    let reply = async {
+        let today = festival::today();
+        let found = if wanted.is_empty() {
+            festival::upcoming(today)
+        } else if let Some(found) = festival::find(wanted) {
+            Some(found)
+        } else if let Some(date) = festival::parse_date(wanted, today) {
+            let found = festival::on(date);
+            if found.is_none() {
+                let (year, month, day) = date;
+                let next = match festival::upcoming(date) {
+                    Some(next) => format!(", the next is {}", next.hanji),
+                    None => String::new(),
+                };
+                return format!("🏮 No festival on {:04}-{:02}-{:02}{}.", year, month, day, next);
+            }
+            found
+        } else {
+            return format!("❓ No festival called {}.\n\n{}", wanted, festival::overview());
+        };
+        let Some(found) = found else {
+            return festival::overview();
+        };
+        found.card(today)
+    };
+    respond::command(ctx, db, command, reply).await;
+}
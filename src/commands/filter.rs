@@ -0,0 +1,120 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::Permissions;
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::context::Context;
+use crate::db;
+use crate::filter::{self, Action};
+use crate::respond;
+
+// For moderators: Discord only shows the command to members who can manage
+// messages, which server admins can adjust under Integrations. Replies are
+// ephemeral so the list isn't posted in the channel.
+pub fn register() -> CreateCommand {
+    let word = || {
+        CreateCommandOption::new(CommandOptionType::String, "word", "Word or part of a query")
+            .required(true)
+    };
+    CreateCommand::new("filter")
+        .description("Words this server's lookups refuse or only answer privately")
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                "Filter queries containing a word",
+            )
+            .add_sub_option(word())
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "action",
+                    "What happens to those queries, refused if left out",
+                )
+                .add_string_choice("Refuse", Action::Refuse.code())
+                .add_string_choice("Only answer privately", Action::Private.code()),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Stop filtering a word")
+                .add_sub_option(word()),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "Show the filtered words",
+        ))
+}
+
+async fn reply(command: &CommandInteraction, db: &SqlitePool) -> String {
+    let Some(guild_id) = command.guild_id else {
+        return "/filter only works in servers.".to_string();
+    };
+    let guild_id = guild_id.to_string();
+    let Some(subcommand) = command.data.options().into_iter().next() else {
+        return "Unknown command.".to_string();
+    };
+    let ResolvedValue::SubCommand(options) = subcommand.value else {
+        return "Unknown command.".to_string();
+    };
+    let mut word = None;
+    let mut action = Action::Refuse;
+    for option in &options {
+        match (option.name, &option.value) {
+            ("word", ResolvedValue::String(value)) => word = Some(filter::normalize(value)),
+            ("action", ResolvedValue::String(value)) => {
+                action = Action::parse(value).unwrap_or(Action::Refuse)
+            }
+            _ => {}
+        }
+    }
+    let word = word.filter(|word| !word.is_empty());
+
+    let result = match (subcommand.name, word) {
+        ("list", _) => db::filtered_words(db, &guild_id).await.map(|words| {
+            if words.is_empty() {
+                return "No words are filtered in this server.".to_string();
+            }
+            let lines: Vec<String> = words
+                .iter()
+                .map(|(word, action)| match Action::parse(action) {
+                    Some(Action::Private) => format!("• ||{}|| (only answered privately)", word),
+                    _ => format!("• ||{}|| (refused)", word),
+                })
+                .collect();
+            format!("Filtered words:\n{}", lines.join("\n"))
+        }),
+        ("add", Some(word)) => {
+            let added_by = command.user.id.to_string();
+            db::filter_word(db, &guild_id, &word, action.code(), &added_by)
+                .await
+                .map(|()| match action {
+                    Action::Refuse => format!("Queries containing ||{}|| are now refused.", word),
+                    Action::Private => format!(
+                        "Queries containing ||{}|| are now only answered privately.",
+                        word
+                    ),
+                })
+        }
+        ("remove", Some(word)) => db::unfilter_word(db, &guild_id, &word).await.map(|removed| {
+            if removed {
+                format!("||{}|| is no longer filtered.", word)
+            } else {
+                format!("||{}|| wasn't filtered.", word)
+            }
+        }),
+        (_, None) => Ok("Please give the word to filter.".to_string()),
+        _ => Ok("Unknown command.".to_string()),
+    };
+    result.unwrap_or_else(|why| {
+        error!("Error updating the word filter: {why:?}");
+        "Could not update the word filter.".to_string()
+    })
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    respond::command(ctx, db, command, reply(command, db)).await;
+}
@@ -0,0 +1,42 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+
+use crate::context::Context;
+use crate::frequency::{self, MAX_LISTED};
+use crate::respond::{self, Reply};
+
+const DEFAULT_COUNT: usize = 50;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("frequent")
+        .description("The most common Taigi words, as a study list")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "count",
+                "How many words, 50 if left out",
+            )
+            .min_int_value(1)
+            .max_int_value(MAX_LISTED as u64),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    let mut count = DEFAULT_COUNT;
+    for option in command.data.options() {
+        if let ("count", ResolvedValue::Integer(value)) = (option.name, option.value) {
+            count = usize::try_from(value).unwrap_or(DEFAULT_COUNT);
+        }
+    }
+
+    let reply = async {
+        let (content, row) = frequency::study_list(count);
+        Reply {
+            content,
+            components: Some(vec![row]),
+            files: Vec::new(),
+        }
+    };
+    respond::command(ctx, db, command, reply).await;
+}
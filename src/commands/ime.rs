@@ -0,0 +1,87 @@
+use serenity::builder::{
+    CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::context::Context;
+use crate::ime::{self, Spelling};
+
+// Spellings listed for the whole phrase, and per word when the phrase as a
+// whole isn't known
+const MAX_CANDIDATES: usize = 5;
+const MAX_PER_WORD: usize = 3;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("ime")
+        .description("Hanji spellings for a romanized Taigi word or phrase")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "romanized",
+                "Tâi-lô, with or without tone marks, e.g. tsia̍h-pn̄g",
+            )
+            .required(true),
+        )
+}
+
+// `食飯 tsia̍h-pn̄g (×12)`
+fn spelling(spelling: &Spelling) -> String {
+    format!("{} {} (×{})", spelling.hanji, spelling.lomaji, spelling.count)
+}
+
+fn reply(romanized: &str) -> String {
+    let whole = ime::candidates(romanized, MAX_CANDIDATES);
+    if !whole.is_empty() {
+        let lines: Vec<String> = whole
+            .iter()
+            .enumerate()
+            .map(|(idx, found)| format!("{}. {}", idx + 1, spelling(found)))
+            .collect();
+        return format!("⌨️ Hanji for *{}*:\n{}", romanized, lines.join("\n"));
+    }
+
+    // A phrase nobody wrote down as such may still be known word by word
+    let words: Vec<&str> = romanized.split_whitespace().collect();
+    let lines: Vec<String> = words
+        .iter()
+        .map(|word| {
+            let found = ime::candidates(word, MAX_PER_WORD);
+            if found.is_empty() {
+                format!("*{}* → ？", word)
+            } else {
+                let spellings: Vec<String> =
+                    found.iter().map(|found| spelling(found)).collect();
+                format!("*{}* → {}", word, spellings.join(" / "))
+            }
+        })
+        .collect();
+    if words.len() < 2 || !lines.iter().any(|line| !line.ends_with('？')) {
+        return format!(
+            "❌ No Hanji known for *{}*. Try `/taigi search` to search the dictionaries.",
+            romanized
+        );
+    }
+    format!("⌨️ Hanji for *{}*, word by word:\n{}", romanized, lines.join("\n"))
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    let mut romanized = "";
+    for option in command.data.options() {
+        if let ("romanized", ResolvedValue::String(value)) = (option.name, option.value) {
+            romanized = value.trim();
+        }
+    }
+
+    let prefs = answer::load_prefs(db, command.user.id, command.guild_id).await;
+    let data = CreateInteractionResponseMessage::new().content(prefs.render(&reply(romanized)));
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        error!("Cannot respond to /ime: {why:?}");
+    }
+}
@@ -0,0 +1,71 @@
+use serenity::builder::{CreateAttachment, CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+
+#[cfg(feature = "cards")]
+use crate::card;
+use crate::config::Config;
+use crate::context::Context;
+use crate::keyboard;
+use crate::respond::{self, Reply};
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("keyboard")
+        .description("Printable cheat sheet for typing Tâi-lô tone marks")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "format", "Image or text")
+                .add_string_choice("Image", "image")
+                .add_string_choice("Text", "text"),
+        )
+}
+
+// The sheet drawn with the share card font
+#[cfg(feature = "cards")]
+async fn image(config: &Config) -> Result<CreateAttachment, String> {
+    let font = config
+        .card_font
+        .clone()
+        .ok_or("Images are disabled on this bot")?;
+    let png = tokio::task::spawn_blocking(move || {
+        let font_data = std::fs::read(&font).map_err(|_| format!("Cannot read {}", font))?;
+        card::render_sheet(keyboard::sheet(), font_data)
+    })
+    .await
+    .map_err(|_| "Rendering the cheat sheet failed".to_string())??;
+    Ok(CreateAttachment::bytes(png, "taigi-keyboard.png"))
+}
+
+#[cfg(not(feature = "cards"))]
+async fn image(_config: &Config) -> Result<CreateAttachment, String> {
+    Err("Images aren't built into this bot".to_string())
+}
+
+// Posted publicly like /legend. Falls back to text when there's no image.
+pub async fn run(ctx: &Context, command: &CommandInteraction, config: &Config, db: &SqlitePool) {
+    let text_only = command.data.options().iter().any(|option| {
+        option.name == "format" && matches!(option.value, ResolvedValue::String("text"))
+    });
+    // Drawing the image can take a while, so it's done behind respond's defer
+    let reply = async {
+        let text = || CreateAttachment::bytes(keyboard::text().into_bytes(), "taigi-keyboard.txt");
+        let (attachment, note) = if text_only {
+            (text(), None)
+        } else {
+            match image(config).await {
+                Ok(image) => (image, None),
+                Err(err) => (text(), Some(err)),
+            }
+        };
+
+        let mut content = format!("⌨️ {}", keyboard::sheet().title);
+        if let Some(note) = note {
+            content.push_str(&format!(" (as text: {})", note));
+        }
+        Reply {
+            content,
+            components: None,
+            files: vec![attachment],
+        }
+    };
+    respond::public(ctx, db, command, reply).await;
+}
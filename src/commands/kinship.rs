@@ -0,0 +1,38 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+
+use crate::context::Context;
+use crate::kinship;
+use crate::respond;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("kinship")
+        .description("Where a Taigi kinship term sits in the family, with the terms around it")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "term",
+                "A term like a-kong or 阿公, or a Mandarin one like 舅舅",
+            )
+            .max_length(50)
+            .required(true),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    let mut wanted = "";
+    for option in command.data.options() {
+        if let ("term", ResolvedValue::String(value)) = (option.name, option.value) {
+            wanted = value;
+        }
+    }
+
+    let reply = async {
+        let Some(term) = kinship::find(wanted) else {
+            return format!("❓ No kinship term {}.\n\n{}", wanted.trim(), kinship::overview());
+        };
+        kinship::diagram(term)
+    };
+    respond::command(ctx, db, command, reply).await;
+}
@@ -0,0 +1,27 @@
+use serenity::builder::{
+    CreateCommand, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+use serenity::model::application::CommandInteraction;
+use tracing::error;
+
+use crate::context::Context;
+use crate::romanization::CHEAT_SHEET;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("legend").description("Cheat sheet for reading Tâi-lô and POJ")
+}
+
+// Posted publicly so it can be pointed at in a lookup channel
+pub async fn run(ctx: &Context, command: &CommandInteraction) {
+    let embed = CreateEmbed::new()
+        .title("❓ Reading Taigi romanization")
+        .description("Tâi-lô is what the results use; Pe̍h-ōe-jī (POJ) can be picked with `/prefs`.")
+        .fields(CHEAT_SHEET.map(|(title, text)| (title, text, false)));
+    let data = CreateInteractionResponseMessage::new().embed(embed);
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        error!("Cannot respond to /legend: {why:?}");
+    }
+}
@@ -0,0 +1,98 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use serenity::model::channel::ChannelType;
+use sqlx::SqlitePool;
+
+use crate::answer;
+use crate::context::Context;
+use crate::listen;
+use crate::respond;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("listen")
+        .description("Play word pronunciations in a voice channel for passive listening")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "start",
+                "Join a voice channel and play words until stopped",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Channel,
+                    "channel",
+                    "Voice channel to play in, yours if left out",
+                )
+                .channel_types(vec![ChannelType::Voice, ChannelType::Stage]),
+            ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "stop",
+            "End the session and leave the voice channel",
+        ))
+}
+
+async fn reply(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) -> String {
+    let Some(guild_id) = command.guild_id else {
+        return "/listen only works in servers.".to_string();
+    };
+    // Songbird lives on the gateway connection
+    let Some(gateway) = &ctx.gateway else {
+        return "/listen needs the bot's gateway connection.".to_string();
+    };
+    let Some(manager) = songbird::get(gateway).await else {
+        return "Voice isn't set up on this bot.".to_string();
+    };
+    let Some(subcommand) = command.data.options().into_iter().next() else {
+        return "Unknown command.".to_string();
+    };
+
+    match (subcommand.name, subcommand.value) {
+        ("start", ResolvedValue::SubCommand(options)) => {
+            let picked = options.iter().find_map(|option| match option.value {
+                ResolvedValue::Channel(channel) => Some(channel.id),
+                _ => None,
+            });
+            // The asker's own voice channel otherwise
+            let voice_channel = picked.or_else(|| {
+                let guild = guild_id.to_guild_cached(&ctx.cache)?;
+                guild.voice_states.get(&command.user.id)?.channel_id
+            });
+            let Some(voice_channel) = voice_channel else {
+                return "Join a voice channel first, or pick one with `channel:`.".to_string();
+            };
+            // Captions are for everyone in the channel, so the server's romanization
+            let romanization = answer::load_guild(db, Some(guild_id)).await.romanization;
+            match listen::start(
+                ctx.http.clone(),
+                manager,
+                guild_id,
+                voice_channel,
+                command.channel_id,
+                romanization.unwrap_or_default(),
+            )
+            .await
+            {
+                Ok(()) => format!(
+                    "🎧 Playing words in <#{}>, captioned here. `/listen stop` ends it.",
+                    voice_channel
+                ),
+                Err(err) => err,
+            }
+        }
+        ("stop", _) => {
+            if listen::stop(&manager, guild_id).await {
+                "🎧 The listening session is over.".to_string()
+            } else {
+                "No listening session is running here.".to_string()
+            }
+        }
+        _ => "Unknown command.".to_string(),
+    }
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    respond::command(ctx, db, command, reply(ctx, command, db)).await;
+}
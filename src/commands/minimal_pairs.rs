@@ -0,0 +1,71 @@
+use serenity::builder::{
+    CreateActionRow, CreateAttachment, CreateCommand, CreateCommandOption,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::audio;
+use crate::context::Context;
+use crate::practice::{self, Contrast};
+
+// Pairs tried before giving up when Moedict has no recording
+const ATTEMPTS: usize = 3;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("minimal-pairs")
+        .description("Hear one of two similar words and pick which it was")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "contrast", "What the words differ in")
+                .add_string_choice("Tone", "tone")
+                .add_string_choice("Aspiration (p/ph, t/th, k/kh, ts/tsh)", "aspiration"),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    let mut kind = None;
+    for option in command.data.options() {
+        if let ("contrast", ResolvedValue::String(value)) = (option.name, option.value) {
+            kind = Contrast::parse(value);
+        }
+    }
+
+    // Fetching the recording can take longer than Discord waits for a response
+    let defer = CreateInteractionResponseMessage::new().ephemeral(true);
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Defer(defer))
+        .await
+    {
+        error!("Cannot defer /minimal-pairs: {why:?}");
+        return;
+    }
+
+    let prefs = answer::load_prefs(db, command.user.id, command.guild_id).await;
+    let pairs = practice::pairs(kind);
+    let mut edit =
+        EditInteractionResponse::new().content("🔇 Could not fetch a recording, try again.");
+    for _ in 0..ATTEMPTS.min(pairs.len()) {
+        let pair = pairs[practice::random(pairs.len())];
+        let played = practice::random(2);
+        let word = if played == 0 { pair.0 } else { pair.1 };
+        match audio::moedict(word.hanji).await {
+            Ok(Some(audio)) => {
+                edit = EditInteractionResponse::new()
+                    .content("🎧 Which word did you hear?")
+                    .new_attachment(CreateAttachment::bytes(audio.bytes, audio.filename))
+                    .components(vec![CreateActionRow::Buttons(practice::pair_buttons(
+                        pair, played, &prefs,
+                    ))]);
+                break;
+            }
+            Ok(None) => continue,
+            Err(err) => error!("Error fetching audio for {}: {}", word.hanji, err),
+        }
+    }
+
+    if let Err(why) = command.edit_response(&ctx.http, edit).await {
+        error!("Cannot respond to /minimal-pairs: {why:?}");
+    }
+}
@@ -0,0 +1,35 @@
+pub mod admin;
+pub mod annotate;
+pub mod browse;
+pub mod chars;
+pub mod compare;
+pub mod config;
+pub mod contribute;
+pub mod debug_sources;
+pub mod define;
+pub mod entry;
+pub mod examples;
+pub mod explain;
+pub mod festival;
+pub mod filter;
+pub mod frequent;
+pub mod ime;
+pub mod keyboard;
+pub mod kinship;
+pub mod legend;
+#[cfg(feature = "voice")]
+pub mod listen;
+pub mod minimal_pairs;
+pub mod name;
+pub mod phrasebook;
+pub mod prefs;
+pub mod recall;
+pub mod rhyme;
+pub mod saved;
+pub mod search;
+pub mod stats;
+pub mod status;
+pub mod taigi;
+pub mod tonedrill;
+pub mod tutorial;
+pub mod vocab;
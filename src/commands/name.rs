@@ -0,0 +1,64 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+
+use crate::context::Context;
+use crate::lookup::LookupPool;
+use crate::names::{self, Part};
+use crate::respond;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("name")
+        .description("Read a Chinese personal name in Taigi, e.g. 陳小明")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "name", "Surname and given name")
+                .max_length(20)
+                .required(true),
+        )
+}
+
+pub async fn run(
+    ctx: &Context,
+    command: &CommandInteraction,
+    pool: &LookupPool,
+    db: &SqlitePool,
+) {
+    let mut wanted = "";
+    for option in command.data.options() {
+        if let ("name", ResolvedValue::String(value)) = (option.name, option.value) {
+            wanted = value;
+        }
+    }
+
+    let reply = async {
+        let name = match names::read(pool, wanted).await {
+            Ok(name) => name,
+            Err(err) => return err,
+        };
+        let part = |part: &Part| match &part.lomaji {
+            Some(lomaji) => format!("{} {}", part.hanji, lomaji),
+            None => format!("{} ?", part.hanji),
+        };
+
+        let hanji: String = std::iter::once(&name.surname)
+            .chain(&name.given)
+            .map(|part| part.hanji.as_str())
+            .collect();
+        let mut lines = vec![format!("🪪 **{}** {}", hanji, name.lomaji())];
+        let mut parts = vec![format!("{} (surname)", part(&name.surname))];
+        parts.extend(name.given.iter().map(part));
+        lines.push(parts.join(" · "));
+        if !name.known_surname {
+            lines.push(format!(
+                "{} isn't in the surname list, so it's read like the given name, in its \
+                 literary reading.",
+                name.surname.hanji
+            ));
+        }
+        if name.given.iter().chain([&name.surname]).any(|part| part.lomaji.is_none()) {
+            lines.push("Moedict has no reading for the characters marked ?.".to_string());
+        }
+        lines.join("\n")
+    };
+    respond::command(ctx, db, command, reply).await;
+}
@@ -0,0 +1,177 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::context::Context;
+use crate::db::{self, GuildPhrase};
+use crate::filter::{self, Action};
+use crate::phrasebook;
+use crate::respond::{self, Reply};
+use crate::text;
+
+// Everyone can read the phrasebook; adding and removing a server's own
+// phrases is for members who can manage messages
+pub fn register() -> CreateCommand {
+    let text = |name: &str, description: &str| {
+        CreateCommandOption::new(CommandOptionType::String, name, description)
+            .max_length(200)
+            .required(true)
+    };
+    CreateCommand::new("phrasebook")
+        .description("Everyday Taigi phrases for situations like greetings or ordering food")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "show",
+                "The phrases for a situation",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "situation",
+                    "e.g. greetings, food or directions; leave out to see them all",
+                )
+                .max_length(50),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                "Add a phrase to this server's phrasebook (moderators)",
+            )
+            .add_sub_option(text("situation", "e.g. greetings, or a new one like market"))
+            .add_sub_option(text("hanji", "The phrase in Hanji"))
+            .add_sub_option(text("lomaji", "The phrase in Tâi-lô"))
+            .add_sub_option(text("english", "What it means"))
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "audio",
+                    "A word of the phrase for its 🔊 button to play, e.g. 食飽",
+                )
+                .max_length(20),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "remove",
+                "Remove a phrase this server added (moderators)",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "id",
+                    "The phrase's number after # in /phrasebook show",
+                )
+                .required(true),
+            ),
+        )
+}
+
+async fn reply(command: &CommandInteraction, db: &SqlitePool) -> Reply {
+    let Some(subcommand) = command.data.options().into_iter().next() else {
+        return Reply::from("Unknown command.".to_string());
+    };
+    let ResolvedValue::SubCommand(options) = subcommand.value else {
+        return Reply::from("Unknown command.".to_string());
+    };
+    let mut fields = GuildPhrase {
+        id: 0,
+        situation: String::new(),
+        hanji: String::new(),
+        lomaji: String::new(),
+        english: String::new(),
+        audio: None,
+    };
+    for option in &options {
+        match (option.name, &option.value) {
+            ("situation", ResolvedValue::String(value)) => fields.situation = value.to_string(),
+            ("hanji", ResolvedValue::String(value)) => fields.hanji = value.trim().to_string(),
+            ("lomaji", ResolvedValue::String(value)) => fields.lomaji = text::nfc(value.trim()),
+            ("english", ResolvedValue::String(value)) => fields.english = value.trim().to_string(),
+            ("audio", ResolvedValue::String(value)) => {
+                fields.audio = Some(value.trim().to_string()).filter(|word| !word.is_empty())
+            }
+            ("id", ResolvedValue::Integer(value)) => fields.id = *value,
+            _ => {}
+        }
+    }
+    let guild_id = command.guild_id.map(|guild_id| guild_id.to_string());
+
+    if subcommand.name == "show" {
+        let added = match &guild_id {
+            Some(guild_id) => db::guild_phrases(db, guild_id).await.unwrap_or_else(|why| {
+                error!("Error loading the server's phrases: {why:?}");
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        // Phrases added before a word was filtered
+        let mut shown = Vec::new();
+        for phrase in added {
+            if filter::check(db, command.guild_id, &phrase.hanji).await != Some(Action::Refuse) {
+                shown.push(phrase);
+            }
+        }
+        let added = shown;
+        if fields.situation.trim().is_empty() {
+            return Reply::from(phrasebook::overview(&added));
+        }
+        return phrasebook::page(&fields.situation, &added).unwrap_or_else(|| {
+            Reply::from(format!(
+                "There's no situation \"{}\".\n{}",
+                fields.situation.trim(),
+                phrasebook::overview(&added)
+            ))
+        });
+    }
+
+    let Some(guild_id) = guild_id else {
+        return Reply::from("The phrasebook can only be changed in servers.".to_string());
+    };
+    let allowed = command
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.manage_messages());
+    if !allowed {
+        return Reply::from("Only moderators can change the server's phrasebook.".to_string());
+    }
+
+    let result = match subcommand.name {
+        "add" => {
+            for text in [Some(&fields.hanji), fields.audio.as_ref()].into_iter().flatten() {
+                if filter::check(db, command.guild_id, text).await == Some(Action::Refuse) {
+                    return Reply::from(format!("🚫 {} has a word this server filters.", text));
+                }
+            }
+            fields.situation = phrasebook::situation_code(&fields.situation);
+            let added_by = command.user.id.to_string();
+            db::add_guild_phrase(db, &guild_id, &fields, &added_by).await.map(|id| {
+                format!(
+                    "Added {} to {} as #{}, see `/phrasebook show {}`.",
+                    fields.hanji, fields.situation, id, fields.situation
+                )
+            })
+        }
+        "remove" => db::remove_guild_phrase(db, &guild_id, fields.id).await.map(|removed| {
+            if removed {
+                format!("Removed phrase #{}.", fields.id)
+            } else {
+                format!("This server has no phrase #{}.", fields.id)
+            }
+        }),
+        _ => Ok("Unknown command.".to_string()),
+    };
+    Reply::from(result.unwrap_or_else(|why| {
+        error!("Error updating the server's phrasebook: {why:?}");
+        "Could not update the phrasebook.".to_string()
+    }))
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    respond::command(ctx, db, command, reply(command, db)).await;
+}
@@ -0,0 +1,145 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandOptionType, ResolvedOption, ResolvedValue};
+use serenity::model::id::UserId;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::db::{self, UserPrefs};
+use crate::i18n::Locale;
+use crate::romanization::Romanization;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("prefs")
+        .description("Your own defaults for lookups")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "show",
+            "Show your current preferences",
+        ))
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "set", "Change your preferences")
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "romanization",
+                        "How pronunciations are written",
+                    )
+                    .add_string_choice("Tâi-lô", "tailo")
+                    .add_string_choice("Pe̍h-ōe-jī (POJ)", "poj"),
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "ipa",
+                    "Also show pronunciations in IPA",
+                ))
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "limit",
+                        "Maximum number of results to show",
+                    )
+                    .min_int_value(1)
+                    .max_int_value(20),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "language",
+                        "Language the bot replies to you in",
+                    )
+                    .add_string_choice("English", "en")
+                    .add_string_choice("繁體中文", "zh-TW"),
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "private",
+                    "Show /taigi results only to you",
+                ))
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "english",
+                    "Also show English meanings",
+                )),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "reset",
+            "Go back to the server defaults",
+        ))
+}
+
+fn describe(prefs: &UserPrefs) -> String {
+    let romanization = prefs.romanization.unwrap_or_default().label();
+    let ipa = if prefs.ipa.unwrap_or(false) { "on" } else { "off" };
+    let limit = match prefs.result_limit {
+        Some(limit) => limit.to_string(),
+        None => "all".to_string(),
+    };
+    let language = match prefs.locale {
+        Some(locale) => locale.code(),
+        None => "server default",
+    };
+    let private = if prefs.private.unwrap_or(false) { "on" } else { "off" };
+    let english = match prefs.english {
+        Some(true) => "on",
+        Some(false) => "off",
+        None => "server default",
+    };
+    format!(
+        "⚙️ Your preferences:\nRomanization: {}\nIPA: {}\nResults: {}\nLanguage: {}\nPrivate /taigi replies: {}\nEnglish meanings: {}",
+        romanization, ipa, limit, language, private, english
+    )
+}
+
+pub async fn run(options: &[ResolvedOption<'_>], user_id: UserId, db: &SqlitePool) -> String {
+    let user_id = user_id.to_string();
+    let Some(subcommand) = options.first() else {
+        return "Unknown /prefs subcommand.".to_string();
+    };
+
+    let mut prefs = match db::get_prefs(db, &user_id).await {
+        Ok(prefs) => prefs,
+        Err(why) => {
+            error!("Error loading preferences: {why:?}");
+            return "Could not load your preferences.".to_string();
+        }
+    };
+
+    match (subcommand.name, &subcommand.value) {
+        ("show", _) => describe(&prefs),
+        ("set", ResolvedValue::SubCommand(options)) => {
+            for option in options {
+                match (option.name, &option.value) {
+                    ("romanization", ResolvedValue::String(value)) => {
+                        prefs.romanization = Romanization::parse(value);
+                    }
+                    ("ipa", ResolvedValue::Boolean(value)) => prefs.ipa = Some(*value),
+                    ("limit", ResolvedValue::Integer(value)) => {
+                        prefs.result_limit = u32::try_from(*value).ok();
+                    }
+                    ("language", ResolvedValue::String(value)) => {
+                        prefs.locale = Locale::parse(value);
+                    }
+                    ("private", ResolvedValue::Boolean(value)) => prefs.private = Some(*value),
+                    ("english", ResolvedValue::Boolean(value)) => prefs.english = Some(*value),
+                    _ => {}
+                }
+            }
+            match db::save_prefs(db, &user_id, &prefs).await {
+                Ok(()) => describe(&prefs),
+                Err(why) => {
+                    error!("Error saving preferences: {why:?}");
+                    "Could not save your preferences.".to_string()
+                }
+            }
+        }
+        ("reset", _) => match db::delete_prefs(db, &user_id).await {
+            Ok(()) => "⚙️ Your preferences were reset to the server defaults.".to_string(),
+            Err(why) => {
+                error!("Error resetting preferences: {why:?}");
+                "Could not reset your preferences.".to_string()
+            }
+        },
+        _ => "Unknown /prefs subcommand.".to_string(),
+    }
+}
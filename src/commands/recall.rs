@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use serenity::builder::{
+    CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use serenity::model::id::GuildId;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::config::Config;
+use crate::context::Context;
+use crate::db::{self, GuildPhrase};
+use crate::filter::{self, Action};
+use crate::links;
+use crate::query;
+use crate::respond;
+
+// Keywords shown per reply
+const MAX_RECALLED: u32 = 5;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("recall")
+        .description("Search what the bot answered before, without asking the dictionaries")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "keyword", "Taigi or Mandarin word")
+                .required(true),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "private",
+            "Only show the results to you",
+        ))
+}
+
+// Whether the server's filtered words let `word` be shown in this reply
+async fn allowed(db: &SqlitePool, guild_id: Option<GuildId>, word: &str, private: bool) -> bool {
+    match filter::check(db, guild_id, word).await {
+        Some(Action::Refuse) => false,
+        Some(Action::Private) => private,
+        None => true,
+    }
+}
+
+// The server's own phrasebook entries mentioning `keyword`
+async fn phrases(db: &SqlitePool, guild_id: Option<GuildId>, keyword: &str) -> Vec<GuildPhrase> {
+    let Some(guild_id) = guild_id else {
+        return Vec::new();
+    };
+    let phrases = db::guild_phrases(db, &guild_id.to_string()).await.unwrap_or_else(|why| {
+        error!("Error loading the server's phrases: {why:?}");
+        Vec::new()
+    });
+    let lowercase = keyword.to_lowercase();
+    phrases
+        .into_iter()
+        .filter(|phrase| {
+            phrase.hanji.contains(keyword)
+                || phrase.lomaji.to_lowercase().contains(&lowercase)
+                || phrase.english.to_lowercase().contains(&lowercase)
+        })
+        .collect()
+}
+
+// Answers from the history and the server's phrasebook alone, so it's instant
+// and keeps working while the sources are down. Only the server's public
+// lookups are searched (in DMs, the user's own), and the server's filtered
+// words apply as they do to lookups.
+pub async fn run(ctx: &Context, command: &CommandInteraction, config: &Arc<Config>, db: &SqlitePool) {
+    let mut keyword = String::new();
+    let mut private = None;
+    for option in command.data.options() {
+        match (option.name, option.value) {
+            ("keyword", ResolvedValue::String(value)) => keyword = query::normalize(value),
+            ("private", ResolvedValue::Boolean(value)) => private = Some(value),
+            _ => {}
+        }
+    }
+
+    let prefs = answer::load_prefs(db, command.user.id, command.guild_id).await;
+    let guild = answer::load_guild(db, command.guild_id).await;
+    let locale = prefs.locale.or(guild.locale).unwrap_or(config.locale);
+    let private = private.or(prefs.private).unwrap_or(false);
+    let searched = locale.searched(&keyword, None);
+    let guild_id = command.guild_id.map(|guild_id| guild_id.to_string());
+    let user_id = command.user.id.to_string();
+
+    // An empty keyword would match every word ever answered
+    let refusal = if keyword.trim().is_empty() {
+        Some(locale.no_keyword())
+    } else {
+        match filter::check(db, command.guild_id, &keyword).await {
+            Some(Action::Refuse) => Some(locale.filtered()),
+            Some(Action::Private) if !private => Some(locale.filtered_private()),
+            _ => None,
+        }
+    };
+
+    let content = match refusal {
+        Some(refusal) => refusal.to_string(),
+        None => match db::recall(db, &keyword, guild_id.as_deref(), &user_id, MAX_RECALLED).await {
+            Ok(recalled) => {
+                let emoji = config.emoji.for_guild(command.guild_id);
+                let mut lines = Vec::new();
+                for (word, lookup) in recalled {
+                    if !allowed(db, command.guild_id, &word, private).await {
+                        continue;
+                    }
+                    let results: Vec<String> = lookup
+                        .results
+                        .iter()
+                        .zip(&lookup.sources)
+                        .filter(|(_, source)| guild.shows(**source))
+                        .map(|(result, source)| emoji.apply(&prefs.format_result(result), *source))
+                        .collect();
+                    if !results.is_empty() {
+                        lines.push(format!("**{}**", word));
+                        lines.extend(links::render(&results, config.link_style));
+                    }
+                }
+                let mut phrase_lines = Vec::new();
+                for phrase in phrases(db, command.guild_id, &keyword).await {
+                    if allowed(db, command.guild_id, &phrase.hanji, private).await {
+                        phrase_lines.push(format!(
+                            "{} {} — {}",
+                            phrase.hanji, phrase.lomaji, phrase.english
+                        ));
+                    }
+                }
+                if !phrase_lines.is_empty() {
+                    lines.push(locale.recalled_phrases().to_string());
+                    lines.extend(phrase_lines);
+                }
+                if lines.is_empty() {
+                    locale.nothing_recalled(&searched)
+                } else {
+                    format!("{}\n{}", locale.recalled(&searched), lines.join("\n"))
+                }
+            }
+            Err(why) => {
+                error!("Error searching the history for {}: {why:?}", keyword);
+                "Could not search the history.".to_string()
+            }
+        },
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .content(respond::fit_message(&prefs.render(&content)))
+        .ephemeral(private);
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        error!("Cannot respond to /recall: {why:?}");
+    }
+}
@@ -0,0 +1,82 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+
+use crate::context::Context;
+use crate::respond;
+use crate::rhyme;
+
+// Words listed in all, the rest only counted
+const LISTED: usize = 40;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("rhyme")
+        .description("Taigi words that rhyme with a syllable, for songs and poems")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "syllable",
+                "A Tâi-lô syllable, e.g. kûn; for a word its last syllable is rhymed",
+            )
+            .max_length(50)
+            .required(true),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "tone",
+            "Only words ending in the same tone",
+        ))
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    let mut syllable = "";
+    let mut same_tone = false;
+    for option in command.data.options() {
+        match (option.name, option.value) {
+            ("syllable", ResolvedValue::String(value)) => syllable = value,
+            ("tone", ResolvedValue::Boolean(value)) => same_tone = value,
+            _ => {}
+        }
+    }
+
+    let reply = async {
+        let rhymes = match rhyme::find(syllable, same_tone) {
+            Ok(rhymes) => rhymes,
+            Err(err) => return err,
+        };
+        let ending = if same_tone {
+            format!("-{} in tone {}", rhymes.rime, rhymes.tone)
+        } else {
+            format!("-{}", rhymes.rime)
+        };
+        if rhymes.words.is_empty() {
+            return format!("🎶 No words ending in {} are known yet.", ending);
+        }
+
+        // One line per word length, so the single syllables are easy to find
+        let mut lines: Vec<String> = Vec::new();
+        let mut current = 0;
+        for spelling in rhymes.words.iter().take(LISTED) {
+            let word = format!("{} {}", spelling.hanji, spelling.lomaji);
+            if spelling.tones.len() == current {
+                if let Some(line) = lines.last_mut() {
+                    line.push_str(" · ");
+                    line.push_str(&word);
+                }
+                continue;
+            }
+            current = spelling.tones.len();
+            let length = if current == 1 {
+                "1 syllable".to_string()
+            } else {
+                format!("{} syllables", current)
+            };
+            lines.push(format!("**{}:** {}", length, word));
+        }
+        if rhymes.words.len() > LISTED {
+            lines.push(format!("… and {} more", rhymes.words.len() - LISTED));
+        }
+        format!("🎶 Rhymes for *{}*, ending in {}:\n{}", syllable.trim(), ending, lines.join("\n"))
+    };
+    respond::command(ctx, db, command, reply).await;
+}
@@ -0,0 +1,84 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandOptionType, ResolvedOption, ResolvedValue};
+use serenity::model::id::UserId;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::db;
+use crate::jobs::{JobKind, JobQueue};
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("saved")
+        .description("Words you saved with 💾")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "Show your saved words",
+        ))
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove a saved word")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "word", "Word to remove")
+                        .required(true),
+                ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "export",
+            "Get your saved words as a file for Anki, by DM",
+        ))
+}
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    user_id: UserId,
+    db: &SqlitePool,
+    jobs: &JobQueue,
+) -> String {
+    let Some(subcommand) = options.first() else {
+        return "Unknown /saved subcommand.".to_string();
+    };
+    // Looking every word up takes a while, so exports run in the background
+    if subcommand.name == "export" {
+        return match jobs.submit(JobKind::AnkiExport, user_id).await {
+            Ok(()) => "📦 Export queued, I'll DM you the file when it's ready.".to_string(),
+            Err(err) => err,
+        };
+    }
+
+    let user_id = user_id.to_string();
+    match (subcommand.name, &subcommand.value) {
+        ("list", _) => match db::saved_words(db, &user_id).await {
+            Ok(words) if words.is_empty() => {
+                "💾 No saved words yet. Press 💾 under a result to save it.".to_string()
+            }
+            Ok(words) => {
+                let list = words.join("、");
+                let list: String = list.chars().take(1900).collect();
+                format!("💾 Your saved words ({}):\n{}", words.len(), list)
+            }
+            Err(why) => {
+                error!("Error loading saved words: {why:?}");
+                "Could not load your saved words.".to_string()
+            }
+        },
+        ("remove", ResolvedValue::SubCommand(options)) => {
+            let word = options
+                .iter()
+                .find_map(|option| match (option.name, &option.value) {
+                    ("word", ResolvedValue::String(value)) => Some(value.trim()),
+                    _ => None,
+                })
+                .unwrap_or_default();
+            match db::unsave_word(db, &user_id, word).await {
+                Ok(true) => format!("💾 Removed {} from your saved words.", word),
+                Ok(false) => format!("{} isn't in your saved words.", word),
+                Err(why) => {
+                    error!("Error removing saved word: {why:?}");
+                    "Could not remove the word.".to_string()
+                }
+            }
+        }
+        _ => "Unknown /saved subcommand.".to_string(),
+    }
+}
@@ -0,0 +1,83 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+
+use crate::commands::admin;
+use crate::context::Context;
+use crate::pattern::Pattern;
+use crate::respond;
+
+// Matches listed before the rest are only counted
+const LISTED: usize = 40;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("search")
+        .description("Find words in the bot's own word list by a pattern of their reading")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "pattern",
+                "* for any letters and ? for one, e.g. tsh*-pn̄g; no tone marks matches all tones",
+            )
+            .max_length(100)
+            .required(true),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "regex",
+            "Read the pattern as a regular expression (bot owner only)",
+        ))
+}
+
+// Only shown to the asker: lists of matches can be long
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    let mut pattern = "";
+    let mut regex = false;
+    for option in command.data.options() {
+        match (option.name, option.value) {
+            ("pattern", ResolvedValue::String(value)) => pattern = value,
+            ("regex", ResolvedValue::Boolean(value)) => regex = value,
+            _ => {}
+        }
+    }
+
+    // Backticks would end the code span the pattern is echoed in, so they're
+    // shown as the grave accent letter that looks the same
+    let shown = pattern.replace('`', "ˋ");
+    let reply = async {
+        let compiled = if regex {
+            if !admin::is_owner(ctx, command.user.id).await {
+                return "Regular expressions are limited to the bot owner, \
+                        `*` and `?` work for everyone."
+                    .to_string();
+            }
+            Pattern::regex(pattern)
+        } else {
+            Pattern::wildcard(pattern)
+        };
+        let found = match compiled {
+            Ok(compiled) => compiled.search(),
+            Err(err) => return err,
+        };
+        if found.is_empty() {
+            return format!("🔎 Nothing in the word list matches `{}`.", shown);
+        }
+
+        let mut lines: Vec<String> = found
+            .iter()
+            .take(LISTED)
+            .map(|spelling| format!("{} {}", spelling.hanji, spelling.lomaji))
+            .collect();
+        if found.len() > LISTED {
+            lines.push(format!("… and {} more", found.len() - LISTED));
+        }
+        format!(
+            "🔎 {} match{} for `{}`:\n{}",
+            found.len(),
+            if found.len() == 1 { "" } else { "es" },
+            shown,
+            lines.join("\n")
+        )
+    };
+    respond::command(ctx, db, command, reply).await;
+}
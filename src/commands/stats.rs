@@ -0,0 +1,62 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::db::{self, RatedResult};
+use crate::widget;
+
+// Results listed in each part of /stats entries
+const LISTED: u32 = 5;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("stats")
+        .description("What users think of the answers")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "entries",
+            "The best rated and the most disputed answers, from 👍/👎 under results",
+        ))
+}
+
+// `食飯 (tg#4f2a) Sutian: 食飯 [tsia̍h-pn̄g] 吃飯 · 👍 3 👎 1`
+fn line(rated: &RatedResult) -> String {
+    let text: String = widget::fields(&rated.result).text.chars().take(80).collect();
+    format!(
+        "{} (tg#{}) {}: {} · 👍 {} 👎 {}",
+        rated.keyword,
+        rated.entry_id,
+        rated.source.map_or("?", |source| source.name()),
+        text,
+        rated.good,
+        rated.bad
+    )
+}
+
+// Only the votes on words looked up publicly in the server, or in DMs by the
+// user, are listed
+pub async fn run(command: &CommandInteraction, db: &SqlitePool) -> String {
+    let options = command.data.options();
+    if options.first().map(|subcommand| subcommand.name) != Some("entries") {
+        return "Unknown /stats subcommand.".to_string();
+    }
+    let guild_id = command.guild_id.map(|guild_id| guild_id.to_string());
+    let user_id = command.user.id.to_string();
+
+    let mut sections = Vec::new();
+    let parts = [(false, "🏆 Best rated answers"), (true, "⚖️ Most disputed answers")];
+    for (disputed, title) in parts {
+        match db::rated_results(db, guild_id.as_deref(), &user_id, disputed, LISTED).await {
+            Ok(rated) if rated.is_empty() => sections.push(format!("{}\nNone yet.", title)),
+            Ok(rated) => {
+                let lines: Vec<String> = rated.iter().map(line).collect();
+                sections.push(format!("{}\n{}", title, lines.join("\n")));
+            }
+            Err(why) => {
+                error!("Error loading result votes: {why:?}");
+                return "Could not load the votes.".to_string();
+            }
+        }
+    }
+    sections.join("\n\n")
+}
@@ -0,0 +1,41 @@
+use serenity::builder::CreateCommand;
+
+use crate::config::Config;
+use crate::latency;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("status").description("How each dictionary source is responding")
+}
+
+pub fn run(config: &Config) -> String {
+    let mut lines = vec!["📡 Sources since the last restart:".to_string()];
+    for (source, stats) in latency::snapshot() {
+        let line = if !config.sources.enabled(source) {
+            format!("⏸️ {}: disabled", source.name())
+        } else if let Some(average) = stats.average() {
+            let emoji = if stats.last_error.is_some() {
+                "⚠️"
+            } else if average >= config.slow_source {
+                "🐢"
+            } else {
+                "✅"
+            };
+            let mut line = format!(
+                "{} {}: {:.1}s average, {} searches, {} failed",
+                emoji,
+                source.name(),
+                average.as_secs_f64(),
+                stats.requests,
+                stats.failures
+            );
+            if let Some(error) = &stats.last_error {
+                line.push_str(&format!(" (last: {})", error));
+            }
+            line
+        } else {
+            format!("❔ {}: not searched yet", source.name())
+        };
+        lines.push(line);
+    }
+    lines.join("\n")
+}
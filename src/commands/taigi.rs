@@ -0,0 +1,355 @@
+use std::sync::Arc;
+
+use serenity::builder::{
+    CreateActionRow, CreateCommand, CreateCommandOption, CreateInputText,
+    CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal,
+    EditInteractionResponse,
+};
+use serenity::model::application::{
+    ActionRowComponent, CommandInteraction, CommandOptionType, InputTextStyle, ModalInteraction,
+    ResolvedValue,
+};
+use serenity::http::Http;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer::{self, Answer, Asker};
+use crate::config::Config;
+use crate::context::Context;
+use crate::filter::{self, Action};
+use crate::forum;
+use crate::lookup::LookupPool;
+use crate::pos;
+use crate::query::{self, SearchOptions};
+use crate::romanization::Romanization;
+use crate::source::Source;
+use crate::variant::Accent;
+
+// The advanced search form, opened by /taigi advanced
+const ADVANCED_ID: &str = "advanced_search";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("taigi")
+        .description("Look up a word in Taigi")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "search", "Look up a word")
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "keyword",
+                        "Word or sentence to look up",
+                    )
+                    .required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "variant",
+                        "Preferred regional accent",
+                    )
+                    .add_string_choice("漳州", "漳")
+                    .add_string_choice("泉州", "泉")
+                    .add_string_choice("廈門", "廈"),
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "pos",
+                    "Only show results of this part of speech, like 名詞 or 動詞",
+                ))
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "private",
+                    "Only show the results to you",
+                )),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "advanced",
+            "Open a form to pick the sources, number of results and romanization",
+        ))
+}
+
+pub async fn run(
+    ctx: &Context,
+    command: &CommandInteraction,
+    config: &Arc<Config>,
+    pool: &LookupPool,
+    db: &SqlitePool,
+) {
+    let Some(subcommand) = command.data.options().into_iter().next() else {
+        return;
+    };
+    let ResolvedValue::SubCommand(subcommand_options) = subcommand.value else {
+        return;
+    };
+    if subcommand.name == "advanced" {
+        let response = CreateInteractionResponse::Modal(advanced_form());
+        if let Err(why) = command.create_response(&ctx.http, response).await {
+            error!("Cannot open the advanced search form: {why:?}");
+        }
+        return;
+    }
+
+    let mut keyword = String::new();
+    let mut options = SearchOptions::default();
+    let mut private = None;
+    let mut unknown_pos = None;
+    for option in subcommand_options {
+        match (option.name, option.value) {
+            ("keyword", ResolvedValue::String(value)) => keyword = query::normalize(value),
+            ("variant", ResolvedValue::String(value)) => options.variant = Accent::parse(value),
+            ("private", ResolvedValue::Boolean(value)) => private = Some(value),
+            ("pos", ResolvedValue::String(value)) => match pos::parse(value) {
+                Some(tag) => options.pos = Some(tag),
+                None => unknown_pos = Some(value),
+            },
+            _ => {}
+        }
+    }
+
+    let prefs = answer::load_prefs(db, command.user.id, command.guild_id).await;
+    let mut private = private.or(prefs.private).unwrap_or(false);
+
+    // Filtered words are refused, or answered only to the asker, before
+    // anything is shown in the channel
+    let locale = prefs.locale.unwrap_or(config.locale);
+    let problem = if keyword.is_empty() {
+        Some(locale.no_keyword().to_string())
+    } else {
+        match filter::check(db, command.guild_id, &keyword).await {
+            Some(Action::Refuse) => Some(locale.filtered().to_string()),
+            Some(Action::Private) => {
+                private = true;
+                unknown_pos.map(pos::unknown)
+            }
+            None => unknown_pos.map(pos::unknown),
+        }
+    };
+    if let Some(problem) = problem {
+        let data = CreateInteractionResponseMessage::new()
+            .content(problem)
+            .ephemeral(true);
+        if let Err(why) = command
+            .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+            .await
+        {
+            error!("Cannot respond to /taigi: {why:?}");
+        }
+        return;
+    }
+
+    // Lookups can take longer than the 3 seconds Discord waits for a response
+    let defer = CreateInteractionResponseMessage::new().ephemeral(private);
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Defer(defer))
+        .await
+    {
+        error!("Cannot defer /taigi: {why:?}");
+        return;
+    }
+
+    let asker = Asker {
+        user_id: command.user.id,
+        channel_id: command.channel_id,
+        guild_id: command.guild_id,
+        prefs,
+        guild: answer::load_guild(db, command.guild_id).await,
+    };
+    // Slow mode: say where the query stands in line until its turn
+    let _slot = match pool.enter() {
+        Ok(slot) => slot,
+        Err(waiting) => {
+            let edit = EditInteractionResponse::new().content(locale.queued(waiting.position()));
+            if let Err(why) = command.edit_response(&ctx.http, edit).await {
+                error!("Cannot show the queue position for /taigi: {why:?}");
+            }
+            waiting.wait().await
+        }
+    };
+    options.private = private;
+    let edit = search(&ctx.http, db, pool, config, &asker, &keyword, &options).await;
+    if let Err(why) = command.edit_response(&ctx.http, edit).await {
+        error!("Cannot respond to /taigi: {why:?}");
+    }
+}
+
+async fn search(
+    http: &Arc<Http>,
+    db: &SqlitePool,
+    pool: &LookupPool,
+    config: &Arc<Config>,
+    asker: &Asker,
+    keyword: &str,
+    options: &SearchOptions,
+) -> EditInteractionResponse {
+    let answer = answer::answer(db, pool, config, asker, keyword, options).await;
+    let components = answer.components(config, !options.private);
+    // Answers others in a server can see also go to its archive forum
+    if let Some(guild_id) = asker.guild_id.filter(|_| !options.private)
+        && let Some(forum) = forum::forum_for(&asker.guild, config)
+        && let Answer::Found { keyword, content, .. } = &answer
+    {
+        let (keyword, content) = (keyword.clone(), content.clone());
+        forum::archive(http.clone(), db.clone(), forum, guild_id, keyword, content);
+    }
+    let content = match answer {
+        Answer::Breakdown { content, .. }
+        | Answer::Found { content, .. }
+        | Answer::Failed(content)
+        | Answer::NotFound { content, .. } => content,
+    };
+    EditInteractionResponse::new()
+        .content(content)
+        .components(components)
+}
+
+fn advanced_form() -> CreateModal {
+    let inputs = [
+        CreateInputText::new(InputTextStyle::Short, "Keyword", "keyword").required(true),
+        CreateInputText::new(InputTextStyle::Short, "Sources", "sources")
+            .placeholder("All, or e.g. Sutian, Moedict")
+            .required(false),
+        CreateInputText::new(InputTextStyle::Short, "Result limit", "limit")
+            .placeholder("1-20, your /prefs limit if empty")
+            .max_length(2)
+            .required(false),
+        CreateInputText::new(InputTextStyle::Short, "Romanization", "romanization")
+            .placeholder("Tâi-lô or POJ, your /prefs setting if empty")
+            .required(false),
+    ];
+    CreateModal::new(ADVANCED_ID, "Advanced search")
+        .components(inputs.into_iter().map(CreateActionRow::InputText).collect())
+}
+
+// A comma-separated list of source names or codes, e.g. "Sutian, M"
+fn parse_sources(value: &str) -> Result<Vec<Source>, String> {
+    value
+        .split([',', '、', ' '])
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            Source::available()
+                .find(|source| {
+                    source.name().eq_ignore_ascii_case(name)
+                        || name.eq_ignore_ascii_case(&source.code().to_string())
+                })
+                .ok_or_else(|| {
+                    let names: Vec<&str> = Source::available().map(Source::name).collect();
+                    format!("Unknown source \"{}\", pick from {}.", name, names.join(", "))
+                })
+        })
+        .collect()
+}
+
+// Handle a submitted advanced search form. Returns false for other modals.
+pub async fn run_advanced(
+    ctx: &Context,
+    modal: &ModalInteraction,
+    config: &Arc<Config>,
+    pool: &LookupPool,
+    db: &SqlitePool,
+) -> bool {
+    if modal.data.custom_id != ADVANCED_ID {
+        return false;
+    }
+    let field = |id: &str| {
+        modal
+            .data
+            .components
+            .iter()
+            .flat_map(|row| &row.components)
+            .find_map(|component| match component {
+                ActionRowComponent::InputText(input) if input.custom_id == id => {
+                    input.value.clone()
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    };
+    let keyword = query::normalize(&field("keyword"));
+    let mut prefs = answer::load_prefs(db, modal.user.id, modal.guild_id).await;
+    let mut options = SearchOptions::default();
+
+    // Blank fields keep the asker's preferences
+    let mut problems = Vec::new();
+    match parse_sources(&field("sources")) {
+        Ok(sources) if sources.is_empty() => {}
+        Ok(sources) => options.sources = Some(sources),
+        Err(err) => problems.push(err),
+    }
+    let limit = field("limit");
+    if !limit.is_empty() {
+        match limit.parse::<u32>() {
+            Ok(limit @ 1..=20) => prefs.result_limit = Some(limit),
+            _ => problems.push(format!("The result limit should be 1-20, not \"{}\".", limit)),
+        }
+    }
+    let romanization = field("romanization");
+    if !romanization.is_empty() {
+        match Romanization::parse(&romanization) {
+            Some(romanization) => prefs.romanization = Some(romanization),
+            None => problems.push(format!(
+                "Unknown romanization \"{}\", pick Tâi-lô or POJ.",
+                romanization
+            )),
+        }
+    }
+    let locale = prefs.locale.unwrap_or(config.locale);
+    let mut private = prefs.private.unwrap_or(false);
+    if keyword.is_empty() {
+        problems.push(locale.no_keyword().to_string());
+    } else {
+        match filter::check(db, modal.guild_id, &keyword).await {
+            Some(Action::Refuse) => problems.push(locale.filtered().to_string()),
+            Some(Action::Private) => private = true,
+            None => {}
+        }
+    }
+    if !problems.is_empty() {
+        let data = CreateInteractionResponseMessage::new()
+            .content(format!("⚠️ {}", problems.join("\n")))
+            .ephemeral(true);
+        if let Err(why) = modal
+            .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+            .await
+        {
+            error!("Cannot respond to the advanced search: {why:?}");
+        }
+        return true;
+    }
+
+    let defer = CreateInteractionResponseMessage::new().ephemeral(private);
+    if let Err(why) = modal
+        .create_response(&ctx.http, CreateInteractionResponse::Defer(defer))
+        .await
+    {
+        error!("Cannot defer the advanced search: {why:?}");
+        return true;
+    }
+
+    let asker = Asker {
+        user_id: modal.user.id,
+        channel_id: modal.channel_id,
+        guild_id: modal.guild_id,
+        prefs,
+        guild: answer::load_guild(db, modal.guild_id).await,
+    };
+    let _slot = match pool.enter() {
+        Ok(slot) => slot,
+        Err(waiting) => {
+            let edit = EditInteractionResponse::new().content(locale.queued(waiting.position()));
+            if let Err(why) = modal.edit_response(&ctx.http, edit).await {
+                error!("Cannot show the queue position for the advanced search: {why:?}");
+            }
+            waiting.wait().await
+        }
+    };
+    options.private = private;
+    let edit = search(&ctx.http, db, pool, config, &asker, &keyword, &options).await;
+    if let Err(why) = modal.edit_response(&ctx.http, edit).await {
+        error!("Cannot respond to the advanced search: {why:?}");
+    }
+    true
+}
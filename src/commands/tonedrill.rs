@@ -0,0 +1,65 @@
+use serenity::builder::{
+    CreateAttachment, CreateCommand, CreateInteractionResponse, CreateInteractionResponseMessage,
+    EditInteractionResponse,
+};
+use serenity::model::application::CommandInteraction;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::audio;
+use crate::context::Context;
+use crate::db;
+use crate::practice;
+
+// Words tried before giving up when Moedict has no recording
+const ATTEMPTS: usize = 3;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("tonedrill").description("Hear a syllable and pick its tone")
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    // Fetching the recording can take longer than Discord waits for a response
+    let defer = CreateInteractionResponseMessage::new().ephemeral(true);
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Defer(defer))
+        .await
+    {
+        error!("Cannot defer /tonedrill: {why:?}");
+        return;
+    }
+
+    let stats = match db::drill_stats(db, &command.user.id.to_string()).await {
+        Ok(stats) => stats,
+        Err(why) => {
+            error!("Error loading tone drill stats: {why:?}");
+            Vec::new()
+        }
+    };
+
+    let prefs = answer::load_prefs(db, command.user.id, command.guild_id).await;
+    let mut edit =
+        EditInteractionResponse::new().content("🔇 Could not fetch a recording, try again.");
+    for _ in 0..ATTEMPTS {
+        let word = practice::drill_word(&stats);
+        match audio::moedict(word.hanji).await {
+            Ok(Some(audio)) => {
+                edit = EditInteractionResponse::new()
+                    .content(format!(
+                        "🎧 **{}** — which tone did you hear?",
+                        prefs.romanize(&word.base)
+                    ))
+                    .new_attachment(CreateAttachment::bytes(audio.bytes, audio.filename))
+                    .components(practice::drill_buttons(word));
+                break;
+            }
+            Ok(None) => continue,
+            Err(err) => error!("Error fetching audio for {}: {}", word.hanji, err),
+        }
+    }
+
+    if let Err(why) = command.edit_response(&ctx.http, edit).await {
+        error!("Cannot respond to /tonedrill: {why:?}");
+    }
+}
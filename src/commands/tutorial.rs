@@ -0,0 +1,25 @@
+use serenity::builder::{CreateCommand, CreateCommandOption};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+
+use crate::context::Context;
+use crate::respond;
+use crate::tutorial;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("tutorial")
+        .description("A walkthrough of Tâi-lô tones, asking the bot and studying with it")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "restart",
+            "Start from the beginning instead of where you left off",
+        ))
+}
+
+// Only shown to the learner, who goes through it at their own pace
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    let restart = command.data.options().iter().any(|option| {
+        option.name == "restart" && matches!(option.value, ResolvedValue::Boolean(true))
+    });
+    respond::command(ctx, db, command, tutorial::start(db, command.user.id, restart)).await;
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use serenity::builder::{
+    CreateAttachment, CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+};
+use serenity::model::application::{CommandInteraction, CommandOptionType, ResolvedValue};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::breakdown;
+use crate::context::Context;
+use crate::db;
+
+const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("vocab")
+        .description("Study lists of the words looked up in this channel")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "week",
+                "Every word looked up here in the past 7 days",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "format", "File format")
+                    .add_string_choice("Text", "text")
+                    .add_string_choice("CSV", "csv"),
+            ),
+        )
+}
+
+// Quote a CSV field when it needs it
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction, db: &SqlitePool) {
+    let mut csv = false;
+    for option in command.data.options() {
+        if let ("week", ResolvedValue::SubCommand(options)) = (option.name, option.value) {
+            for option in options {
+                if let ("format", ResolvedValue::String(value)) = (option.name, option.value) {
+                    csv = value == "csv";
+                }
+            }
+        }
+    }
+
+    let prefs = answer::load_prefs(db, command.user.id, command.guild_id).await;
+    let mut data = CreateInteractionResponseMessage::new();
+    match db::channel_queries(db, &command.channel_id.to_string(), WEEK).await {
+        Ok(words) if words.is_empty() => {
+            data = data
+                .content("📋 Nothing was looked up in this channel in the past week.")
+                .ephemeral(true);
+        }
+        Ok(words) => {
+            // The Taigi Hanji and reading of each word's first usable result
+            let entries: Vec<(String, String, String)> = words
+                .iter()
+                .map(|(keyword, results)| {
+                    let headword = results.iter().find_map(|result| breakdown::headword(result));
+                    let hanji = headword.map_or("", |(hanji, _)| hanji);
+                    let lomaji = headword.and_then(|(_, lomaji)| lomaji).unwrap_or_default();
+                    (keyword.clone(), hanji.to_string(), lomaji.to_string())
+                })
+                .collect();
+
+            let (file, filename) = if csv {
+                let mut lines = vec!["No.,Word,Taigi,Lô-jī".to_string()];
+                for (number, (keyword, hanji, lomaji)) in entries.iter().enumerate() {
+                    let fields = [keyword, hanji, lomaji].map(|field| csv_field(field));
+                    lines.push(format!("{},{}", number + 1, fields.join(",")));
+                }
+                (lines.join("\n"), "vocab-week.csv")
+            } else {
+                let lines: Vec<String> = entries
+                    .iter()
+                    .enumerate()
+                    .map(|(number, (keyword, hanji, lomaji))| {
+                        let mut line = format!("{}. {}", number + 1, keyword);
+                        if !hanji.is_empty() && hanji != keyword {
+                            line.push_str(&format!(" → {}", hanji));
+                        }
+                        if !lomaji.is_empty() {
+                            line.push_str(&format!(" {}", lomaji));
+                        }
+                        line
+                    })
+                    .collect();
+                (lines.join("\n"), "vocab-week.txt")
+            };
+            data = data
+                .content(format!(
+                    "📋 {} words looked up in this channel in the past week.",
+                    entries.len()
+                ))
+                .add_file(CreateAttachment::bytes(prefs.render(&file).into_bytes(), filename));
+        }
+        Err(why) => {
+            error!("Error loading the channel's queries: {why:?}");
+            data = data.content("Could not load this week's words.").ephemeral(true);
+        }
+    }
+
+    if let Err(why) = command
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        error!("Cannot respond to /vocab: {why:?}");
+    }
+}
@@ -0,0 +1,1380 @@
+use std::collections::HashMap;
+use std::env;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use figment::Figment;
+use figment::providers::{Env, Format, Toml, Yaml};
+use serde::Deserialize;
+use serenity::model::id::{ChannelId, GuildId};
+use tracing::{error, info, warn};
+
+use crate::emoji::{self, Emoji, EmojiSet};
+use crate::i18n::Locale;
+use crate::interactions;
+use crate::links::LinkStyle;
+use crate::plugin;
+use crate::script::Hook;
+use crate::source::Source;
+use crate::upstream;
+
+// Channel the bot answered in before it was configurable
+const DEFAULT_LOOKUP_CHANNEL: u64 = 1372944023026794576;
+
+// Environment variables from before config.toml existed, and the key each
+// one overrides
+const LEGACY_ENV: [(&str, &str); 7] = [
+    ("DISCORD_TOKEN", "discord.token"),
+    ("LOOKUP_CHANNEL_IDS", "discord.lookup_channels"),
+    ("OPS_CHANNEL_ID", "discord.ops_channel"),
+    ("DATABASE_URL", "database.url"),
+    ("CACHE_TTL_SECS", "cache.ttl_secs"),
+    ("CACHE_WARMUP", "cache.warmup"),
+    ("ITAIGI_TOKEN", "itaigi.token"),
+];
+
+pub struct Config {
+    pub discord_token: String,
+    // Key Discord signs HTTP interactions with, the endpoint is off if unset
+    pub public_key: Option<String>,
+    // Whether to connect to the gateway. Off, only HTTP interactions arrive.
+    pub gateway: bool,
+    pub lookup_channels: Vec<ChannelId>,
+    // Channels where ordinary chat is watched for Taigi words
+    pub passive_channels: Vec<ChannelId>,
+    pub ops_channel: Option<ChannelId>,
+    // Channel receiving error reports (source failures, panics…)
+    pub log_channel: Option<ChannelId>,
+    // Channel receiving the nightly digest of lookups
+    pub digest_channel: Option<ChannelId>,
+    // Channel told about festivals on the day, with the digest
+    pub festival_channel: Option<ChannelId>,
+    // Forum channel getting a post for each keyword looked up
+    pub archive_forum: Option<ChannelId>,
+    // UTC hour the digest is posted at
+    pub digest_hour: u32,
+    pub database_url: String,
+    pub cache_ttl: Duration,
+    pub cache_warmup: u32,
+    // Lookups run at once for breakdowns, /compare and other batches
+    pub batch_concurrency: usize,
+    // Queries looked up at once before the rest wait in line, unlimited if 0
+    pub slow_mode: usize,
+    pub itaigi_token: Option<String>,
+    pub sources: SourceToggles,
+    pub limits: SourceLimits,
+    // Sources taking longer than this get a note under the results
+    pub slow_source: Duration,
+    pub alerts: AlertSettings,
+    pub locale: Locale,
+    pub link_style: LinkStyle,
+    // Emoji replacing the built-in source and tone ones, per server
+    pub emoji: Emoji,
+    // Add English meanings unless a user opts out
+    pub english_glosses: bool,
+    // Ask Wiktionary for English meanings the bundled data and corpus lack
+    pub wiktionary_glosses: bool,
+    // Whether answers in lookup channels ping the asker they reply to
+    pub reply_ping: bool,
+    // Address of the HTTP server (feeds), off if unset
+    pub http_bind: Option<SocketAddr>,
+    // Where the HTTP server is reachable from outside, for links
+    pub public_url: Option<String>,
+    pub widget: WidgetSettings,
+    // Address of the gRPC service, off if unset or built without `grpc`
+    pub grpc_bind: Option<SocketAddr>,
+    // Font with CJK glyphs for /define share cards, which are off without one
+    pub card_font: Option<String>,
+    // Example sentence file searched by /examples, loaded once at startup
+    pub corpus_path: Option<String>,
+    // Directory of WebAssembly source plugins, loaded once at startup
+    pub plugin_dir: Option<String>,
+    // Script run over the results before they are shown
+    pub result_script: Option<Arc<Hook>>,
+    // Share the database with other instances, only the leader answering
+    pub leader_election: bool,
+    // Name in the leader lease, unique per instance
+    pub instance_name: String,
+    // How long a silent leader keeps the lease before a standby takes over
+    pub leader_lease: Duration,
+    // User-Agent and Accept-Language sent to the sources
+    pub upstream_headers: reqwest::header::HeaderMap,
+    // Responses larger than this are abandoned while being read
+    pub upstream_max_body: usize,
+    // Directory recordings are kept in, fetched again every time if unset
+    pub audio_cache: Option<String>,
+    // ffmpeg binary re-encoding recordings to Opus, sent as fetched if unset
+    pub audio_ffmpeg: Option<String>,
+}
+
+// Which sources are searched
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SourceToggles {
+    pub taigitv: bool,
+    pub sutian: bool,
+    pub itaigi: bool,
+    pub moedict: bool,
+    pub plugins: bool,
+    pub places: bool,
+}
+
+impl Default for SourceToggles {
+    fn default() -> Self {
+        SourceToggles {
+            taigitv: true,
+            sutian: true,
+            itaigi: true,
+            moedict: true,
+            plugins: true,
+            places: true,
+        }
+    }
+}
+
+impl SourceToggles {
+    pub fn enabled(&self, source: Source) -> bool {
+        match source {
+            Source::TaigiTv => self.taigitv,
+            Source::Sutian => self.sutian,
+            Source::ITaigi => self.itaigi,
+            Source::Moedict => self.moedict,
+            Source::Plugins => self.plugins && plugin::any(),
+            Source::Places => self.places,
+        }
+    }
+}
+
+// Maximum number of results taken from each source
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SourceLimits {
+    pub taigitv: usize,
+    pub sutian: usize,
+    pub itaigi: usize,
+    // Counted in heteronyms, each showing its first definition
+    pub moedict: usize,
+    // From each plugin
+    pub plugins: usize,
+}
+
+impl SourceLimits {
+    // Limits for the ➕ shortcut
+    pub fn expanded(&self) -> SourceLimits {
+        SourceLimits {
+            taigitv: self.taigitv * 3,
+            sutian: self.sutian * 3,
+            itaigi: self.itaigi * 3,
+            moedict: self.moedict * 3,
+            plugins: self.plugins * 3,
+        }
+    }
+}
+
+impl Default for SourceLimits {
+    fn default() -> Self {
+        SourceLimits {
+            taigitv: 3,
+            sutian: 1,
+            itaigi: 3,
+            moedict: 2,
+            plugins: 3,
+        }
+    }
+}
+
+// When a source's failures over the last 15 minutes are worth telling the
+// owner about
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    // Share of failed requests, between 0 and 1; 0 turns alerts off
+    pub failure_rate: f64,
+    // Fewer requests than this are too few to judge by
+    pub min_requests: u32,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        AlertSettings {
+            failure_rate: 0.5,
+            min_requests: 10,
+        }
+    }
+}
+
+// Sites embedding the search widget, each with its own key and its own
+// budget of lookups, apart from the Discord users'
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WidgetSettings {
+    // Keys from /admin key also work
+    pub keys: Vec<String>,
+    // Lookups each key may make per minute
+    pub per_minute: u32,
+}
+
+impl Default for WidgetSettings {
+    fn default() -> Self {
+        WidgetSettings {
+            keys: Vec::new(),
+            per_minute: 30,
+        }
+    }
+}
+
+// Discord IDs may be written as numbers, strings or (from the legacy
+// environment variable) a comma-separated list
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Ids {
+    List(Vec<Id>),
+    One(Id),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Id {
+    Number(u64),
+    Text(String),
+}
+
+impl Ids {
+    fn values(&self) -> Vec<String> {
+        match self {
+            Ids::One(id) => id.values(),
+            Ids::List(ids) => ids.iter().flat_map(Id::values).collect(),
+        }
+    }
+}
+
+impl Id {
+    fn values(&self) -> Vec<String> {
+        match self {
+            Id::Number(id) => vec![id.to_string()],
+            Id::Text(text) => text
+                .split(',')
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect(),
+        }
+    }
+}
+
+// Layout of config.toml
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FileConfig {
+    discord: DiscordSection,
+    sources: SourceToggles,
+    limits: SourceLimits,
+    latency: LatencySection,
+    alerts: AlertSettings,
+    cache: CacheSection,
+    batch: BatchSection,
+    slow_mode: SlowModeSection,
+    digest: DigestSection,
+    database: DatabaseSection,
+    locales: LocalesSection,
+    display: DisplaySection,
+    emoji: EmojiSection,
+    http: HttpSection,
+    widget: WidgetSettings,
+    grpc: GrpcSection,
+    plugins: PluginsSection,
+    scripts: ScriptsSection,
+    card: CardSection,
+    corpus: CorpusSection,
+    audio: AudioSection,
+    leader: LeaderSection,
+    upstream: UpstreamSection,
+    itaigi: ItaigiSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DiscordSection {
+    token: Option<String>,
+    public_key: Option<String>,
+    gateway: Option<bool>,
+    lookup_channels: Option<Ids>,
+    passive_channels: Option<Ids>,
+    ops_channel: Option<Id>,
+    log_channel: Option<Id>,
+    digest_channel: Option<Id>,
+    festival_channel: Option<Id>,
+    archive_forum: Option<Id>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct CacheSection {
+    ttl_secs: u64,
+    warmup: u32,
+}
+
+impl Default for CacheSection {
+    fn default() -> Self {
+        CacheSection {
+            ttl_secs: 6 * 60 * 60,
+            warmup: 50,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct LatencySection {
+    slow_secs: f64,
+}
+
+impl Default for LatencySection {
+    fn default() -> Self {
+        LatencySection { slow_secs: 5.0 }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct BatchSection {
+    concurrency: usize,
+}
+
+impl Default for BatchSection {
+    fn default() -> Self {
+        BatchSection { concurrency: 4 }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct SlowModeSection {
+    queries: usize,
+}
+
+impl Default for SlowModeSection {
+    fn default() -> Self {
+        SlowModeSection { queries: 8 }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct DigestSection {
+    hour: u32,
+}
+
+impl Default for DigestSection {
+    fn default() -> Self {
+        // Midnight in Taiwan
+        DigestSection { hour: 16 }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct DatabaseSection {
+    url: String,
+}
+
+impl Default for DatabaseSection {
+    fn default() -> Self {
+        DatabaseSection {
+            url: "sqlite://taigi-bot.db".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct LocalesSection {
+    default: Locale,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DisplaySection {
+    links: LinkStyle,
+    english: bool,
+    wiktionary: bool,
+    reply_ping: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct EmojiSection {
+    #[serde(flatten)]
+    default: EmojiSet,
+    // By server ID
+    guilds: HashMap<String, EmojiSet>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct HttpSection {
+    bind: Option<String>,
+    public_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GrpcSection {
+    bind: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PluginsSection {
+    dir: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ScriptsSection {
+    results: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CardSection {
+    font: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CorpusSection {
+    path: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AudioSection {
+    cache_dir: Option<String>,
+    ffmpeg: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct LeaderSection {
+    election: bool,
+    instance: Option<String>,
+    lease_secs: u64,
+}
+
+impl Default for LeaderSection {
+    fn default() -> Self {
+        LeaderSection {
+            election: false,
+            instance: None,
+            lease_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct UpstreamSection {
+    user_agent: String,
+    accept_language: String,
+    max_body_bytes: usize,
+}
+
+impl Default for UpstreamSection {
+    fn default() -> Self {
+        UpstreamSection {
+            user_agent: upstream::DEFAULT_USER_AGENT.to_string(),
+            accept_language: upstream::DEFAULT_ACCEPT_LANGUAGE.to_string(),
+            max_body_bytes: upstream::DEFAULT_MAX_BODY,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ItaigiSection {
+    token: Option<String>,
+}
+
+// Outcome of validating one setting, printed in the startup report
+enum Check {
+    Ok(String),
+    Warning(String),
+    Error(String),
+}
+
+fn parse_channel(name: &str, value: &str) -> Result<ChannelId, String> {
+    match value.trim().parse::<u64>() {
+        Ok(id) if id != 0 => Ok(ChannelId::new(id)),
+        _ => Err(format!(
+            "{}: \"{}\" is not a channel ID, copy it with Developer Mode → Copy Channel ID",
+            name, value
+        )),
+    }
+}
+
+// A setting that takes at most one channel ID, as (channel, value)
+fn single_channel(name: &str, id: Option<&Id>) -> Result<Option<(ChannelId, String)>, String> {
+    match id.map(Id::values).as_deref() {
+        None | Some([]) => Ok(None),
+        Some([value]) => Ok(Some((parse_channel(name, value)?, value.clone()))),
+        Some(_) => Err(format!("{} takes a single channel ID", name)),
+    }
+}
+
+// Files looked for when CONFIG_PATH isn't set, the first one found is read
+const CONFIG_FILES: [&str; 3] = ["config.toml", "config.yaml", "config.yml"];
+
+// Where the configuration is read from: CONFIG_PATH, or the first of
+// CONFIG_FILES that exists, config.toml if none does
+pub fn path() -> String {
+    env::var("CONFIG_PATH").unwrap_or_else(|_| {
+        CONFIG_FILES
+            .into_iter()
+            .find(|file| Path::new(file).exists())
+            .unwrap_or(CONFIG_FILES[0])
+            .to_string()
+    })
+}
+
+// Whether a configuration file is YAML rather than TOML, by its extension
+pub fn is_yaml(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"))
+}
+
+// The config file (TOML, or YAML), overridden by the legacy environment
+// variables, overridden by TAIGI_<SECTION>__<KEY> variables
+fn figment(path: &str) -> Figment {
+    let legacy = Env::raw()
+        .only(&LEGACY_ENV.map(|(name, _)| name))
+        .map(|name| {
+            LEGACY_ENV
+                .iter()
+                .find(|(legacy, _)| name.as_str().eq_ignore_ascii_case(legacy))
+                .map(|(_, key)| *key)
+                .unwrap_or(name.as_str())
+                .to_string()
+                .into()
+        });
+
+    let file = if is_yaml(path) {
+        Figment::from(Yaml::file_exact(path))
+    } else {
+        Figment::from(Toml::file_exact(path))
+    };
+    file.merge(legacy)
+        .merge(Env::prefixed("TAIGI_").split("__"))
+}
+
+impl Config {
+    // Read and validate every setting, printing a diagnostics report.
+    // Returns the list of fatal problems if the bot can't start.
+    pub fn load() -> Result<Config, Vec<String>> {
+        let mut checks = Vec::new();
+
+        let path = path();
+        if Path::new(&path).exists() {
+            checks.push(Check::Ok(format!("Loaded {}", path)));
+        } else {
+            checks.push(Check::Warning(format!(
+                "{} not found, using environment variables and defaults",
+                path
+            )));
+        }
+
+        let file: FileConfig = match figment(&path).extract() {
+            Ok(file) => file,
+            Err(why) => {
+                checks.push(Check::Error(format!("Invalid configuration: {}", why)));
+                FileConfig::default()
+            }
+        };
+
+        let discord_token = file.discord.token.unwrap_or_default().trim().to_string();
+        if discord_token.is_empty() {
+            checks.push(Check::Error(
+                "discord.token (DISCORD_TOKEN) is not set".to_string(),
+            ));
+        } else if serenity::utils::validate_token(&discord_token).is_err() {
+            checks.push(Check::Error(
+                "discord.token doesn't look like a bot token, copy it again from the Developer Portal → Bot"
+                    .to_string(),
+            ));
+        } else {
+            checks.push(Check::Ok("discord.token looks valid".to_string()));
+        }
+
+        let channel_values: Vec<String> = match &file.discord.lookup_channels {
+            None => vec![DEFAULT_LOOKUP_CHANNEL.to_string()],
+            Some(ids) => ids.values(),
+        };
+        let mut lookup_channels = Vec::new();
+        for value in &channel_values {
+            match parse_channel("discord.lookup_channels", value) {
+                Ok(channel) => lookup_channels.push(channel),
+                Err(err) => checks.push(Check::Error(err)),
+            }
+        }
+        if lookup_channels.is_empty() {
+            checks.push(Check::Error(
+                "discord.lookup_channels is empty, the bot would never answer".to_string(),
+            ));
+        } else {
+            checks.push(Check::Ok(format!(
+                "discord.lookup_channels = {}",
+                channel_values.join(", ")
+            )));
+        }
+
+        let passive_values = file
+            .discord
+            .passive_channels
+            .as_ref()
+            .map(Ids::values)
+            .unwrap_or_default();
+        let mut passive_channels = Vec::new();
+        for value in &passive_values {
+            match parse_channel("discord.passive_channels", value) {
+                Ok(channel) => passive_channels.push(channel),
+                Err(err) => checks.push(Check::Error(err)),
+            }
+        }
+        if !passive_channels.is_empty() {
+            checks.push(Check::Ok(format!(
+                "discord.passive_channels = {}",
+                passive_values.join(", ")
+            )));
+        }
+
+        let ops_channel =
+            match single_channel("discord.ops_channel", file.discord.ops_channel.as_ref()) {
+                Ok(None) => {
+                    checks.push(Check::Warning(
+                        "discord.ops_channel is not set, reports won't be forwarded".to_string(),
+                    ));
+                    None
+                }
+                Ok(Some((channel, value))) => {
+                    checks.push(Check::Ok(format!("discord.ops_channel = {}", value)));
+                    Some(channel)
+                }
+                Err(err) => {
+                    checks.push(Check::Error(err));
+                    None
+                }
+            };
+
+        let log_channel =
+            match single_channel("discord.log_channel", file.discord.log_channel.as_ref()) {
+                Ok(None) => None,
+                Ok(Some((channel, value))) => {
+                    checks.push(Check::Ok(format!("discord.log_channel = {}", value)));
+                    Some(channel)
+                }
+                Err(err) => {
+                    checks.push(Check::Error(err));
+                    None
+                }
+            };
+
+        let digest_channel =
+            match single_channel("discord.digest_channel", file.discord.digest_channel.as_ref()) {
+                Ok(None) => None,
+                Ok(Some((channel, value))) => {
+                    checks.push(Check::Ok(format!(
+                        "discord.digest_channel = {}, posted at {:02}:00 UTC",
+                        value, file.digest.hour
+                    )));
+                    Some(channel)
+                }
+                Err(err) => {
+                    checks.push(Check::Error(err));
+                    None
+                }
+            };
+        let festival_channel = match single_channel(
+            "discord.festival_channel",
+            file.discord.festival_channel.as_ref(),
+        ) {
+            Ok(None) => None,
+            Ok(Some((channel, value))) => {
+                checks.push(Check::Ok(format!(
+                    "discord.festival_channel = {}, posted at {:02}:00 UTC",
+                    value, file.digest.hour
+                )));
+                Some(channel)
+            }
+            Err(err) => {
+                checks.push(Check::Error(err));
+                None
+            }
+        };
+        let archive_forum =
+            match single_channel("discord.archive_forum", file.discord.archive_forum.as_ref()) {
+                Ok(None) => None,
+                Ok(Some((channel, value))) => {
+                    checks.push(Check::Ok(format!("discord.archive_forum = {}", value)));
+                    Some(channel)
+                }
+                Err(err) => {
+                    checks.push(Check::Error(err));
+                    None
+                }
+            };
+
+        if file.digest.hour > 23 {
+            checks.push(Check::Error("digest.hour must be between 0 and 23".to_string()));
+        }
+
+        let database_url = file.database.url;
+        if database_url.starts_with("sqlite:") {
+            checks.push(Check::Ok(format!("database.url = {}", database_url)));
+        } else {
+            checks.push(Check::Error(format!(
+                "database.url: \"{}\" is not supported, use sqlite://<path>",
+                database_url
+            )));
+        }
+
+        let enabled: Vec<&str> = Source::ALL
+            .into_iter()
+            .filter(|source| file.sources.enabled(*source))
+            .map(Source::name)
+            .collect();
+        if enabled.is_empty() {
+            checks.push(Check::Error("sources: every source is disabled".to_string()));
+        } else {
+            checks.push(Check::Ok(format!("sources = {}", enabled.join(", "))));
+        }
+
+        let slow_source = Duration::try_from_secs_f64(file.latency.slow_secs);
+        match slow_source {
+            Ok(_) => checks.push(Check::Ok(format!(
+                "latency.slow_secs = {}",
+                file.latency.slow_secs
+            ))),
+            Err(_) => checks.push(Check::Error(
+                "latency.slow_secs must be a positive number of seconds".to_string(),
+            )),
+        }
+
+        if !(0.0..=1.0).contains(&file.alerts.failure_rate) {
+            checks.push(Check::Error(
+                "alerts.failure_rate must be between 0 and 1".to_string(),
+            ));
+        } else if file.alerts.failure_rate == 0.0 {
+            checks.push(Check::Ok("alerts: off".to_string()));
+        } else {
+            checks.push(Check::Ok(format!(
+                "alerts: over {}% of at least {} requests failing in 15 minutes",
+                file.alerts.failure_rate * 100.0,
+                file.alerts.min_requests
+            )));
+        }
+
+        checks.push(Check::Ok(format!(
+            "cache: {}s TTL, {} warm-up queries",
+            file.cache.ttl_secs, file.cache.warmup
+        )));
+        if file.batch.concurrency == 0 {
+            checks.push(Check::Error("batch.concurrency must be at least 1".to_string()));
+        } else {
+            checks.push(Check::Ok(format!("batch.concurrency = {}", file.batch.concurrency)));
+        }
+        if file.slow_mode.queries == 0 {
+            checks.push(Check::Ok("slow_mode: off".to_string()));
+        } else {
+            checks.push(Check::Ok(format!("slow_mode.queries = {}", file.slow_mode.queries)));
+        }
+        checks.push(Check::Ok(format!("locales.default = {}", file.locales.default.code())));
+        checks.push(Check::Ok(format!(
+            "display: {} links, English meanings {}, from Wiktionary {}, reply pings {}",
+            file.display.links.code(),
+            if file.display.english { "on" } else { "off" },
+            if file.display.wiktionary { "on" } else { "off" },
+            if file.display.reply_ping { "on" } else { "off" }
+        )));
+
+        let http_bind = match file.http.bind.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(value) => match value.parse::<SocketAddr>() {
+                Ok(addr) if cfg!(feature = "http") => {
+                    checks.push(Check::Ok(format!("http.bind = {}", addr)));
+                    Some(addr)
+                }
+                Ok(_) => {
+                    checks.push(Check::Warning(
+                        "http.bind is set but the bot was built without the http feature"
+                            .to_string(),
+                    ));
+                    None
+                }
+                Err(_) => {
+                    checks.push(Check::Error(format!(
+                        "http.bind: \"{}\" is not an address, use e.g. 127.0.0.1:8080",
+                        value
+                    )));
+                    None
+                }
+            },
+        };
+
+        let public_key = file
+            .discord
+            .public_key
+            .map(|key| key.trim().to_ascii_lowercase())
+            .filter(|key| !key.is_empty());
+        let gateway = file.discord.gateway.unwrap_or(true);
+        match &public_key {
+            Some(key) if interactions::verifier(key).is_none() => checks.push(Check::Error(
+                "discord.public_key is not a public key, copy it from the Developer Portal → \
+                 General Information"
+                    .to_string(),
+            )),
+            Some(_) if http_bind.is_none() => checks.push(Check::Error(
+                "discord.public_key is set but http.bind isn't, interactions can't arrive"
+                    .to_string(),
+            )),
+            Some(_) => checks.push(Check::Ok(format!(
+                "HTTP interactions on, gateway {}",
+                if gateway { "on" } else { "off" }
+            ))),
+            None if !gateway => checks.push(Check::Error(
+                "discord.gateway is off but discord.public_key isn't set, nothing would arrive"
+                    .to_string(),
+            )),
+            None => {}
+        }
+
+        let public_url = file
+            .http
+            .public_url
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty());
+
+        let widget = WidgetSettings {
+            keys: file
+                .widget
+                .keys
+                .iter()
+                .map(|key| key.trim().to_string())
+                .filter(|key| !key.is_empty())
+                .collect(),
+            per_minute: file.widget.per_minute,
+        };
+        if widget.keys.is_empty() {
+            checks.push(Check::Ok("widget: no keys in the config".to_string()));
+        } else if widget.per_minute == 0 {
+            checks.push(Check::Error("widget.per_minute must be at least 1".to_string()));
+        } else if http_bind.is_none() {
+            checks.push(Check::Warning(
+                "widget.keys are set but http.bind isn't, the widget is unreachable".to_string(),
+            ));
+        } else {
+            checks.push(Check::Ok(format!(
+                "widget: {} keys, {} lookups a minute each",
+                widget.keys.len(),
+                widget.per_minute
+            )));
+        }
+
+        let grpc_bind = match file.grpc.bind.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(value) => match value.parse::<SocketAddr>() {
+                Ok(addr) if cfg!(feature = "grpc") => {
+                    checks.push(Check::Ok(format!("grpc.bind = {}", addr)));
+                    Some(addr)
+                }
+                Ok(_) => {
+                    checks.push(Check::Warning(
+                        "grpc.bind is set but the bot was built without the grpc feature"
+                            .to_string(),
+                    ));
+                    None
+                }
+                Err(_) => {
+                    checks.push(Check::Error(format!(
+                        "grpc.bind: \"{}\" is not an address, use e.g. 127.0.0.1:50051",
+                        value
+                    )));
+                    None
+                }
+            },
+        };
+
+        let card_font = file.card.font.filter(|path| !path.trim().is_empty());
+        match &card_font {
+            Some(_) if !cfg!(feature = "cards") => checks.push(Check::Warning(
+                "card.font is set but the bot was built without the cards feature".to_string(),
+            )),
+            Some(path) if Path::new(path).is_file() => {
+                checks.push(Check::Ok(format!("card.font = {}", path)));
+            }
+            Some(path) => checks.push(Check::Error(format!("card.font: {} doesn't exist", path))),
+            None => checks.push(Check::Warning(
+                "card.font is not set, /define share cards and the /keyboard image are disabled"
+                    .to_string(),
+            )),
+        }
+
+        // Servers with a bad ID or emoji are skipped rather than failing
+        // startup, since replies still work with the built-in emoji
+        let mut emoji = Emoji {
+            default: file.emoji.default,
+            guilds: HashMap::new(),
+        };
+        let mut emoji_problems: Vec<String> = emoji
+            .default
+            .entries()
+            .into_iter()
+            .filter_map(|(key, value)| {
+                emoji::check(value).err().map(|err| format!("emoji.{}: {}", key, err))
+            })
+            .collect();
+        for (guild, set) in file.emoji.guilds {
+            let prefix = format!("emoji.guilds.{}", guild);
+            let Ok(guild_id) = guild.trim().parse::<u64>().map(GuildId::new) else {
+                emoji_problems.push(format!("{}: \"{}\" is not a server ID", prefix, guild));
+                continue;
+            };
+            let problems: Vec<String> = set
+                .entries()
+                .into_iter()
+                .filter_map(|(key, value)| {
+                    emoji::check(value).err().map(|err| format!("{}.{}: {}", prefix, key, err))
+                })
+                .collect();
+            if problems.is_empty() {
+                emoji.guilds.insert(guild_id, set);
+            } else {
+                emoji_problems.extend(problems);
+            }
+        }
+        if emoji_problems.is_empty() {
+            if emoji != Emoji::default() {
+                checks.push(Check::Ok(format!(
+                    "emoji: custom emoji set, {} server override(s)",
+                    emoji.guilds.len()
+                )));
+            }
+        } else {
+            checks.extend(emoji_problems.into_iter().map(Check::Warning));
+        }
+
+        let corpus_path = file.corpus.path.filter(|path| !path.trim().is_empty());
+        match &corpus_path {
+            Some(path) if Path::new(path).is_file() => {
+                checks.push(Check::Ok(format!("corpus.path = {}", path)));
+            }
+            Some(path) => checks.push(Check::Error(format!("corpus.path: {} doesn't exist", path))),
+            None => checks.push(Check::Warning(
+                "corpus.path is not set, /examples only shows Moedict's examples".to_string(),
+            )),
+        }
+
+        let plugin_dir = file.plugins.dir.filter(|dir| !dir.trim().is_empty());
+        match &plugin_dir {
+            Some(_) if !cfg!(feature = "plugins") => checks.push(Check::Warning(
+                "plugins.dir is set but the bot was built without the plugins feature"
+                    .to_string(),
+            )),
+            Some(dir) if Path::new(dir).is_dir() => {
+                checks.push(Check::Ok(format!("plugins.dir = {}", dir)));
+            }
+            Some(dir) => checks.push(Check::Error(format!("plugins.dir: {} doesn't exist", dir))),
+            None => {}
+        }
+
+        let result_script = match file.scripts.results.filter(|path| !path.trim().is_empty()) {
+            Some(path) if !cfg!(feature = "scripting") => {
+                checks.push(Check::Warning(format!(
+                    "scripts.results is set to {} but the bot was built without the scripting \
+                     feature",
+                    path
+                )));
+                None
+            }
+            Some(path) => match Hook::load(&path) {
+                Ok(hook) => {
+                    checks.push(Check::Ok(format!("scripts.results = {}", path)));
+                    Some(Arc::new(hook))
+                }
+                Err(err) => {
+                    checks.push(Check::Error(format!("scripts.results: {}", err)));
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let audio_cache = file.audio.cache_dir.filter(|dir| !dir.trim().is_empty());
+        if let Some(dir) = &audio_cache {
+            match std::fs::create_dir_all(dir) {
+                Ok(()) => checks.push(Check::Ok(format!("audio.cache_dir = {}", dir))),
+                Err(why) => checks.push(Check::Error(format!("audio.cache_dir: {}: {}", dir, why))),
+            }
+        }
+        let runs = |ffmpeg: &str| {
+            std::process::Command::new(ffmpeg)
+                .arg("-version")
+                .output()
+                .is_ok_and(|output| output.status.success())
+        };
+        let audio_ffmpeg = match file.audio.ffmpeg.filter(|ffmpeg| !ffmpeg.trim().is_empty()) {
+            Some(ffmpeg) if runs(&ffmpeg) => {
+                checks.push(Check::Ok(format!("audio.ffmpeg = {}", ffmpeg)));
+                Some(ffmpeg)
+            }
+            Some(ffmpeg) => {
+                checks.push(Check::Warning(format!(
+                    "audio.ffmpeg: {} doesn't run, recordings are sent as Moedict serves them",
+                    ffmpeg
+                )));
+                None
+            }
+            None => None,
+        };
+
+        // Process IDs tell instances on one host apart, host names the rest
+        let instance_name = file
+            .leader
+            .instance
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| {
+                let host = env::var("HOSTNAME").unwrap_or_else(|_| "taigi-bot".to_string());
+                format!("{}-{}", host, std::process::id())
+            });
+        if file.leader.election {
+            if file.leader.lease_secs < 3 {
+                checks.push(Check::Error("leader.lease_secs must be at least 3".to_string()));
+            } else {
+                checks.push(Check::Ok(format!(
+                    "leader: election on as {}, {}s lease",
+                    instance_name, file.leader.lease_secs
+                )));
+            }
+        }
+
+        let upstream_headers =
+            upstream::headers(&file.upstream.user_agent, &file.upstream.accept_language);
+        match &upstream_headers {
+            Ok(_) => checks.push(Check::Ok(format!(
+                "upstream: User-Agent \"{}\", Accept-Language \"{}\"",
+                file.upstream.user_agent.trim(),
+                file.upstream.accept_language.trim()
+            ))),
+            Err(err) => checks.push(Check::Error(err.clone())),
+        }
+        if file.upstream.max_body_bytes == 0 {
+            checks.push(Check::Error("upstream.max_body_bytes must be at least 1".to_string()));
+        } else {
+            checks.push(Check::Ok(format!(
+                "upstream.max_body_bytes = {}",
+                file.upstream.max_body_bytes
+            )));
+        }
+
+        let itaigi_token = file.itaigi.token.filter(|token| !token.trim().is_empty());
+        if itaigi_token.is_some() {
+            checks.push(Check::Ok("itaigi.token is set, iTaigi contributions enabled".to_string()));
+        } else {
+            checks.push(Check::Warning(
+                "itaigi.token is not set, /contribute and voting are disabled".to_string(),
+            ));
+        }
+
+        info!("Configuration:");
+        let mut errors = Vec::new();
+        for check in checks {
+            match check {
+                Check::Ok(line) => info!("  ✅ {}", line),
+                Check::Warning(line) => warn!("  ⚠️ {}", line),
+                Check::Error(line) => {
+                    error!("  ❌ {}", line);
+                    errors.push(line);
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(Config {
+            discord_token,
+            public_key,
+            gateway,
+            lookup_channels,
+            passive_channels,
+            ops_channel,
+            log_channel,
+            digest_channel,
+            festival_channel,
+            archive_forum,
+            digest_hour: file.digest.hour,
+            database_url,
+            cache_ttl: Duration::from_secs(file.cache.ttl_secs),
+            cache_warmup: file.cache.warmup,
+            batch_concurrency: file.batch.concurrency,
+            slow_mode: file.slow_mode.queries,
+            itaigi_token,
+            sources: file.sources,
+            limits: file.limits,
+            slow_source: slow_source.unwrap_or_default(),
+            alerts: file.alerts,
+            locale: file.locales.default,
+            link_style: file.display.links,
+            emoji,
+            english_glosses: file.display.english,
+            wiktionary_glosses: file.display.wiktionary,
+            reply_ping: file.display.reply_ping,
+            http_bind,
+            public_url,
+            widget,
+            grpc_bind,
+            card_font,
+            corpus_path,
+            plugin_dir,
+            result_script,
+            leader_election: file.leader.election,
+            instance_name,
+            leader_lease: Duration::from_secs(file.leader.lease_secs),
+            upstream_headers: upstream_headers.unwrap_or_default(),
+            upstream_max_body: file.upstream.max_body_bytes,
+            audio_cache,
+            audio_ffmpeg,
+        })
+    }
+}
+
+// The live configuration, swapped as a whole on reload so readers always
+// see a consistent snapshot
+pub struct SharedConfig {
+    current: RwLock<Arc<Config>>,
+}
+
+// What a reload changed, by config key
+#[derive(Default)]
+pub struct ReloadReport {
+    pub applied: Vec<&'static str>,
+    pub restart_required: Vec<&'static str>,
+}
+
+impl ReloadReport {
+    // Cached lookups were made with the old source settings
+    pub fn invalidates_cache(&self) -> bool {
+        self.applied.iter().any(|key| *key == "sources" || *key == "limits")
+    }
+}
+
+impl SharedConfig {
+    pub fn new(config: Config) -> Self {
+        SharedConfig {
+            current: RwLock::new(Arc::new(config)),
+        }
+    }
+
+    pub fn get(&self) -> Arc<Config> {
+        self.current.read().unwrap().clone()
+    }
+
+    // Re-read the configuration and apply what can change at runtime.
+    // Settings only used at startup keep their current value until restart.
+    pub fn reload(&self) -> Result<ReloadReport, Vec<String>> {
+        let mut new = Config::load()?;
+        let old = self.get();
+        let mut report = ReloadReport::default();
+
+        if new.lookup_channels != old.lookup_channels {
+            report.applied.push("discord.lookup_channels");
+        }
+        if new.passive_channels != old.passive_channels {
+            report.applied.push("discord.passive_channels");
+        }
+        if new.ops_channel != old.ops_channel {
+            report.applied.push("discord.ops_channel");
+        }
+        if new.log_channel != old.log_channel {
+            report.applied.push("discord.log_channel");
+        }
+        if new.digest_channel != old.digest_channel {
+            report.applied.push("discord.digest_channel");
+        }
+        if new.festival_channel != old.festival_channel {
+            report.applied.push("discord.festival_channel");
+        }
+        if new.archive_forum != old.archive_forum {
+            report.applied.push("discord.archive_forum");
+        }
+        if new.sources != old.sources {
+            report.applied.push("sources");
+        }
+        if new.limits != old.limits {
+            report.applied.push("limits");
+        }
+        if new.slow_source != old.slow_source {
+            report.applied.push("latency.slow_secs");
+        }
+        if new.alerts != old.alerts {
+            report.applied.push("alerts");
+        }
+        if new.locale != old.locale {
+            report.applied.push("locales.default");
+        }
+        if new.link_style != old.link_style {
+            report.applied.push("display.links");
+        }
+        if new.english_glosses != old.english_glosses {
+            report.applied.push("display.english");
+        }
+        if new.wiktionary_glosses != old.wiktionary_glosses {
+            report.applied.push("display.wiktionary");
+        }
+        if new.reply_ping != old.reply_ping {
+            report.applied.push("display.reply_ping");
+        }
+        if new.emoji != old.emoji {
+            report.applied.push("emoji");
+        }
+        if new.public_url != old.public_url {
+            report.applied.push("http.public_url");
+        }
+        if new.widget != old.widget {
+            report.applied.push("widget");
+        }
+        if new.result_script.as_ref().map(|hook| hook.source())
+            != old.result_script.as_ref().map(|hook| hook.source())
+        {
+            report.applied.push("scripts.results");
+        }
+        if new.card_font != old.card_font {
+            report.applied.push("card.font");
+        }
+        if new.itaigi_token != old.itaigi_token {
+            report.applied.push("itaigi.token");
+        }
+
+        if new.discord_token != old.discord_token {
+            report.restart_required.push("discord.token");
+            new.discord_token = old.discord_token.clone();
+        }
+        if new.public_key != old.public_key {
+            report.restart_required.push("discord.public_key");
+            new.public_key = old.public_key.clone();
+        }
+        if new.gateway != old.gateway {
+            report.restart_required.push("discord.gateway");
+            new.gateway = old.gateway;
+        }
+        if new.database_url != old.database_url {
+            report.restart_required.push("database.url");
+            new.database_url = old.database_url.clone();
+        }
+        if new.cache_ttl != old.cache_ttl {
+            report.restart_required.push("cache.ttl_secs");
+            new.cache_ttl = old.cache_ttl;
+        }
+        if new.cache_warmup != old.cache_warmup {
+            report.restart_required.push("cache.warmup");
+            new.cache_warmup = old.cache_warmup;
+        }
+        if new.http_bind != old.http_bind {
+            report.restart_required.push("http.bind");
+            new.http_bind = old.http_bind;
+        }
+        if new.grpc_bind != old.grpc_bind {
+            report.restart_required.push("grpc.bind");
+            new.grpc_bind = old.grpc_bind;
+        }
+        if new.digest_hour != old.digest_hour {
+            report.restart_required.push("digest.hour");
+            new.digest_hour = old.digest_hour;
+        }
+        if new.batch_concurrency != old.batch_concurrency {
+            report.restart_required.push("batch.concurrency");
+            new.batch_concurrency = old.batch_concurrency;
+        }
+        if new.slow_mode != old.slow_mode {
+            report.restart_required.push("slow_mode.queries");
+            new.slow_mode = old.slow_mode;
+        }
+        if new.plugin_dir != old.plugin_dir {
+            report.restart_required.push("plugins.dir");
+            new.plugin_dir = old.plugin_dir.clone();
+        }
+        if new.corpus_path != old.corpus_path {
+            report.restart_required.push("corpus.path");
+            new.corpus_path = old.corpus_path.clone();
+        }
+        if new.leader_election != old.leader_election
+            || new.instance_name != old.instance_name
+            || new.leader_lease != old.leader_lease
+        {
+            report.restart_required.push("leader");
+        }
+        new.leader_election = old.leader_election;
+        new.instance_name = old.instance_name.clone();
+        new.leader_lease = old.leader_lease;
+        if new.upstream_headers != old.upstream_headers
+            || new.upstream_max_body != old.upstream_max_body
+        {
+            report.restart_required.push("upstream");
+        }
+        new.upstream_headers = old.upstream_headers.clone();
+        new.upstream_max_body = old.upstream_max_body;
+        if new.audio_cache != old.audio_cache || new.audio_ffmpeg != old.audio_ffmpeg {
+            report.restart_required.push("audio");
+        }
+        new.audio_cache = old.audio_cache.clone();
+        new.audio_ffmpeg = old.audio_ffmpeg.clone();
+
+        *self.current.write().unwrap() = Arc::new(new);
+        Ok(report)
+    }
+}
+
+// Check every source answers, so a blocked or moved site shows up at boot
+// rather than as a stream of per-query errors. Unreachable sources are only
+// a warning since the others keep working.
+pub async fn check_sources(sources: &SourceToggles) {
+    let check = |source: Source| async move {
+        if !sources.enabled(source) {
+            return (source, None);
+        }
+        let Some(url) = source.home_url() else {
+            return (source, None);
+        };
+        let started = Instant::now();
+        let result = upstream::client()
+            .get(url)
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await;
+        (source, Some(result.map(|response| (response.status(), started.elapsed()))))
+    };
+
+    let (taigitv, sutian, itaigi, moedict) = tokio::join!(
+        check(Source::TaigiTv),
+        check(Source::Sutian),
+        check(Source::ITaigi),
+        check(Source::Moedict)
+    );
+
+    info!("Sources:");
+    for (source, result) in [taigitv, sutian, itaigi, moedict] {
+        match result {
+            None => info!("  ⏸️ {} disabled", source.name()),
+            Some(Ok((status, elapsed))) if status.is_success() => info!(
+                "  ✅ {} reachable ({} ms)",
+                source.name(),
+                elapsed.as_millis()
+            ),
+            Some(Ok((status, _))) => warn!("  ⚠️ {} answered with {}", source.name(), status),
+            Some(Err(why)) => warn!("  ⚠️ {} unreachable: {}", source.name(), why),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use serenity::cache::Cache;
+use serenity::http::{CacheHttp, Http};
+use serenity::model::event::UserUpdateEvent;
+
+// What the handlers need from Discord. Made from the gateway's context, or on
+// its own when interactions arrive over HTTP without a gateway connection.
+#[derive(Clone)]
+pub struct Context {
+    pub http: Arc<Http>,
+    pub cache: Arc<Cache>,
+    // Voice needs the gateway's own context. None over HTTP.
+    pub gateway: Option<serenity::prelude::Context>,
+}
+
+impl From<&serenity::prelude::Context> for Context {
+    fn from(ctx: &serenity::prelude::Context) -> Self {
+        Context {
+            http: ctx.http.clone(),
+            cache: ctx.cache.clone(),
+            gateway: Some(ctx.clone()),
+        }
+    }
+}
+
+impl Context {
+    // Without the gateway the cache stays empty but for the bot's own user,
+    // fetched once so handlers can still tell its ID
+    pub async fn without_gateway(http: Arc<Http>) -> Result<Self, String> {
+        let user = http
+            .get_current_user()
+            .await
+            .map_err(|why| format!("cannot fetch the bot user: {}", why))?;
+        let cache = Arc::new(Cache::new());
+        let mut event: UserUpdateEvent = serde_json::to_value(&user)
+            .and_then(serde_json::from_value)
+            .map_err(|why| format!("cannot cache the bot user: {}", why))?;
+        cache.update(&mut event);
+        Ok(Context {
+            http,
+            cache,
+            gateway: None,
+        })
+    }
+}
+
+impl CacheHttp for Context {
+    fn http(&self) -> &Http {
+        &self.http
+    }
+
+    fn cache(&self) -> Option<&Arc<Cache>> {
+        Some(&self.cache)
+    }
+}
+
+impl AsRef<Http> for Context {
+    fn as_ref(&self) -> &Http {
+        &self.http
+    }
+}
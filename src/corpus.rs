@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::text;
+
+// Header names recognised for each column: the ChhoeTaigi exports' own, or
+// plain ones for hand-made files
+const HANJI_COLUMNS: [&str; 4] = ["HanLoTaibunKip", "HanLoTaibun", "hanji", "漢字"];
+const LOMAJI_COLUMNS: [&str; 4] = ["KipUnicode", "PojUnicode", "lomaji", "羅馬字"];
+const MANDARIN_COLUMNS: [&str; 4] = ["HoaBun", "HoaBunHoaGi", "mandarin", "華語"];
+// Optional, for exports with English meanings like the Maryknoll dictionary's
+const ENGLISH_COLUMNS: [&str; 3] = ["EngBun", "english", "英語"];
+// Optional, for word lists whose entries are sorted by theme
+const CATEGORY_COLUMNS: [&str; 2] = ["category", "分類"];
+
+// One example sentence of the corpus
+pub struct Sentence {
+    pub hanji: String,
+    pub lomaji: String,
+    pub mandarin: String,
+    // English meaning, empty unless the corpus has them
+    pub english: String,
+    // Themes of the row for /browse, empty for most sentences
+    pub category: String,
+}
+
+// The sentences, indexed by every Han character and lowercased Lô-jī
+// syllable they contain so a search only scans sentences that can match
+struct Corpus {
+    sentences: Vec<Sentence>,
+    index: HashMap<String, Vec<usize>>,
+}
+
+static CORPUS: OnceLock<Corpus> = OnceLock::new();
+
+fn is_han(c: char) -> bool {
+    matches!(c, '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}')
+}
+
+// The index terms of a piece of text: its Han characters, then its Lô-jī
+// syllables (split on spaces, hyphens and ASCII punctuation)
+fn terms(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = text.chars().filter(|c| is_han(*c)).map(String::from).collect();
+    terms.extend(
+        text.to_lowercase()
+            .split(|c: char| c.is_whitespace() || c.is_ascii_punctuation() || is_han(c))
+            .filter(|syllable| !syllable.is_empty())
+            .map(String::from),
+    );
+    terms
+}
+
+// Split one line of a CSV (quoted fields, "" for a quote) or TSV file
+fn fields(line: &str, separator: char) -> Vec<String> {
+    if separator == '\t' {
+        return line.split('\t').map(String::from).collect();
+    }
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn parse(text: &str, separator: char) -> Result<Corpus, String> {
+    let mut lines = text.lines();
+    let header = fields(lines.next().ok_or("The file is empty")?, separator);
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|name| names.iter().any(|wanted| name.trim().eq_ignore_ascii_case(wanted)))
+    };
+    let hanji = column(&HANJI_COLUMNS).ok_or("No Taigi Hanji column (e.g. \"hanji\")")?;
+    let lomaji = column(&LOMAJI_COLUMNS);
+    let mandarin = column(&MANDARIN_COLUMNS);
+    let english = column(&ENGLISH_COLUMNS);
+    let category = column(&CATEGORY_COLUMNS);
+
+    let mut corpus = Corpus {
+        sentences: Vec::new(),
+        index: HashMap::new(),
+    };
+    for line in lines {
+        let row = fields(line, separator);
+        let cell = |column: Option<usize>| {
+            column
+                .and_then(|column| row.get(column))
+                .map_or(String::new(), |cell| cell.trim().to_string())
+        };
+        let sentence = Sentence {
+            hanji: cell(Some(hanji)),
+            lomaji: text::nfc(&cell(lomaji)),
+            mandarin: cell(mandarin),
+            english: cell(english),
+            category: cell(category),
+        };
+        if sentence.hanji.is_empty() {
+            continue;
+        }
+
+        let id = corpus.sentences.len();
+        let text = format!("{} {} {}", sentence.hanji, sentence.lomaji, sentence.mandarin);
+        for term in terms(&text) {
+            let postings = corpus.index.entry(term).or_default();
+            if postings.last() != Some(&id) {
+                postings.push(id);
+            }
+        }
+        corpus.sentences.push(sentence);
+    }
+    Ok(corpus)
+}
+
+// Read and index the corpus at `path` (.csv or .tsv with a header row) once
+// at startup, returning how many sentences it has
+pub fn load(path: &str) -> Result<usize, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|why| format!("Cannot read {}: {}", path, why))?;
+    let separator = if path.ends_with(".tsv") { '\t' } else { ',' };
+    let corpus = parse(&text, separator).map_err(|err| format!("{}: {}", path, err))?;
+    let count = corpus.sentences.len();
+    CORPUS
+        .set(corpus)
+        .map_err(|_| "The corpus is already loaded".to_string())?;
+    Ok(count)
+}
+
+// Every sentence of the corpus, empty when none is configured
+pub fn sentences() -> &'static [Sentence] {
+    CORPUS.get().map_or(&[], |corpus| &corpus.sentences)
+}
+
+// Sentences containing the keyword in any column, shortest first. Empty
+// when no corpus is configured.
+pub fn search(keyword: &str, limit: usize) -> Vec<&'static Sentence> {
+    let Some(corpus) = CORPUS.get() else {
+        return Vec::new();
+    };
+    let keyword = text::nfc(keyword.trim()).to_lowercase();
+
+    // Scanning the rarest term's sentences is enough, each match has them all
+    let Some(candidates) = terms(&keyword)
+        .iter()
+        .map(|term| corpus.index.get(term).map_or(&[][..], Vec::as_slice))
+        .min_by_key(|postings| postings.len())
+    else {
+        return Vec::new();
+    };
+    let mut found: Vec<&Sentence> = candidates
+        .iter()
+        .map(|&id| &corpus.sentences[id])
+        .filter(|sentence| {
+            sentence.hanji.contains(&keyword)
+                || sentence.mandarin.contains(&keyword)
+                || sentence.lomaji.to_lowercase().contains(&keyword)
+        })
+        .collect();
+    found.sort_by_key(|sentence| sentence.hanji.chars().count());
+    found.truncate(limit);
+    found
+}
+
+// English meanings the corpus gives for exactly `word`, in Hanji or Lô-jī
+pub fn english(word: &str, limit: usize) -> Vec<&'static str> {
+    let word = text::nfc(word.trim());
+    let mut meanings: Vec<&str> = Vec::new();
+    for sentence in search(&word, usize::MAX) {
+        let is_word = sentence.hanji == word || sentence.lomaji.eq_ignore_ascii_case(&word);
+        if is_word && !sentence.english.is_empty() && !meanings.contains(&&*sentence.english) {
+            meanings.push(&sentence.english);
+        }
+    }
+    meanings.truncate(limit);
+    meanings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_english_columns() {
+        let text = "HanLoTaibunKip\tKipUnicode\tHoaBun\tEngBun\n\
+                    食飯\ttsia̍h-pn̄g\t吃飯\tto eat a meal\n\
+                    睏\tkhùn\t睡\t\n";
+        let corpus = parse(text, '\t').unwrap();
+        assert_eq!(corpus.sentences[0].english, "to eat a meal");
+        assert_eq!(corpus.sentences[1].english, "");
+
+        let without = parse("hanji,mandarin\n食飯,吃飯\n", ',').unwrap();
+        assert_eq!(without.sentences[0].english, "");
+    }
+}
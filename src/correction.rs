@@ -0,0 +1,275 @@
+use serenity::builder::{
+    CreateActionRow, CreateButton, CreateInputText, CreateInteractionResponse, CreateMessage,
+    CreateModal,
+};
+use serenity::model::application::{
+    ActionRowComponent, ButtonStyle, ComponentInteraction, InputTextStyle, ModalInteraction,
+};
+use serenity::model::id::ChannelId;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::breakdown::{self, Gloss};
+use crate::context::Context;
+use crate::db;
+use crate::memory;
+use crate::respond::{self, Mode, Reply};
+use crate::text;
+
+// `suggest:<memory ID>` opens the form and submits it, `review:<approve|
+// reject>:<correction ID>` are the moderators' buttons in the ops channel
+const SUGGEST_PREFIX: &str = "suggest:";
+const REVIEW_PREFIX: &str = "review:";
+
+const INPUT_ID: &str = "glosses";
+
+// Under sentence breakdowns that are in the translation memory
+pub fn button(memory_id: i64) -> CreateButton {
+    CreateButton::new(format!("{}{}", SUGGEST_PREFIX, memory_id))
+        .style(ButtonStyle::Secondary)
+        .emoji('✏')
+        .label("Suggest a correction")
+}
+
+// The form's text: one `word = Hanji Tâi-lô` line per word
+fn form_text(glosses: &[Gloss]) -> String {
+    glosses
+        .iter()
+        .map(|gloss| {
+            let taigi = [gloss.hanji.as_deref(), gloss.lomaji.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!("{} = {}", gloss.word, taigi)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// The stored glosses with the form's lines applied. Lines left out keep
+// their gloss; a word can't be added or renamed.
+fn apply_form(mut glosses: Vec<Gloss>, text: &str) -> Result<Vec<Gloss>, String> {
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let (word, taigi) = line
+            .split_once('=')
+            .ok_or_else(|| format!("\"{}\" should look like `word = Hanji Tâi-lô`", line))?;
+        let word = word.trim();
+        let gloss = glosses
+            .iter_mut()
+            .find(|gloss| gloss.word == word)
+            .ok_or_else(|| format!("\"{}\" isn't a word of this sentence", word))?;
+        let mut parts = taigi.split_whitespace();
+        gloss.hanji = parts.next().filter(|hanji| *hanji != "？").map(str::to_string);
+        let lomaji = text::nfc(&parts.collect::<Vec<_>>().join(" "));
+        gloss.lomaji = Some(lomaji).filter(|lomaji| !lomaji.is_empty());
+    }
+    Ok(glosses)
+}
+
+async fn open_form(ctx: &Context, db: &SqlitePool, component: &ComponentInteraction, id: i64) {
+    let response = match db::translation(db, id).await {
+        Ok(Some((_, _, stored))) => {
+            let label = "Word = Hanji Tâi-lô";
+            let input = CreateInputText::new(InputTextStyle::Paragraph, label, INPUT_ID)
+                .value(form_text(&memory::decode(&stored)))
+                .required(true);
+            CreateInteractionResponse::Modal(
+                CreateModal::new(format!("{}{}", SUGGEST_PREFIX, id), "Suggest a correction")
+                    .components(vec![CreateActionRow::InputText(input)]),
+            )
+        }
+        result => {
+            if let Err(why) = result {
+                error!("Error loading the translation memory: {why:?}");
+            }
+            let reply = async { "This translation is no longer stored.".to_string() };
+            respond::component(ctx, db, component, Mode::Ephemeral, reply).await;
+            return;
+        }
+    };
+    if let Err(why) = component.create_response(&ctx.http, response).await {
+        error!("Cannot open the correction form: {why:?}");
+    }
+}
+
+fn review_buttons(correction_id: i64) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{}approve:{}", REVIEW_PREFIX, correction_id))
+            .style(ButtonStyle::Success)
+            .label("Approve"),
+        CreateButton::new(format!("{}reject:{}", REVIEW_PREFIX, correction_id))
+            .style(ButtonStyle::Danger)
+            .label("Reject"),
+    ])
+}
+
+// Store a submitted form and put it up for review in the ops channel
+async fn submit(
+    ctx: &Context,
+    db: &SqlitePool,
+    ops_channel: Option<ChannelId>,
+    modal: &ModalInteraction,
+    id: i64,
+) -> String {
+    let text = modal
+        .data
+        .components
+        .iter()
+        .flat_map(|row| &row.components)
+        .find_map(|component| match component {
+            ActionRowComponent::InputText(input) if input.custom_id == INPUT_ID => {
+                input.value.clone()
+            }
+            _ => None,
+        })
+        .unwrap_or_default();
+    let (sentence, stored) = match db::translation(db, id).await {
+        Ok(Some((_, sentence, stored))) => (sentence, memory::decode(&stored)),
+        Ok(None) => return "This translation is no longer stored.".to_string(),
+        Err(why) => {
+            error!("Error loading the translation memory: {why:?}");
+            return "Could not load the translation.".to_string();
+        }
+    };
+    let before = breakdown::render(&stored);
+    let suggested = match apply_form(stored, &text) {
+        Ok(suggested) => suggested,
+        Err(err) => return format!("Could not read the correction: {}", err),
+    };
+    let after = breakdown::render(&suggested);
+    if after == before {
+        return "That's the translation as it is, nothing to correct.".to_string();
+    }
+
+    let encoded = memory::encode(&suggested);
+    let correction_id =
+        match db::add_correction(db, id, &encoded, &modal.user.id.to_string()).await {
+            Ok(correction_id) => correction_id,
+            Err(why) => {
+                error!("Error saving the correction: {why:?}");
+                return "Could not save the correction.".to_string();
+            }
+        };
+    if let Some(ops) = ops_channel {
+        let notice = CreateMessage::new()
+            .content(respond::fit_message(&format!(
+                "✏️ {} suggests a correction for \"{}\":\n{}\n{}",
+                modal.user.name, sentence, before, after
+            )))
+            .components(vec![review_buttons(correction_id)]);
+        if let Err(why) = ops.send_message(&ctx.http, notice).await {
+            error!("Error notifying ops channel: {why:?}");
+        }
+    }
+    "✏️ Thanks! A moderator will look at your correction.".to_string()
+}
+
+// A moderator approving or rejecting a correction, noted under the notice.
+// Errors are returned apart so they don't replace the notice.
+async fn review(
+    db: &SqlitePool,
+    component: &ComponentInteraction,
+    rest: &str,
+) -> Result<Reply, String> {
+    let Some((decision, correction_id)) = rest
+        .split_once(':')
+        .and_then(|(decision, id)| Some((decision, id.parse::<i64>().ok()?)))
+    else {
+        return Err("Unknown correction.".to_string());
+    };
+    let (memory_id, glosses, user_id) = match db::correction(db, correction_id).await {
+        Ok(Some(correction)) => correction,
+        Ok(None) => return Err("Unknown correction.".to_string()),
+        Err(why) => {
+            error!("Error loading the correction: {why:?}");
+            return Err("Could not load the correction.".to_string());
+        }
+    };
+    let reviewer = component.user.id.to_string();
+    let status = if decision == "approve" { "approved" } else { "rejected" };
+    match db::review_correction(db, correction_id, status, &reviewer).await {
+        Ok(true) => {}
+        Ok(false) => return Err("This correction was already reviewed.".to_string()),
+        Err(why) => {
+            error!("Error reviewing the correction: {why:?}");
+            return Err("Could not review the correction.".to_string());
+        }
+    }
+    // An approved correction replaces the remembered translation, which isn't
+    // overwritten by fresh lookups after that
+    if status == "approved" {
+        match db::translation(db, memory_id).await {
+            Ok(Some((key, _, _))) => {
+                if let Err(why) = db::edit_translation(db, &key, &glosses, &reviewer).await {
+                    error!("Error applying the correction: {why:?}");
+                }
+            }
+            // Purged in the meantime
+            Ok(None) => {}
+            Err(why) => error!("Error loading the translation memory: {why:?}"),
+        }
+    }
+
+    let verdict = if status == "approved" {
+        format!("✅ Approved by <@{}>, suggested by <@{}>.", reviewer, user_id)
+    } else {
+        format!("❌ Rejected by <@{}>.", reviewer)
+    };
+    Ok(Reply {
+        content: format!("{}\n{}", component.message.content, verdict),
+        components: Some(Vec::new()),
+        files: Vec::new(),
+    })
+}
+
+// Handle the suggestion button and the moderators' buttons. Returns false for
+// other components.
+pub async fn handle(ctx: &Context, db: &SqlitePool, component: &ComponentInteraction) -> bool {
+    let custom_id = &component.data.custom_id;
+    if let Some(id) = custom_id.strip_prefix(SUGGEST_PREFIX) {
+        match id.parse::<i64>() {
+            Ok(id) => open_form(ctx, db, component, id).await,
+            Err(_) => return false,
+        }
+        return true;
+    }
+    let Some(rest) = custom_id.strip_prefix(REVIEW_PREFIX) else {
+        return false;
+    };
+    // Anyone who can manage messages in the ops channel moderates
+    let is_moderator = component
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.manage_messages());
+    if !is_moderator {
+        let reply = async { "Only moderators can review corrections.".to_string() };
+        respond::component(ctx, db, component, Mode::Ephemeral, reply).await;
+        return true;
+    }
+    match review(db, component, rest).await {
+        Ok(reply) => respond::component(ctx, db, component, Mode::Update, async { reply }).await,
+        Err(err) => respond::component(ctx, db, component, Mode::Ephemeral, async { err }).await,
+    }
+    true
+}
+
+// Handle a submitted correction form. Returns false for other modals.
+pub async fn handle_modal(
+    ctx: &Context,
+    db: &SqlitePool,
+    ops_channel: Option<ChannelId>,
+    modal: &ModalInteraction,
+) -> bool {
+    let Some(Ok(id)) = modal
+        .data
+        .custom_id
+        .strip_prefix(SUGGEST_PREFIX)
+        .map(str::parse::<i64>)
+    else {
+        return false;
+    };
+    respond::modal(ctx, db, modal, submit(ctx, db, ops_channel, modal, id)).await;
+    true
+}
@@ -0,0 +1,1890 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use serenity::model::id::ChannelId;
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePool, SqlitePoolOptions};
+use sqlx::{Connection, Row};
+
+use crate::apikey::{ApiKey, Scope};
+use crate::i18n::Locale;
+use crate::itaigi::VoteTarget;
+use crate::lookup::{self, Lookup};
+use crate::romanization::{self, Romanization};
+use crate::source::Source;
+use crate::variant::Accent;
+
+pub async fn connect(url: &str) -> Result<SqlitePool, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await?;
+    migrate(&pool).await?;
+    Ok(pool)
+}
+
+// Bring the schema up to date with the migrations in migrations/, each
+// applied once and recorded in _sqlx_migrations. The write lock is held
+// throughout, so instances sharing the database (see `leader.election`)
+// starting at the same time don't both apply one.
+async fn migrate(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let mut tx = conn.begin_with("BEGIN IMMEDIATE").await?;
+
+    // Databases from before migrations may lack columns added later on, which
+    // the first migration's CREATE TABLE IF NOT EXISTS wouldn't add
+    add_column(&mut tx, "user_prefs", "private", "INTEGER").await?;
+    add_column(&mut tx, "user_prefs", "english", "INTEGER").await?;
+    add_column(&mut tx, "guild_settings", "footnotes", "INTEGER").await?;
+
+    MIGRATOR.run(&mut *tx).await?;
+    tx.commit().await
+}
+
+static MIGRATOR: Migrator = sqlx::migrate!();
+
+// Add a column to a table created by an older version, if the table is
+// there and the column is missing
+async fn add_column(
+    conn: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    let (has_table, has_column): (bool, bool) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM pragma_table_info(?1)),
+                EXISTS (SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+    )
+    .bind(table)
+    .bind(column)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if has_table && !has_column {
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+// A result a user flagged as wrong or mis-parsed
+pub struct Feedback {
+    pub query: String,
+    pub source: String,
+    pub output: String,
+    pub reporter_id: String,
+    pub channel_id: String,
+    pub message_id: String,
+}
+
+pub async fn insert_feedback(pool: &SqlitePool, feedback: &Feedback) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO feedback (query, source, output, reporter_id, channel_id, message_id)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&feedback.query)
+    .bind(&feedback.source)
+    .bind(&feedback.output)
+    .bind(&feedback.reporter_id)
+    .bind(&feedback.channel_id)
+    .bind(&feedback.message_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// A lookup as the (results, sources, vote_targets, pos) columns stored with it
+fn encode_lookup(lookup: &Lookup) -> (String, String, String, String) {
+    let results = json!(lookup.results).to_string();
+    let sources: String = lookup.sources.iter().map(|source| source.code()).collect();
+    let vote_targets: Vec<Value> = lookup
+        .vote_targets
+        .iter()
+        .map(|target| json!({ "id": target.entry_id, "label": target.label }))
+        .collect();
+    let pos = json!(lookup.pos).to_string();
+    (results, sources, Value::Array(vote_targets).to_string(), pos)
+}
+
+fn decode_lookup(results: &str, sources: &str, vote_targets: &str, pos: &str) -> Lookup {
+    Lookup {
+        results: serde_json::from_str(results).unwrap_or_default(),
+        sources: sources.chars().filter_map(Source::from_code).collect(),
+        vote_targets: serde_json::from_str::<Vec<Value>>(vote_targets)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|target| {
+                Some(VoteTarget {
+                    entry_id: target.get("id")?.as_str()?.to_string(),
+                    label: target.get("label")?.as_str()?.to_string(),
+                })
+            })
+            .collect(),
+        pos: serde_json::from_str(pos).unwrap_or_default(),
+        errors: Vec::new(),
+        timings: Vec::new(),
+    }
+}
+
+// One answered query, kept with its results so they can be replayed later
+pub struct HistoryEntry<'a> {
+    pub keyword: &'a str,
+    pub variant: Option<Accent>,
+    pub lookup: &'a Lookup,
+    pub user_id: String,
+    pub channel_id: String,
+    // None in DMs
+    pub guild_id: Option<String>,
+    // Whether the answer was posted where others see it
+    pub public: bool,
+}
+
+pub async fn record_query(pool: &SqlitePool, entry: &HistoryEntry<'_>) -> Result<(), sqlx::Error> {
+    let (results, sources, vote_targets, pos) = encode_lookup(entry.lookup);
+
+    sqlx::query(
+        "INSERT INTO query_history
+            (keyword, variant, results, sources, vote_targets, pos, complete, user_id,
+             channel_id, guild_id, public)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(entry.keyword)
+    .bind(entry.variant.map(|accent| accent.label()))
+    .bind(results)
+    .bind(sources)
+    .bind(vote_targets)
+    .bind(pos)
+    .bind(entry.lookup.is_complete())
+    .bind(&entry.user_id)
+    .bind(&entry.channel_id)
+    .bind(&entry.guild_id)
+    .bind(entry.public)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// The most frequently asked keywords with their latest complete results
+// still younger than `max_age`, as (cache key, lookup, age)
+pub async fn top_queries(
+    pool: &SqlitePool,
+    limit: u32,
+    max_age: Duration,
+) -> Result<Vec<(String, Lookup, Duration)>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT h.keyword, h.variant, h.results, h.sources, h.vote_targets, h.pos,
+                CAST(strftime('%s', 'now') - strftime('%s', h.created_at) AS INTEGER) AS age
+         FROM query_history h
+         JOIN (
+             SELECT MAX(id) AS latest, COUNT(*) AS uses
+             FROM query_history
+             WHERE complete = 1 AND created_at >= datetime('now', ?)
+             GROUP BY keyword, variant
+             ORDER BY uses DESC
+             LIMIT ?
+         ) top ON h.id = top.latest",
+    )
+    .bind(format!("-{} seconds", max_age.as_secs()))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let mut queries = Vec::new();
+    for row in rows {
+        let keyword: String = row.get("keyword");
+        let variant: Option<String> = row.get("variant");
+        let results: String = row.get("results");
+        let sources: String = row.get("sources");
+        let vote_targets: String = row.get("vote_targets");
+        let pos: String = row.get("pos");
+        let age: i64 = row.get("age");
+
+        let lookup = decode_lookup(&results, &sources, &vote_targets, &pos);
+
+        let variant = variant.as_deref().and_then(Accent::parse);
+        let key = lookup::cache_key(&keyword, variant);
+        queries.push((key, lookup, Duration::from_secs(age.max(0) as u64)));
+    }
+
+    Ok(queries)
+}
+
+// Previously answered keywords matching `text`, in the keyword itself or in
+// its latest complete results, exact matches first. Only reads the history:
+// in a server its public lookups, in DMs the user's own DM lookups.
+pub async fn recall(
+    pool: &SqlitePool,
+    text: &str,
+    guild_id: Option<&str>,
+    user_id: &str,
+    limit: u32,
+) -> Result<Vec<(String, Lookup)>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT h.keyword, h.results, h.sources, h.vote_targets, h.pos
+         FROM query_history h
+         JOIN (
+             SELECT MAX(id) AS latest
+             FROM query_history
+             WHERE complete = 1
+               AND CASE WHEN ?3 IS NULL THEN guild_id IS NULL AND user_id = ?4
+                        ELSE guild_id = ?3 AND public = 1 END
+             GROUP BY keyword
+         ) top ON h.id = top.latest
+         WHERE instr(h.keyword, ?1) > 0 OR instr(h.results, ?1) > 0
+         ORDER BY h.keyword != ?1, instr(h.keyword, ?1) = 0, length(h.keyword), h.keyword
+         LIMIT ?2",
+    )
+    .bind(text)
+    .bind(limit)
+    .bind(guild_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let results: String = row.get("results");
+            let sources: String = row.get("sources");
+            let vote_targets: String = row.get("vote_targets");
+            let pos: String = row.get("pos");
+            (
+                row.get("keyword"),
+                decode_lookup(&results, &sources, &vote_targets, &pos),
+            )
+        })
+        .collect())
+}
+
+// Keywords answered publicly in full that contain `character`
+pub async fn answered_with(
+    pool: &SqlitePool,
+    character: char,
+    limit: u32,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT DISTINCT keyword FROM query_history
+         WHERE complete = 1 AND public = 1 AND instr(keyword, ?) > 0
+         LIMIT ?",
+    )
+    .bind(character.to_string())
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+// Every keyword answered publicly in full, with its latest results, their
+// sources and how often it was asked, for the glossary export. The glossary
+// is published, so private and DM lookups never count.
+pub async fn glossary(pool: &SqlitePool) -> Result<Vec<(String, i64, Lookup)>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT h.keyword, top.uses, h.results, h.sources, h.vote_targets, h.pos
+         FROM query_history h
+         JOIN (
+             SELECT MAX(id) AS latest, COUNT(*) AS uses
+             FROM query_history
+             WHERE complete = 1 AND public = 1
+             GROUP BY keyword
+         ) top ON h.id = top.latest
+         ORDER BY h.keyword",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let results: String = row.get("results");
+            let sources: String = row.get("sources");
+            let vote_targets: String = row.get("vote_targets");
+            let pos: String = row.get("pos");
+            (
+                row.get("keyword"),
+                row.get("uses"),
+                decode_lookup(&results, &sources, &vote_targets, &pos),
+            )
+        })
+        .collect())
+}
+
+// Every keyword answered publicly in a server in the last `period`, most
+// asked first, as (keyword, times asked, latest results). DMs and private
+// answers never count, since digests and the public feed are made of these.
+// With a server, only its lookups.
+pub async fn recent_queries(
+    pool: &SqlitePool,
+    guild_id: Option<&str>,
+    period: Duration,
+) -> Result<Vec<(String, i64, Vec<String>)>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT h.keyword, top.uses, h.results
+         FROM query_history h
+         JOIN (
+             SELECT MAX(id) AS latest, COUNT(*) AS uses
+             FROM query_history
+             WHERE created_at >= datetime('now', ?2)
+               AND public = 1 AND (?1 IS NULL OR guild_id = ?1)
+             GROUP BY keyword
+         ) top ON h.id = top.latest
+         ORDER BY top.uses DESC, h.keyword",
+    )
+    .bind(guild_id)
+    .bind(format!("-{} seconds", period.as_secs()))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let results: String = row.get("results");
+            (
+                row.get("keyword"),
+                row.get("uses"),
+                serde_json::from_str(&results).unwrap_or_default(),
+            )
+        })
+        .collect())
+}
+
+// The distinct keywords answered publicly in a channel in the last `period`,
+// in the order first asked, as (keyword, latest results)
+pub async fn channel_queries(
+    pool: &SqlitePool,
+    channel_id: &str,
+    period: Duration,
+) -> Result<Vec<(String, Vec<String>)>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT h.keyword, h.results
+         FROM query_history h
+         JOIN (
+             SELECT MIN(id) AS first, MAX(id) AS latest
+             FROM query_history
+             WHERE channel_id = ? AND created_at >= datetime('now', ?) AND public = 1
+             GROUP BY keyword
+         ) asked ON h.id = asked.latest
+         ORDER BY asked.first",
+    )
+    .bind(channel_id)
+    .bind(format!("-{} seconds", period.as_secs()))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let results: String = row.get("results");
+            (row.get("keyword"), serde_json::from_str(&results).unwrap_or_default())
+        })
+        .collect())
+}
+
+// Persisted copy of the result cache, so lookups survive restarts.
+// `stored_at` is a Unix timestamp.
+pub async fn save_cached(pool: &SqlitePool, key: &str, lookup: &Lookup) -> Result<(), sqlx::Error> {
+    let (results, sources, vote_targets, pos) = encode_lookup(lookup);
+
+    sqlx::query(
+        "INSERT OR REPLACE INTO lookup_cache (key, results, sources, vote_targets, pos, stored_at)
+         VALUES (?, ?, ?, ?, ?, strftime('%s', 'now'))",
+    )
+    .bind(key)
+    .bind(results)
+    .bind(sources)
+    .bind(vote_targets)
+    .bind(pos)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Cached lookups younger than `max_age`, as (cache key, lookup, age)
+pub async fn load_cached(
+    pool: &SqlitePool,
+    max_age: Duration,
+) -> Result<Vec<(String, Lookup, Duration)>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT key, results, sources, vote_targets, pos,
+                CAST(strftime('%s', 'now') AS INTEGER) - stored_at AS age
+         FROM lookup_cache
+         WHERE stored_at >= CAST(strftime('%s', 'now') AS INTEGER) - ?",
+    )
+    .bind(max_age.as_secs() as i64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let results: String = row.get("results");
+            let sources: String = row.get("sources");
+            let vote_targets: String = row.get("vote_targets");
+            let pos: String = row.get("pos");
+            let age: i64 = row.get("age");
+            (
+                row.get("key"),
+                decode_lookup(&results, &sources, &vote_targets, &pos),
+                Duration::from_secs(age.max(0) as u64),
+            )
+        })
+        .collect())
+}
+
+// Drop cached lookups older than `max_age`, returning how many were removed
+pub async fn compact_cached(pool: &SqlitePool, max_age: Duration) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM lookup_cache WHERE stored_at < CAST(strftime('%s', 'now') AS INTEGER) - ?",
+    )
+    .bind(max_age.as_secs() as i64)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn clear_cached(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM lookup_cache").execute(pool).await?;
+
+    Ok(())
+}
+
+// FNV-1a, so the same keyword gets the same ID on every install
+pub fn entry_hash(key: &str) -> String {
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+// The short ID of a keyword's entry, handing one out on first use. IDs are
+// the start of a hash of the cache key, lengthened on the rare collision.
+pub async fn entry_id(
+    pool: &SqlitePool,
+    keyword: &str,
+    variant: Option<Accent>,
+) -> Result<String, sqlx::Error> {
+    let key = lookup::cache_key(keyword, variant);
+    let known: Option<String> = sqlx::query_scalar("SELECT id FROM entries WHERE key = ?")
+        .bind(&key)
+        .fetch_optional(pool)
+        .await?;
+    if let Some(id) = known {
+        return Ok(id);
+    }
+
+    let hash = entry_hash(&key);
+    for len in (4..=hash.len()).step_by(2) {
+        let inserted = sqlx::query(
+            "INSERT OR IGNORE INTO entries (id, key, keyword, variant) VALUES (?, ?, ?, ?)",
+        )
+        .bind(&hash[..len])
+        .bind(&key)
+        .bind(keyword)
+        .bind(variant.map(|accent| accent.label()))
+        .execute(pool)
+        .await?;
+        if inserted.rows_affected() == 1 {
+            return Ok(hash[..len].to_string());
+        }
+    }
+    // Another reply handed out an ID for the same key in the meantime
+    sqlx::query_scalar("SELECT id FROM entries WHERE key = ?")
+        .bind(&key)
+        .fetch_one(pool)
+        .await
+}
+
+// The keyword and stored lookup behind a short ID. The cached copy
+// is used while there is one, otherwise the latest complete answer from the
+// history, so old IDs keep working after the cache expires.
+pub async fn entry(
+    pool: &SqlitePool,
+    id: &str,
+) -> Result<Option<(String, Lookup)>, sqlx::Error> {
+    let Some(row) = sqlx::query("SELECT key, keyword, variant FROM entries WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let key: String = row.get("key");
+    let keyword: String = row.get("keyword");
+    let variant: Option<String> = row.get("variant");
+
+    let stored = match sqlx::query(
+        "SELECT results, sources, vote_targets, pos FROM lookup_cache WHERE key = ?",
+    )
+    .bind(&key)
+    .fetch_optional(pool)
+    .await?
+    {
+        Some(row) => Some(row),
+        None => {
+            sqlx::query(
+                "SELECT results, sources, vote_targets, pos FROM query_history
+                 WHERE keyword = ? AND variant IS ? AND complete = 1
+                 ORDER BY id DESC LIMIT 1",
+            )
+            .bind(&keyword)
+            .bind(&variant)
+            .fetch_optional(pool)
+            .await?
+        }
+    };
+
+    Ok(stored.map(|row| {
+        let results: String = row.get("results");
+        let sources: String = row.get("sources");
+        let vote_targets: String = row.get("vote_targets");
+        let pos: String = row.get("pos");
+        (keyword, decode_lookup(&results, &sources, &vote_targets, &pos))
+    }))
+}
+
+// A user's own defaults, set with /prefs. Unset fields fall back to the
+// bot-wide configuration.
+#[derive(Clone, Copy, Default)]
+pub struct UserPrefs {
+    pub romanization: Option<Romanization>,
+    pub ipa: Option<bool>,
+    pub result_limit: Option<u32>,
+    pub locale: Option<Locale>,
+    // Reply to /taigi with ephemeral messages
+    pub private: Option<bool>,
+    // Add English meanings next to the Mandarin ones
+    pub english: Option<bool>,
+}
+
+impl UserPrefs {
+    // Rewrite the pronunciations of a formatted result
+    pub fn format_result(&self, result: &str) -> String {
+        romanization::apply(
+            result,
+            self.romanization.unwrap_or_default(),
+            self.ipa.unwrap_or(false),
+        )
+    }
+
+    // Convert a bare Tâi-lô reading
+    pub fn romanize(&self, tailo: &str) -> String {
+        self.romanization.unwrap_or_default().write(tailo)
+    }
+
+    // Convert the Tâi-lô in a message about to be sent
+    pub fn render(&self, text: &str) -> String {
+        romanization::render(text, self.romanization.unwrap_or_default())
+    }
+}
+
+pub async fn get_prefs(pool: &SqlitePool, user_id: &str) -> Result<UserPrefs, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT romanization, ipa, result_limit, locale, private, english FROM user_prefs
+         WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(UserPrefs::default());
+    };
+
+    let romanization: Option<String> = row.get("romanization");
+    let locale: Option<String> = row.get("locale");
+    Ok(UserPrefs {
+        romanization: romanization.as_deref().and_then(Romanization::parse),
+        ipa: row.get("ipa"),
+        result_limit: row.get("result_limit"),
+        locale: locale.as_deref().and_then(Locale::parse),
+        private: row.get("private"),
+        english: row.get("english"),
+    })
+}
+
+pub async fn save_prefs(
+    pool: &SqlitePool,
+    user_id: &str,
+    prefs: &UserPrefs,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO user_prefs (user_id, romanization, ipa, result_limit, locale, private, english)
+         VALUES (?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT (user_id) DO UPDATE SET
+            romanization = excluded.romanization,
+            ipa = excluded.ipa,
+            result_limit = excluded.result_limit,
+            locale = excluded.locale,
+            private = excluded.private,
+            english = excluded.english,
+            updated_at = CURRENT_TIMESTAMP",
+    )
+    .bind(user_id)
+    .bind(prefs.romanization.map(|romanization| romanization.code()))
+    .bind(prefs.ipa)
+    .bind(prefs.result_limit)
+    .bind(prefs.locale.map(|locale| locale.code()))
+    .bind(prefs.private)
+    .bind(prefs.english)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_prefs(pool: &SqlitePool, user_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM user_prefs WHERE user_id = ?")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// What a server picked in the setup wizard. Unset fields fall back to the
+// bot-wide configuration.
+#[derive(Clone, Default)]
+pub struct GuildSettings {
+    // Answered in addition to the configured lookup channels
+    pub lookup_channel: Option<ChannelId>,
+    pub locale: Option<Locale>,
+    // Sources whose results are shown in the server
+    pub sources: Option<Vec<Source>>,
+    // Explain the romanization symbols under results, for newcomers
+    pub footnotes: bool,
+    // For members who haven't set their own
+    pub romanization: Option<Romanization>,
+    // Opted out of /admin announce
+    pub announcements_muted: bool,
+    // Where the server's daily digest goes
+    pub digest_channel: Option<ChannelId>,
+    // Forum getting a post per keyword answered publicly in the server
+    pub archive_forum: Option<ChannelId>,
+    // English meanings for members who haven't set their own
+    pub english: Option<bool>,
+}
+
+impl GuildSettings {
+    pub fn shows(&self, source: Source) -> bool {
+        self.sources
+            .as_ref()
+            .is_none_or(|sources| sources.contains(&source))
+    }
+}
+
+pub async fn get_guild_settings(
+    pool: &SqlitePool,
+    guild_id: &str,
+) -> Result<GuildSettings, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT lookup_channel, locale, sources, footnotes, romanization, announcements_muted,
+                digest_channel, archive_forum, english
+         FROM guild_settings WHERE guild_id = ?",
+    )
+    .bind(guild_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(GuildSettings::default());
+    };
+
+    let lookup_channel: Option<String> = row.get("lookup_channel");
+    let locale: Option<String> = row.get("locale");
+    let sources: Option<String> = row.get("sources");
+    let footnotes: Option<bool> = row.get("footnotes");
+    let romanization: Option<String> = row.get("romanization");
+    let announcements_muted: Option<bool> = row.get("announcements_muted");
+    let digest_channel: Option<String> = row.get("digest_channel");
+    let archive_forum: Option<String> = row.get("archive_forum");
+    Ok(GuildSettings {
+        lookup_channel: lookup_channel
+            .and_then(|id| id.parse::<u64>().ok())
+            .map(ChannelId::new),
+        locale: locale.as_deref().and_then(Locale::parse),
+        sources: sources.map(|codes| codes.chars().filter_map(Source::from_code).collect()),
+        footnotes: footnotes.unwrap_or(false),
+        romanization: romanization.as_deref().and_then(Romanization::parse),
+        announcements_muted: announcements_muted.unwrap_or(false),
+        digest_channel: digest_channel
+            .and_then(|id| id.parse::<u64>().ok())
+            .map(ChannelId::new),
+        archive_forum: archive_forum
+            .and_then(|id| id.parse::<u64>().ok())
+            .map(ChannelId::new),
+        english: row.get("english"),
+    })
+}
+
+pub async fn save_guild_settings(
+    pool: &SqlitePool,
+    guild_id: &str,
+    settings: &GuildSettings,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO guild_settings
+            (guild_id, lookup_channel, locale, sources, footnotes, romanization,
+             announcements_muted, digest_channel, archive_forum, english)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT (guild_id) DO UPDATE SET
+            lookup_channel = excluded.lookup_channel,
+            locale = excluded.locale,
+            sources = excluded.sources,
+            footnotes = excluded.footnotes,
+            romanization = excluded.romanization,
+            announcements_muted = excluded.announcements_muted,
+            digest_channel = excluded.digest_channel,
+            archive_forum = excluded.archive_forum,
+            english = excluded.english,
+            updated_at = CURRENT_TIMESTAMP",
+    )
+    .bind(guild_id)
+    .bind(settings.lookup_channel.map(|id| id.to_string()))
+    .bind(settings.locale.map(|locale| locale.code()))
+    .bind(
+        settings
+            .sources
+            .as_ref()
+            .map(|sources| sources.iter().map(|source| source.code()).collect::<String>()),
+    )
+    .bind(settings.footnotes)
+    .bind(settings.romanization.map(|romanization| romanization.code()))
+    .bind(settings.announcements_muted)
+    .bind(settings.digest_channel.map(|id| id.to_string()))
+    .bind(settings.archive_forum.map(|id| id.to_string()))
+    .bind(settings.english)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Servers that picked a digest channel, as (server ID, channel)
+pub async fn digest_channels(pool: &SqlitePool) -> Result<Vec<(String, ChannelId)>, sqlx::Error> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT guild_id, digest_channel FROM guild_settings WHERE digest_channel IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(guild_id, channel)| {
+            Some((guild_id, ChannelId::new(channel.parse().ok()?)))
+        })
+        .collect())
+}
+
+// Every blocklist entry as (kind, id)
+pub async fn blocked(pool: &SqlitePool) -> Result<Vec<(String, u64)>, sqlx::Error> {
+    let rows = sqlx::query("SELECT kind, id FROM blocklist")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let id: String = row.get("id");
+            Some((row.get("kind"), id.parse().ok()?))
+        })
+        .collect())
+}
+
+pub async fn block(pool: &SqlitePool, kind: &str, id: u64, added_by: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT OR IGNORE INTO blocklist (kind, id, added_by) VALUES (?, ?, ?)")
+        .bind(kind)
+        .bind(id.to_string())
+        .bind(added_by)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn unblock(pool: &SqlitePool, kind: &str, id: u64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM blocklist WHERE kind = ? AND id = ?")
+        .bind(kind)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// A server's filtered words as (word, action), alphabetically
+pub async fn filtered_words(
+    pool: &SqlitePool,
+    guild_id: &str,
+) -> Result<Vec<(String, String)>, sqlx::Error> {
+    let rows = sqlx::query("SELECT word, action FROM filtered_words WHERE guild_id = ? ORDER BY word")
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(|row| (row.get("word"), row.get("action"))).collect())
+}
+
+// Adding a word again changes its action
+pub async fn filter_word(
+    pool: &SqlitePool,
+    guild_id: &str,
+    word: &str,
+    action: &str,
+    added_by: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO filtered_words (guild_id, word, action, added_by) VALUES (?, ?, ?, ?)
+         ON CONFLICT (guild_id, word) DO UPDATE SET action = excluded.action",
+    )
+    .bind(guild_id)
+    .bind(word)
+    .bind(action)
+    .bind(added_by)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Returns false if the word wasn't filtered
+pub async fn unfilter_word(pool: &SqlitePool, guild_id: &str, word: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM filtered_words WHERE guild_id = ? AND word = ?")
+        .bind(guild_id)
+        .bind(word)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Returns false if the word was already saved
+pub async fn save_word(pool: &SqlitePool, user_id: &str, keyword: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("INSERT OR IGNORE INTO saved_words (user_id, keyword) VALUES (?, ?)")
+        .bind(user_id)
+        .bind(keyword)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// A user's saved words, oldest first
+pub async fn saved_words(pool: &SqlitePool, user_id: &str) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT keyword FROM saved_words WHERE user_id = ? ORDER BY created_at, keyword")
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+}
+
+// Returns false if the word wasn't saved
+pub async fn unsave_word(pool: &SqlitePool, user_id: &str, keyword: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM saved_words WHERE user_id = ? AND keyword = ?")
+        .bind(user_id)
+        .bind(keyword)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// A post of the public feed: a word of the day or a weekly digest
+pub struct FeedEntry {
+    pub id: i64,
+    pub kind: String,
+    pub title: String,
+    pub content: String,
+    // UTC, as `YYYY-MM-DD HH:MM:SS`
+    pub created_at: String,
+}
+
+pub async fn add_feed_entry(
+    pool: &SqlitePool,
+    kind: &str,
+    title: &str,
+    content: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO feed_entries (kind, title, content) VALUES (?, ?, ?)")
+        .bind(kind)
+        .bind(title)
+        .bind(content)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// The latest feed entries, newest first
+pub async fn feed_entries(pool: &SqlitePool, limit: u32) -> Result<Vec<FeedEntry>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, kind, title, content, created_at FROM feed_entries
+         ORDER BY id DESC LIMIT ?",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| FeedEntry {
+            id: row.get("id"),
+            kind: row.get("kind"),
+            title: row.get("title"),
+            content: row.get("content"),
+            created_at: row.get("created_at"),
+        })
+        .collect())
+}
+
+// A background job, with the message it reports progress in once posted
+pub struct JobRow {
+    pub id: i64,
+    pub kind: String,
+    pub user_id: String,
+    pub channel_id: Option<String>,
+    pub message_id: Option<String>,
+}
+
+pub async fn add_job(pool: &SqlitePool, kind: &str, user_id: &str) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query("INSERT INTO jobs (kind, user_id) VALUES (?, ?)")
+        .bind(kind)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+pub async fn get_job(pool: &SqlitePool, id: i64) -> Result<Option<JobRow>, sqlx::Error> {
+    let row = sqlx::query("SELECT id, kind, user_id, channel_id, message_id FROM jobs WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|row| JobRow {
+        id: row.get("id"),
+        kind: row.get("kind"),
+        user_id: row.get("user_id"),
+        channel_id: row.get("channel_id"),
+        message_id: row.get("message_id"),
+    }))
+}
+
+// Jobs that were queued or running when the bot stopped, oldest first
+pub async fn unfinished_jobs(pool: &SqlitePool) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar("SELECT id FROM jobs WHERE status IN ('queued', 'running') ORDER BY id")
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn set_job_message(
+    pool: &SqlitePool,
+    id: i64,
+    channel_id: &str,
+    message_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE jobs SET channel_id = ?, message_id = ? WHERE id = ?")
+        .bind(channel_id)
+        .bind(message_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// `queued`, `running`, `done` or `failed`
+pub async fn set_job_status(pool: &SqlitePool, id: i64, status: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE jobs SET status = ? WHERE id = ?")
+        .bind(status)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// Store an announcement for every server with a lookup channel that hasn't
+// turned them off, returning its ID and how many servers it goes to
+pub async fn add_announcement(
+    pool: &SqlitePool,
+    content: &str,
+    created_by: &str,
+) -> Result<(i64, u64), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let id = sqlx::query("INSERT INTO announcements (content, created_by) VALUES (?, ?)")
+        .bind(content)
+        .bind(created_by)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+    let recipients = sqlx::query(
+        "INSERT INTO announcement_deliveries (announcement_id, guild_id)
+         SELECT ?, guild_id FROM guild_settings
+         WHERE lookup_channel IS NOT NULL AND NOT COALESCE(announcements_muted, 0)",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+
+    Ok((id, recipients))
+}
+
+// An announcement still to be sent to a server
+pub struct Delivery {
+    pub announcement_id: i64,
+    pub guild_id: String,
+    pub channel_id: String,
+    pub content: String,
+}
+
+// Announcements not sent yet, oldest first. Those for servers that turned
+// them off or unset their lookup channel since are skipped.
+pub async fn pending_deliveries(pool: &SqlitePool) -> Result<Vec<Delivery>, sqlx::Error> {
+    sqlx::query(
+        "UPDATE announcement_deliveries SET status = 'skipped', updated_at = CURRENT_TIMESTAMP
+         WHERE status = 'pending' AND guild_id NOT IN (
+             SELECT guild_id FROM guild_settings
+             WHERE lookup_channel IS NOT NULL AND NOT COALESCE(announcements_muted, 0))",
+    )
+    .execute(pool)
+    .await?;
+
+    let rows = sqlx::query(
+        "SELECT d.announcement_id, d.guild_id, g.lookup_channel, a.content
+         FROM announcement_deliveries d
+         JOIN announcements a ON a.id = d.announcement_id
+         JOIN guild_settings g ON g.guild_id = d.guild_id
+         WHERE d.status = 'pending'
+         ORDER BY d.announcement_id, d.guild_id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| Delivery {
+            announcement_id: row.get("announcement_id"),
+            guild_id: row.get("guild_id"),
+            channel_id: row.get("lookup_channel"),
+            content: row.get("content"),
+        })
+        .collect())
+}
+
+// Mark a delivery as being sent. Returns false if another job already has it,
+// so no server gets an announcement twice.
+pub async fn claim_delivery(pool: &SqlitePool, delivery: &Delivery) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE announcement_deliveries SET status = 'sending', updated_at = CURRENT_TIMESTAMP
+         WHERE announcement_id = ? AND guild_id = ? AND status = 'pending'",
+    )
+    .bind(delivery.announcement_id)
+    .bind(&delivery.guild_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+// Put deliveries a stopped bot left `sending` back to `pending`, returning
+// how many. The bot may have stopped after the message went out, so a server
+// can get it twice, but none is left without it.
+pub async fn release_deliveries(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE announcement_deliveries SET status = 'pending', updated_at = CURRENT_TIMESTAMP
+         WHERE status = 'sending'",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// `sent` or `failed`
+pub async fn finish_delivery(
+    pool: &SqlitePool,
+    delivery: &Delivery,
+    status: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE announcement_deliveries SET status = ?, updated_at = CURRENT_TIMESTAMP
+         WHERE announcement_id = ? AND guild_id = ?",
+    )
+    .bind(status)
+    .bind(delivery.announcement_id)
+    .bind(&delivery.guild_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// The /tutorial step a user is at and whether they finished it once
+pub async fn tutorial_progress(
+    pool: &SqlitePool,
+    user_id: &str,
+) -> Result<Option<(usize, bool)>, sqlx::Error> {
+    let row: Option<(i64, bool)> =
+        sqlx::query_as("SELECT step, finished FROM tutorial_progress WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(row.map(|(step, finished)| (step.max(0) as usize, finished)))
+}
+
+pub async fn save_tutorial_progress(
+    pool: &SqlitePool,
+    user_id: &str,
+    step: usize,
+    finished: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO tutorial_progress (user_id, step, finished) VALUES (?, ?, ?)
+         ON CONFLICT (user_id) DO UPDATE SET
+            step = excluded.step,
+            finished = excluded.finished,
+            updated_at = CURRENT_TIMESTAMP",
+    )
+    .bind(user_id)
+    .bind(step as i64)
+    .bind(finished)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// A phrase a server added to /phrasebook
+pub struct GuildPhrase {
+    pub id: i64,
+    pub situation: String,
+    pub hanji: String,
+    pub lomaji: String,
+    pub english: String,
+    // The word of the phrase its 🔊 button plays
+    pub audio: Option<String>,
+}
+
+// A server's phrases, oldest first
+pub async fn guild_phrases(
+    pool: &SqlitePool,
+    guild_id: &str,
+) -> Result<Vec<GuildPhrase>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, situation, hanji, lomaji, english, audio FROM guild_phrases
+         WHERE guild_id = ? ORDER BY id",
+    )
+    .bind(guild_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| GuildPhrase {
+            id: row.get("id"),
+            situation: row.get("situation"),
+            hanji: row.get("hanji"),
+            lomaji: row.get("lomaji"),
+            english: row.get("english"),
+            audio: row.get("audio"),
+        })
+        .collect())
+}
+
+// Returns the new phrase's ID
+pub async fn add_guild_phrase(
+    pool: &SqlitePool,
+    guild_id: &str,
+    phrase: &GuildPhrase,
+    added_by: &str,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO guild_phrases (guild_id, situation, hanji, lomaji, english, audio, added_by)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(guild_id)
+    .bind(&phrase.situation)
+    .bind(&phrase.hanji)
+    .bind(&phrase.lomaji)
+    .bind(&phrase.english)
+    .bind(&phrase.audio)
+    .bind(added_by)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+// Returns false if the server has no phrase with that ID
+pub async fn remove_guild_phrase(
+    pool: &SqlitePool,
+    guild_id: &str,
+    id: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM guild_phrases WHERE guild_id = ? AND id = ?")
+        .bind(guild_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Count one /tonedrill answer towards the user's accuracy on that tone
+pub async fn record_drill(
+    pool: &SqlitePool,
+    user_id: &str,
+    tone: u8,
+    correct: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO tone_drills (user_id, tone, asked, correct) VALUES (?, ?, 1, ?)
+         ON CONFLICT (user_id, tone)
+         DO UPDATE SET asked = asked + 1, correct = correct + excluded.correct",
+    )
+    .bind(user_id)
+    .bind(tone)
+    .bind(correct as i64)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// A user's drill results as (tone, asked, correct), by tone
+pub async fn drill_stats(
+    pool: &SqlitePool,
+    user_id: &str,
+) -> Result<Vec<(u8, i64, i64)>, sqlx::Error> {
+    sqlx::query_as("SELECT tone, asked, correct FROM tone_drills WHERE user_id = ? ORDER BY tone")
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+}
+
+// Message IDs only grow, so an older message handled late can't move this back
+pub async fn set_last_message(
+    pool: &SqlitePool,
+    channel_id: &str,
+    message_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO channel_progress (channel_id, message_id) VALUES (?, ?)
+         ON CONFLICT (channel_id) DO UPDATE SET message_id = excluded.message_id
+         WHERE CAST(excluded.message_id AS INTEGER) > CAST(message_id AS INTEGER)",
+    )
+    .bind(channel_id)
+    .bind(message_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn last_message(
+    pool: &SqlitePool,
+    channel_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT message_id FROM channel_progress WHERE channel_id = ?")
+        .bind(channel_id)
+        .fetch_optional(pool)
+        .await
+}
+
+// Lookup channels picked by servers in the setup wizard
+pub async fn guild_lookup_channels(pool: &SqlitePool) -> Result<Vec<ChannelId>, sqlx::Error> {
+    let channels: Vec<String> = sqlx::query_scalar(
+        "SELECT lookup_channel FROM guild_settings WHERE lookup_channel IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(channels
+        .iter()
+        .filter_map(|id| id.parse::<u64>().ok())
+        .map(ChannelId::new)
+        .collect())
+}
+
+// Archive forums picked by servers with /config
+pub async fn archive_forums(pool: &SqlitePool) -> Result<Vec<ChannelId>, sqlx::Error> {
+    let forums: Vec<String> = sqlx::query_scalar(
+        "SELECT archive_forum FROM guild_settings WHERE archive_forum IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(forums
+        .iter()
+        .filter_map(|id| id.parse::<u64>().ok())
+        .map(ChannelId::new)
+        .collect())
+}
+
+// Take or renew the leader lease for `lease_secs`. Only succeeds if this
+// instance holds it already or the previous holder let it expire.
+pub async fn claim_leadership(
+    pool: &SqlitePool,
+    instance: &str,
+    lease_secs: u64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO leader_lease (id, instance, expires_at)
+         VALUES (1, ?, CAST(strftime('%s', 'now') AS INTEGER) + ?)
+         ON CONFLICT (id) DO UPDATE SET
+            instance = excluded.instance,
+            expires_at = excluded.expires_at
+         WHERE instance = excluded.instance
+            OR expires_at < CAST(strftime('%s', 'now') AS INTEGER)",
+    )
+    .bind(instance)
+    .bind(lease_secs as i64)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+// The row ID and glosses stored for a sentence
+pub async fn remembered_translation(
+    pool: &SqlitePool,
+    key: &str,
+) -> Result<Option<(i64, String)>, sqlx::Error> {
+    sqlx::query_as("SELECT rowid, glosses FROM translation_memory WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+}
+
+// Stored translations don't replace edited ones. Returns the row ID unless
+// an edited translation was kept.
+pub async fn remember_translation(
+    pool: &SqlitePool,
+    key: &str,
+    sentence: &str,
+    glosses: &str,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "INSERT INTO translation_memory (key, sentence, glosses) VALUES (?, ?, ?)
+         ON CONFLICT (key) DO UPDATE SET
+            glosses = excluded.glosses,
+            updated_at = CURRENT_TIMESTAMP
+         WHERE edited_by IS NULL
+         RETURNING rowid",
+    )
+    .bind(key)
+    .bind(sentence)
+    .bind(glosses)
+    .fetch_optional(pool)
+    .await
+}
+
+// Every stored translation of a sentence, one per accent, as (key, glosses)
+pub async fn sentence_translations(
+    pool: &SqlitePool,
+    sentence: &str,
+) -> Result<Vec<(String, String)>, sqlx::Error> {
+    sqlx::query_as("SELECT key, glosses FROM translation_memory WHERE sentence = ? ORDER BY key")
+        .bind(sentence)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn edit_translation(
+    pool: &SqlitePool,
+    key: &str,
+    glosses: &str,
+    edited_by: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE translation_memory
+         SET glosses = ?, edited_by = ?, updated_at = CURRENT_TIMESTAMP WHERE key = ?",
+    )
+    .bind(glosses)
+    .bind(edited_by)
+    .bind(key)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Drop the translations of one sentence, or all of them, returning how many
+pub async fn forget_translations(
+    pool: &SqlitePool,
+    sentence: Option<&str>,
+) -> Result<u64, sqlx::Error> {
+    let result = match sentence {
+        Some(sentence) => {
+            sqlx::query("DELETE FROM translation_memory WHERE sentence = ?")
+                .bind(sentence)
+                .execute(pool)
+                .await?
+        }
+        None => sqlx::query("DELETE FROM translation_memory").execute(pool).await?,
+    };
+    Ok(result.rows_affected())
+}
+
+// A remembered translation by row ID, as (key, sentence, glosses)
+pub async fn translation(
+    pool: &SqlitePool,
+    id: i64,
+) -> Result<Option<(String, String, String)>, sqlx::Error> {
+    sqlx::query_as("SELECT key, sentence, glosses FROM translation_memory WHERE rowid = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn add_correction(
+    pool: &SqlitePool,
+    memory_id: i64,
+    glosses: &str,
+    user_id: &str,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO translation_corrections (memory_id, glosses, user_id) VALUES (?, ?, ?)",
+    )
+    .bind(memory_id)
+    .bind(glosses)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+// A suggested correction, as (memory ID, glosses, suggested by)
+pub async fn correction(
+    pool: &SqlitePool,
+    id: i64,
+) -> Result<Option<(i64, String, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT memory_id, glosses, user_id FROM translation_corrections WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+// Approve or reject a pending correction. False if it was already reviewed.
+pub async fn review_correction(
+    pool: &SqlitePool,
+    id: i64,
+    status: &str,
+    reviewed_by: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE translation_corrections SET status = ?, reviewed_by = ?
+         WHERE id = ? AND status = 'pending'",
+    )
+    .bind(status)
+    .bind(reviewed_by)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+// The archive forum post already made for a keyword, if any
+pub async fn forum_post(
+    pool: &SqlitePool,
+    forum_id: &str,
+    keyword: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT thread_id FROM forum_posts WHERE forum_id = ? AND keyword = ?")
+        .bind(forum_id)
+        .bind(keyword)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn record_forum_post(
+    pool: &SqlitePool,
+    forum_id: &str,
+    keyword: &str,
+    thread_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT OR IGNORE INTO forum_posts (forum_id, keyword, thread_id) VALUES (?, ?, ?)")
+        .bind(forum_id)
+        .bind(keyword)
+        .bind(thread_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// Store a new API key by its hash, returning its ID
+pub async fn add_api_key(
+    pool: &SqlitePool,
+    hash: &str,
+    label: &str,
+    scope: Scope,
+    per_minute: u32,
+    created_by: &str,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO api_keys (hash, label, scope, per_minute, created_by) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(hash)
+    .bind(label)
+    .bind(scope.code())
+    .bind(per_minute)
+    .bind(created_by)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+fn api_key_row(row: &sqlx::sqlite::SqliteRow) -> Option<ApiKey> {
+    Some(ApiKey {
+        id: Some(row.get("id")),
+        label: row.get("label"),
+        scope: Scope::parse(row.get("scope"))?,
+        per_minute: row.get("per_minute"),
+    })
+}
+
+// The key hashed as `hash`, unless it was revoked
+pub async fn api_key(pool: &SqlitePool, hash: &str) -> Result<Option<ApiKey>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT id, label, scope, per_minute FROM api_keys WHERE hash = ? AND revoked_at IS NULL",
+    )
+    .bind(hash)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.as_ref().and_then(api_key_row))
+}
+
+// Keys that weren't revoked, oldest first
+pub async fn api_keys(pool: &SqlitePool) -> Result<Vec<ApiKey>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, label, scope, per_minute FROM api_keys WHERE revoked_at IS NULL ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().filter_map(api_key_row).collect())
+}
+
+// Returns false if there was no such key, or it was already revoked
+pub async fn revoke_api_key(pool: &SqlitePool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE api_keys SET revoked_at = CURRENT_TIMESTAMP WHERE id = ? AND revoked_at IS NULL",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// A result voted on with 👍/👎, with its tally
+pub struct RatedResult {
+    pub entry_id: String,
+    pub keyword: String,
+    pub source: Option<Source>,
+    pub result: String,
+    pub good: i64,
+    pub bad: i64,
+}
+
+// Claim a message before answering it. Returns false if it was already
+// answered. The user_id column is left empty, as only passive 📖 reactions
+// were ever claimed per user.
+pub async fn claim_message(pool: &SqlitePool, message_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("INSERT OR IGNORE INTO processed_messages (message_id) VALUES (?)")
+        .bind(message_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Drop answered messages older than `max_age`, which can no longer be
+// delivered again
+pub async fn forget_processed(pool: &SqlitePool, max_age: Duration) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM processed_messages
+         WHERE processed_at < CAST(strftime('%s', 'now') AS INTEGER) - ?",
+    )
+    .bind(max_age.as_secs() as i64)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// Claim a user's one vote on an iTaigi entry before sending it. Returns
+// false if they already voted on it.
+pub async fn claim_itaigi_vote(
+    pool: &SqlitePool,
+    entry_id: &str,
+    user_id: &str,
+    good: bool,
+) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("INSERT OR IGNORE INTO itaigi_votes (entry_id, user_id, good) VALUES (?, ?, ?)")
+            .bind(entry_id)
+            .bind(user_id)
+            .bind(good)
+            .execute(pool)
+            .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Give a vote back when iTaigi didn't take it, so the user can try again
+pub async fn release_itaigi_vote(
+    pool: &SqlitePool,
+    entry_id: &str,
+    user_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM itaigi_votes WHERE entry_id = ? AND user_id = ?")
+        .bind(entry_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// Record a user's vote on a result of an entry, replacing their earlier one
+pub async fn vote_result(
+    pool: &SqlitePool,
+    entry_id: &str,
+    result: &str,
+    source: Source,
+    user_id: &str,
+    good: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO result_votes (entry_id, result, source, user_id, good) VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT (entry_id, result, user_id)
+         DO UPDATE SET good = excluded.good, voted_at = CURRENT_TIMESTAMP",
+    )
+    .bind(entry_id)
+    .bind(result)
+    .bind(source.code().to_string())
+    .bind(user_id)
+    .bind(good)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 👍 minus 👎 of each voted result of a keyword's entry
+pub async fn result_scores(
+    pool: &SqlitePool,
+    keyword: &str,
+    variant: Option<Accent>,
+) -> Result<HashMap<String, i64>, sqlx::Error> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT v.result, SUM(CASE WHEN v.good THEN 1 ELSE -1 END)
+         FROM result_votes v JOIN entries e ON e.id = v.entry_id
+         WHERE e.key = ? GROUP BY v.result",
+    )
+    .bind(lookup::cache_key(keyword, variant))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+// The best rated results, or with `disputed` those with the most votes
+// either way. Only keywords looked up publicly in the server are listed, in
+// DMs those the user looked up there.
+pub async fn rated_results(
+    pool: &SqlitePool,
+    guild_id: Option<&str>,
+    user_id: &str,
+    disputed: bool,
+    limit: u32,
+) -> Result<Vec<RatedResult>, sqlx::Error> {
+    let order = if disputed {
+        "HAVING ups > 0 AND downs > 0 ORDER BY MIN(ups, downs) DESC, ups + downs DESC"
+    } else {
+        "HAVING ups > downs ORDER BY ups - downs DESC, ups DESC"
+    };
+    let rows = sqlx::query(&format!(
+        "SELECT v.entry_id, e.keyword, v.source, v.result,
+                SUM(v.good) AS ups, SUM(1 - v.good) AS downs
+         FROM result_votes v JOIN entries e ON e.id = v.entry_id
+         WHERE EXISTS (
+             SELECT 1 FROM query_history h
+             WHERE h.keyword = e.keyword
+               AND CASE WHEN ?1 IS NULL THEN h.guild_id IS NULL AND h.user_id = ?2
+                        ELSE h.guild_id = ?1 AND h.public = 1 END
+         )
+         GROUP BY v.entry_id, v.result {} LIMIT ?3",
+        order
+    ))
+    .bind(guild_id)
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| RatedResult {
+            entry_id: row.get("entry_id"),
+            keyword: row.get("keyword"),
+            source: row.get::<String, _>("source").chars().next().and_then(Source::from_code),
+            result: row.get("result"),
+            good: row.get("ups"),
+            bad: row.get("downs"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh database with the migrations applied
+    async fn database() -> SqlitePool {
+        connect("sqlite::memory:").await.unwrap()
+    }
+
+    async fn record(pool: &SqlitePool, keyword: &str, guild_id: Option<&str>, public: bool) {
+        record_by(pool, keyword, "1", guild_id, public).await;
+    }
+
+    async fn record_by(
+        pool: &SqlitePool,
+        keyword: &str,
+        user_id: &str,
+        guild_id: Option<&str>,
+        public: bool,
+    ) {
+        let lookup = Lookup {
+            results: vec![format!("{} result", keyword)],
+            sources: vec![Source::Moedict],
+            ..Lookup::default()
+        };
+        let entry = HistoryEntry {
+            keyword,
+            variant: None,
+            lookup: &lookup,
+            user_id: user_id.to_string(),
+            channel_id: "2".to_string(),
+            guild_id: guild_id.map(str::to_string),
+            public,
+        };
+        record_query(pool, &entry).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn glossary_leaves_out_private_lookups() {
+        let pool = database().await;
+        record(&pool, "食飯", Some("10"), true).await;
+        record(&pool, "秘密", Some("10"), false).await;
+        record(&pool, "心事", None, false).await;
+        let words: Vec<String> =
+            glossary(&pool).await.unwrap().into_iter().map(|(word, _, _)| word).collect();
+        assert_eq!(words, ["食飯"]);
+    }
+
+    #[tokio::test]
+    async fn cached_lookups_keep_their_parts_of_speech() {
+        let pool = database().await;
+        let line = "📖 食 [tsia̍h] (動) 吃。 - [萌典](https://www.moedict.org/#'食)".to_string();
+        let lookup = Lookup {
+            results: vec![line.clone()],
+            sources: vec![Source::Moedict],
+            pos: HashMap::from([(line.clone(), vec!["動".to_string()])]),
+            ..Lookup::default()
+        };
+        save_cached(&pool, "食", &lookup).await.unwrap();
+        let cached = load_cached(&pool, Duration::from_secs(60)).await.unwrap();
+        let (_, cached, _) = &cached[0];
+        assert_eq!(cached.pos[&line], ["動"]);
+    }
+
+    #[tokio::test]
+    async fn recall_stays_in_the_server_or_dm() {
+        let pool = database().await;
+        record(&pool, "食飯", Some("10"), true).await;
+        record(&pool, "食薰", Some("10"), false).await;
+        record(&pool, "食茶", Some("20"), true).await;
+        record_by(&pool, "食菜", "1", None, false).await;
+        record_by(&pool, "食酒", "2", None, false).await;
+        let recalled = async |guild_id, user_id| -> Vec<String> {
+            let recalled = recall(&pool, "食", guild_id, user_id, 10).await.unwrap();
+            recalled.into_iter().map(|(word, _)| word).collect()
+        };
+        assert_eq!(recalled(Some("10"), "3").await, ["食飯"]);
+        assert_eq!(recalled(None, "1").await, ["食菜"]);
+    }
+
+    #[tokio::test]
+    async fn channel_queries_leave_out_private_lookups() {
+        let pool = database().await;
+        record(&pool, "食飯", Some("10"), true).await;
+        record(&pool, "秘密", Some("10"), false).await;
+        let week = Duration::from_secs(7 * 24 * 60 * 60);
+        let words: Vec<String> = channel_queries(&pool, "2", week)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(word, _)| word)
+            .collect();
+        assert_eq!(words, ["食飯"]);
+    }
+
+    #[tokio::test]
+    async fn answered_with_leaves_out_private_lookups() {
+        let pool = database().await;
+        record(&pool, "食飯", Some("10"), true).await;
+        record(&pool, "食薰", Some("10"), false).await;
+        assert_eq!(answered_with(&pool, '食', 10).await.unwrap(), ["食飯"]);
+    }
+
+    #[tokio::test]
+    async fn deliveries_left_sending_are_sent_again() {
+        let pool = database().await;
+        sqlx::query("INSERT INTO guild_settings (guild_id, lookup_channel) VALUES ('10', '20')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        add_announcement(&pool, "News", "1").await.unwrap();
+        let delivery = pending_deliveries(&pool).await.unwrap().remove(0);
+        assert!(claim_delivery(&pool, &delivery).await.unwrap());
+        assert!(pending_deliveries(&pool).await.unwrap().is_empty());
+
+        assert_eq!(release_deliveries(&pool).await.unwrap(), 1);
+        let pending = pending_deliveries(&pool).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].guild_id, "10");
+    }
+
+    #[tokio::test]
+    async fn rated_results_stay_in_the_server() {
+        let pool = database().await;
+        record(&pool, "食飯", Some("10"), true).await;
+        record(&pool, "食薰", Some("10"), false).await;
+        record(&pool, "食茶", Some("20"), true).await;
+        for keyword in ["食飯", "食薰", "食茶"] {
+            let entry_id = entry_id(&pool, keyword, None).await.unwrap();
+            let result = format!("{} result", keyword);
+            vote_result(&pool, &entry_id, &result, Source::Moedict, "3", true).await.unwrap();
+        }
+        let rated = rated_results(&pool, Some("10"), "3", false, 10).await.unwrap();
+        let keywords: Vec<&str> = rated.iter().map(|rated| rated.keyword.as_str()).collect();
+        assert_eq!(keywords, ["食飯"]);
+        assert!(rated_results(&pool, None, "3", false, 10).await.unwrap().is_empty());
+    }
+}
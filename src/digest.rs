@@ -0,0 +1,202 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serenity::http::Http;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::breakdown;
+use crate::config::SharedConfig;
+use crate::db;
+use crate::festival;
+use crate::leader::Leader;
+use crate::lookup::{Lookup, LookupPool};
+use crate::i18n::Locale;
+use crate::preflight;
+use crate::respond;
+use crate::romanization;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+// Keywords listed with their Taigi, the rest are only named
+const TOP_ENTRIES: usize = 10;
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+// Time left until the next `hour`:00 UTC
+fn until(hour: u32) -> Duration {
+    let now = unix_now() % DAY.as_secs();
+    let target = u64::from(hour) * 60 * 60;
+    match (target + DAY.as_secs() - now) % DAY.as_secs() {
+        0 => DAY,
+        secs => Duration::from_secs(secs),
+    }
+}
+
+// 1970-01-01 was a Thursday
+fn is_sunday() -> bool {
+    (unix_now() / DAY.as_secs()) % 7 == 3
+}
+
+// The summary of a day or week: how much was asked, the most asked words
+// with their Taigi, then every other word. None if nothing was asked.
+pub fn render(queries: &[(String, i64, Vec<String>)], locale: Locale, weekly: bool) -> Option<String> {
+    if queries.is_empty() {
+        return None;
+    }
+
+    let total: i64 = queries.iter().map(|(_, uses, _)| uses).sum();
+    let mut lines = vec![locale.digest_header(total, queries.len(), weekly)];
+    for (idx, (keyword, uses, results)) in queries.iter().take(TOP_ENTRIES).enumerate() {
+        let line = match results.iter().find_map(|result| breakdown::headword(result)) {
+            Some((hanji, Some(lomaji))) => {
+                format!("{}. {} ×{} → {} [{}]", idx + 1, keyword, uses, hanji, lomaji)
+            }
+            Some((hanji, None)) => format!("{}. {} ×{} → {}", idx + 1, keyword, uses, hanji),
+            None => format!("{}. {} ×{}", idx + 1, keyword, uses),
+        };
+        lines.push(line);
+    }
+
+    let rest: Vec<&str> = queries
+        .iter()
+        .skip(TOP_ENTRIES)
+        .map(|(keyword, _, _)| keyword.as_str())
+        .collect();
+    if !rest.is_empty() {
+        lines.push(String::new());
+        lines.push(locale.digest_rest(&rest.join("、")));
+    }
+
+    Some(respond::fit_message(&lines.join("\n")))
+}
+
+// The day's most asked word across servers becomes the word of the day in the
+// public feed, with the results just `refreshed` for it when there are some
+async fn word_of_the_day(
+    db: &SqlitePool,
+    queries: &[(String, i64, Vec<String>)],
+    refreshed: Option<&Lookup>,
+) {
+    let Some((keyword, _, stored)) = queries.first() else {
+        return;
+    };
+    let results = match refreshed {
+        Some(lookup) if !lookup.results.is_empty() => &lookup.results,
+        _ => stored,
+    };
+    let title = match results.iter().find_map(|result| breakdown::headword(result)) {
+        Some((hanji, Some(lomaji))) => format!("{} → {} [{}]", keyword, hanji, lomaji),
+        Some((hanji, None)) => format!("{} → {}", keyword, hanji),
+        None => keyword.clone(),
+    };
+    if let Err(why) = db::add_feed_entry(db, "wotd", &title, &results.join("\n")).await {
+        error!("Error adding the word of the day to the feed: {why:?}");
+    }
+}
+
+// Every day at the configured hour: announce the day's festival if there is
+// one, post each server's digest of the last day in whichever channel is
+// configured at that time and pick the word of the day. On Sundays the week's digest is
+// added to the feed as well. Standby instances leave it to the leader.
+pub fn spawn(
+    http: Arc<Http>,
+    config: Arc<SharedConfig>,
+    db: SqlitePool,
+    pool: LookupPool,
+    leader: Arc<Leader>,
+) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(until(config.get().digest_hour)).await;
+            if !leader.is_leader() {
+                continue;
+            }
+
+            let config = config.get();
+            let today = festival::today();
+            if let Some(channel) = config.festival_channel
+                && let Some(found) = festival::on(today)
+                && let Err(why) = channel.say(&http, festival::announcement(found, today)).await
+            {
+                error!("Error announcing the festival: {why:?}");
+            }
+
+            // Each server's digest only has its own public lookups, posted in the
+            // channel it picked with /config or, for the server it is in,
+            // `discord.digest_channel`
+            let mut digests = match db::digest_channels(&db).await {
+                Ok(digests) => digests,
+                Err(why) => {
+                    error!("Error loading digest channels: {why:?}");
+                    Vec::new()
+                }
+            };
+            if let Some(channel) = config.digest_channel
+                && let Some(guild_id) = preflight::guild_of(&http, channel).await
+                && !digests.iter().any(|(id, _)| *id == guild_id.to_string())
+            {
+                digests.push((guild_id.to_string(), channel));
+            }
+            for (guild_id, channel) in digests {
+                let queries = match db::recent_queries(&db, Some(&guild_id), DAY).await {
+                    Ok(queries) => queries,
+                    Err(why) => {
+                        error!("Error loading the day's queries for {}: {why:?}", guild_id);
+                        continue;
+                    }
+                };
+                let settings = db::get_guild_settings(&db, &guild_id).await.unwrap_or_default();
+                let locale = settings.locale.unwrap_or(config.locale);
+                let romanization = settings.romanization.unwrap_or_default();
+                if let Some(digest) = render(&queries, locale, false)
+                    && let Err(why) =
+                        channel.say(&http, romanization::render(&digest, romanization)).await
+                {
+                    error!("Error posting the digest for {}: {why:?}", guild_id);
+                }
+            }
+
+            let queries = match db::recent_queries(&db, None, DAY).await {
+                Ok(queries) => queries,
+                Err(why) => {
+                    error!("Error loading the day's queries: {why:?}");
+                    continue;
+                }
+            };
+            // The day's most asked words are looked up again through the pool,
+            // so the cache holds what the sources say now and the word of the
+            // day is made from fresh results
+            let top: Vec<String> = queries
+                .iter()
+                .take(TOP_ENTRIES)
+                .map(|(keyword, _, _)| keyword.clone())
+                .collect();
+            let refreshed = pool.refresh_all(&top, &config).await;
+            word_of_the_day(&db, &queries, refreshed.first()).await;
+
+            if !is_sunday() {
+                continue;
+            }
+            let weekly = match db::recent_queries(&db, None, WEEK).await {
+                Ok(queries) => render(&queries, config.locale, true),
+                Err(why) => {
+                    error!("Error loading the week's queries: {why:?}");
+                    continue;
+                }
+            };
+            if let Some(weekly) = weekly
+                && let Some((title, content)) = weekly.split_once('\n')
+                && let Err(why) = db::add_feed_entry(&db, "digest", title, content).await
+            {
+                error!("Error adding the weekly digest to the feed: {why:?}");
+            }
+        }
+    });
+}
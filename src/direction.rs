@@ -0,0 +1,58 @@
+// Han characters only Taigi writes, wherever they appear
+const TAIGI_CHARS: &[&str] = &["佇", "恁", "𪜶", "袂", "媠", "佮", "囝", "𠢕", "呷", "枵", "挵"];
+
+// Words only Taigi uses. Many are written with characters Mandarin words use
+// too (遮陽, 揣測, 遐想, 毋寧, 靠攏), so they only count as a whole.
+const TAIGI_WORDS: &[&str] = &[
+    "啥物", "囡仔", "查某", "按怎", "敢若", "真濟", "足濟", "歹勢", "食飽", "無閒", "頭家",
+    "塗跤", "尻川", "遮爾", "遐爾", "遮的", "遐的", "揣無", "揣著", "厝邊", "厝內", "阮厝",
+    "毋知", "毋是", "毋通", "毋好", "攏無", "攏總", "攏是",
+];
+
+// The distinctly Taigi characters and words in `text`, in list order
+pub fn taigi_hanji(text: &str) -> Vec<&'static str> {
+    TAIGI_CHARS
+        .iter()
+        .chain(TAIGI_WORDS)
+        .filter(|hanji| text.contains(*hanji))
+        .copied()
+        .collect()
+}
+
+// Which way a query is looked up. Most users ask for the Taigi of a Mandarin
+// word, but Taigi speakers also ask what a Taigi word means in Mandarin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    MandarinToTaigi,
+    TaigiToMandarin,
+}
+
+impl Direction {
+    // Queries with Han words only Taigi uses (媠, 佮, 啥物…) are Taigi
+    pub fn detect(query: &str) -> Direction {
+        if taigi_hanji(query).is_empty() {
+            Direction::MandarinToTaigi
+        } else {
+            Direction::TaigiToMandarin
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Direction;
+
+    #[test]
+    fn detects_taigi() {
+        for query in ["媠", "你佇佗位", "遮爾好", "揣無", "厝邊", "毋知影", "攏無", "啥物"] {
+            assert_eq!(Direction::detect(query), Direction::TaigiToMandarin, "{}", query);
+        }
+    }
+
+    #[test]
+    fn leaves_mandarin_words_sharing_characters() {
+        for query in ["遮陽", "揣測", "遐想", "毋寧", "靠攏", "睡覺", "遮"] {
+            assert_eq!(Direction::detect(query), Direction::MandarinToTaigi, "{}", query);
+        }
+    }
+}
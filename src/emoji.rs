@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serenity::model::id::GuildId;
+
+use crate::source::Source;
+
+// Emoji replacing the built-in ones in replies. Unset entries keep the
+// built-in emoji; custom server emoji are written `<:name:id>`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct EmojiSet {
+    pub taigitv: Option<String>,
+    pub sutian: Option<String>,
+    pub itaigi: Option<String>,
+    pub moedict: Option<String>,
+    pub plugins: Option<String>,
+    pub places: Option<String>,
+    // By tone number ("2" = "<:tone2:…>"), shown in romanization footnotes
+    pub tones: HashMap<String, String>,
+}
+
+impl EmojiSet {
+    fn source(&self, source: Source) -> Option<&str> {
+        match source {
+            Source::TaigiTv => self.taigitv.as_deref(),
+            Source::Sutian => self.sutian.as_deref(),
+            Source::ITaigi => self.itaigi.as_deref(),
+            Source::Moedict => self.moedict.as_deref(),
+            Source::Plugins => self.plugins.as_deref(),
+            Source::Places => self.places.as_deref(),
+        }
+    }
+
+    pub fn tone(&self, tone: u8) -> Option<&str> {
+        self.tones.get(&tone.to_string()).map(String::as_str)
+    }
+
+    // Every emoji set here, as (key, value), for validation
+    pub fn entries(&self) -> Vec<(String, &str)> {
+        let sources = [
+            ("taigitv", &self.taigitv),
+            ("sutian", &self.sutian),
+            ("itaigi", &self.itaigi),
+            ("moedict", &self.moedict),
+            ("plugins", &self.plugins),
+            ("places", &self.places),
+        ];
+        let mut entries: Vec<(String, &str)> = sources
+            .into_iter()
+            .filter_map(|(key, emoji)| Some((key.to_string(), emoji.as_deref()?)))
+            .collect();
+        entries.extend(
+            self.tones
+                .iter()
+                .map(|(tone, emoji)| (format!("tones.{}", tone), emoji.as_str())),
+        );
+        entries
+    }
+
+    // This set with `other`'s emoji wherever it sets one
+    fn overlay(&self, other: &EmojiSet) -> EmojiSet {
+        let mut tones = self.tones.clone();
+        tones.extend(other.tones.clone());
+        EmojiSet {
+            taigitv: other.taigitv.clone().or(self.taigitv.clone()),
+            sutian: other.sutian.clone().or(self.sutian.clone()),
+            itaigi: other.itaigi.clone().or(self.itaigi.clone()),
+            moedict: other.moedict.clone().or(self.moedict.clone()),
+            plugins: other.plugins.clone().or(self.plugins.clone()),
+            places: other.places.clone().or(self.places.clone()),
+            tones,
+        }
+    }
+
+    // Swap the source emoji a formatted result starts with, e.g. the 📚 of
+    // `📚 食飯 [tsia̍h-pn̄g] - …`
+    pub fn apply(&self, result: &str, source: Source) -> String {
+        match (self.source(source), result.split_once(' ')) {
+            (Some(emoji), Some((_, rest))) => format!("{} {}", emoji, rest),
+            _ => result.to_string(),
+        }
+    }
+}
+
+// The `[emoji]` section: a set for every server plus per-server overrides
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Emoji {
+    pub default: EmojiSet,
+    pub guilds: HashMap<GuildId, EmojiSet>,
+}
+
+impl Emoji {
+    pub fn for_guild(&self, guild_id: Option<GuildId>) -> EmojiSet {
+        match guild_id.and_then(|guild_id| self.guilds.get(&guild_id)) {
+            Some(guild) => self.default.overlay(guild),
+            None => self.default.clone(),
+        }
+    }
+}
+
+// Discord only renders custom emoji in full `<:name:id>` form; `:name:`
+// shows up as plain text
+pub fn check(value: &str) -> Result<(), String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("is empty".to_string());
+    }
+    if value.starts_with(':') && value.ends_with(':') {
+        return Err(format!(
+            "\"{}\" needs the full form <{}ID>, type \\{} in Discord to get it",
+            value, value, value
+        ));
+    }
+    if value.starts_with('<') {
+        let inner = value.trim_start_matches('<').trim_end_matches('>');
+        let parts: Vec<&str> = inner.split(':').collect();
+        let valid = matches!(parts[..], ["" | "a", name, id]
+            if !name.is_empty() && id.parse::<u64>().is_ok());
+        if !valid || !value.ends_with('>') {
+            return Err(format!("\"{}\" is not a custom emoji like <:name:123>", value));
+        }
+    }
+    Ok(())
+}
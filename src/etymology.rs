@@ -0,0 +1,159 @@
+use scraper::{ElementRef, Html, Selector};
+use serde_json::Value;
+
+use crate::upstream;
+
+// Longest origin note shown per source
+const MAX_CHARS: usize = 300;
+
+// The 來源 (origin) field of a Sutian entry page, e.g. 日語「弁当」
+pub async fn sutian_origin(entry_url: &str) -> Result<Option<String>, String> {
+    let response_text = match upstream::client().get(entry_url).send().await {
+        Ok(response) => match upstream::text(response).await {
+            Ok(text) => text,
+            Err(_) => return Err("Error reading entry from Sutian".to_string()),
+        },
+        Err(_) => return Err("Error fetching entry from Sutian".to_string()),
+    };
+
+    let document = Html::parse_document(&response_text);
+    let label_selector = Selector::parse("th, dt, .fs-6, strong")
+        .map_err(|_| "Could not parse Sutian origin selector".to_string())?;
+
+    // The value follows its label, in the next cell or definition
+    let origin = document
+        .select(&label_selector)
+        .filter(|label| label.text().collect::<String>().trim().trim_end_matches('：') == "來源")
+        .find_map(|label| label.next_siblings().find_map(ElementRef::wrap))
+        .map(|value| value.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|origin| !origin.is_empty());
+
+    Ok(origin.map(|origin| origin.chars().take(MAX_CHARS).collect()))
+}
+
+// Turn wikitext into plain text: `[[a|b]]` → b, `{{l|ja|弁当}}` → 弁当,
+// `'''bold'''` → bold
+fn plain_text(wikitext: &str) -> String {
+    let mut output = String::new();
+    let mut rest = wikitext;
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix("[[") {
+            let Some((link, after)) = inner.split_once("]]") else {
+                break;
+            };
+            output.push_str(link.rsplit('|').next().unwrap_or(link));
+            rest = after;
+        } else if let Some(inner) = rest.strip_prefix("{{") {
+            let Some((template, after)) = inner.split_once("}}") else {
+                break;
+            };
+            // Keep the last positional argument, which is usually the term
+            let text = template
+                .split('|')
+                .skip(1)
+                .filter(|arg| !arg.contains('='))
+                .last()
+                .unwrap_or("");
+            output.push_str(text);
+            rest = after;
+        } else {
+            let next = rest.find(['[', '{']).unwrap_or(rest.len()).max(1);
+            output.push_str(&rest[..next]);
+            rest = &rest[next..];
+        }
+    }
+    output.replace("'''", "").replace("''", "")
+}
+
+// The Chinese section of a word's English Wiktionary page, which covers
+// Hokkien too
+async fn chinese_section(word: &str) -> Result<Option<String>, String> {
+    let url = format!(
+        "https://en.wiktionary.org/w/api.php?action=parse&prop=wikitext&format=json&formatversion=2&page={}",
+        urlencoding::encode(word)
+    );
+
+    // Wikimedia asks API clients to identify themselves, which the shared
+    // client's User-Agent does
+    let response_text = match upstream::client().get(&url).send().await {
+        Ok(response) => match upstream::text(response).await {
+            Ok(text) => text,
+            Err(_) => return Err("Error reading response from Wiktionary".to_string()),
+        },
+        Err(_) => return Err("Error fetching from Wiktionary".to_string()),
+    };
+    let json: Value = serde_json::from_str(&response_text)
+        .map_err(|_| "Error parsing Wiktionary response".to_string())?;
+
+    // Missing pages come back as an error object
+    let Some(wikitext) = json.pointer("/parse/wikitext").and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+
+    let Some(chinese) = wikitext.split("\n==Chinese==").nth(1) else {
+        return Ok(None);
+    };
+    // Up to the next language (level 2) header
+    let end = chinese
+        .match_indices("\n==")
+        .map(|(idx, _)| idx)
+        .find(|&idx| !chinese[idx + 3..].starts_with('='))
+        .unwrap_or(chinese.len());
+    Ok(Some(chinese[..end].to_string()))
+}
+
+// The etymology paragraph of the Chinese section on English Wiktionary,
+// which covers Hokkien loanwords
+pub async fn wiktionary(word: &str) -> Result<Option<String>, String> {
+    let Some(chinese) = chinese_section(word).await? else {
+        return Ok(None);
+    };
+
+    let etymology = chinese
+        .split("\n===Etymology")
+        .nth(1)
+        .and_then(|section| section.split_once("===\n"))
+        .map(|(_, body)| body.split("\n=").next().unwrap_or(body))
+        .map(|body| {
+            body.lines()
+                .filter(|line| !line.starts_with(['{', '|', '}']))
+                .map(plain_text)
+                .collect::<Vec<_>>()
+                .join(" ")
+                .trim()
+                .to_string()
+        })
+        .filter(|text| !text.is_empty());
+
+    Ok(etymology.map(|text| text.chars().take(MAX_CHARS).collect()))
+}
+
+// English meanings from the numbered definitions (`# to sleep`) of the
+// Chinese section, with labels like `{{lb|zh|Hokkien}}` left out
+pub async fn english_glosses(word: &str, limit: usize) -> Result<Vec<String>, String> {
+    let Some(chinese) = chinese_section(word).await? else {
+        return Ok(Vec::new());
+    };
+
+    let mut glosses: Vec<String> = Vec::new();
+    for line in chinese.lines() {
+        let Some(definition) = line.strip_prefix("# ") else {
+            continue;
+        };
+        let mut definition = definition.to_string();
+        while let Some(start) = definition.find("{{lb|") {
+            let Some(end) = definition[start..].find("}}") else {
+                break;
+            };
+            definition.replace_range(start..start + end + 2, "");
+        }
+        let gloss = plain_text(&definition).trim().to_string();
+        if !gloss.is_empty() && !glosses.contains(&gloss) {
+            glosses.push(gloss);
+        }
+        if glosses.len() >= limit {
+            break;
+        }
+    }
+    Ok(glosses)
+}
@@ -0,0 +1,172 @@
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde_json::json;
+use sqlx::SqlitePool;
+
+use crate::db::{self, UserPrefs};
+use crate::lookup::Lookup;
+use crate::source::Source;
+use crate::text::escape;
+
+// Moedict's page for a word, where its recording can be played
+fn audio_url(keyword: &str) -> String {
+    format!("https://www.moedict.tw/'{}", urlencoding::encode(keyword))
+}
+
+// Part of speech tags and source names of a lookup, without duplicates
+fn tags(lookup: &Lookup) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let pos_tags = lookup
+        .results
+        .iter()
+        .filter_map(|result| lookup.pos.get(result))
+        .flatten()
+        .map(String::as_str);
+    let names = lookup.sources.iter().map(|source| source.name());
+    for tag in pos_tags.chain(names) {
+        if !tags.iter().any(|known| known == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+// A result line as HTML: escaped, with its Markdown links as anchors
+fn result_html(result: &str) -> String {
+    let text = escape(&result.replace("**", ""));
+    let mut html = String::new();
+    let mut rest = text.as_str();
+    while let Some(open) = rest.find('[') {
+        let link = rest[open + 1..].split_once("](").and_then(|(label, tail)| {
+            let (url, after) = tail.split_once(')')?;
+            let url = url.trim_start_matches("&lt;").trim_end_matches("&gt;");
+            (!label.contains('[') && url.starts_with("http")).then_some((label, url, after))
+        });
+        match link {
+            Some((label, url, after)) => {
+                html.push_str(&rest[..open]);
+                html.push_str(&format!("<a href=\"{}\">{}</a>", url, label));
+                rest = after;
+            }
+            None => {
+                html.push_str(&rest[..=open]);
+                rest = &rest[open + 1..];
+            }
+        }
+    }
+    html.push_str(rest);
+    html
+}
+
+fn json(entries: &[(String, i64, Lookup)]) -> String {
+    let entries: Vec<_> = entries
+        .iter()
+        .map(|(keyword, asked, lookup)| {
+            let has_audio = lookup.sources.contains(&Source::Moedict);
+            json!({
+                "keyword": keyword,
+                "asked": asked,
+                "results": lookup.results,
+                "sources": lookup.sources.iter().map(|source| source.name()).collect::<Vec<_>>(),
+                "tags": tags(lookup),
+                "audio": has_audio.then(|| audio_url(keyword)),
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&json!({ "entries": entries })).unwrap_or_default()
+}
+
+// One page listing every entry, each under an anchor named after the keyword
+fn html(entries: &[(String, i64, Lookup)]) -> String {
+    let mut index = String::new();
+    let mut body = String::new();
+    for (keyword, asked, lookup) in entries {
+        let id = escape(keyword);
+        index.push_str(&format!("<a href=\"#{}\">{}</a>\n", id, id));
+        body.push_str(&format!("<section id=\"{}\">\n<h2>{}</h2>\n", id, id));
+        let mut meta = tags(lookup)
+            .iter()
+            .map(|tag| format!("<span class=\"tag\">{}</span>", escape(tag)))
+            .collect::<Vec<_>>();
+        if lookup.sources.contains(&Source::Moedict) {
+            meta.push(format!("<a href=\"{}\">🔊</a>", escape(&audio_url(keyword))));
+        }
+        meta.push(format!("asked {}×", asked));
+        body.push_str(&format!("<p class=\"meta\">{}</p>\n<ul>\n", meta.join(" ")));
+        for result in &lookup.results {
+            body.push_str(&format!("<li>{}</li>\n", result_html(result)));
+        }
+        body.push_str("</ul>\n</section>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"nan-Hant-TW\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>台語詞彙</title>\n<style>body {{ max-width: 48em; margin: auto; \
+         font-family: sans-serif; }} nav a {{ margin-right: 0.5em; }} \
+         .tag {{ background: #eee; border-radius: 0.3em; padding: 0 0.3em; }} \
+         .meta {{ color: #666; }}</style>\n</head>\n<body>\n<h1>台語詞彙</h1>\n\
+         <p>{} entries, also in <a href=\"glossary.json\">glossary.json</a>.</p>\n\
+         <nav>\n{}</nav>\n{}</body>\n</html>\n",
+        entries.len(),
+        index,
+        body
+    )
+}
+
+// Append one file to a tar archive
+fn tar_file(tar: &mut Vec<u8>, name: &str, contents: &[u8], mtime: u64) {
+    let mut header = [0u8; 512];
+    let mut field = |offset: usize, value: &[u8]| {
+        header[offset..offset + value.len()].copy_from_slice(value);
+    };
+    field(0, name.as_bytes());
+    field(100, b"0000644\0");
+    field(108, b"0000000\0");
+    field(116, b"0000000\0");
+    field(124, format!("{:011o}\0", contents.len()).as_bytes());
+    field(136, format!("{:011o}\0", mtime).as_bytes());
+    field(148, b"        ");
+    field(156, b"0");
+    field(257, b"ustar\0");
+    field(263, b"00");
+    let checksum: u32 = header.iter().map(|&byte| byte as u32).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+
+    tar.extend_from_slice(&header);
+    tar.extend_from_slice(contents);
+    tar.resize(tar.len().div_ceil(512) * 512, 0);
+}
+
+// The glossary of everything the bot has answered as a static site: a
+// gzipped tarball with `index.html` and `glossary.json`, ready to publish
+// as is, with readings in `prefs`' romanization. Returns the file name, the
+// archive and the number of entries.
+pub async fn site(db: &SqlitePool, prefs: &UserPrefs) -> Result<(String, Vec<u8>, usize), String> {
+    let mut entries = db::glossary(db)
+        .await
+        .map_err(|why| format!("cannot load the history: {}", why))?;
+    for (_, _, lookup) in &mut entries {
+        for result in &mut lookup.results {
+            *result = prefs.format_result(result);
+        }
+    }
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let mut tar = Vec::new();
+    tar_file(&mut tar, "glossary/index.html", html(&entries).as_bytes(), stamp);
+    tar_file(&mut tar, "glossary/glossary.json", json(&entries).as_bytes(), stamp);
+    tar.resize(tar.len() + 1024, 0);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let compressed = encoder
+        .write_all(&tar)
+        .and_then(|_| encoder.finish())
+        .map_err(|why| format!("cannot compress the export: {}", why))?;
+    Ok((format!("taigi-glossary-{}.tar.gz", stamp), compressed, entries.len()))
+}
@@ -0,0 +1,49 @@
+use crate::db::FeedEntry;
+use crate::text::escape;
+
+// SQLite's `YYYY-MM-DD HH:MM:SS` (UTC) as RFC 3339
+fn timestamp(created_at: &str) -> String {
+    format!("{}Z", created_at.replacen(' ', "T", 1))
+}
+
+// Atom feed of the words of the day and weekly digests. `public_url` is
+// where the HTTP server can be reached from outside, for the self link.
+pub fn atom(entries: &[FeedEntry], public_url: Option<&str>) -> String {
+    let updated = entries
+        .first()
+        .map(|entry| timestamp(&entry.created_at))
+        .unwrap_or_else(|| "1970-01-01T00:00:00Z".to_string());
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str("  <title>Taigi word of the day</title>\n");
+    xml.push_str("  <id>tag:taigi-translate-bot,2025:feed/wotd</id>\n");
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated));
+    xml.push_str("  <author><name>taigi-translate-bot</name></author>\n");
+    if let Some(url) = public_url {
+        let url = format!("{}/feed/wotd.xml", url.trim_end_matches('/'));
+        xml.push_str(&format!("  <link rel=\"self\" href=\"{}\"/>\n", escape(&url)));
+    }
+
+    for entry in entries {
+        let title = match entry.kind.as_str() {
+            "wotd" => format!("Word of the day: {}", entry.title),
+            _ => entry.title.clone(),
+        };
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape(&title)));
+        xml.push_str(&format!(
+            "    <id>tag:taigi-translate-bot,2025:feed/{}/{}</id>\n",
+            entry.kind, entry.id
+        ));
+        xml.push_str(&format!("    <updated>{}</updated>\n", timestamp(&entry.created_at)));
+        xml.push_str(&format!(
+            "    <content type=\"text\">{}</content>\n",
+            escape(&entry.content)
+        ));
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
@@ -0,0 +1,267 @@
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::romanization;
+
+// Taiwan's festivals with their Taigi names and words that go with them,
+// from `data/festivals.txt`. The lunar ones are listed with the Gregorian
+// days they fall on, since working those out needs the lunar calendar.
+
+const BUNDLED: &str = include_str!("../data/festivals.txt");
+
+// Festivals fall on days in Taiwan, UTC+8
+const TAIWAN_OFFSET: u64 = 8 * 60 * 60;
+
+// A day as (year, month, day)
+pub type Date = (i64, u32, u32);
+
+pub struct Word {
+    pub hanji: String,
+    pub lomaji: String,
+    pub english: String,
+}
+
+pub struct Festival {
+    pub code: String,
+    pub hanji: String,
+    pub lomaji: String,
+    pub english: String,
+    // In the traditional calendar, e.g. 農曆八月十五
+    pub when: String,
+    // `YYYY-MM-DD`, or `MM-DD` for festivals on the same day every year
+    pub dates: Vec<String>,
+    pub words: Vec<Word>,
+}
+
+impl Festival {
+    fn is(&self, wanted: &str) -> bool {
+        self.code.eq_ignore_ascii_case(wanted)
+            || self.hanji == wanted
+            || self.hanji.strip_suffix('節') == Some(wanted)
+            || self.english.eq_ignore_ascii_case(wanted)
+            || romanization::toneless(&self.lomaji).to_lowercase()
+                == romanization::toneless(wanted).to_lowercase()
+    }
+
+    pub fn falls_on(&self, (year, month, day): Date) -> bool {
+        let full = format!("{:04}-{:02}-{:02}", year, month, day);
+        let yearly = format!("{:02}-{:02}", month, day);
+        self.dates.iter().any(|date| *date == full || *date == yearly)
+    }
+
+    // The first day it falls on from `today`, None past the listed years
+    pub fn next(&self, today: Date) -> Option<String> {
+        let (year, month, day) = today;
+        let today = format!("{:04}-{:02}-{:02}", year, month, day);
+        self.dates
+            .iter()
+            .flat_map(|date| match date.len() {
+                5 => vec![format!("{}-{}", year, date), format!("{}-{}", year + 1, date)],
+                _ => vec![date.clone()],
+            })
+            .filter(|date| *date >= today)
+            .min()
+    }
+
+    // The name, when it falls and its words
+    pub fn card(&self, today: Date) -> String {
+        let mut lines = vec![format!("🏮 **{}** {} — {}", self.hanji, self.lomaji, self.english)];
+        lines.push(match self.next(today) {
+            Some(date) => format!("{} · next on {}", self.when, date),
+            None => self.when.clone(),
+        });
+        for word in &self.words {
+            lines.push(format!(
+                "{} {} — {}",
+                word.hanji,
+                word.lomaji,
+                word.english
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+pub fn festivals() -> &'static [Festival] {
+    static FESTIVALS: OnceLock<Vec<Festival>> = OnceLock::new();
+    FESTIVALS.get_or_init(|| {
+        let mut festivals: Vec<Festival> = Vec::new();
+        for line in BUNDLED.lines().filter(|line| !line.starts_with('#')) {
+            if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                let (code, hanji) = header.split_once(' ').unwrap_or((header, header));
+                festivals.push(Festival {
+                    code: code.to_string(),
+                    hanji: hanji.to_string(),
+                    lomaji: String::new(),
+                    english: String::new(),
+                    when: String::new(),
+                    dates: Vec::new(),
+                    words: Vec::new(),
+                });
+                continue;
+            }
+            let Some(festival) = festivals.last_mut() else {
+                continue;
+            };
+            let mut fields = line.split('\t');
+            match fields.next() {
+                Some("name") => {
+                    festival.lomaji = fields.next().unwrap_or_default().to_string();
+                    festival.english = fields.next().unwrap_or_default().to_string();
+                }
+                Some("when") => festival.when = fields.next().unwrap_or_default().to_string(),
+                Some("dates") => festival.dates = fields.map(str::to_string).collect(),
+                Some(hanji) if !hanji.is_empty() => festival.words.push(Word {
+                    hanji: hanji.to_string(),
+                    lomaji: fields.next().unwrap_or_default().to_string(),
+                    english: fields.next().unwrap_or_default().to_string(),
+                }),
+                _ => {}
+            }
+        }
+        festivals
+    })
+}
+
+// The civil date `days` after 1970-01-01
+fn civil(days: i64) -> Date {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// The date in Taiwan `secs` after the Unix epoch
+fn taiwan_date(secs: u64) -> Date {
+    civil(((secs + TAIWAN_OFFSET) / (24 * 60 * 60)) as i64)
+}
+
+// Today in Taiwan
+pub fn today() -> Date {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    taiwan_date(now)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// `2026-09-25`, `2026/9/25`, or `9-25` and `9/25` in this year
+pub fn parse_date(text: &str, today: Date) -> Option<Date> {
+    let parts: Vec<&str> = text.trim().split(['-', '/']).collect();
+    let number = |part: &str| part.trim().parse::<u32>().ok();
+    let (year, month, day) = match parts[..] {
+        [year, month, day] => (year.trim().parse::<i64>().ok()?, number(month)?, number(day)?),
+        [month, day] => (today.0, number(month)?, number(day)?),
+        _ => return None,
+    };
+    let valid = (1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day);
+    valid.then_some((year, month, day))
+}
+
+// The festival a query names, by code, Hanji, English name or reading
+pub fn find(wanted: &str) -> Option<&'static Festival> {
+    let wanted = wanted.trim();
+    if wanted.is_empty() {
+        return None;
+    }
+    festivals().iter().find(|festival| festival.is(wanted))
+}
+
+pub fn on(date: Date) -> Option<&'static Festival> {
+    festivals().iter().find(|festival| festival.falls_on(date))
+}
+
+// The next festival from `today` on, today's included
+pub fn upcoming(today: Date) -> Option<&'static Festival> {
+    festivals()
+        .iter()
+        .filter_map(|festival| Some((festival.next(today)?, festival)))
+        .min_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, festival)| festival)
+}
+
+// Every festival by code, for queries that name none
+pub fn overview() -> String {
+    let lines: Vec<String> = festivals()
+        .iter()
+        .map(|festival| format!("`{}` {} {}", festival.code, festival.hanji, festival.english))
+        .collect();
+    format!("🏮 Festivals, e.g. `/festival 中秋節` or `/festival 9/25`:\n{}", lines.join("\n"))
+}
+
+// The post on a festival day
+pub fn announcement(festival: &Festival, today: Date) -> String {
+    format!("Today is {}!\n{}", festival.hanji, festival.card(today))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_counts_from_the_epoch() {
+        assert_eq!(civil(0), (1970, 1, 1));
+        assert_eq!(civil(-1), (1969, 12, 31));
+        assert_eq!(civil(365), (1971, 1, 1));
+        assert_eq!(civil(20_721), (2026, 9, 25));
+    }
+
+    #[test]
+    fn civil_has_leap_days() {
+        assert_eq!(civil(11_016), (2000, 2, 29));
+        assert_eq!(civil(11_017), (2000, 3, 1));
+        assert_eq!(civil(19_782), (2024, 2, 29));
+        // 2100 isn't a leap year
+        assert_eq!(civil(47_540), (2100, 2, 28));
+        assert_eq!(civil(47_541), (2100, 3, 1));
+    }
+
+    #[test]
+    fn taiwan_date_turns_at_utc_16() {
+        let day = 24 * 60 * 60;
+        assert_eq!(taiwan_date(0), (1970, 1, 1));
+        assert_eq!(taiwan_date(16 * 60 * 60 - 1), (1970, 1, 1));
+        assert_eq!(taiwan_date(16 * 60 * 60), (1970, 1, 2));
+        // 2024-12-31 16:00 UTC is already New Year in Taiwan
+        assert_eq!(taiwan_date(20_088 * day + 16 * 60 * 60 - 1), (2024, 12, 31));
+        assert_eq!(taiwan_date(20_088 * day + 16 * 60 * 60), (2025, 1, 1));
+    }
+
+    #[test]
+    fn parse_date_formats() {
+        let today = (2026, 9, 25);
+        assert_eq!(parse_date("2026-09-25", today), Some((2026, 9, 25)));
+        assert_eq!(parse_date("2027/1/5", today), Some((2027, 1, 5)));
+        assert_eq!(parse_date(" 10/6 ", today), Some((2026, 10, 6)));
+        assert_eq!(parse_date("13-1", today), None);
+        assert_eq!(parse_date("9-31", today), None);
+        assert_eq!(parse_date("tomorrow", today), None);
+    }
+
+    #[test]
+    fn parse_date_knows_leap_days() {
+        assert_eq!(parse_date("2024-02-29", (2026, 1, 1)), Some((2024, 2, 29)));
+        assert_eq!(parse_date("2026-02-29", (2026, 1, 1)), None);
+        assert_eq!(parse_date("2/29", (2028, 1, 1)), Some((2028, 2, 29)));
+        assert_eq!(parse_date("2/29", (2027, 1, 1)), None);
+        assert_eq!(parse_date("2100-2-29", (2026, 1, 1)), None);
+        assert_eq!(parse_date("2000-2-29", (2026, 1, 1)), Some((2000, 2, 29)));
+    }
+}
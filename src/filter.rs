@@ -0,0 +1,65 @@
+use serenity::model::id::GuildId;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::db;
+use crate::text;
+
+// What happens to a query containing one of a server's filtered words,
+// managed by its moderators with /filter
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    // Not looked up at all
+    Refuse,
+    // Only answered where no one else sees it
+    Private,
+}
+
+impl Action {
+    pub fn parse(value: &str) -> Option<Action> {
+        match value {
+            "refuse" => Some(Action::Refuse),
+            "private" => Some(Action::Private),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Action::Refuse => "refuse",
+            Action::Private => "private",
+        }
+    }
+}
+
+// Words are stored lowercased and matched anywhere in the query, so a
+// filtered word can't be slipped through inside a phrase
+pub fn normalize(word: &str) -> String {
+    text::nfc(word.trim()).to_lowercase()
+}
+
+// The strictest action of the filtered words in a query. Outside servers, or
+// if the list can't be read, nothing is filtered.
+pub async fn check(db: &SqlitePool, guild_id: Option<GuildId>, query: &str) -> Option<Action> {
+    let guild_id = guild_id?;
+    let words = match db::filtered_words(db, &guild_id.to_string()).await {
+        Ok(words) => words,
+        Err(why) => {
+            error!("Error loading filtered words: {why:?}");
+            return None;
+        }
+    };
+    let query = normalize(query);
+    let mut strictest = None;
+    for (word, action) in words {
+        if !query.contains(&word) {
+            continue;
+        }
+        match Action::parse(&action) {
+            Some(Action::Refuse) => return Some(Action::Refuse),
+            Some(Action::Private) => strictest = Some(Action::Private),
+            None => {}
+        }
+    }
+    strictest
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use serenity::builder::{CreateAllowedMentions, CreateForumPost, CreateMessage};
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, GuildId};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::config::Config;
+use crate::db::{self, GuildSettings};
+use crate::preflight;
+
+// Forum post titles are limited to 100 characters
+const MAX_TITLE: usize = 100;
+
+// The archive forum for a lookup answered publicly in `guild`: its own from
+// /config, else `discord.archive_forum`, which `post` only lets that forum's
+// own server use
+pub fn forum_for(guild: &GuildSettings, config: &Config) -> Option<ChannelId> {
+    guild.archive_forum.or(config.archive_forum)
+}
+
+// Post the results for `keyword` in the archive forum in the background,
+// titled with the keyword, unless it already has a post there. Over time the
+// forum becomes a browsable, searchable record of what the bot was asked.
+pub fn archive(
+    http: Arc<Http>,
+    db: SqlitePool,
+    forum: ChannelId,
+    guild_id: GuildId,
+    keyword: String,
+    content: String,
+) {
+    tokio::spawn(async move { post(&http, &db, forum, guild_id, &keyword, &content).await });
+}
+
+async fn post(
+    http: &Http,
+    db: &SqlitePool,
+    forum: ChannelId,
+    guild_id: GuildId,
+    keyword: &str,
+    content: &str,
+) {
+    let forum_id = forum.to_string();
+    match db::forum_post(db, &forum_id, keyword).await {
+        Ok(None) => {}
+        Ok(Some(_)) => return,
+        Err(why) => {
+            error!("Error loading archive forum posts: {why:?}");
+            return;
+        }
+    }
+
+    // Another server's forum never gets this one's lookups
+    if preflight::guild_of(http, forum).await != Some(guild_id) {
+        return;
+    }
+
+    let title: String = keyword.chars().take(MAX_TITLE).collect();
+    let message = CreateMessage::new()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new());
+    let thread = match forum
+        .create_forum_post(http, CreateForumPost::new(title, message))
+        .await
+    {
+        Ok(thread) => thread,
+        Err(why) => {
+            error!("Error posting to the archive forum: {why:?}");
+            return;
+        }
+    };
+    if let Err(why) = db::record_forum_post(db, &forum_id, keyword, &thread.id.to_string()).await {
+        error!("Error recording an archive forum post: {why:?}");
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use serenity::builder::{CreateActionRow, CreateButton};
+use serenity::model::application::{ButtonStyle, ComponentInteraction};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::context::Context;
+use crate::corpus;
+use crate::db;
+use crate::ime;
+use crate::respond::{self, Mode};
+use crate::widget;
+
+const BUNDLED: &str = include_str!("../data/frequency.tsv");
+
+const PREFIX: &str = "frequent:";
+
+// Most a /frequent list or its save button takes
+pub const MAX_LISTED: usize = 100;
+
+// How common a word is, from its rank in the frequency list
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Band {
+    Common,
+    Fair,
+    Uncommon,
+}
+
+// Ranks below these are common and fairly common in a corpus
+const COMMON: usize = 300;
+const FAIR: usize = 2000;
+
+impl Band {
+    // The bundled starter list holds common words only, so without a corpus
+    // its first fifth is common and the rest fairly common
+    fn of(rank: usize, words: usize, bundled: bool) -> Band {
+        if bundled && rank < words / 5 {
+            Band::Common
+        } else if bundled {
+            Band::Fair
+        } else if rank < COMMON {
+            Band::Common
+        } else if rank < FAIR {
+            Band::Fair
+        } else {
+            Band::Uncommon
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Band::Common => "★★★ common",
+            Band::Fair => "★★ fairly common",
+            Band::Uncommon => "★ uncommon",
+        }
+    }
+}
+
+// One word of the frequency list
+pub struct Word {
+    pub hanji: String,
+    pub lomaji: String,
+}
+
+struct List {
+    words: Vec<Word>,
+    // Hanji -> position in `words`
+    ranks: HashMap<String, usize>,
+    // Whether it's the bundled list, for lack of a corpus
+    bundled: bool,
+}
+
+// The corpus's words by how often they appear, their most common reading
+// with them, or the bundled list when no corpus is loaded. Built on first
+// use, after the corpus was read at startup.
+fn list() -> &'static List {
+    static LIST: OnceLock<List> = OnceLock::new();
+    LIST.get_or_init(|| {
+        let bundled = corpus::sentences().is_empty();
+        let words: Vec<Word> = if bundled {
+            BUNDLED
+                .lines()
+                .filter(|line| !line.starts_with('#'))
+                .filter_map(|line| line.split_once('\t'))
+                .map(|(hanji, lomaji)| Word {
+                    hanji: hanji.to_string(),
+                    lomaji: lomaji.to_string(),
+                })
+                .collect()
+        } else {
+            let mut counts: HashMap<&str, (usize, &ime::Spelling)> = HashMap::new();
+            for spelling in ime::spellings() {
+                let (count, best) = counts.entry(&spelling.hanji).or_insert((0, spelling));
+                *count += spelling.count;
+                if spelling.count > best.count {
+                    *best = spelling;
+                }
+            }
+            let mut counted: Vec<(usize, &ime::Spelling)> = counts.into_values().collect();
+            counted.sort_by(|(a, a_best), (b, b_best)| {
+                b.cmp(a).then_with(|| a_best.hanji.cmp(&b_best.hanji))
+            });
+            counted
+                .into_iter()
+                .map(|(_, best)| Word {
+                    hanji: best.hanji.clone(),
+                    lomaji: best.lomaji.clone(),
+                })
+                .collect()
+        };
+        let mut ranks = HashMap::new();
+        for (rank, word) in words.iter().enumerate() {
+            ranks.entry(word.hanji.clone()).or_insert(rank);
+        }
+        List { words, ranks, bundled }
+    })
+}
+
+// How common a Hanji word is, None for words not in the list
+pub fn band(hanji: &str) -> Option<Band> {
+    let list = list();
+    list.ranks.get(hanji).map(|rank| Band::of(*rank, list.words.len(), list.bundled))
+}
+
+// A formatted result with its headword's band added, e.g.
+// `📚 食飯 [tsia̍h-pn̄g] … ★★★ common`
+pub fn mark(result: &str) -> String {
+    match widget::fields(result).hanji.and_then(band) {
+        Some(band) => format!("{} {}", result, band.label()),
+        None => result.to_string(),
+    }
+}
+
+// The `count` most common words
+pub fn top(count: usize) -> &'static [Word] {
+    let words = &list().words;
+    &words[..count.min(words.len())]
+}
+
+// `/frequent` as a numbered list, with a button saving it as flashcards
+pub fn study_list(count: usize) -> (String, CreateActionRow) {
+    let words = top(count.min(MAX_LISTED));
+    let lines: Vec<String> = words
+        .iter()
+        .enumerate()
+        .map(|(idx, word)| format!("{}. {} {}", idx + 1, word.hanji, word.lomaji))
+        .collect();
+    let content = format!("📊 The {} most common words:\n{}", words.len(), lines.join("\n"));
+    let button = CreateButton::new(format!("{}save:{}", PREFIX, words.len()))
+        .style(ButtonStyle::Secondary)
+        .emoji('💾')
+        .label("Save all to my word list");
+    (content, CreateActionRow::Buttons(vec![button]))
+}
+
+async fn save(db: &SqlitePool, user_id: &str, count: usize) -> String {
+    let mut added = 0;
+    for word in top(count.min(MAX_LISTED)) {
+        match db::save_word(db, user_id, &word.hanji).await {
+            Ok(true) => added += 1,
+            Ok(false) => {}
+            Err(why) => {
+                error!("Error saving word: {why:?}");
+                return "Could not save the words.".to_string();
+            }
+        }
+    }
+    format!("💾 Added {} words to your word list, see `/saved`.", added)
+}
+
+// Handle the save button of /frequent. Returns false for other components.
+pub async fn handle(ctx: &Context, db: &SqlitePool, component: &ComponentInteraction) -> bool {
+    let Some(count) = component.data.custom_id.strip_prefix(PREFIX).and_then(|rest| {
+        rest.strip_prefix("save:")?.parse::<usize>().ok()
+    }) else {
+        return false;
+    };
+    let user_id = component.user.id.to_string();
+    respond::component(ctx, db, component, Mode::Ephemeral, save(db, &user_id, count)).await;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corpus_bands_by_rank() {
+        assert_eq!(Band::of(0, 30_000, false), Band::Common);
+        assert_eq!(Band::of(299, 30_000, false), Band::Common);
+        assert_eq!(Band::of(300, 30_000, false), Band::Fair);
+        assert_eq!(Band::of(2000, 30_000, false), Band::Uncommon);
+    }
+
+    #[test]
+    fn bundled_list_has_no_uncommon_words() {
+        assert_eq!(Band::of(0, 155, true), Band::Common);
+        assert_eq!(Band::of(30, 155, true), Band::Common);
+        assert_eq!(Band::of(31, 155, true), Band::Fair);
+        assert_eq!(Band::of(154, 155, true), Band::Fair);
+    }
+}
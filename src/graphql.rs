@@ -0,0 +1,260 @@
+use std::sync::Arc;
+
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Enum, Object, SchemaBuilder, SimpleObject,
+};
+use tracing::error;
+
+use crate::config::SharedConfig;
+use crate::corpus::{self, Sentence};
+use crate::db::UserPrefs;
+use crate::lookup::LookupPool;
+use crate::query;
+use crate::reading;
+use crate::romanization::Romanization;
+use crate::source::{self, Source};
+use crate::widget;
+
+pub type Schema = async_graphql::Schema<Query, EmptyMutation, EmptySubscription>;
+
+// Nesting and cost allowed per query. Fields cost 1, except those asking the
+// dictionaries: a lookup costs FETCH, and pronunciations and examples, fetched
+// for each of up to MAX_ENTRIES entries, cost FETCH per entry. So a query can
+// make one lookup with both nested, or a few without, but not alias `lookup`
+// over and over.
+const MAX_DEPTH: usize = 6;
+const MAX_COMPLEXITY: usize = 250;
+const FETCH: usize = 5;
+// Entries returned per lookup at most
+const MAX_ENTRIES: usize = 20;
+
+// Examples given per entry at most
+const MAX_EXAMPLES: usize = 10;
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(remote = "crate::source::Source")]
+pub enum SourceName {
+    #[graphql(name = "TAIGITV")]
+    TaigiTv,
+    Sutian,
+    #[graphql(name = "ITAIGI")]
+    ITaigi,
+    Moedict,
+    Plugins,
+    Places,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(remote = "crate::romanization::Romanization")]
+pub enum Spelling {
+    Tailo,
+    Poj,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(remote = "crate::reading::Kind")]
+pub enum ReadingKind {
+    Literary,
+    Colloquial,
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    // Look a word up in the dictionaries like /taigi, optionally in some of
+    // them only, with readings in Tâi-lô or POJ
+    #[graphql(complexity = "FETCH + child_complexity")]
+    async fn lookup(
+        &self,
+        ctx: &Context<'_>,
+        word: String,
+        sources: Option<Vec<SourceName>>,
+        romanization: Option<Spelling>,
+    ) -> async_graphql::Result<LookupResult> {
+        let keyword = query::normalize(&word);
+        if keyword.is_empty() || keyword.chars().count() > widget::MAX_QUERY {
+            return Err("Give a word to look up".into());
+        }
+        let config = ctx.data::<Arc<SharedConfig>>()?.get();
+        let lookup = ctx
+            .data::<LookupPool>()?
+            .lookup(&keyword, None, &config)
+            .await;
+
+        let romanization = romanization.map_or(Romanization::Tailo, Romanization::from);
+        let prefs = UserPrefs {
+            romanization: Some(romanization),
+            ..UserPrefs::default()
+        };
+        let wanted: Option<Vec<Source>> =
+            sources.map(|sources| sources.into_iter().map(Source::from).collect());
+        let entries = lookup
+            .results
+            .iter()
+            .zip(&lookup.sources)
+            .filter(|(_, source)| wanted.as_ref().is_none_or(|wanted| wanted.contains(source)))
+            .take(MAX_ENTRIES)
+            .map(|(line, &source)| {
+                let line = prefs.format_result(line);
+                let fields = widget::fields(&line);
+                Entry {
+                    source: source.into(),
+                    hanji: fields.hanji.map(str::to_string),
+                    lomaji: fields.lomaji.map(str::to_string),
+                    text: fields.text.to_string(),
+                    url: fields.url.map(str::to_string),
+                    romanization,
+                }
+            })
+            .collect();
+        Ok(LookupResult {
+            query: keyword,
+            complete: lookup.is_complete(),
+            entries,
+        })
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct LookupResult {
+    query: String,
+    // False when a dictionary failed, so entries may be missing
+    complete: bool,
+    // The first MAX_ENTRIES
+    entries: Vec<Entry>,
+}
+
+pub struct Entry {
+    source: SourceName,
+    hanji: Option<String>,
+    lomaji: Option<String>,
+    text: String,
+    url: Option<String>,
+    romanization: Romanization,
+}
+
+#[Object]
+impl Entry {
+    async fn source(&self) -> SourceName {
+        self.source
+    }
+
+    async fn hanji(&self) -> Option<&str> {
+        self.hanji.as_deref()
+    }
+
+    async fn lomaji(&self) -> Option<&str> {
+        self.lomaji.as_deref()
+    }
+
+    async fn text(&self) -> &str {
+        &self.text
+    }
+
+    async fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    // Moedict's readings of the headword, marked literary or colloquial when
+    // they are
+    #[graphql(complexity = "MAX_ENTRIES * FETCH + child_complexity")]
+    async fn pronunciations(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Pronunciation>> {
+        let Some(hanji) = &self.hanji else {
+            return Ok(Vec::new());
+        };
+        let pool = ctx.data::<LookupPool>()?;
+        Ok(match reading::moedict(pool, hanji).await {
+            Ok(readings) => readings
+                .into_iter()
+                .map(|reading| Pronunciation {
+                    text: self.romanization.write(&reading.text),
+                    kind: reading.kind().map(ReadingKind::from),
+                    label: reading.mark(),
+                })
+                .collect(),
+            Err(err) => {
+                error!("Error fetching readings of {}: {}", hanji, err);
+                Vec::new()
+            }
+        })
+    }
+
+    // Moedict's example sentences of the headword, then the corpus'
+    #[graphql(complexity = "MAX_ENTRIES * FETCH + child_complexity")]
+    async fn examples(&self, #[graphql(default = 3)] limit: usize) -> Vec<Example> {
+        let Some(hanji) = &self.hanji else {
+            return Vec::new();
+        };
+        let limit = limit.min(MAX_EXAMPLES);
+        let sentences = source::moedict_examples(hanji, limit)
+            .await
+            .unwrap_or_else(|err| {
+                error!("Error fetching Moedict examples: {}", err);
+                Vec::new()
+            });
+        let example = |sentence: &Sentence| Example {
+            hanji: sentence.hanji.clone(),
+            lomaji: self.romanization.write(&sentence.lomaji),
+            mandarin: sentence.mandarin.clone(),
+        };
+        let mut examples: Vec<Example> = sentences.iter().map(example).collect();
+        for sentence in corpus::search(hanji, limit - examples.len()) {
+            if !sentences.iter().any(|known| known.hanji == sentence.hanji) {
+                examples.push(example(sentence));
+            }
+        }
+        examples
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Pronunciation {
+    text: String,
+    kind: Option<ReadingKind>,
+    // e.g. 文讀, 白讀, or Moedict's other marks like 俗
+    label: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct Example {
+    hanji: String,
+    lomaji: String,
+    mandarin: String,
+}
+
+fn limited() -> SchemaBuilder<Query, EmptyMutation, EmptySubscription> {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+}
+
+pub fn schema(lookups: LookupPool, config: Arc<SharedConfig>) -> Schema {
+    limited().data(lookups).data(config).finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Whether the query is turned down for its cost, checked before anything
+    // is looked up
+    async fn too_complex(query: &str) -> bool {
+        let response = limited().finish().execute(query).await;
+        response.errors.iter().any(|error| error.message.contains("too complex"))
+    }
+
+    #[tokio::test]
+    async fn limits_lookups_per_query() {
+        let nested = "entries { hanji pronunciations { text } examples { hanji } }";
+        assert!(!too_complex(&format!("{{ lookup(word: \"食\") {{ {} }} }}", nested)).await);
+        let aliased: String = (0..2)
+            .map(|idx| format!("l{}: lookup(word: \"食\") {{ {} }} ", idx, nested))
+            .collect();
+        assert!(too_complex(&format!("{{ {} }}", aliased)).await);
+        let bare: String = (0..60)
+            .map(|idx| format!("l{}: lookup(word: \"食\") {{ complete }} ", idx))
+            .collect();
+        assert!(too_complex(&format!("{{ {} }}", bare)).await);
+    }
+}
@@ -0,0 +1,160 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+use crate::audio;
+use crate::breakdown;
+use crate::commands::annotate::{MAX_TEXT, MAX_WORDS};
+use crate::config::SharedConfig;
+use crate::db::UserPrefs;
+use crate::lookup::LookupPool;
+use crate::query;
+use crate::romanization::Romanization;
+use crate::source::Source;
+use crate::widget;
+
+// Generated from proto/taigi.proto
+pub mod proto {
+    tonic::include_proto!("taigi");
+}
+
+use proto::taigi_server::{Taigi, TaigiServer};
+
+struct Service {
+    lookups: LookupPool,
+    config: Arc<SharedConfig>,
+}
+
+fn prefs(romanization: i32) -> UserPrefs {
+    let romanization = match proto::Romanization::try_from(romanization) {
+        Ok(proto::Romanization::Poj) => Romanization::Poj,
+        _ => Romanization::Tailo,
+    };
+    UserPrefs {
+        romanization: Some(romanization),
+        ..UserPrefs::default()
+    }
+}
+
+#[tonic::async_trait]
+impl Taigi for Service {
+    async fn lookup(
+        &self,
+        request: Request<proto::LookupRequest>,
+    ) -> Result<Response<proto::LookupReply>, Status> {
+        let request = request.into_inner();
+        let keyword = query::normalize(&request.word);
+        if keyword.is_empty() || keyword.chars().count() > widget::MAX_QUERY {
+            return Err(Status::invalid_argument("give a word to look up"));
+        }
+        let mut wanted = Vec::new();
+        for name in &request.sources {
+            match Source::ALL.into_iter().find(|source| source.name().eq_ignore_ascii_case(name)) {
+                Some(source) => wanted.push(source),
+                None => return Err(Status::invalid_argument(format!("unknown source {}", name))),
+            }
+        }
+
+        let config = self.config.get();
+        let lookup = self.lookups.lookup(&keyword, None, &config).await;
+        let prefs = prefs(request.romanization);
+        let entries = lookup
+            .results
+            .iter()
+            .zip(&lookup.sources)
+            .filter(|(_, source)| wanted.is_empty() || wanted.contains(source))
+            .map(|(line, source)| {
+                let line = prefs.format_result(line);
+                let fields = widget::fields(&line);
+                proto::Entry {
+                    source: source.name().to_string(),
+                    hanji: fields.hanji.map(str::to_string),
+                    lomaji: fields.lomaji.map(str::to_string),
+                    text: fields.text.to_string(),
+                    url: fields.url.map(str::to_string),
+                }
+            })
+            .collect();
+        Ok(Response::new(proto::LookupReply {
+            query: keyword,
+            complete: lookup.is_complete(),
+            entries,
+        }))
+    }
+
+    async fn annotate(
+        &self,
+        request: Request<proto::AnnotateRequest>,
+    ) -> Result<Response<proto::AnnotateReply>, Status> {
+        let request = request.into_inner();
+        if request.text.chars().count() > MAX_TEXT as usize {
+            return Err(Status::invalid_argument(format!(
+                "passages are limited to {} characters",
+                MAX_TEXT
+            )));
+        }
+        let mut words: Vec<String> = Vec::new();
+        for token in breakdown::tokens(&request.text) {
+            if breakdown::has_han(&token) && !words.contains(&token) {
+                words.push(token);
+            }
+        }
+        let skipped = words.len().saturating_sub(MAX_WORDS);
+        words.truncate(MAX_WORDS);
+
+        let config = self.config.get();
+        let glosses = breakdown::gloss(words, None, &self.lookups, &config).await;
+        let prefs = prefs(request.romanization);
+        Ok(Response::new(proto::AnnotateReply {
+            complete: glosses.iter().all(|gloss| gloss.complete),
+            glosses: glosses
+                .into_iter()
+                .map(|gloss| proto::Gloss {
+                    lomaji: gloss.lomaji.map(|lomaji| prefs.romanize(&lomaji)),
+                    word: gloss.word,
+                    hanji: gloss.hanji,
+                })
+                .collect(),
+            skipped: skipped as u32,
+        }))
+    }
+
+    async fn synthesize(
+        &self,
+        request: Request<proto::SynthesizeRequest>,
+    ) -> Result<Response<proto::SynthesizeReply>, Status> {
+        let word = request.into_inner().word.trim().to_string();
+        if word.is_empty() {
+            return Err(Status::invalid_argument("give a word"));
+        }
+        match audio::moedict(&word).await {
+            Ok(Some(audio)) => Ok(Response::new(proto::SynthesizeReply {
+                filename: audio.filename,
+                audio: audio.bytes,
+            })),
+            Ok(None) => Err(Status::not_found(format!("no recording of {}", word))),
+            Err(err) => {
+                error!("Error fetching the recording of {}: {}", word, err);
+                Err(Status::unavailable("could not fetch the recording"))
+            }
+        }
+    }
+}
+
+// Serve the gRPC service next to the bot. It has no keys of its own, so it
+// belongs on a private network.
+pub fn spawn(bind: SocketAddr, lookups: LookupPool, config: Arc<SharedConfig>) {
+    let service = TaigiServer::new(Service { lookups, config });
+    tokio::spawn(async move {
+        info!("gRPC service listening on {}", bind);
+        let served = tonic::transport::Server::builder()
+            .add_service(service)
+            .serve(bind)
+            .await;
+        if let Err(why) = served {
+            error!("gRPC service error: {why:?}");
+        }
+    });
+}
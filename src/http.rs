@@ -0,0 +1,465 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, Request, State};
+use axum::http::{HeaderMap, Method, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use serenity::interactions_endpoint::Verifier;
+use serenity::model::application::Interaction;
+use sqlx::SqlitePool;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+use crate::apikey::{self, ApiKey, Limiter, Scope};
+use crate::config::SharedConfig;
+use crate::context::Context;
+use crate::db::{self, UserPrefs};
+use crate::export;
+use crate::feed;
+use crate::graphql;
+use crate::interactions::{self, Dispatch};
+use crate::latency;
+use crate::lookup::{LookupPool, Searched};
+use crate::panics;
+use crate::query;
+use crate::source::Source;
+use crate::widget;
+
+// Entries shown in the feed
+const FEED_ENTRIES: u32 = 30;
+
+#[derive(Clone)]
+struct AppState {
+    db: SqlitePool,
+    config: Arc<SharedConfig>,
+    lookups: LookupPool,
+    limiter: Arc<Limiter>,
+    schema: graphql::Schema,
+}
+
+async fn wotd_feed(State(state): State<AppState>) -> impl IntoResponse {
+    match db::feed_entries(&state.db, FEED_ENTRIES).await {
+        Ok(entries) => {
+            let config = state.config.get();
+            let xml = feed::atom(&entries, config.public_url.as_deref());
+            (StatusCode::OK, [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")], xml)
+        }
+        Err(why) => {
+            error!("Error loading the feed: {why:?}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+                "Could not load the feed".to_string(),
+            )
+        }
+    }
+}
+
+async fn metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        latency::prometheus() + panics::prometheus().as_str(),
+    )
+}
+
+// The widget is embedded in other sites, so any origin may call the API
+const CORS: [(header::HeaderName, &str); 4] = [
+    (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*"),
+    (header::ACCESS_CONTROL_ALLOW_METHODS, "GET, POST, OPTIONS"),
+    (header::ACCESS_CONTROL_ALLOW_HEADERS, "X-Api-Key, Content-Type"),
+    (header::ACCESS_CONTROL_MAX_AGE, "86400"),
+];
+
+fn api_error(status: StatusCode, message: &str) -> Response {
+    (status, CORS, Json(json!({ "error": message }))).into_response()
+}
+
+// The key from the X-Api-Key header or the `key` parameter
+fn given_key(request: &Request) -> Option<String> {
+    if let Some(key) = request.headers().get("x-api-key") {
+        return key.to_str().ok().map(str::to_string);
+    }
+    let key = request
+        .uri()
+        .query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("key="))?;
+    urlencoding::decode(key).ok().map(|key| key.into_owned())
+}
+
+// Whose `key` is: keys from `widget.keys` may only look words up
+async fn find_key(state: &AppState, key: &str) -> Result<Option<ApiKey>, sqlx::Error> {
+    let config = state.config.get();
+    if config.widget.keys.iter().any(|known| known == key) {
+        return Ok(Some(ApiKey {
+            id: None,
+            label: "widget.keys".to_string(),
+            scope: Scope::Lookup,
+            per_minute: config.widget.per_minute,
+        }));
+    }
+    db::api_key(&state.db, &apikey::hash(key)).await
+}
+
+// Only let through requests with a key allowed `scope`, within its limit
+async fn authorize(
+    State((state, scope)): State<(AppState, Scope)>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    // Browsers ask before sending the X-Api-Key header across origins,
+    // without the key
+    if request.method() == Method::OPTIONS {
+        return next.run(request).await;
+    }
+    let Some(given) = given_key(&request) else {
+        return api_error(StatusCode::UNAUTHORIZED, "Missing API key");
+    };
+    let key = match find_key(&state, &given).await {
+        Ok(Some(key)) => key,
+        Ok(None) => return api_error(StatusCode::UNAUTHORIZED, "Unknown API key"),
+        Err(why) => {
+            error!("Error checking an API key: {why:?}");
+            return api_error(StatusCode::INTERNAL_SERVER_ERROR, "Could not check the key");
+        }
+    };
+    if !key.allows(scope) {
+        return api_error(StatusCode::FORBIDDEN, "The key may not use this endpoint");
+    }
+    let caller = Caller {
+        hash: apikey::hash(&given),
+        per_minute: key.per_minute,
+    };
+    if let Err(wait) = state.limiter.check(&caller.hash, caller.per_minute) {
+        let mut response = api_error(StatusCode::TOO_MANY_REQUESTS, "Too many requests");
+        if let Ok(value) = (wait.as_secs() + 1).to_string().parse() {
+            response.headers_mut().insert(header::RETRY_AFTER, value);
+        }
+        return response;
+    }
+    request.extensions_mut().insert(caller);
+    next.run(request).await
+}
+
+// The key a request was let through with, for endpoints counting more than
+// the request itself against its limit
+#[derive(Clone)]
+struct Caller {
+    hash: String,
+    per_minute: u32,
+}
+
+#[derive(Deserialize)]
+struct WidgetParams {
+    q: Option<String>,
+}
+
+async fn preflight() -> impl IntoResponse {
+    (StatusCode::NO_CONTENT, CORS)
+}
+
+// Look a word up for a search box on another site
+async fn widget_lookup(
+    State(state): State<AppState>,
+    Query(params): Query<WidgetParams>,
+) -> Response {
+    let keyword = query::normalize(params.q.as_deref().unwrap_or_default());
+    if keyword.is_empty() || keyword.chars().count() > widget::MAX_QUERY {
+        return api_error(StatusCode::BAD_REQUEST, "Give a word to look up as `q`");
+    }
+    let config = state.config.get();
+    let lookup = state.lookups.lookup(&keyword, None, &config).await;
+    (CORS, Json(widget::response(&keyword, &lookup))).into_response()
+}
+
+// The per-source stats of /status
+async fn stats() -> impl IntoResponse {
+    let sources: Vec<_> = latency::snapshot()
+        .into_iter()
+        .map(|(source, stats)| {
+            json!({
+                "source": source.name(),
+                "requests": stats.requests,
+                "failures": stats.failures,
+                "average_ms": stats.average().map(|average| average.as_millis() as u64),
+                "last_error": stats.last_error,
+            })
+        })
+        .collect();
+    (CORS, Json(json!({ "sources": sources })))
+}
+
+// The glossary site of `/admin export`, in Tâi-lô
+async fn glossary(State(state): State<AppState>) -> Response {
+    match export::site(&state.db, &UserPrefs::default()).await {
+        Ok((filename, bytes, _)) => (
+            [
+                (header::CONTENT_TYPE, "application/gzip".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", filename),
+                ),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(err) => {
+            error!("Error exporting the glossary: {}", err);
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "Could not export the glossary")
+        }
+    }
+}
+
+async fn widget_schema() -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, "application/schema+json"),
+            (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*"),
+        ],
+        widget::SCHEMA,
+    )
+}
+
+#[derive(Deserialize)]
+struct LiveQuery {
+    q: String,
+}
+
+fn live_event(source: Source, (result, elapsed): &Searched<Vec<String>>) -> Value {
+    let elapsed_ms = elapsed.map(|elapsed| elapsed.as_millis() as u64);
+    match result {
+        Ok(results) => json!({
+            "event": "source_result",
+            "source": source.name(),
+            "results": results
+                .iter()
+                .map(|line| widget::result(line, source.name()))
+                .collect::<Vec<_>>(),
+            "elapsed_ms": elapsed_ms,
+        }),
+        Err(err) => json!({
+            "event": "source_error",
+            "source": source.name(),
+            "error": err,
+            "elapsed_ms": elapsed_ms,
+        }),
+    }
+}
+
+async fn send(socket: &mut WebSocket, event: Value) -> bool {
+    socket.send(Message::Text(event.to_string().into())).await.is_ok()
+}
+
+// Look up each `{"q": ...}` sent over the socket, streaming every source's
+// results as they arrive rather than waiting for the slowest. Each query
+// counts against the key's limit.
+async fn live_lookups(mut socket: WebSocket, state: AppState, caller: Caller) {
+    while let Some(Ok(message)) = socket.recv().await {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => return,
+            _ => continue,
+        };
+        let keyword = serde_json::from_str::<LiveQuery>(&text)
+            .map(|query| query::normalize(&query.q))
+            .unwrap_or_default();
+        if keyword.is_empty() || keyword.chars().count() > widget::MAX_QUERY {
+            let error = json!({ "event": "error", "error": "Send {\"q\": word} to look up" });
+            if !send(&mut socket, error).await {
+                return;
+            }
+            continue;
+        }
+        if let Err(wait) = state.limiter.check(&caller.hash, caller.per_minute) {
+            let error = json!({
+                "event": "error",
+                "error": "Too many requests",
+                "retry_after": wait.as_secs() + 1,
+            });
+            if !send(&mut socket, error).await {
+                return;
+            }
+            continue;
+        }
+
+        let config = state.config.get();
+        for source in Source::ALL.into_iter().filter(|source| config.sources.enabled(*source)) {
+            let started = json!({ "event": "source_started", "source": source.name() });
+            if !send(&mut socket, started).await {
+                return;
+            }
+        }
+        // The events are sent from here while the lookup runs in its task,
+        // until it's done and drops its end of the channel
+        let (events, mut received) = mpsc::unbounded_channel();
+        let lookups = state.lookups.clone();
+        let searched = keyword.clone();
+        let lookup = tokio::spawn(async move {
+            let report = |source, searched: &Searched<Vec<String>>| {
+                let _ = events.send(live_event(source, searched));
+            };
+            lookups.lookup_reporting(&searched, None, &config, &report).await
+        });
+        while let Some(event) = received.recv().await {
+            if !send(&mut socket, event).await {
+                return;
+            }
+        }
+        let complete = lookup.await.is_ok_and(|lookup| lookup.is_complete());
+        let done = json!({ "event": "done", "query": keyword, "complete": complete });
+        if !send(&mut socket, done).await {
+            return;
+        }
+    }
+}
+
+// Browsers can't set headers on WebSockets, so the key comes as `key`
+async fn live(
+    State(state): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    upgrade.on_upgrade(move |socket| live_lookups(socket, state, caller))
+}
+
+// Run a GraphQL query over the dictionaries, posted as JSON
+async fn graphql_query(
+    State(state): State<AppState>,
+    Json(request): Json<async_graphql::Request>,
+) -> impl IntoResponse {
+    (CORS, Json(state.schema.execute(request).await))
+}
+
+// The schema in GraphQL's own language, for tooling
+async fn graphql_schema(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8"),
+            (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*"),
+        ],
+        state.schema.sdl(),
+    )
+}
+
+// What the interactions endpoint needs to answer
+#[derive(Clone)]
+struct Endpoint {
+    verifier: Verifier,
+    ctx: Context,
+    dispatch: Arc<dyn Dispatch>,
+}
+
+async fn interaction(
+    State(endpoint): State<Endpoint>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let signature = header("X-Signature-Ed25519");
+    let timestamp = header("X-Signature-Timestamp");
+    // Discord checks that unsigned requests are turned away before it accepts
+    // the endpoint. Old timestamps are turned away too, against replays.
+    if !interactions::verify(&endpoint.verifier, &signature, &timestamp, &body) {
+        return (StatusCode::UNAUTHORIZED, "Invalid request signature").into_response();
+    }
+    let interaction: Interaction = match serde_json::from_slice(&body) {
+        Ok(interaction) => interaction,
+        Err(why) => {
+            error!("Error reading an HTTP interaction: {why:?}");
+            return (StatusCode::BAD_REQUEST, "Unknown interaction").into_response();
+        }
+    };
+    // Discord pings the endpoint when it's saved in the Developer Portal
+    if let Interaction::Ping(_) = interaction {
+        return Json(json!({ "type": 1 })).into_response();
+    }
+    // The handlers answer through the interaction callback like over the
+    // gateway, so the request itself is only acknowledged
+    tokio::spawn(async move {
+        endpoint.dispatch.dispatch(endpoint.ctx.clone(), interaction).await;
+    });
+    StatusCode::ACCEPTED.into_response()
+}
+
+// `POST /interactions`, the Interactions Endpoint URL to set in the Developer
+// Portal
+pub fn interactions(verifier: Verifier, ctx: Context, dispatch: Arc<dyn Dispatch>) -> Router {
+    let endpoint = Endpoint {
+        verifier,
+        ctx,
+        dispatch,
+    };
+    Router::new()
+        .route("/interactions", post(interaction))
+        .with_state(endpoint)
+}
+
+// Serve the public pages next to the bot. `interactions` is the HTTP
+// interactions endpoint, when it's on.
+pub fn spawn(
+    bind: SocketAddr,
+    db: SqlitePool,
+    config: Arc<SharedConfig>,
+    lookups: LookupPool,
+    interactions: Option<Router>,
+) {
+    let state = AppState {
+        db,
+        schema: graphql::schema(lookups.clone(), config.clone()),
+        config,
+        lookups,
+        limiter: Arc::default(),
+    };
+    // API keys are checked per route, by the scope it needs
+    let scoped = |scope| middleware::from_fn_with_state((state.clone(), scope), authorize);
+    let app = Router::new()
+        .route("/feed/wotd.xml", get(wotd_feed))
+        .route("/metrics", get(metrics))
+        .route("/widget/schema.json", get(widget_schema))
+        .route(
+            "/widget/lookup",
+            get(widget_lookup)
+                .options(preflight)
+                .route_layer(scoped(Scope::Lookup)),
+        )
+        .route(
+            "/graphql",
+            post(graphql_query)
+                .options(preflight)
+                .route_layer(scoped(Scope::Lookup))
+                // Added after the layer, so the schema needs no key
+                .get(graphql_schema),
+        )
+        .route("/ws", get(live).route_layer(scoped(Scope::Lookup)))
+        .route("/api/stats", get(stats).route_layer(scoped(Scope::Stats)))
+        .route("/api/export", get(glossary).route_layer(scoped(Scope::Admin)))
+        .with_state(state)
+        .merge(interactions.unwrap_or_default());
+
+    tokio::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(bind).await {
+            Ok(listener) => listener,
+            Err(why) => {
+                error!("Cannot listen on {}: {why:?}", bind);
+                return;
+            }
+        };
+        info!("HTTP server listening on {}", bind);
+        if let Err(why) = axum::serve(listener, app).await {
+            error!("HTTP server error: {why:?}");
+        }
+    });
+}
@@ -0,0 +1,229 @@
+use serde::Deserialize;
+
+// Languages the bot can reply in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "zh-TW", alias = "zh-tw", alias = "zh-Hant")]
+    ZhTw,
+}
+
+impl Locale {
+    pub fn parse(value: &str) -> Option<Locale> {
+        match value.trim().to_lowercase().as_str() {
+            "en" => Some(Locale::En),
+            "zh-tw" | "zh-hant" => Some(Locale::ZhTw),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::ZhTw => "zh-TW",
+        }
+    }
+
+    pub fn no_keyword(self) -> &'static str {
+        match self {
+            Locale::En => "Please provide a keyword to search for.",
+            Locale::ZhTw => "請輸入要查詢的詞。",
+        }
+    }
+
+    // How the searched keyword is quoted in the reply, mentioning the
+    // original input when it was converted (e.g. from Pinyin)
+    pub fn searched(self, keyword: &str, original: Option<&str>) -> String {
+        match (self, original) {
+            (Locale::En, Some(original)) => format!("\"{}\" (from \"{}\")", keyword, original),
+            (Locale::En, None) => format!("\"{}\"", keyword),
+            (Locale::ZhTw, Some(original)) => format!("「{}」（由「{}」轉換）", keyword, original),
+            (Locale::ZhTw, None) => format!("「{}」", keyword),
+        }
+    }
+
+    pub fn found(self, count: usize, searched: &str) -> String {
+        match self {
+            Locale::En if count == 1 => format!("Found 1 result for {}:", searched),
+            Locale::En => format!("Found {} results for {}:", count, searched),
+            Locale::ZhTw => format!("{}共找到 {} 筆結果：", searched, count),
+        }
+    }
+
+    pub fn not_found(self, searched: &str) -> String {
+        match self {
+            Locale::En => format!("❌ Nothing found for {}.", searched),
+            Locale::ZhTw => format!("❌ 查無{}的結果。", searched),
+        }
+    }
+
+    pub fn breakdown(self, searched: &str) -> String {
+        match self {
+            Locale::En => format!("Word by word for {}:", searched),
+            Locale::ZhTw => format!("{}逐詞對照：", searched),
+        }
+    }
+
+    pub fn part_of_speech(self, searched: &str, tag: &str) -> String {
+        match self {
+            Locale::En => format!("{} ({} only)", searched, tag),
+            Locale::ZhTw => format!("{}（限{}）", searched, tag),
+        }
+    }
+
+    pub fn taigi_to_mandarin(self, searched: &str) -> String {
+        match self {
+            Locale::En => format!("{} (Taigi → Mandarin)", searched),
+            Locale::ZhTw => format!("{}（台語→華語）", searched),
+        }
+    }
+
+    pub fn digest_header(self, total: i64, unique: usize, weekly: bool) -> String {
+        match (self, weekly) {
+            (Locale::En, false) => {
+                format!("📅 Today's lookups: {} queries for {} words", total, unique)
+            }
+            (Locale::En, true) => {
+                format!("📅 This week's lookups: {} queries for {} words", total, unique)
+            }
+            (Locale::ZhTw, false) => format!("📅 今日查詢：{} 次，共 {} 個詞", total, unique),
+            (Locale::ZhTw, true) => format!("📅 本週查詢：{} 次，共 {} 個詞", total, unique),
+        }
+    }
+
+    pub fn digest_rest(self, keywords: &str) -> String {
+        match self {
+            Locale::En => format!("Also looked up: {}", keywords),
+            Locale::ZhTw => format!("其他查詢：{}", keywords),
+        }
+    }
+
+    pub fn did_you_mean(self) -> &'static str {
+        match self {
+            Locale::En => "Did you mean:",
+            Locale::ZhTw => "你是不是要找：",
+        }
+    }
+
+    pub fn english(self, glosses: &str) -> String {
+        match self {
+            Locale::En => format!("🇬🇧 English: {}", glosses),
+            Locale::ZhTw => format!("🇬🇧 英文釋義：{}", glosses),
+        }
+    }
+
+    pub fn partial_failure(self, errors: &str) -> String {
+        match self {
+            Locale::En => format!("⚠️ Some sources had issues: {}", errors),
+            Locale::ZhTw => format!("⚠️ 部分來源發生問題：{}", errors),
+        }
+    }
+
+    pub fn slow_source(self, source: &str, secs: f64) -> String {
+        match self {
+            Locale::En => format!("🐢 {} was slow: {:.1}s", source, secs),
+            Locale::ZhTw => format!("🐢 {}回應較慢：{:.1} 秒", source, secs),
+        }
+    }
+
+    // Shown under results that can be brought back with /entry
+    pub fn tones(self, keyword: &str, groups: &str) -> String {
+        match self {
+            Locale::En => format!("🎵 *{}* in each tone: {}", keyword, groups),
+            Locale::ZhTw => format!("🎵 *{}* 各聲調：{}", keyword, groups),
+        }
+    }
+
+    pub fn permalink(self, id: &str) -> String {
+        match self {
+            Locale::En => format!("🔖 tg#{} · `/entry {}` shows this again later", id, id),
+            Locale::ZhTw => format!("🔖 tg#{} · 之後可用 `/entry {}` 再看一次", id, id),
+        }
+    }
+
+    pub fn entry(self, id: &str, searched: &str) -> String {
+        match self {
+            Locale::En => format!("🔖 tg#{} — saved results for {}:", id, searched),
+            Locale::ZhTw => format!("🔖 tg#{} — {}的查詢紀錄：", id, searched),
+        }
+    }
+
+    pub fn unknown_entry(self, id: &str) -> String {
+        match self {
+            Locale::En => format!("❌ No entry tg#{}.", id),
+            Locale::ZhTw => format!("❌ 查無條目 tg#{}。", id),
+        }
+    }
+
+    pub fn recalled(self, searched: &str) -> String {
+        match self {
+            Locale::En => format!("🗂️ Answered before, matching {}:", searched),
+            Locale::ZhTw => format!("🗂️ 過去查過、符合{}的詞：", searched),
+        }
+    }
+
+    pub fn recalled_phrases(self) -> &'static str {
+        match self {
+            Locale::En => "📒 From this server's phrasebook:",
+            Locale::ZhTw => "📒 本伺服器常用語：",
+        }
+    }
+
+    pub fn nothing_recalled(self, searched: &str) -> String {
+        match self {
+            Locale::En => format!("❌ Nothing answered before matches {}.", searched),
+            Locale::ZhTw => format!("❌ 過去的查詢中沒有符合{}的詞。", searched),
+        }
+    }
+
+    pub fn examples(self, searched: &str) -> String {
+        match self {
+            Locale::En => format!("💬 Example sentences with {}:", searched),
+            Locale::ZhTw => format!("💬 {}的例句：", searched),
+        }
+    }
+
+    pub fn no_examples(self, searched: &str) -> String {
+        match self {
+            Locale::En => format!("❌ No example sentences found with {}.", searched),
+            Locale::ZhTw => format!("❌ 查無含{}的例句。", searched),
+        }
+    }
+
+    pub fn total_failure(self, errors: &str) -> String {
+        match self {
+            Locale::En => format!("Could not search any sources. Errors: {}", errors),
+            Locale::ZhTw => format!("所有來源都查詢失敗。錯誤：{}", errors),
+        }
+    }
+
+    // Replies to queries caught by the server's word filter
+    pub fn filtered(self) -> &'static str {
+        match self {
+            Locale::En => "🚫 This server doesn't allow looking up that word.",
+            Locale::ZhTw => "🚫 本伺服器不允許查詢這個詞。",
+        }
+    }
+
+    pub fn filtered_private(self) -> &'static str {
+        match self {
+            Locale::En => {
+                "🔒 This server only answers that word privately. Ask with `/taigi search` instead."
+            }
+            Locale::ZhTw => "🔒 本伺服器只私下回答這個詞，請改用 `/taigi search` 查詢。",
+        }
+    }
+
+    // Posted while a query waits in slow mode, then replaced by the answer
+    pub fn queued(self, position: usize) -> String {
+        match self {
+            Locale::En => format!(
+                "⏳ Busy right now: queued, position {}. The answer will show up here.",
+                position
+            ),
+            Locale::ZhTw => format!("⏳ 目前查詢較多，排隊中：第 {} 位。結果會顯示在這裡。", position),
+        }
+    }
+}
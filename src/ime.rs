@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::breakdown;
+use crate::corpus;
+use crate::practice;
+use crate::romanization;
+use crate::text;
+
+// One way a reading is written, and how often it was seen that way
+pub struct Spelling {
+    pub hanji: String,
+    // The reading as written in the data, tone marks included
+    pub lomaji: String,
+    pub tones: Vec<u8>,
+    pub count: usize,
+}
+
+// Toneless syllables joined with hyphens, e.g. "tsiah-png", with POJ's o͘
+// and ⁿ spelt oo and nn like Tâi-lô's so `kó͘` and `kóo` find the same words
+pub fn key(syllables: &[(String, u8)]) -> String {
+    syllables
+        .iter()
+        .map(|(base, _)| romanization::toneless(base))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+// Index key -> the spellings seen for it, most common first
+fn index() -> &'static HashMap<String, Vec<Spelling>> {
+    static INDEX: OnceLock<HashMap<String, Vec<Spelling>>> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        let mut index: HashMap<String, Vec<Spelling>> = HashMap::new();
+        let mut add = |hanji: &str, lomaji: &str| {
+            let syllables = romanization::syllables(lomaji);
+            if syllables.is_empty() {
+                return;
+            }
+            let key = key(&syllables);
+            let tones: Vec<u8> = syllables.iter().map(|(_, tone)| *tone).collect();
+            let spellings = index.entry(key).or_default();
+            match spellings
+                .iter_mut()
+                .find(|spelling| spelling.hanji == hanji && spelling.tones == tones)
+            {
+                Some(spelling) => spelling.count += 1,
+                None => spellings.push(Spelling {
+                    hanji: hanji.to_string(),
+                    lomaji: text::nfc(lomaji).to_lowercase(),
+                    tones,
+                    count: 1,
+                }),
+            }
+        };
+
+        for word in practice::words() {
+            add(word.hanji, word.tailo);
+        }
+        // The corpus pairs whole sentences, so words are only taken from
+        // ones with a Han character for every syllable, word by word
+        for sentence in corpus::sentences() {
+            if sentence.hanji.chars().any(|c| c.is_ascii_alphanumeric()) {
+                continue;
+            }
+            let han: Vec<char> = sentence.hanji.chars().filter(|c| breakdown::is_han(*c)).collect();
+            let words: Vec<(&str, usize)> = sentence
+                .lomaji
+                .split_whitespace()
+                .map(|word| word.trim_matches(|c: char| c.is_ascii_punctuation()))
+                .map(|word| (word, romanization::syllables(word).len()))
+                .filter(|(_, count)| *count > 0)
+                .collect();
+            if words.iter().map(|(_, count)| count).sum::<usize>() != han.len() {
+                continue;
+            }
+            let mut start = 0;
+            for (word, count) in words {
+                let hanji: String = han[start..start + count].iter().collect();
+                add(&hanji, word);
+                start += count;
+            }
+        }
+
+        for spellings in index.values_mut() {
+            spellings.sort_by_key(|spelling| std::cmp::Reverse(spelling.count));
+        }
+        index
+    })
+}
+
+// Every spelling in the index, in no particular order
+pub fn spellings() -> impl Iterator<Item = &'static Spelling> {
+    index().values().flatten()
+}
+
+// Hanji spellings for a romanized word or phrase, best first. With tone
+// marks the spellings read with the same tones come first; without them
+// every tone is as likely.
+pub fn candidates(romanized: &str, limit: usize) -> Vec<&'static Spelling> {
+    let syllables = romanization::syllables(romanized);
+    if syllables.is_empty() {
+        return Vec::new();
+    }
+    let Some(spellings) = index().get(&key(&syllables)) else {
+        return Vec::new();
+    };
+    let tones: Vec<u8> = syllables.iter().map(|(_, tone)| *tone).collect();
+    let mut found: Vec<&Spelling> = spellings.iter().collect();
+    if romanization::has_tone_marks(romanized) {
+        // Stable, so each group stays most common first
+        found.sort_by_key(|spelling| spelling.tones != tones);
+    }
+    found.truncate(limit);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_of(text: &str) -> String {
+        key(&romanization::syllables(text))
+    }
+
+    #[test]
+    fn keys_drop_tones() {
+        assert_eq!(key_of("tsia̍h-pn̄g"), "tsiah-png");
+        assert_eq!(key_of("tsiah png"), "tsiah-png");
+    }
+
+    #[test]
+    fn keys_spell_poj_like_tailo() {
+        assert_eq!(key_of("kó͘"), "koo");
+        assert_eq!(key_of("kóo"), "koo");
+        assert_eq!(key_of("siⁿ"), "sinn");
+        assert_eq!(key_of("pháiⁿ-sè"), key_of("pháinn-sè"));
+    }
+
+    #[test]
+    fn poj_finds_the_tailo_spellings() {
+        let hanji = |romanized| -> Vec<&str> {
+            candidates(romanized, 10).iter().map(|spelling| spelling.hanji.as_str()).collect()
+        };
+        assert!(hanji("koo").contains(&"姑"));
+        assert_eq!(hanji("ko͘"), hanji("koo"));
+    }
+}
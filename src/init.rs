@@ -0,0 +1,240 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+use std::process;
+
+use serenity::builder::CreateCommand;
+use serenity::http::Http;
+use serenity::model::application::Command;
+
+use crate::config::{self, Config};
+use crate::upstream;
+
+// Database used when none is given, as in the defaults
+const DEFAULT_DATABASE: &str = "sqlite://taigi-bot.db";
+
+// Print a question and read the answer, `default` if left empty. Fails once
+// stdin is closed, so a wizard run without a terminal stops instead of looping.
+fn ask(question: &str, default: &str) -> Result<String, String> {
+    if default.is_empty() {
+        print!("{}: ", question);
+    } else {
+        print!("{} [{}]: ", question, default);
+    }
+    io::stdout().flush().map_err(|why| format!("cannot write the prompt: {}", why))?;
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) => Err("setup cancelled".to_string()),
+        Ok(_) if line.trim().is_empty() => Ok(default.to_string()),
+        Ok(_) => Ok(line.trim().to_string()),
+        Err(why) => Err(format!("cannot read the answer: {}", why)),
+    }
+}
+
+// Turn the terminal's echo on or off with stty, false where there's no
+// terminal for it
+fn echo(on: bool) -> bool {
+    cfg!(unix)
+        && process::Command::new("stty")
+            .arg(if on { "echo" } else { "-echo" })
+            .status()
+            .is_ok_and(|status| status.success())
+}
+
+// Echo turned off until it's dropped, whichever way the read ends
+struct Hidden;
+
+impl Drop for Hidden {
+    fn drop(&mut self) {
+        echo(true);
+        // The newline typed wasn't echoed either
+        println!();
+    }
+}
+
+// Like `ask`, without showing what is typed, for the token
+fn ask_secret(question: &str) -> Result<String, String> {
+    let _hidden = echo(false).then_some(Hidden);
+    ask(question, "")
+}
+
+fn confirm(question: &str) -> Result<bool, String> {
+    let answer = ask(&format!("{} (y/n)", question), "n")?;
+    Ok(answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"))
+}
+
+// Channel IDs separated by commas or spaces
+fn channel_ids(value: &str) -> Result<Vec<u64>, String> {
+    value
+        .split([',', ' '])
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            id.parse::<u64>()
+                .ok()
+                .filter(|id| *id != 0)
+                .ok_or_else(|| format!("\"{}\" is not a channel ID", id))
+        })
+        .collect()
+}
+
+// Ask until the answer is a list of channel IDs, empty if `optional`
+fn ask_channels(question: &str, optional: bool) -> Result<Vec<u64>, String> {
+    loop {
+        match channel_ids(&ask(question, "")?) {
+            Ok(ids) if ids.is_empty() && !optional => println!("  Give at least one channel."),
+            Ok(ids) => return Ok(ids),
+            Err(err) => println!("  {}", err),
+        }
+    }
+}
+
+// Ask until Discord accepts the token, returning the client it works with
+async fn ask_token() -> Result<(String, Http), String> {
+    println!("The bot token is under Bot → Reset Token in the Developer Portal.");
+    loop {
+        let token = ask_secret("Bot token (not shown)")?;
+        if serenity::utils::validate_token(&token).is_err() {
+            println!("  That doesn't look like a bot token, copy it again.");
+            continue;
+        }
+        let http = Http::new(&token);
+        match http.get_current_user().await {
+            Ok(user) => {
+                println!("  ✅ Logged in as {}", user.name);
+                return Ok((token, http));
+            }
+            Err(why) => println!("  Discord refused the token: {}", why),
+        }
+    }
+}
+
+struct Answers {
+    token: String,
+    lookup_channels: Vec<u64>,
+    ops_channel: Option<u64>,
+    database_url: String,
+}
+
+// A quoted string, valid in TOML and YAML alike
+fn quoted(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn render(answers: &Answers, yaml: bool) -> String {
+    let channels = answers
+        .lookup_channels
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut out = String::from(
+        "# Written by `init`, see config.example.toml for every setting\n",
+    );
+    if yaml {
+        out += "discord:\n";
+        out += &format!("  token: {}\n", quoted(&answers.token));
+        out += &format!("  lookup_channels: [{}]\n", channels);
+        if let Some(id) = answers.ops_channel {
+            out += &format!("  ops_channel: {}\n", id);
+        }
+        out += "database:\n";
+        out += &format!("  url: {}\n", quoted(&answers.database_url));
+    } else {
+        out += "[discord]\n";
+        out += &format!("token = {}\n", quoted(&answers.token));
+        out += &format!("lookup_channels = [{}]\n", channels);
+        if let Some(id) = answers.ops_channel {
+            out += &format!("ops_channel = {}\n", id);
+        }
+        out += "\n[database]\n";
+        out += &format!("url = {}\n", quoted(&answers.database_url));
+    }
+    out
+}
+
+// Write the config readable by its owner alone (0600 on Unix), as it holds the
+// token. A file being replaced gets the same permissions.
+fn write_private(path: &str, content: &str) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    file.write_all(content.as_bytes())
+}
+
+async fn register(http: &Http, commands: Vec<CreateCommand>) -> Result<usize, String> {
+    let app = http
+        .get_current_application_info()
+        .await
+        .map_err(|why| format!("cannot fetch the application: {}", why))?;
+    http.set_application_id(app.id);
+    let registered = Command::set_global_commands(http, commands)
+        .await
+        .map_err(|why| format!("cannot register the slash commands: {}", why))?;
+    Ok(registered.len())
+}
+
+// `init`: ask for the essential settings, write them to the config file, then
+// check the result the way the bot will at startup. Also registers the slash
+// commands and tries the dictionaries, so problems show before the first run.
+pub async fn run(commands: Vec<CreateCommand>) -> Result<(), String> {
+    let path = config::path();
+    let yaml = config::is_yaml(&path);
+    println!("Setting up {} ({}).", path, if yaml { "YAML" } else { "TOML" });
+    if Path::new(&path).exists() && !confirm(&format!("{} exists, replace it?", path))? {
+        return Err("kept the existing configuration".to_string());
+    }
+
+    // Ctrl-C ends the process without unwinding, so no guard would turn echo
+    // back on if it came while the token is read
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            echo(true);
+            println!();
+            process::exit(130);
+        }
+    });
+    let (token, http) = ask_token().await?;
+    let lookup_channels = ask_channels(
+        "Channels to answer lookups in (IDs, from Copy Channel ID in Discord)",
+        false,
+    )?;
+    let ops_channel = loop {
+        match ask_channels("Channel for result reports and suggestions (optional)", true)?[..] {
+            [] => break None,
+            [id] => break Some(id),
+            _ => println!("  Give a single channel."),
+        }
+    };
+    let database_url = ask("Database", DEFAULT_DATABASE)?;
+
+    let answers = Answers {
+        token,
+        lookup_channels,
+        ops_channel,
+        database_url,
+    };
+    write_private(&path, &render(&answers, yaml))
+        .map_err(|why| format!("cannot write {}: {}", path, why))?;
+    println!("✅ Wrote {}", path);
+
+    // The same checks as at startup, with the file just written
+    let config = Config::load()
+        .map_err(|errors| format!("{} has {} problem(s), see above", path, errors.len()))?;
+
+    match register(&http, commands).await {
+        Ok(count) => println!("✅ Registered {} slash commands", count),
+        Err(err) => println!("⚠️ {}, they are registered again at startup", err),
+    }
+
+    upstream::init(config.upstream_headers.clone(), config.upstream_max_body);
+    config::check_sources(&config.sources).await;
+
+    println!("Done. Start the bot without `init` to go online.");
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serenity::async_trait;
+use serenity::interactions_endpoint::Verifier;
+use serenity::model::application::Interaction;
+
+use crate::context::Context;
+
+// Handles interactions, whether from the gateway or the HTTP endpoint, so
+// both run the same commands
+#[async_trait]
+pub trait Dispatch: Send + Sync + 'static {
+    async fn dispatch(&self, ctx: Context, interaction: Interaction);
+}
+
+// How far a request's timestamp may be from the clock, either way, so a
+// signed request that was captured can't be replayed later
+const MAX_SKEW: u64 = 5 * 60;
+
+// The key from the Developer Portal, 64 hex digits, or None if it isn't one
+pub fn verifier(public_key: &str) -> Option<Verifier> {
+    if public_key.len() != 64 {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (idx, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(public_key.get(idx * 2..idx * 2 + 2)?, 16).ok()?;
+    }
+    Verifier::try_new(bytes).ok()
+}
+
+// Whether the signature is Discord's for this body and timestamp, and the
+// timestamp is recent
+pub fn verify(verifier: &Verifier, signature: &str, timestamp: &str, body: &[u8]) -> bool {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    fresh(timestamp, now) && verifier.verify(signature, timestamp, body).is_ok()
+}
+
+// `timestamp` is in seconds since the Unix epoch
+fn fresh(timestamp: &str, now: u64) -> bool {
+    timestamp.parse::<u64>().is_ok_and(|sent| sent.abs_diff(now) <= MAX_SKEW)
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    // A key pair like the Developer Portal's, and a request signed with it
+    fn signed(timestamp: &str, body: &[u8]) -> (Verifier, String) {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let verifier = verifier(&hex(key.verifying_key().as_bytes())).unwrap();
+        let message = [timestamp.as_bytes(), body].concat();
+        (verifier, hex(&key.sign(&message).to_bytes()))
+    }
+
+    fn now() -> String {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        now.as_secs().to_string()
+    }
+
+    #[test]
+    fn reads_the_public_key() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        assert!(verifier(&hex(key.verifying_key().as_bytes())).is_some());
+        assert!(verifier("abcd").is_none());
+        assert!(verifier(&"zz".repeat(32)).is_none());
+    }
+
+    #[test]
+    fn accepts_signed_requests() {
+        let timestamp = now();
+        let (verifier, signature) = signed(&timestamp, b"{}");
+        assert!(verify(&verifier, &signature, &timestamp, b"{}"));
+    }
+
+    #[test]
+    fn rejects_changed_or_unsigned_requests() {
+        let timestamp = now();
+        let (verifier, signature) = signed(&timestamp, b"{}");
+        assert!(!verify(&verifier, &signature, &timestamp, b"{\"type\":1}"));
+        assert!(!verify(&verifier, "", &timestamp, b"{}"));
+        assert!(!verify(&verifier, &"00".repeat(64), &timestamp, b"{}"));
+    }
+
+    #[test]
+    fn rejects_old_timestamps() {
+        let timestamp = (now().parse::<u64>().unwrap() - MAX_SKEW - 1).to_string();
+        let (verifier, signature) = signed(&timestamp, b"{}");
+        assert!(!verify(&verifier, &signature, &timestamp, b"{}"));
+    }
+
+    #[test]
+    fn timestamps_are_fresh_for_a_few_minutes() {
+        assert!(fresh("1000", 1000));
+        assert!(fresh("1000", 1000 + MAX_SKEW));
+        assert!(fresh("1000", 1000 - MAX_SKEW));
+        assert!(!fresh("1000", 1001 + MAX_SKEW));
+        assert!(!fresh("1000", 999 - MAX_SKEW));
+        assert!(!fresh("", 1000));
+        assert!(!fresh("soon", 1000));
+    }
+}
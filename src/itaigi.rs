@@ -0,0 +1,95 @@
+use serenity::builder::{CreateActionRow, CreateButton};
+use serenity::model::application::ButtonStyle;
+
+use crate::upstream;
+
+const ITAIGI_API: &str = "https://itaigi.tw";
+
+// Custom ID prefix of the vote buttons attached to results
+pub const VOTE_PREFIX: &str = "itaigi_vote";
+
+// An iTaigi translation that can be voted on from the results
+#[derive(Clone)]
+pub struct VoteTarget {
+    pub entry_id: String,
+    pub label: String,
+}
+
+// Contributions and votes are sent from the account of the configured
+// ITAIGI_TOKEN; without it the bot stays read-only
+pub async fn contribute(
+    token: &str,
+    foreign_word: &str,
+    taigi_text: &str,
+    pronunciation: &str,
+    contributor: &str,
+) -> Result<(), String> {
+    let source = serde_json::json!({ "名": contributor }).to_string();
+
+    let response = upstream::client()
+        .post(format!("{}/平臺項目/加外語新詞文本", ITAIGI_API))
+        .header("Authorization", format!("Token {}", token))
+        .form(&[
+            ("外語資料", foreign_word),
+            ("文本資料", taigi_text),
+            ("音標資料", pronunciation),
+            ("來源", source.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|_| "Error sending contribution to iTaigi".to_string())?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("iTaigi rejected the contribution ({})", response.status()))
+    }
+}
+
+pub async fn vote(token: &str, entry_id: &str, good: bool) -> Result<(), String> {
+    let decision = if good { "按呢講好" } else { "按呢無好" };
+
+    let response = upstream::client()
+        .post(format!("{}/平臺項目/投票", ITAIGI_API))
+        .header("Authorization", format!("Token {}", token))
+        .form(&[("平臺項目編號", entry_id), ("decision", decision)])
+        .send()
+        .await
+        .map_err(|_| "Error sending vote to iTaigi".to_string())?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("iTaigi rejected the vote ({})", response.status()))
+    }
+}
+
+// One 👍/👎 row per iTaigi result, custom ID `itaigi_vote:<good|bad>:<entry id>`
+pub fn vote_buttons(targets: &[VoteTarget]) -> Vec<CreateActionRow> {
+    targets
+        .iter()
+        .take(3) // Discord allows at most 5 rows, two are kept for rating and the shortcuts
+        .map(|target| {
+            let label: String = target.label.chars().take(70).collect();
+            CreateActionRow::Buttons(vec![
+                CreateButton::new(format!("{}:good:{}", VOTE_PREFIX, target.entry_id))
+                    .style(ButtonStyle::Success)
+                    .label(format!("👍 {}", label)),
+                CreateButton::new(format!("{}:bad:{}", VOTE_PREFIX, target.entry_id))
+                    .style(ButtonStyle::Danger)
+                    .label(format!("👎 {}", label)),
+            ])
+        })
+        .collect()
+}
+
+// Parse a vote button custom ID into (entry id, is good vote)
+pub fn parse_vote(custom_id: &str) -> Option<(&str, bool)> {
+    let rest = custom_id.strip_prefix(VOTE_PREFIX)?.strip_prefix(':')?;
+    let (decision, entry_id) = rest.split_once(':')?;
+    match decision {
+        "good" => Some((entry_id, true)),
+        "bad" => Some((entry_id, false)),
+        _ => None,
+    }
+}
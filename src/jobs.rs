@@ -0,0 +1,371 @@
+use std::sync::Arc;
+
+use serenity::builder::{CreateAllowedMentions, CreateAttachment, CreateMessage, EditMessage};
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, MessageId, UserId};
+use sqlx::SqlitePool;
+use tokio::sync::{Mutex, mpsc};
+use tracing::{error, info};
+
+use crate::answer;
+use crate::breakdown;
+use crate::config::{Config, SharedConfig};
+use crate::db::{self, JobRow, UserPrefs};
+use crate::lookup::LookupPool;
+use crate::source::Source;
+
+// Jobs run at once, the rest wait their turn
+const WORKERS: usize = 2;
+// Saved words looked up between two progress updates
+const EXPORT_CHUNK: usize = 10;
+// Servers announced to between two progress updates
+const ANNOUNCE_CHUNK: usize = 10;
+
+// What a job does, stored by code
+#[derive(Clone, Copy, PartialEq)]
+pub enum JobKind {
+    // The user's saved words as a file Anki can import
+    AnkiExport,
+    // Announcements from /admin announce not sent yet
+    Announcements,
+}
+
+impl JobKind {
+    fn code(self) -> &'static str {
+        match self {
+            JobKind::AnkiExport => "anki",
+            JobKind::Announcements => "announce",
+        }
+    }
+
+    fn parse(code: &str) -> Option<JobKind> {
+        match code {
+            "anki" => Some(JobKind::AnkiExport),
+            "announce" => Some(JobKind::Announcements),
+            _ => None,
+        }
+    }
+}
+
+// Hands jobs to the workers. Jobs are stored before they are queued, so
+// ones cut off by a restart run again.
+#[derive(Clone)]
+pub struct JobQueue {
+    db: SqlitePool,
+    sender: mpsc::UnboundedSender<i64>,
+}
+
+pub type JobReceiver = mpsc::UnboundedReceiver<i64>;
+
+pub fn queue(db: SqlitePool) -> (JobQueue, JobReceiver) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    (JobQueue { db, sender }, receiver)
+}
+
+impl JobQueue {
+    pub async fn submit(&self, kind: JobKind, user_id: UserId) -> Result<(), String> {
+        let id = db::add_job(&self.db, kind.code(), &user_id.to_string())
+            .await
+            .map_err(|why| {
+                error!("Error storing job: {why:?}");
+                "Could not queue the job".to_string()
+            })?;
+        self.sender
+            .send(id)
+            .map_err(|_| "The job queue is not running".to_string())
+    }
+}
+
+// The message a job reports progress in: a DM to whoever asked, posted when
+// the job first starts and edited from then on
+struct Progress<'a> {
+    http: &'a Http,
+    channel_id: ChannelId,
+    message_id: MessageId,
+}
+
+impl<'a> Progress<'a> {
+    async fn open(http: &'a Http, db: &SqlitePool, job: &JobRow) -> Result<Progress<'a>, String> {
+        let posted = job
+            .channel_id
+            .as_deref()
+            .zip(job.message_id.as_deref())
+            .and_then(|(channel_id, message_id)| {
+                Some((channel_id.parse::<u64>().ok()?, message_id.parse::<u64>().ok()?))
+            });
+        if let Some((channel_id, message_id)) = posted {
+            return Ok(Progress {
+                http,
+                channel_id: ChannelId::new(channel_id),
+                message_id: MessageId::new(message_id),
+            });
+        }
+
+        let user_id = job
+            .user_id
+            .parse::<u64>()
+            .map(UserId::new)
+            .map_err(|_| format!("Invalid user ID {}", job.user_id))?;
+        let channel = user_id
+            .create_dm_channel(http)
+            .await
+            .map_err(|why| format!("Cannot DM {}: {why:?}", user_id))?;
+        let message = channel
+            .id
+            .send_message(http, CreateMessage::new().content("⏳ Starting…"))
+            .await
+            .map_err(|why| format!("Cannot DM {}: {why:?}", user_id))?;
+        if let Err(why) =
+            db::set_job_message(db, job.id, &channel.id.to_string(), &message.id.to_string()).await
+        {
+            error!("Error storing job message: {why:?}");
+        }
+        Ok(Progress {
+            http,
+            channel_id: channel.id,
+            message_id: message.id,
+        })
+    }
+
+    async fn update(&self, edit: EditMessage) {
+        if let Err(why) = self
+            .channel_id
+            .edit_message(self.http, self.message_id, edit)
+            .await
+        {
+            error!("Error updating job progress: {why:?}");
+        }
+    }
+}
+
+// Tabs and line breaks would split the fields of an Anki note
+fn field(text: &str) -> String {
+    text.replace(['\t', '\n'], " ")
+}
+
+async fn anki_export(
+    db: &SqlitePool,
+    pool: &LookupPool,
+    config: &Arc<Config>,
+    job: &JobRow,
+    progress: &Progress<'_>,
+) -> Result<(), String> {
+    let words = db::saved_words(db, &job.user_id)
+        .await
+        .map_err(|why| format!("Error loading saved words: {why:?}"))?;
+    if words.is_empty() {
+        let edit = EditMessage::new().content("💾 You have no saved words to export yet.");
+        progress.update(edit).await;
+        return Ok(());
+    }
+    let prefs = match job.user_id.parse::<u64>() {
+        Ok(id) => answer::load_prefs(db, UserId::new(id), None).await,
+        Err(_) => UserPrefs::default(),
+    };
+
+    // Header lines tell Anki how to read the file
+    let mut lines = vec![
+        "#separator:tab".to_string(),
+        "#html:false".to_string(),
+        "#columns:華語\t台語\tLô-jī\t釋義".to_string(),
+    ];
+    let mut done = 0;
+    for chunk in words.chunks(EXPORT_CHUNK) {
+        let lookups = pool.lookup_all(chunk, None, config).await;
+        for (word, lookup) in chunk.iter().zip(&lookups) {
+            let headword = lookup.results.iter().find_map(|result| breakdown::headword(result));
+            let gloss = lookup
+                .results
+                .iter()
+                .zip(&lookup.sources)
+                .filter(|(_, source)| **source == Source::Moedict)
+                .find_map(|(result, _)| breakdown::definition(result));
+            let lomaji = headword
+                .and_then(|(_, lomaji)| lomaji)
+                .map(|lomaji| prefs.romanize(lomaji))
+                .unwrap_or_default();
+            let fields = [
+                word.as_str(),
+                headword.map_or("", |(hanji, _)| hanji),
+                &lomaji,
+                gloss.unwrap_or_default(),
+            ];
+            lines.push(fields.map(field).join("\t"));
+        }
+        done += chunk.len();
+        let edit = EditMessage::new().content(format!(
+            "⏳ Exporting your saved words… {}/{}",
+            done,
+            words.len()
+        ));
+        progress.update(edit).await;
+    }
+
+    let file = CreateAttachment::bytes(lines.join("\n").into_bytes(), "taigi-saved-words.txt");
+    let edit = EditMessage::new()
+        .content(format!(
+            "✅ {} saved words exported. Import the file in Anki with File → Import.",
+            words.len()
+        ))
+        .new_attachment(file);
+    progress.update(edit).await;
+    Ok(())
+}
+
+// Send every pending announcement to its server's lookup channel. Deliveries
+// are claimed one by one, so a job resumed after a restart, or one running
+// next to it, doesn't announce anything twice.
+async fn announce(http: &Http, db: &SqlitePool, progress: &Progress<'_>) -> Result<(), String> {
+    let pending = db::pending_deliveries(db)
+        .await
+        .map_err(|why| format!("Error loading announcements: {why:?}"))?;
+    let (mut sent, mut failed) = (0, 0);
+    for (idx, delivery) in pending.iter().enumerate() {
+        let claimed = db::claim_delivery(db, delivery)
+            .await
+            .map_err(|why| format!("Error claiming announcement: {why:?}"))?;
+        if !claimed {
+            continue;
+        }
+
+        // Announcements never ping anyone
+        let message = CreateMessage::new()
+            .content(format!(
+                "📣 {}\n-# Server managers can turn announcements off with `/config`.",
+                delivery.content
+            ))
+            .allowed_mentions(CreateAllowedMentions::new());
+        let channel_id = delivery.channel_id.parse::<u64>().map(ChannelId::new);
+        let result = match channel_id {
+            Ok(channel_id) => channel_id.send_message(http, message).await.map_err(|why| {
+                format!("{why:?}")
+            }),
+            Err(_) => Err(format!("invalid channel {}", delivery.channel_id)),
+        };
+        let status = match result {
+            Ok(_) => {
+                sent += 1;
+                "sent"
+            }
+            Err(err) => {
+                error!("Error announcing in {}: {}", delivery.guild_id, err);
+                failed += 1;
+                "failed"
+            }
+        };
+        if let Err(why) = db::finish_delivery(db, delivery, status).await {
+            error!("Error updating announcement delivery: {why:?}");
+        }
+
+        if (idx + 1) % ANNOUNCE_CHUNK == 0 {
+            let edit = EditMessage::new().content(format!(
+                "⏳ Announcing… {}/{} servers",
+                idx + 1,
+                pending.len()
+            ));
+            progress.update(edit).await;
+        }
+    }
+
+    let mut summary = format!("📣 Announced in {} servers.", sent);
+    if failed > 0 {
+        summary.push_str(&format!(" {} couldn't be reached, see the logs.", failed));
+    }
+    progress.update(EditMessage::new().content(summary)).await;
+    Ok(())
+}
+
+async fn run(http: &Http, db: &SqlitePool, pool: &LookupPool, config: &SharedConfig, id: i64) {
+    let job = match db::get_job(db, id).await {
+        Ok(Some(job)) => job,
+        Ok(None) => return,
+        Err(why) => {
+            error!("Error loading job {}: {why:?}", id);
+            return;
+        }
+    };
+    if let Err(why) = db::set_job_status(db, id, "running").await {
+        error!("Error updating job {}: {why:?}", id);
+    }
+
+    let result = match Progress::open(http, db, &job).await {
+        Ok(progress) => {
+            let result = match JobKind::parse(&job.kind) {
+                Some(JobKind::AnkiExport) => {
+                    anki_export(db, pool, &config.get(), &job, &progress).await
+                }
+                Some(JobKind::Announcements) => announce(http, db, &progress).await,
+                None => Err(format!("Unknown job kind {}", job.kind)),
+            };
+            if result.is_err() {
+                let edit = EditMessage::new().content("❌ Sorry, this job failed.");
+                progress.update(edit).await;
+            }
+            result
+        }
+        Err(err) => Err(err),
+    };
+
+    let status = match result {
+        Ok(()) => "done",
+        Err(err) => {
+            error!("Job {} failed: {}", id, err);
+            "failed"
+        }
+    };
+    if let Err(why) = db::set_job_status(db, id, status).await {
+        error!("Error updating job {}: {why:?}", id);
+    }
+}
+
+// Start the workers, first queueing the jobs a restart cut off
+pub async fn spawn(
+    queue: &JobQueue,
+    receiver: JobReceiver,
+    http: Arc<Http>,
+    pool: LookupPool,
+    config: Arc<SharedConfig>,
+    resume: bool,
+) {
+    // A standby would run the jobs the leader is still working on, and send
+    // the announcements it is sending
+    let unfinished = if resume {
+        match db::release_deliveries(&queue.db).await {
+            Ok(0) => {}
+            Ok(released) => info!("Sending {} interrupted announcements again", released),
+            Err(why) => error!("Error releasing announcement deliveries: {why:?}"),
+        }
+        db::unfinished_jobs(&queue.db).await
+    } else {
+        Ok(Vec::new())
+    };
+    match unfinished {
+        Ok(ids) => {
+            if !ids.is_empty() {
+                info!("Resuming {} unfinished jobs", ids.len());
+            }
+            for id in ids {
+                let _ = queue.sender.send(id);
+            }
+        }
+        Err(why) => error!("Error loading unfinished jobs: {why:?}"),
+    }
+
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..WORKERS {
+        let receiver = receiver.clone();
+        let http = http.clone();
+        let db = queue.db.clone();
+        let pool = pool.clone();
+        let config = config.clone();
+        tokio::spawn(async move {
+            loop {
+                // Only one idle worker waits on the channel at a time
+                let Some(id) = receiver.lock().await.recv().await else {
+                    break;
+                };
+                run(&http, &db, &pool, &config, id).await;
+            }
+        });
+    }
+}
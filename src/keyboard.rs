@@ -0,0 +1,48 @@
+use std::sync::OnceLock;
+
+// The cheat sheet's content, shared by the image and the text version
+const SHEET: &str = include_str!("../data/keyboard.txt");
+
+pub struct Sheet {
+    pub title: &'static str,
+    // Each section's name and lines, in order
+    pub sections: Vec<(&'static str, Vec<&'static str>)>,
+}
+
+pub fn sheet() -> &'static Sheet {
+    static PARSED: OnceLock<Sheet> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        let mut lines = SHEET
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty());
+        let title = lines.next().unwrap_or_default();
+        let mut sections: Vec<(&str, Vec<&str>)> = Vec::new();
+        for line in lines {
+            match line.strip_prefix('[').and_then(|name| name.strip_suffix(']')) {
+                Some(name) => sections.push((name, Vec::new())),
+                None => match sections.last_mut() {
+                    Some((_, section)) => section.push(line),
+                    None => sections.push(("", vec![line])),
+                },
+            }
+        }
+        Sheet { title, sections }
+    })
+}
+
+// The sheet as plain text, for bots without share cards and for copying
+// the marks out of
+pub fn text() -> String {
+    let sheet = sheet();
+    let mut out = format!("{}\n{}\n", sheet.title, "=".repeat(sheet.title.chars().count()));
+    for (name, lines) in &sheet.sections {
+        out.push('\n');
+        if !name.is_empty() {
+            out.push_str(&format!("{}\n", name));
+        }
+        for line in lines {
+            out.push_str(&format!("{}\n", line));
+        }
+    }
+    out
+}
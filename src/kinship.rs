@@ -0,0 +1,177 @@
+use std::sync::OnceLock;
+
+use crate::romanization;
+
+// Taigi kinship terms as a family tree seen from 我, from
+// `data/kinship.tsv`. Taigi names relatives by side and by age where
+// Mandarin and English often don't: 阿伯 and 阿叔 are the father's elder and
+// younger brothers, 阿舅 the mother's brother.
+
+const BUNDLED: &str = include_str!("../data/kinship.tsv");
+
+pub struct Term {
+    // The reading without tone marks, e.g. `a-kong`
+    pub code: String,
+    pub hanji: String,
+    pub lomaji: String,
+    pub english: String,
+    pub mandarin: Vec<String>,
+    pub parents: Vec<String>,
+    // Set on the side of a couple that married in
+    pub spouse: Option<String>,
+}
+
+pub fn terms() -> &'static [Term] {
+    static TERMS: OnceLock<Vec<Term>> = OnceLock::new();
+    TERMS.get_or_init(|| {
+        let list = |field: Option<&str>, separator: char| -> Vec<String> {
+            field
+                .unwrap_or_default()
+                .split(separator)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        BUNDLED
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                Some(Term {
+                    code: fields.next()?.to_string(),
+                    hanji: fields.next()?.to_string(),
+                    lomaji: fields.next()?.to_string(),
+                    english: fields.next().unwrap_or_default().to_string(),
+                    mandarin: list(fields.next(), '/'),
+                    parents: list(fields.next(), ','),
+                    spouse: fields.next().filter(|spouse| !spouse.is_empty()).map(str::to_string),
+                })
+            })
+            .collect()
+    })
+}
+
+fn get(code: &str) -> Option<&'static Term> {
+    terms().iter().find(|term| term.code == code)
+}
+
+// A term asked for by its reading (tone marks optional), Hanji or Mandarin
+pub fn find(wanted: &str) -> Option<&'static Term> {
+    let wanted = wanted.trim();
+    if wanted.is_empty() {
+        return None;
+    }
+    let toneless = romanization::toneless(wanted).to_lowercase().replace(' ', "-");
+    terms().iter().find(|term| {
+        term.code == toneless
+            || term.hanji == wanted
+            || term.mandarin.iter().any(|mandarin| mandarin == wanted)
+    })
+}
+
+// Whoever a term is married to, either side of the couple
+fn spouses(term: &Term) -> Vec<&'static Term> {
+    terms()
+        .iter()
+        .filter(|other| {
+            term.spouse.as_deref() == Some(other.code.as_str())
+                || other.spouse.as_deref() == Some(term.code.as_str())
+        })
+        .collect()
+}
+
+// The children of a term or of its spouses
+fn children(term: &Term) -> Vec<&'static Term> {
+    let mut couple = vec![term.code.as_str()];
+    couple.extend(spouses(term).iter().map(|spouse| spouse.code.as_str()));
+    terms()
+        .iter()
+        .filter(|child| child.parents.iter().any(|parent| couple.contains(&parent.as_str())))
+        .collect()
+}
+
+// `阿伯 a-peh ＝ 阿姆 a-ḿ`
+fn couple(term: &Term) -> String {
+    let mut names = vec![format!("{} {}", term.hanji, term.lomaji)];
+    let married: Vec<String> = spouses(term)
+        .iter()
+        .map(|spouse| format!("{} {}", spouse.hanji, spouse.lomaji))
+        .collect();
+    if !married.is_empty() {
+        names.push(married.join(" / "));
+    }
+    names.join(" ＝ ")
+}
+
+// The family around a term: its parents, its siblings with their spouses
+// and its own children, the asked-for term marked ◀. A term that married in
+// is shown beside its spouse. Every term drawn is explained under the tree.
+pub fn diagram(term: &Term) -> String {
+    let anchor = term.spouse.as_deref().and_then(get).unwrap_or(term);
+    let shown_couple = |member: &Term| {
+        let line = couple(member);
+        let marked = member.code == term.code
+            || spouses(member).iter().any(|spouse| spouse.code == term.code);
+        if marked { format!("{} ◀", line) } else { line }
+    };
+
+    // The anchor's children, drawn under it after `pad`
+    let below = |pad: &str| -> Vec<(String, &'static Term)> {
+        let kids = children(anchor);
+        kids.iter()
+            .enumerate()
+            .map(|(idx, kid)| {
+                let branch = if idx + 1 == kids.len() { "└─ " } else { "├─ " };
+                (format!("{}{}{}", pad, branch, shown_couple(kid)), *kid)
+            })
+            .collect()
+    };
+
+    let mut drawn: Vec<(String, &Term)> = Vec::new();
+    match anchor.parents.first().and_then(|code| get(code)) {
+        Some(parent) => {
+            drawn.push((shown_couple(parent), parent));
+            let siblings = children(parent);
+            for (idx, sibling) in siblings.iter().enumerate() {
+                let last = idx + 1 == siblings.len();
+                let branch = if last { "└─ " } else { "├─ " };
+                drawn.push((format!("{}{}", branch, shown_couple(sibling)), *sibling));
+                if sibling.code == anchor.code {
+                    drawn.extend(below(if last { "   " } else { "│  " }));
+                }
+            }
+        }
+        None => {
+            drawn.push((shown_couple(anchor), anchor));
+            drawn.extend(below(""));
+        }
+    }
+    let lines: Vec<&str> = drawn.iter().map(|(line, _)| line.as_str()).collect();
+
+    let mut explained = vec![explain(term, true)];
+    for (_, member) in &drawn {
+        for person in std::iter::once(*member).chain(spouses(member)) {
+            if person.code != term.code {
+                explained.push(explain(person, false));
+            }
+        }
+    }
+    format!("```\n{}\n```\n{}", lines.join("\n"), explained.join("\n"))
+}
+
+// `**阿公** a-kong: grandfather, father's father (Mandarin 爺爺/祖父)`
+fn explain(term: &Term, asked: bool) -> String {
+    let hanji = if asked { format!("**{}**", term.hanji) } else { term.hanji.clone() };
+    let mandarin = if term.mandarin.iter().all(|mandarin| *mandarin == term.hanji) {
+        String::new()
+    } else {
+        format!(" (Mandarin {})", term.mandarin.join("/"))
+    };
+    format!("{} {}: {}{}", hanji, term.lomaji, term.english, mandarin)
+}
+
+// Every term by its Hanji, for queries that name none
+pub fn overview() -> String {
+    let names: Vec<&str> = terms().iter().map(|term| term.hanji.as_str()).collect();
+    format!("👪 Kinship terms, e.g. `/kinship a-kong` or `/kinship 阿公`:\n{}", names.join(" · "))
+}
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use tracing::{Instrument, field, info_span};
+
+use crate::alerts;
+use crate::opslog::{self, Kind};
+use crate::source::Source;
+
+// Averages are taken over this many of the latest requests
+const WINDOW: usize = 20;
+
+// Response times and failures of one source since startup
+#[derive(Clone)]
+pub struct SourceStats {
+    recent: VecDeque<Duration>,
+    pub requests: u64,
+    pub failures: u64,
+    pub total: Duration,
+    // Error of the latest request, if it failed
+    pub last_error: Option<String>,
+}
+
+impl SourceStats {
+    const EMPTY: SourceStats = SourceStats {
+        recent: VecDeque::new(),
+        requests: 0,
+        failures: 0,
+        total: Duration::ZERO,
+        last_error: None,
+    };
+
+    // Average over the latest requests
+    pub fn average(&self) -> Option<Duration> {
+        let count = self.recent.len() as u32;
+        (count > 0).then(|| self.recent.iter().sum::<Duration>() / count)
+    }
+}
+
+// Indexed like Source::ALL
+static STATS: Mutex<[SourceStats; Source::ALL.len()]> =
+    Mutex::new([SourceStats::EMPTY; Source::ALL.len()]);
+
+fn index(source: Source) -> usize {
+    Source::ALL
+        .iter()
+        .position(|known| *known == source)
+        .unwrap_or_default()
+}
+
+fn record(source: Source, elapsed: Duration, error: Option<&String>) {
+    let mut stats = STATS.lock().unwrap_or_else(PoisonError::into_inner);
+    let stats = &mut stats[index(source)];
+    if stats.recent.len() == WINDOW {
+        stats.recent.pop_front();
+    }
+    stats.recent.push_back(elapsed);
+    stats.requests += 1;
+    stats.total += elapsed;
+    if error.is_some() {
+        stats.failures += 1;
+    }
+    stats.last_error = error.cloned();
+    alerts::record(source, error.is_some());
+}
+
+// Run one source search, recording how long it took
+pub async fn timed<T>(
+    source: Source,
+    search: impl Future<Output = Result<T, String>>,
+) -> (Result<T, String>, Option<Duration>) {
+    let started = Instant::now();
+    // One span per request to the source, to tell which one held up a reply
+    let span = info_span!("source", source = source.name(), error = field::Empty);
+    let result = search.instrument(span.clone()).await;
+    let elapsed = started.elapsed();
+    if let Err(err) = &result {
+        span.record("error", err.as_str());
+    }
+    record(source, elapsed, result.as_ref().err());
+    if let Err(err) = &result {
+        opslog::report(Kind::SourceFailure, format!("{}: {}", source.name(), err));
+    }
+    (result, Some(elapsed))
+}
+
+// Current stats of every source, in Source::ALL order
+pub fn snapshot() -> Vec<(Source, SourceStats)> {
+    let stats = STATS.lock().unwrap_or_else(PoisonError::into_inner);
+    Source::ALL.into_iter().zip(stats.iter().cloned()).collect()
+}
+
+// Name, help text and value of a per-source counter
+type Metric = (&'static str, &'static str, fn(&SourceStats) -> String);
+
+// The stats in the Prometheus text format, for /metrics
+pub fn prometheus() -> String {
+    let metrics: [Metric; 3] = [
+        (
+            "taigi_source_request_duration_seconds_total",
+            "Time spent searching each source",
+            |stats| format!("{:.3}", stats.total.as_secs_f64()),
+        ),
+        (
+            "taigi_source_requests_total",
+            "Searches made per source",
+            |stats| stats.requests.to_string(),
+        ),
+        (
+            "taigi_source_failures_total",
+            "Searches that failed per source",
+            |stats| stats.failures.to_string(),
+        ),
+    ];
+
+    let stats = snapshot();
+    let mut text = String::new();
+    for (name, help, value) in metrics {
+        text.push_str(&format!("# HELP {} {}\n# TYPE {} counter\n", name, help, name));
+        for (source, stats) in &stats {
+            text.push_str(&format!("{}{{source=\"{}\"}} {}\n", name, source.name(), value(stats)));
+        }
+    }
+    text
+}
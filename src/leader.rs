@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tracing::{error, info};
+
+use crate::db;
+
+// Whether this instance is the one answering. With several instances on
+// the same token every one of them receives every event, so all but the
+// leader stay on standby: connected, but silent until the leader's lease
+// runs out.
+pub struct Leader {
+    leading: AtomicBool,
+    // Set when taking over from another instance, whose last minutes may
+    // have gone unanswered
+    caught_up: AtomicBool,
+}
+
+impl Leader {
+    // Without an election this instance always leads
+    pub fn new(election: bool) -> Arc<Leader> {
+        Arc::new(Leader {
+            leading: AtomicBool::new(!election),
+            caught_up: AtomicBool::new(true),
+        })
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.leading.load(Ordering::Relaxed)
+    }
+
+    // True once after taking over, to answer the backlog left behind
+    pub fn take_catch_up(&self) -> bool {
+        self.is_leader() && !self.caught_up.swap(true, Ordering::Relaxed)
+    }
+}
+
+async fn claim(leader: &Leader, db: &SqlitePool, instance: &str, lease: Duration, first: bool) {
+    let leading = match db::claim_leadership(db, instance, lease.as_secs()).await {
+        Ok(leading) => leading,
+        Err(why) => {
+            error!("Error renewing the leader lease: {why:?}");
+            false
+        }
+    };
+    let was_leading = leader.leading.swap(leading, Ordering::Relaxed);
+    if leading && !was_leading {
+        info!("{} is now the leader, answering messages", instance);
+        // The ready event answers the backlog at startup
+        if !first {
+            leader.caught_up.store(false, Ordering::Relaxed);
+        }
+    } else if !leading && (was_leading || first) {
+        info!("{} is on standby, another instance is answering", instance);
+    }
+}
+
+// Claim the lease once before returning, so startup work knows whether it's
+// this instance's to do, then renew it three times per lease period so one
+// slow renewal doesn't hand leadership over. A database error steps down:
+// the other instance can't reach the lease either, but answering twice is
+// worse than a pause.
+pub async fn spawn(leader: Arc<Leader>, db: SqlitePool, instance: String, lease: Duration) {
+    claim(&leader, &db, &instance, lease, true).await;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(lease / 3);
+        // The first tick completes at once
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            claim(&leader, &db, &instance, lease, false).await;
+        }
+    });
+}
@@ -0,0 +1,84 @@
+// Everything but the Discord client setup, which is in main.rs. Kept as a
+// library so the benchmarks can reach the parsers.
+pub mod alerts;
+pub mod align;
+pub mod answer;
+pub mod apikey;
+pub mod audio;
+pub mod backup;
+pub mod blocklist;
+pub mod breakdown;
+pub mod browse;
+pub mod cache;
+#[cfg(feature = "cards")]
+pub mod card;
+pub mod circuit;
+pub mod commands;
+pub mod config;
+pub mod context;
+pub mod corpus;
+pub mod correction;
+pub mod db;
+pub mod digest;
+pub mod direction;
+pub mod emoji;
+pub mod etymology;
+pub mod export;
+pub mod feed;
+pub mod festival;
+pub mod filter;
+pub mod forum;
+pub mod frequency;
+#[cfg(feature = "http")]
+pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod i18n;
+pub mod ime;
+pub mod init;
+pub mod interactions;
+pub mod itaigi;
+pub mod jobs;
+pub mod keyboard;
+pub mod kinship;
+pub mod latency;
+pub mod leader;
+pub mod links;
+#[cfg(feature = "voice")]
+pub mod listen;
+pub mod logging;
+pub mod lookup;
+pub mod memory;
+pub mod names;
+pub mod onboarding;
+pub mod opslog;
+pub mod panics;
+pub mod pinyin;
+pub mod places;
+pub mod plugin;
+pub mod pos;
+pub mod passive;
+pub mod pattern;
+pub mod phrasebook;
+pub mod practice;
+pub mod preflight;
+pub mod query;
+pub mod rating;
+pub mod reading;
+pub mod related;
+pub mod report;
+pub mod respond;
+pub mod rhyme;
+pub mod romanization;
+pub mod script;
+pub mod shortcuts;
+pub mod source;
+pub mod spelling;
+pub mod text;
+pub mod toneless;
+pub mod tutorial;
+pub mod upstream;
+pub mod variant;
+pub mod widget;
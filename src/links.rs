@@ -0,0 +1,63 @@
+use serde::Deserialize;
+
+// How links in results are shown. Discord renders a large preview for every
+// link in a message, so by default they are wrapped in `<...>` to suppress it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStyle {
+    // No previews at all
+    #[default]
+    Compact,
+    // A preview for the first link only
+    Rich,
+}
+
+impl LinkStyle {
+    pub fn code(self) -> &'static str {
+        match self {
+            LinkStyle::Compact => "compact",
+            LinkStyle::Rich => "rich",
+        }
+    }
+}
+
+// Wrap the URL of every `[name](url)` link in angle brackets, keeping the
+// first `keep` links as they are
+fn suppress(text: &str, mut keep: usize) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("](") {
+        let url_start = start + 2;
+        let Some(end) = rest[url_start..].find(')').map(|end| url_start + end) else {
+            break;
+        };
+        output.push_str(&rest[..url_start]);
+        let url = &rest[url_start..end];
+        if keep > 0 || url.starts_with('<') {
+            keep = keep.saturating_sub(1);
+            output.push_str(url);
+        } else {
+            output.push_str(&format!("<{}>", url));
+        }
+        output.push(')');
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+// Apply the link style to the result lines of one reply
+pub fn render(results: &[String], style: LinkStyle) -> Vec<String> {
+    let mut keep = match style {
+        LinkStyle::Compact => 0,
+        LinkStyle::Rich => 1,
+    };
+    results
+        .iter()
+        .map(|result| {
+            let line = suppress(result, keep);
+            keep = keep.saturating_sub(result.matches("](").count());
+            line
+        })
+        .collect()
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, GuildId};
+use songbird::Songbird;
+use tokio::task::JoinHandle;
+use tracing::error;
+
+use crate::audio;
+use crate::practice::{self, Word};
+use crate::romanization::{self, Romanization};
+
+// Silence between two words, and how often a playing word is checked on
+const PAUSE: Duration = Duration::from_secs(2);
+const POLL: Duration = Duration::from_millis(250);
+// Words in a row that may fail to be fetched before the session ends, as
+// Moedict is likely down then
+const MAX_FAILURES: usize = 5;
+
+// Listening rooms being played, by server. One per server, since the bot
+// can only be in one voice channel of each.
+static SESSIONS: Mutex<Option<HashMap<GuildId, JoinHandle<()>>>> = Mutex::new(None);
+
+// The drill words in a new random order
+fn shuffled() -> Vec<&'static Word> {
+    let mut words: Vec<&Word> = practice::words().iter().collect();
+    for i in (1..words.len()).rev() {
+        words.swap(i, practice::random(i + 1));
+    }
+    words
+}
+
+enum Played {
+    Done,
+    // No recording could be fetched
+    Failed,
+    // The bot left the voice channel or was removed from it
+    Gone,
+}
+
+// Fetch a word's recording, then caption it in the text channel and play it
+// to the end
+async fn play(
+    http: &Http,
+    manager: &Songbird,
+    guild_id: GuildId,
+    text_channel: ChannelId,
+    word: &Word,
+    romanization: Romanization,
+) -> Played {
+    if manager.get(guild_id).is_none() {
+        return Played::Gone;
+    }
+    let recording = match audio::moedict(word.hanji).await {
+        Ok(Some(recording)) => recording,
+        Ok(None) => return Played::Failed,
+        Err(err) => {
+            error!("Error fetching audio for {}: {}", word.hanji, err);
+            return Played::Failed;
+        }
+    };
+    let Some(call) = manager.get(guild_id) else {
+        return Played::Gone;
+    };
+    let caption = format!("🔊 {} {}", word.hanji, word.tailo);
+    let caption = romanization::render(&caption, romanization);
+    if let Err(why) = text_channel.say(http, caption).await {
+        error!("Error captioning the listening room: {why:?}");
+    }
+    let track = call.lock().await.play_input(recording.bytes.into());
+    while let Ok(state) = track.get_info().await {
+        if state.playing.is_done() {
+            break;
+        }
+        tokio::time::sleep(POLL).await;
+    }
+    Played::Done
+}
+
+// Join the voice channel and play the word list over and over, captioning
+// each word in the text channel. Replaces a session already running there.
+// Ends once the bot is out of the voice channel, or leaves it when recordings
+// keep failing to be fetched.
+pub async fn start(
+    http: Arc<Http>,
+    manager: Arc<Songbird>,
+    guild_id: GuildId,
+    voice_channel: ChannelId,
+    text_channel: ChannelId,
+    romanization: Romanization,
+) -> Result<(), String> {
+    if stop_session(guild_id)
+        && let Some(call) = manager.get(guild_id)
+    {
+        call.lock().await.stop();
+    }
+    manager
+        .join(guild_id, voice_channel)
+        .await
+        .map_err(|why| format!("Cannot join <#{}>: {}", voice_channel, why))?;
+
+    let task = tokio::spawn(async move {
+        let mut failures = 0;
+        loop {
+            for word in shuffled() {
+                match play(&http, &manager, guild_id, text_channel, word, romanization).await {
+                    Played::Done => failures = 0,
+                    Played::Failed if failures + 1 < MAX_FAILURES => {
+                        failures += 1;
+                        continue;
+                    }
+                    Played::Failed => {
+                        forget_session(guild_id);
+                        stop(&manager, guild_id).await;
+                        let notice = "🔇 The recordings can't be fetched, so the listening \
+                                      session is over.";
+                        if let Err(why) = text_channel.say(&http, notice).await {
+                            error!("Error ending the listening room: {why:?}");
+                        }
+                        return;
+                    }
+                    Played::Gone => {
+                        forget_session(guild_id);
+                        return;
+                    }
+                }
+                tokio::time::sleep(PAUSE).await;
+            }
+        }
+    });
+    SESSIONS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(guild_id, task);
+    Ok(())
+}
+
+// Drop the running session of a server from within it, unless another has
+// replaced it already
+fn forget_session(guild_id: GuildId) {
+    let mut sessions = SESSIONS.lock().unwrap();
+    let Some(sessions) = sessions.as_mut() else {
+        return;
+    };
+    let current = tokio::task::try_id();
+    if sessions.get(&guild_id).is_some_and(|task| Some(task.id()) == current) {
+        sessions.remove(&guild_id);
+    }
+}
+
+// Stop playing without leaving the channel. Returns false if nothing was.
+fn stop_session(guild_id: GuildId) -> bool {
+    let task = SESSIONS
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|sessions| sessions.remove(&guild_id));
+    match task {
+        Some(task) => {
+            task.abort();
+            true
+        }
+        None => false,
+    }
+}
+
+// End the session of a server and leave its voice channel. Returns false if
+// there was none.
+pub async fn stop(manager: &Songbird, guild_id: GuildId) -> bool {
+    let stopped = stop_session(guild_id);
+    if manager.get(guild_id).is_some()
+        && let Err(why) = manager.remove(guild_id).await
+    {
+        error!("Error leaving the voice channel: {why:?}");
+    }
+    stopped
+}
@@ -0,0 +1,149 @@
+use std::env;
+use std::fmt;
+use std::future::Future;
+use std::time::Instant;
+
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Instrument, Span, Subscriber, info};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::{JsonFields, Writer};
+use tracing_subscriber::fmt::time::{FormatTime, SystemTime};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+
+// Log lines go to stdout as text, or with LOG_FORMAT=json as one JSON object
+// per line for Loki, CloudWatch and other collectors
+pub fn init() {
+    let json = env::var("LOG_FORMAT").is_ok_and(|format| format.eq_ignore_ascii_case("json"));
+    let output = if json {
+        tracing_subscriber::fmt::layer()
+            .fmt_fields(JsonFields::new())
+            .event_format(JsonLines)
+            .boxed()
+    } else {
+        tracing_subscriber::fmt::layer().with_target(false).boxed()
+    };
+    let registry = tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(output);
+    #[cfg(feature = "otel")]
+    let registry = registry.with(otel_layer());
+    registry.init();
+}
+
+// Spans of this crate (events, queries and the source requests under them)
+// sent over OTLP/HTTP to OTEL_EXPORTER_OTLP_ENDPOINT, e.g. a Jaeger or Tempo
+// collector at http://localhost:4318. Off if it isn't set.
+#[cfg(feature = "otel")]
+fn otel_layer<S>() -> Option<impl tracing_subscriber::Layer<S>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_sdk::Resource;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing_subscriber::filter::Targets;
+
+    env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()?;
+    let exporter = match opentelemetry_otlp::SpanExporter::builder().with_http().build() {
+        Ok(exporter) => exporter,
+        Err(why) => {
+            // Logging isn't set up yet
+            println!("Cannot set up the OTLP exporter: {why}");
+            return None;
+        }
+    };
+    let name = env!("CARGO_PKG_NAME");
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(name).build())
+        .build();
+    let spans = Targets::new().with_target(env!("CARGO_CRATE_NAME"), LevelFilter::INFO);
+    Some(
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer(name))
+            .with_filter(spans),
+    )
+}
+
+// Handle one event inside `span`, then log how long it took
+pub async fn timed(span: Span, work: impl Future<Output = ()>) {
+    let started = Instant::now();
+    async {
+        work.await;
+        info!(latency_ms = started.elapsed().as_millis() as u64, "handled");
+    }
+    .instrument(span)
+    .await;
+}
+
+// {"ts", "level", "span", then the fields of the spans the event happened
+// in (such as "guild") and its own ("message", "latency_ms"…)}
+struct JsonLines;
+
+impl<S, N> FormatEvent<S, N> for JsonLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut ts = String::new();
+        SystemTime.format_time(&mut Writer::new(&mut ts))?;
+        let mut line = Map::new();
+        line.insert("ts".to_string(), ts.into());
+        line.insert("level".to_string(), event.metadata().level().as_str().into());
+
+        // Outermost first, so inner spans win on a field both set
+        if let Some(scope) = ctx.event_scope() {
+            let mut names = Vec::new();
+            for span in scope.from_root() {
+                names.push(span.name());
+                let extensions = span.extensions();
+                let fields = extensions
+                    .get::<FormattedFields<N>>()
+                    .and_then(|fields| serde_json::from_str::<Map<String, Value>>(fields).ok());
+                line.extend(fields.unwrap_or_default());
+            }
+            line.insert("span".to_string(), names.join(":").into());
+        }
+
+        event.record(&mut Fields(&mut line));
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+// Collects an event's fields into the JSON line
+struct Fields<'a>(&'a mut Map<String, Value>);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+}
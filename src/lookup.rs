@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+use tracing::error;
+
+use crate::cache::ResultCache;
+use crate::config::{Config, SourceLimits, SourceToggles};
+use crate::direction::Direction;
+use crate::itaigi::VoteTarget;
+use crate::latency;
+use crate::opslog::{self, Kind};
+use crate::places;
+use crate::plugin;
+use crate::source::{self, Source};
+use crate::variant::Accent;
+
+// Everything found for one keyword across all sources
+#[derive(Clone, Default)]
+pub struct Lookup {
+    pub results: Vec<String>,
+    // Source of each entry in `results`
+    pub sources: Vec<Source>,
+    pub vote_targets: Vec<VoteTarget>,
+    // Part of speech tags of the results that have some, by the line as its
+    // source gave it; only Moedict's have any
+    pub pos: HashMap<String, Vec<String>>,
+    pub errors: Vec<String>,
+    // How long each source took, empty for cached lookups
+    pub timings: Vec<(Source, Duration)>,
+}
+
+// What one source returned and how long it took, if it was searched
+pub type Searched<T> = (Result<T, String>, Option<Duration>);
+
+impl Lookup {
+    // A lookup where some source failed shouldn't be reused
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    // Merge in one source's results, after those already collected
+    pub fn collect(&mut self, source: Source, (result, elapsed): Searched<Vec<String>>) {
+        if let Some(elapsed) = elapsed {
+            self.timings.push((source, elapsed));
+        }
+        match result {
+            Ok(mut results) => {
+                self.sources.extend(std::iter::repeat_n(source, results.len()));
+                self.results.append(&mut results);
+            }
+            Err(err) => self.errors.push(format!("{}: {}", source.name(), err)),
+        }
+    }
+}
+
+pub fn cache_key(keyword: &str, variant: Option<Accent>) -> String {
+    match variant {
+        Some(accent) => format!("{}|{}", keyword, accent.label()),
+        None => keyword.to_string(),
+    }
+}
+
+// Told each enabled source's results as soon as they arrive, for streaming
+pub type Report<'a> = &'a (dyn Fn(Source, &Searched<Vec<String>>) + Sync);
+
+// Search all enabled sources concurrently
+pub async fn search(
+    keyword: &str,
+    variant: Option<Accent>,
+    sources: &SourceToggles,
+    limits: &SourceLimits,
+) -> Lookup {
+    search_reporting(keyword, variant, sources, limits, &|_, _| {}).await
+}
+
+pub async fn search_reporting(
+    keyword: &str,
+    variant: Option<Accent>,
+    sources: &SourceToggles,
+    limits: &SourceLimits,
+    report: Report<'_>,
+) -> Lookup {
+    let direction = Direction::detect(keyword);
+    let reported = |source, searched: Searched<Vec<String>>| {
+        report(source, &searched);
+        searched
+    };
+    let (
+        taigitv_result,
+        sutian_result,
+        itaigi_result,
+        moedict_result,
+        plugin_result,
+        place_result,
+    ) = tokio::join!(
+        async {
+            if sources.taigitv {
+                let search = source::search_taigitv(keyword, limits.taigitv);
+                reported(Source::TaigiTv, latency::timed(Source::TaigiTv, search).await)
+            } else {
+                (Ok(Vec::new()), None)
+            }
+        },
+        async {
+            if sources.sutian {
+                let search = source::search_sutian(keyword, variant, direction, limits.sutian);
+                reported(Source::Sutian, latency::timed(Source::Sutian, search).await)
+            } else {
+                (Ok(Vec::new()), None)
+            }
+        },
+        async {
+            if sources.itaigi {
+                let search = source::search_itaigi(keyword, limits.itaigi);
+                let searched = latency::timed(Source::ITaigi, search).await;
+                let results = searched.0.as_ref().map(|(results, _)| results.clone());
+                report(Source::ITaigi, &(results.map_err(Clone::clone), searched.1));
+                searched
+            } else {
+                (Ok((Vec::new(), Vec::new())), None)
+            }
+        },
+        async {
+            if sources.moedict {
+                let search = source::search_moedict(keyword, limits.moedict);
+                let searched = latency::timed(Source::Moedict, search).await;
+                let results = searched.0.as_ref().map(|(results, _)| results.clone());
+                report(Source::Moedict, &(results.map_err(Clone::clone), searched.1));
+                searched
+            } else {
+                (Ok(Default::default()), None)
+            }
+        },
+        async {
+            if sources.enabled(Source::Plugins) {
+                let search = plugin::search(keyword, limits.plugins);
+                reported(Source::Plugins, latency::timed(Source::Plugins, search).await)
+            } else {
+                (Ok(Vec::new()), None)
+            }
+        },
+        async {
+            if sources.places {
+                let search = places::search(keyword);
+                reported(Source::Places, latency::timed(Source::Places, search).await)
+            } else {
+                (Ok(Vec::new()), None)
+            }
+        }
+    );
+
+    let mut lookup = Lookup::default();
+    // A known place's Taigi name goes above what the dictionaries make of it
+    lookup.collect(Source::Places, place_result);
+    lookup.collect(Source::TaigiTv, taigitv_result);
+    lookup.collect(Source::Sutian, sutian_result);
+    let itaigi_result = (
+        itaigi_result.0.map(|(results, targets)| {
+            lookup.vote_targets = targets;
+            results
+        }),
+        itaigi_result.1,
+    );
+    lookup.collect(Source::ITaigi, itaigi_result);
+    let moedict_result = (
+        moedict_result.0.map(|(results, pos)| {
+            lookup.pos = pos;
+            results
+        }),
+        moedict_result.1,
+    );
+    lookup.collect(Source::Moedict, moedict_result);
+    lookup.collect(Source::Plugins, plugin_result);
+    lookup
+}
+
+// Reuse a cached lookup when there is one, caching fresh complete lookups
+pub async fn cached(
+    cache: &ResultCache,
+    keyword: &str,
+    variant: Option<Accent>,
+    sources: &SourceToggles,
+    limits: &SourceLimits,
+) -> Lookup {
+    cached_reporting(cache, keyword, variant, sources, limits, &|_, _| {}).await
+}
+
+// A cached lookup is reported all at once, per source
+pub async fn cached_reporting(
+    cache: &ResultCache,
+    keyword: &str,
+    variant: Option<Accent>,
+    sources: &SourceToggles,
+    limits: &SourceLimits,
+    report: Report<'_>,
+) -> Lookup {
+    let key = cache_key(keyword, variant);
+    if let Some(lookup) = cache.get(&key) {
+        for source in Source::ALL.into_iter().filter(|source| sources.enabled(*source)) {
+            let results = lookup
+                .results
+                .iter()
+                .zip(&lookup.sources)
+                .filter(|(_, found)| **found == source)
+                .map(|(result, _)| result.clone())
+                .collect();
+            report(source, &(Ok(results), None));
+        }
+        return lookup;
+    }
+
+    let lookup = search_reporting(keyword, variant, sources, limits, report).await;
+    if lookup.is_complete() {
+        let stored = Lookup {
+            timings: Vec::new(),
+            ..lookup.clone()
+        };
+        cache.insert(key, stored);
+    }
+    lookup
+}
+
+// The operator's script may hide, reorder or annotate a lookup's results. Run
+// on every lookup the pool hands out, cached or not, so everything that shows
+// results shows the same; the cache keeps what the sources returned.
+fn scripted(keyword: &str, mut lookup: Lookup, config: &Config) -> Lookup {
+    let Some(hook) = &config.result_script else {
+        return lookup;
+    };
+    let results = lookup.results.drain(..).zip(lookup.sources.drain(..)).collect();
+    (lookup.results, lookup.sources) = hook.process(keyword, results).into_iter().unzip();
+    lookup
+}
+
+// Looks up many keywords at once (phrase breakdowns, /compare, batch jobs)
+// with at most `concurrency` lookups in flight across all of them, so a
+// burst of long queries can't spawn an unbounded number of tasks
+#[derive(Clone)]
+pub struct LookupPool {
+    cache: Arc<ResultCache>,
+    permits: Arc<Semaphore>,
+    // Slow mode: queries answered at once, the rest wait in line. None
+    // when unlimited.
+    queries: Option<Arc<Semaphore>>,
+    waiting: Arc<AtomicUsize>,
+}
+
+// A query's turn to be looked up, until dropped
+pub struct Slot {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+// A query waiting in line for a slot
+pub struct Waiting {
+    queries: Arc<Semaphore>,
+    waiting: Arc<AtomicUsize>,
+    position: usize,
+}
+
+impl Waiting {
+    // 1 for the next query to be answered
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // Tokio hands out permits in the order they were asked for, so the
+    // line moves first come, first served
+    pub async fn wait(self) -> Slot {
+        let permit = self.queries.clone().acquire_owned().await.ok();
+        Slot { _permit: permit }
+    }
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        self.waiting.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl LookupPool {
+    pub fn new(cache: Arc<ResultCache>, concurrency: usize, queries: usize) -> Self {
+        LookupPool {
+            cache,
+            permits: Arc::new(Semaphore::new(concurrency.max(1))),
+            queries: (queries > 0).then(|| Arc::new(Semaphore::new(queries))),
+            waiting: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    // A slot for answering a query right away, or its place in line when
+    // slow mode is full
+    pub fn enter(&self) -> Result<Slot, Waiting> {
+        let Some(queries) = &self.queries else {
+            return Ok(Slot { _permit: None });
+        };
+        match queries.clone().try_acquire_owned() {
+            Ok(permit) => Ok(Slot {
+                _permit: Some(permit),
+            }),
+            Err(_) => Err(Waiting {
+                queries: queries.clone(),
+                waiting: self.waiting.clone(),
+                position: self.waiting.fetch_add(1, Ordering::Relaxed) + 1,
+            }),
+        }
+    }
+
+    // A single lookup, not counted against the pool
+    pub async fn lookup(&self, keyword: &str, variant: Option<Accent>, config: &Config) -> Lookup {
+        let lookup = cached(&self.cache, keyword, variant, &config.sources, &config.limits).await;
+        scripted(keyword, lookup, config)
+    }
+
+    // A lookup with the expanded limits of "more results". Not cached, as the
+    // cache holds lookups made with the normal limits.
+    pub async fn lookup_more(
+        &self,
+        keyword: &str,
+        variant: Option<Accent>,
+        config: &Config,
+    ) -> Lookup {
+        let limits = config.limits.expanded();
+        let lookup = search(keyword, variant, &config.sources, &limits).await;
+        scripted(keyword, lookup, config)
+    }
+
+    // A single lookup, telling `report` what each source found as it arrives
+    pub async fn lookup_reporting(
+        &self,
+        keyword: &str,
+        variant: Option<Accent>,
+        config: &Config,
+        report: Report<'_>,
+    ) -> Lookup {
+        // What each source found goes through the script on its own
+        let report = |source: Source, (found, elapsed): &Searched<Vec<String>>| {
+            let found = found.as_ref().map(|results| {
+                let results = results.iter().map(|result| (result.clone(), source)).collect();
+                let scripted = match &config.result_script {
+                    Some(hook) => hook.process(keyword, results),
+                    None => results,
+                };
+                scripted.into_iter().map(|(result, _)| result).collect()
+            });
+            report(source, &(found.map_err(String::clone), *elapsed));
+        };
+        let (sources, limits) = (&config.sources, &config.limits);
+        let lookup =
+            cached_reporting(&self.cache, keyword, variant, sources, limits, &report).await;
+        scripted(keyword, lookup, config)
+    }
+
+    // Another request to a source, outside a lookup: made with a slot of the
+    // pool so it's limited like lookups, and its lines cached under `key`
+    pub async fn fetch(
+        &self,
+        key: String,
+        source: Source,
+        request: impl Future<Output = Result<Vec<String>, String>>,
+    ) -> Result<Vec<String>, String> {
+        if let Some(cached) = self.cache.get(&key) {
+            return Ok(cached.results);
+        }
+        let permit = self.permits.acquire().await.ok();
+        let results = request.await?;
+        drop(permit);
+        let stored = Lookup {
+            results: results.clone(),
+            sources: vec![source; results.len()],
+            ..Lookup::default()
+        };
+        self.cache.insert(key, stored);
+        Ok(results)
+    }
+
+    // A Moedict entry as JSON, from its Taigi (`t`) or Mandarin (`a`)
+    // dictionary, through `fetch`. None when it has no entry for `word`.
+    pub async fn moedict_json(
+        &self,
+        dictionary: &str,
+        word: &str,
+    ) -> Result<Option<Value>, String> {
+        let key = format!("moedict:{}|{}", dictionary, word);
+        let request = async {
+            let entry = source::moedict_json(dictionary, word).await?;
+            Ok(entry.iter().map(Value::to_string).collect())
+        };
+        let lines = self.fetch(key, Source::Moedict, request).await?;
+        Ok(lines.first().and_then(|json| serde_json::from_str(json).ok()))
+    }
+
+    // Lookups for every keyword, in the same order
+    pub async fn lookup_all(
+        &self,
+        keywords: &[String],
+        variant: Option<Accent>,
+        config: &Arc<Config>,
+    ) -> Vec<Lookup> {
+        self.pooled(keywords, variant, config, false).await
+    }
+
+    // Like `lookup_all`, but asking the sources again and caching what they
+    // answer now in place of what the cache held, e.g. to keep popular words
+    // fresh
+    pub async fn refresh_all(&self, keywords: &[String], config: &Arc<Config>) -> Vec<Lookup> {
+        self.pooled(keywords, None, config, true).await
+    }
+
+    async fn pooled(
+        &self,
+        keywords: &[String],
+        variant: Option<Accent>,
+        config: &Arc<Config>,
+        fresh: bool,
+    ) -> Vec<Lookup> {
+        let mut tasks = JoinSet::new();
+        for (idx, keyword) in keywords.iter().enumerate() {
+            // Wait for a free slot before spawning the next task
+            let Ok(permit) = self.permits.clone().acquire_owned().await else {
+                break;
+            };
+            let cache = self.cache.clone();
+            let config = config.clone();
+            let keyword = keyword.clone();
+            tasks.spawn(async move {
+                let (sources, limits) = (&config.sources, &config.limits);
+                let lookup = if fresh {
+                    let lookup = search(&keyword, variant, sources, limits).await;
+                    if lookup.is_complete() {
+                        let stored = Lookup {
+                            timings: Vec::new(),
+                            ..lookup.clone()
+                        };
+                        cache.insert(cache_key(&keyword, variant), stored);
+                    }
+                    lookup
+                } else {
+                    cached(&cache, &keyword, variant, sources, limits).await
+                };
+                drop(permit);
+                (idx, scripted(&keyword, lookup, &config))
+            });
+        }
+
+        let mut lookups = vec![Lookup::default(); keywords.len()];
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((idx, lookup)) => lookups[idx] = lookup,
+                Err(why) => {
+                    error!("Error in pooled lookup: {why:?}");
+                    if why.is_panic() {
+                        opslog::report(Kind::Panic, format!("Pooled lookup: {}", why));
+                    }
+                }
+            }
+        }
+        lookups
+    }
+}
@@ -1,498 +1,931 @@
 use std::env;
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::time::Duration;
 
 use dotenv::dotenv;
 use serenity::async_trait;
-use serenity::model::channel::Message;
+use serenity::builder::{
+    CreateAllowedMentions, CreateCommand, CreateMessage, EditMessage,
+};
+use serenity::model::application::{Command, Interaction};
+use serenity::builder::GetMessages;
+use serenity::http::Http;
+use serenity::model::Timestamp;
+use serenity::model::channel::{Message, MessageReference};
+use serenity::model::id::MessageId;
 use serenity::model::gateway::Ready;
+use serenity::model::guild::Guild;
 use serenity::prelude::*;
 
-use reqwest;
-use scraper::{Html, Selector};
-use serde_json::Value;
-use urlencoding;
-
-struct Handler;
-
-async fn search_taigitv(keyword: &str) -> Result<Vec<String>, String> {
-    let search_url = format!(
-        "https://www.taigitv.org.tw/taigi-words?keyword={}",
-        urlencoding::encode(keyword)
-    );
-
-    let response_text = match reqwest::get(&search_url).await {
-        Ok(response) => match response.text().await {
-            Ok(text) => text,
-            Err(_) => return Err("Error reading response from TaigiTV".to_string()),
-        },
-        Err(_) => return Err("Error fetching from TaigiTV".to_string()),
-    };
+use sqlx::SqlitePool;
+use tracing::{error, info, info_span};
+
+use taigi_translate_bot::{
+    alerts, answer, audio, backup, blocklist, browse, cache, circuit, commands, config, context,
+    corpus, correction, db, digest, forum, frequency, init, interactions, itaigi, jobs,
+    leader, logging, lookup, onboarding, opslog, panics, passive, practice, preflight, query,
+    rating, report, respond, shortcuts, spelling, tutorial, upstream,
+};
+
+use cache::ResultCache;
+use config::{Config, SharedConfig};
+use lookup::LookupPool;
+use answer::{Answer, Asker};
+use blocklist::Blocklist;
+use circuit::Circuit;
+use interactions::Dispatch;
+use jobs::JobQueue;
+use leader::Leader;
+use preflight::Purpose;
+
+// Missed messages answered per channel after a reconnect, newest first
+const BACKLOG_LIMIT: u8 = 50;
+// Older missed queries are probably no longer waited on
+const BACKLOG_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+// Answered messages remembered to skip duplicate deliveries, well above the
+// backlog fetched after a reconnect
+const PROCESSED_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Answers are replies to the query, so it's clear which question each one
+// belongs to in a busy channel. They still go out if the query was deleted
+// in the meantime.
+fn reply_to(msg: &Message, ping: bool) -> CreateMessage {
+    let mut reference = MessageReference::from(msg);
+    reference.fail_if_not_exists = Some(false);
+    CreateMessage::new()
+        .reference_message(reference)
+        .allowed_mentions(CreateAllowedMentions::new().replied_user(ping))
+}
 
-    // Parse HTML document
-    let document = Html::parse_document(&response_text);
-
-    // Fixed selectors for TaigiTV
-    let link_selector = Selector::parse(".btngaa .h3 a")
-        .map_err(|_| "Could not parse TaigiTV selector".to_string())?;
-
-    // Extract results
-    let results: Vec<String> = document
-        .select(&link_selector)
-        .filter_map(|element| {
-            let text = element.text().collect::<String>().trim().to_string();
-            let url = element.value().attr("href").map(|href| {
-                if href.starts_with("http") {
-                    href.to_string()
-                } else if href.starts_with("/") {
-                    format!("https://www.taigitv.org.tw{}", href)
-                } else {
-                    format!("https://www.taigitv.org.tw/{}", href)
-                }
-            });
+// Server an interaction came from, for the log lines about it
+fn interaction_guild(interaction: &Interaction) -> Option<u64> {
+    let guild_id = match interaction {
+        Interaction::Command(command) => command.guild_id,
+        Interaction::Component(component) => component.guild_id,
+        Interaction::Modal(modal) => modal.guild_id,
+        _ => None,
+    };
+    guild_id.map(|id| id.get())
+}
 
-            url.map(|u| format!("📺 {} - [台語新詞辭庫]({})", text, u))
-        })
-        .take(3) // Limit to 3 results from TaigiTV
-        .collect();
+// Slash commands registered with Discord, the same over the gateway and HTTP
+fn slash_commands() -> Vec<CreateCommand> {
+    #[allow(unused_mut)]
+    let mut commands = vec![
+        commands::taigi::register(),
+        commands::define::register(),
+        commands::compare::register(),
+        commands::annotate::register(),
+        commands::entry::register(),
+        commands::recall::register(),
+        commands::chars::register(),
+        commands::examples::register(),
+        commands::ime::register(),
+        commands::search::register(),
+        commands::rhyme::register(),
+        commands::frequent::register(),
+        commands::browse::register(),
+        commands::name::register(),
+        commands::festival::register(),
+        commands::kinship::register(),
+        commands::phrasebook::register(),
+        commands::legend::register(),
+        commands::keyboard::register(),
+        commands::minimal_pairs::register(),
+        commands::tonedrill::register(),
+        commands::tutorial::register(),
+        commands::vocab::register(),
+        commands::contribute::register(),
+        commands::prefs::register(),
+        commands::saved::register(),
+        commands::status::register(),
+        commands::stats::register(),
+        commands::admin::register(),
+        commands::config::register(),
+        commands::filter::register(),
+        commands::debug_sources::register(),
+        commands::explain::register(),
+    ];
+    #[cfg(feature = "voice")]
+    commands.push(commands::listen::register());
+    commands
+}
 
-    Ok(results)
+struct Handler {
+    config: Arc<SharedConfig>,
+    db: SqlitePool,
+    cache: Arc<ResultCache>,
+    lookups: LookupPool,
+    jobs: JobQueue,
+    blocklist: Blocklist,
+    leader: Arc<Leader>,
+    // Channels where posting results keeps failing
+    circuit: Circuit,
 }
 
-async fn search_sutian(keyword: &str) -> Result<Vec<String>, String> {
-    let search_url = format!(
-        "https://sutian.moe.edu.tw/zh-hant/tshiau/?lui=hua_su&tsha={}",
-        urlencoding::encode(keyword)
-    );
-
-    let response_text = match reqwest::get(&search_url).await {
-        Ok(response) => match response.text().await {
-            Ok(text) => text,
-            Err(_) => return Err("Error reading response from Sutian".to_string()),
-        },
-        Err(_) => return Err("Error fetching from Sutian".to_string()),
-    };
+impl Handler {
+    async fn answer_message(&self, ctx: &Context, msg: &Message) {
+        let config = self.config.get();
 
-    // Parse HTML document
-    let document = Html::parse_document(&response_text);
-
-    // Selectors for Sutian - extracting from both mobile and desktop tables
-    let mobile_link_selector = Selector::parse("table.d-md-none tbody tr:nth-child(2) td a")
-        .map_err(|_| "Could not parse Sutian mobile selector".to_string())?;
-    let desktop_link_selector =
-        Selector::parse("table.d-none.d-md-table tbody tr td:nth-child(2) a")
-            .map_err(|_| "Could not parse Sutian desktop selector".to_string())?;
-
-    let mobile_pronunciation_selector = Selector::parse("table.d-md-none tbody tr:nth-child(3) td")
-        .map_err(|_| "Could not parse Sutian mobile pronunciation selector".to_string())?;
-    let desktop_pronunciation_selector =
-        Selector::parse("table.d-none.d-md-table tbody tr td:nth-child(3)")
-            .map_err(|_| "Could not parse Sutian desktop pronunciation selector".to_string())?;
-
-    let mut results = Vec::new();
-
-    // Try mobile table first
-    if let (Some(link_element), Some(pronunciation_element)) = (
-        document.select(&mobile_link_selector).next(),
-        document.select(&mobile_pronunciation_selector).next(),
-    ) {
-        let word = link_element.text().collect::<String>().trim().to_string();
-        let href = link_element.value().attr("href").unwrap_or("");
-        let pronunciation = pronunciation_element
-            .text()
-            .collect::<String>()
-            .trim()
-            .lines()
-            .next()
-            .unwrap_or("")
-            .trim()
-            .to_string();
-
-        let full_url = if href.starts_with("http") {
-            href.to_string()
-        } else if href.starts_with("/") {
-            format!("https://sutian.moe.edu.tw{}", href)
-        } else {
-            format!("https://sutian.moe.edu.tw/{}", href)
-        };
+        // Ignore messages from bots, blocked users and guilds
+        if msg.author.bot || self.blocklist.is_blocked(msg.author.id, msg.guild_id) {
+            return;
+        }
+        // In passive channels, only mark messages that contain Taigi words
+        if config.passive_channels.contains(&msg.channel_id) {
+            if !passive::detect(&msg.content).is_empty()
+                && let Err(why) = msg.react(&ctx.http, passive::EMOJI).await
+            {
+                error!("Error adding passive reaction: {why:?}");
+            }
+            return;
+        }
 
-        if !word.is_empty() && !pronunciation.is_empty() {
-            results.push(format!("📚 {} [{}] - [教育部臺灣台語常用詞辭典]({})", word, pronunciation, full_url));
+        // Servers can add a lookup channel of their own in the setup wizard
+        let guild = answer::load_guild(&self.db, msg.guild_id).await;
+        if !config.lookup_channels.contains(&msg.channel_id)
+            && guild.lookup_channel != Some(msg.channel_id)
+        {
+            return;
         }
-    }
-    // If no mobile results, try desktop table
-    else if let (Some(link_element), Some(pronunciation_element)) = (
-        document.select(&desktop_link_selector).next(),
-        document.select(&desktop_pronunciation_selector).next(),
-    ) {
-        let word = link_element.text().collect::<String>().trim().to_string();
-        let href = link_element.value().attr("href").unwrap_or("");
-        let pronunciation = pronunciation_element
-            .text()
-            .collect::<String>()
-            .trim()
-            .lines()
-            .next()
-            .unwrap_or("")
-            .trim()
-            .to_string();
-
-        let full_url = if href.starts_with("http") {
-            href.to_string()
-        } else if href.starts_with("/") {
-            format!("https://sutian.moe.edu.tw{}", href)
-        } else {
-            format!("https://sutian.moe.edu.tw/{}", href)
+        // Events can be redelivered after a reconnect, and the backlog fetched
+        // on ready may overlap with messages already answered
+        match db::claim_message(&self.db, &msg.id.to_string()).await {
+            Ok(true) => {}
+            Ok(false) => {
+                info!("Skipping duplicate delivery of message {}", msg.id);
+                return;
+            }
+            Err(why) => error!("Error recording message {}: {why:?}", msg.id),
+        }
+        // Remembered so queries sent while disconnected can be answered later
+        if let Err(why) =
+            db::set_last_message(&self.db, &msg.channel_id.to_string(), &msg.id.to_string()).await
+        {
+            error!("Error storing the last message: {why:?}");
+        }
+
+        let (query, options) = match query::parse(&msg.content) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                let reply = reply_to(msg, config.reply_ping).content(err);
+                if let Err(why) = msg.channel_id.send_message(&ctx.http, reply).await {
+                    error!("Error sending option error message: {why:?}");
+                }
+                return;
+            }
         };
 
-        if !word.is_empty() && !pronunciation.is_empty() {
-            results.push(format!("📚 {} [{}] - [教育部臺灣台語常用詞辭典]({})", word, pronunciation, full_url));
+        let prefs = answer::load_prefs(&self.db, msg.author.id, msg.guild_id).await;
+        let locale = prefs.locale.or(guild.locale).unwrap_or(config.locale);
+
+        if query.is_empty() {
+            let reply = reply_to(msg, config.reply_ping).content(locale.no_keyword());
+            if let Err(why) = msg.channel_id.send_message(&ctx.http, reply).await {
+                error!("Error sending empty keyword message: {why:?}");
+            }
+            return;
         }
-    }
 
-    Ok(results)
-}
+        // In slow mode, queries past the limit are told their place in line.
+        // That message becomes the answer once it's their turn.
+        let (_slot, queued) = match self.lookups.enter() {
+            Ok(slot) => (slot, None),
+            Err(waiting) => {
+                let reply =
+                    reply_to(msg, config.reply_ping).content(locale.queued(waiting.position()));
+                let queued = match msg.channel_id.send_message(&ctx.http, reply).await {
+                    Ok(queued) => Some(queued),
+                    Err(why) => {
+                        error!("Error sending queue position: {why:?}");
+                        None
+                    }
+                };
+                (waiting.wait().await, queued)
+            }
+        };
 
-async fn search_itaigi(keyword: &str) -> Result<Vec<String>, String> {
-    let search_url = format!(
-        "https://itaigi.tw/平臺項目列表/揣列表?關鍵字={}",
-        urlencoding::encode(keyword)
-    );
-
-    let response_text = match reqwest::get(&search_url).await {
-        Ok(response) => match response.text().await {
-            Ok(text) => text,
-            Err(_) => return Err("Error reading response from iTaigi".to_string()),
-        },
-        Err(_) => return Err("Error fetching from iTaigi".to_string()),
-    };
+        // Start typing indicator to show the bot is searching
+        let _ = msg.channel_id.broadcast_typing(&ctx.http).await;
 
-    // Parse JSON response
-    let json: Value = match serde_json::from_str(&response_text) {
-        Ok(json) => json,
-        Err(_) => return Err("Error parsing JSON from iTaigi".to_string()),
-    };
+        // Channel messages can't be ephemeral, so `--private` is ignored here
+        let asker = Asker {
+            user_id: msg.author.id,
+            channel_id: msg.channel_id,
+            guild_id: msg.guild_id,
+            prefs,
+            guild,
+        };
+        let answer = answer::answer(&self.db, &self.lookups, &config, &asker, &query, &options).await;
+        let components = answer.components(&config, true);
+        // Words found in a server also get a post in its archive forum, once each
+        if let Some(guild_id) = msg.guild_id
+            && let Some(forum) = forum::forum_for(&asker.guild, &config)
+            && let Answer::Found { keyword, content, .. } = &answer
+        {
+            let (keyword, content) = (keyword.clone(), content.clone());
+            forum::archive(ctx.http.clone(), self.db.clone(), forum, guild_id, keyword, content);
+        }
 
-    let mut results = Vec::new();
-
-    // Parse the 列表 array
-    if let Some(list) = json.get("列表").and_then(|v| v.as_array()) {
-        for item in list.iter().take(3) {
-            // Limit to 3 results
-            // Get 外語資料 (foreign word)
-            let foreign_word = item
-                .get("外語資料")
-                .and_then(|v| v.as_str())
-                .unwrap_or("N/A");
-
-            // Get the first 新詞文本 entry if available
-            if let Some(new_word_list) = item.get("新詞文本").and_then(|v| v.as_array()) {
-                if let Some(first_entry) = new_word_list.first() {
-                    let taigi_text = first_entry
-                        .get("文本資料")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("N/A");
-                    let pronunciation = first_entry
-                        .get("音標資料")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("N/A");
-                    let contributor = first_entry
-                        .get("貢獻者")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("匿名");
-                    let good_votes = first_entry
-                        .get("按呢講好")
-                        .and_then(|v| v.as_i64())
-                        .unwrap_or(0);
-                    let bad_votes = first_entry
-                        .get("按呢無好")
-                        .and_then(|v| v.as_i64())
-                        .unwrap_or(0);
-
-                    // Create iTaigi URL
-                    let itaigi_url = format!("https://itaigi.tw/k/{}", foreign_word);
-
-                    results.push(format!(
-                        "🏷️ {} → {} [{}] (👍{} 👎{}) by {} - [iTaigi 愛台語]({})",
-                        foreign_word,
-                        taigi_text,
-                        pronunciation,
-                        good_votes,
-                        bad_votes,
-                        contributor,
-                        itaigi_url
-                    ));
+        let content = match answer {
+            Answer::Breakdown { content, .. }
+            | Answer::Found { content, .. }
+            | Answer::Failed(content) => Some(content),
+            // Offer the suggestions as buttons, otherwise just react
+            Answer::NotFound {
+                content,
+                suggestions,
+                ..
+            } if !suggestions.is_empty() => Some(content),
+            Answer::NotFound { .. } => None,
+        };
+
+        // The lookup is cached and recorded either way, only posting is skipped
+        if !self.circuit.allows(msg.channel_id) {
+            info!("Not posting in {} while sends are failing", msg.channel_id);
+            return;
+        }
+        match (content, queued) {
+            (Some(content), Some(mut queued)) => {
+                let edit = EditMessage::new().content(content).components(components);
+                let edited = queued.edit(&ctx.http, edit).await;
+                if let Err(why) = &edited {
+                    error!("Error editing queued message: {why:?}");
+                }
+                self.circuit.record(msg.channel_id, edited);
+            }
+            (Some(content), None) => {
+                let reply = reply_to(msg, config.reply_ping)
+                    .content(content)
+                    .components(components);
+                let sent = msg.channel_id.send_message(&ctx.http, reply).await;
+                if let Err(why) = &sent {
+                    error!("Error sending message: {why:?}");
+                }
+                self.circuit.record(msg.channel_id, sent.map(|_| ()));
+            }
+            (None, queued) => {
+                if let Some(queued) = queued
+                    && let Err(why) = queued.delete(&ctx.http).await
+                {
+                    error!("Error deleting queued message: {why:?}");
+                }
+                if let Err(why) = msg.react(&ctx.http, '❌').await {
+                    error!("Error adding reaction: {why:?}");
                 }
             }
         }
     }
 
-    // If no results from 列表, check 其他建議
-    if results.is_empty() {
-        if let Some(suggestions) = json.get("其他建議").and_then(|v| v.as_array()) {
-            for suggestion in suggestions.iter().take(3) {
-                let taigi_text = suggestion
-                    .get("文本資料")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("N/A");
-                let pronunciation = suggestion
-                    .get("音標資料")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("N/A");
-
-                // Get associated foreign words if available
-                let mut foreign_words = Vec::new();
-                if let Some(foreign_list) = suggestion
-                    .get("按呢講的外語列表")
-                    .and_then(|v| v.as_array())
-                {
-                    for foreign_item in foreign_list.iter().take(2) {
-                        if let Some(foreign_word) =
-                            foreign_item.get("外語資料").and_then(|v| v.as_str())
-                        {
-                            foreign_words.push(foreign_word);
-                        }
-                    }
+    // After a fresh gateway session, answer the queries sent to lookup
+    // channels while the bot was away. Resumed sessions don't need this:
+    // Discord replays the events they missed.
+    async fn answer_backlog(&self, ctx: &Context) {
+        let config = self.config.get();
+        let mut channels = config.lookup_channels.clone();
+        match db::guild_lookup_channels(&self.db).await {
+            Ok(extra) => channels.extend(extra),
+            Err(why) => error!("Error loading server lookup channels: {why:?}"),
+        }
+        channels.sort();
+        channels.dedup();
+
+        for channel_id in channels {
+            // Channels never answered in have no backlog to catch up on
+            let last = match db::last_message(&self.db, &channel_id.to_string()).await {
+                Ok(Some(last)) => last,
+                Ok(None) => continue,
+                Err(why) => {
+                    error!("Error loading the last message: {why:?}");
+                    continue;
                 }
+            };
+            let Ok(last) = last.parse::<u64>().map(MessageId::new) else {
+                continue;
+            };
+            let request = GetMessages::new().after(last).limit(BACKLOG_LIMIT);
+            let messages = match channel_id.messages(&ctx.http, request).await {
+                Ok(messages) => messages,
+                Err(why) => {
+                    error!("Error fetching missed messages in {}: {why:?}", channel_id);
+                    continue;
+                }
+            };
+            if messages.is_empty() {
+                continue;
+            }
 
-                let foreign_display = if foreign_words.is_empty() {
-                    keyword.to_string()
-                } else {
-                    foreign_words.join(", ")
-                };
-
-                results.push(format!(
-                    "🏷️ {} → {} [{}] (建議) - [iTaigi 愛台語](https://itaigi.tw)",
-                    foreign_display, taigi_text, pronunciation
-                ));
+            // Fetched messages don't say which server they're from
+            let guild_id = match channel_id.to_channel(&ctx.http).await {
+                Ok(channel) => channel.guild().map(|channel| channel.guild_id),
+                Err(_) => None,
+            };
+            let cutoff = Timestamp::now().unix_timestamp() - BACKLOG_MAX_AGE.as_secs() as i64;
+            info!("Answering {} missed messages in {}", messages.len(), channel_id);
+            // Oldest first, the way they were sent
+            for mut msg in messages.into_iter().rev() {
+                if msg.timestamp.unix_timestamp() < cutoff {
+                    continue;
+                }
+                msg.guild_id = guild_id;
+                self.answer_message(ctx, &msg).await;
             }
         }
     }
 
-    Ok(results)
-}
+    async fn handle_interaction(&self, ctx: context::Context, interaction: Interaction) {
+        if !self.leader.is_leader() {
+            return;
+        }
+        let config = self.config.get();
 
-async fn search_moedict(keyword: &str) -> Result<Vec<String>, String> {
-    let search_url = format!(
-        "https://www.moedict.tw/t/{}.json",
-        urlencoding::encode(keyword)
-    );
-
-    let response_text = match reqwest::get(&search_url).await {
-        Ok(response) => {
-            if response.status().is_success() {
-                match response.text().await {
-                    Ok(text) => text,
-                    Err(_) => return Err("Error reading response from Moedict".to_string()),
+        match interaction {
+            Interaction::Command(command) => {
+                if self.blocklist.is_blocked(command.user.id, command.guild_id) {
+                    return;
                 }
-            } else {
-                return Ok(Vec::new()); // No results found, but not an error
+
+
+                // Lookups reply on their own since they may be slow and ephemeral
+                match command.data.name.as_str() {
+                    "taigi" => {
+                        commands::taigi::run(&ctx, &command, &config, &self.lookups, &self.db)
+                            .await;
+                        return;
+                    }
+                    commands::explain::NAME => {
+                        commands::explain::run(&ctx, &command, &config, &self.lookups, &self.db)
+                            .await;
+                        return;
+                    }
+                    "define" => {
+                        commands::define::run(&ctx, &command, &config, &self.lookups, &self.db)
+                            .await;
+                        return;
+                    }
+                    "compare" => {
+                        commands::compare::run(&ctx, &command, &config, &self.lookups, &self.db)
+                            .await;
+                        return;
+                    }
+                    "entry" => {
+                        commands::entry::run(&ctx, &command, &config, &self.db).await;
+                        return;
+                    }
+                    "examples" => {
+                        commands::examples::run(&ctx, &command, &config, &self.db).await;
+                        return;
+                    }
+                    "chars" => {
+                        commands::chars::run(&ctx, &command, &config, &self.lookups, &self.db).await;
+                        return;
+                    }
+                    "recall" => {
+                        commands::recall::run(&ctx, &command, &config, &self.db).await;
+                        return;
+                    }
+                    "ime" => {
+                        commands::ime::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "search" => {
+                        commands::search::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "rhyme" => {
+                        commands::rhyme::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "frequent" => {
+                        commands::frequent::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "browse" => {
+                        commands::browse::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "name" => {
+                        commands::name::run(&ctx, &command, &self.lookups, &self.db).await;
+                        return;
+                    }
+                    "festival" => {
+                        commands::festival::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "kinship" => {
+                        commands::kinship::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "phrasebook" => {
+                        commands::phrasebook::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "vocab" => {
+                        commands::vocab::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "tonedrill" => {
+                        commands::tonedrill::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "tutorial" => {
+                        commands::tutorial::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "minimal-pairs" => {
+                        commands::minimal_pairs::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "legend" => {
+                        commands::legend::run(&ctx, &command).await;
+                        return;
+                    }
+                    "keyboard" => {
+                        commands::keyboard::run(&ctx, &command, &config, &self.db).await;
+                        return;
+                    }
+                    #[cfg(feature = "voice")]
+                    "listen" => {
+                        commands::listen::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "config" => {
+                        commands::config::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "filter" => {
+                        commands::filter::run(&ctx, &command, &self.db).await;
+                        return;
+                    }
+                    "debug-sources" => {
+                        commands::debug_sources::run(&ctx, &command, &config, &self.db).await;
+                        return;
+                    }
+                    "annotate" => {
+                        commands::annotate::run(&ctx, &command, &config, &self.lookups, &self.db)
+                            .await;
+                        return;
+                    }
+                    _ => {}
+                }
+
+                let content = async {
+                    match command.data.name.as_str() {
+                        "contribute" => {
+                            commands::contribute::run(
+                                &command.data.options(),
+                                config.itaigi_token.as_deref(),
+                                &command.user.name,
+                            )
+                            .await
+                        }
+                        "prefs" => {
+                            commands::prefs::run(&command.data.options(), command.user.id, &self.db)
+                                .await
+                        }
+                        "status" => commands::status::run(&config),
+                        "stats" => commands::stats::run(&command, &self.db).await,
+                        "saved" => {
+                            commands::saved::run(
+                                &command.data.options(),
+                                command.user.id,
+                                &self.db,
+                                &self.jobs,
+                            )
+                            .await
+                        }
+                        "admin" => {
+                            commands::admin::run(
+                                &ctx,
+                                &command,
+                                &self.config,
+                                &self.cache,
+                                &self.db,
+                                &self.blocklist,
+                                &self.jobs,
+                            )
+                            .await
+                        }
+                        _ => "Unknown command.".to_string(),
+                    }
+                };
+                respond::command(&ctx, &self.db, &command, content).await;
             }
-        },
-        Err(_) => return Err("Error fetching from Moedict".to_string()),
-    };
+            Interaction::Component(component) => {
+                if self.blocklist.is_blocked(component.user.id, component.guild_id) {
+                    return;
+                }
 
-    // Parse JSON response
-    let json: Value = match serde_json::from_str(&response_text) {
-        Ok(json) => json,
-        Err(_) => return Err("Error parsing JSON from Moedict".to_string()),
-    };
 
-    let mut results = Vec::new();
+                if report::handle(&ctx, &self.db, config.ops_channel, &component).await {
+                    return;
+                }
+                if onboarding::handle(&ctx, &self.db, &component).await {
+                    return;
+                }
+                if correction::handle(&ctx, &self.db, &component).await {
+                    return;
+                }
+                if practice::handle(&ctx, &self.db, &component).await {
+                    return;
+                }
+                if tutorial::handle(&ctx, &self.db, &component).await {
+                    return;
+                }
+                if rating::handle(&ctx, &self.db, &component).await {
+                    return;
+                }
+                if frequency::handle(&ctx, &self.db, &component).await {
+                    return;
+                }
+                if browse::handle(&ctx, &self.db, &component).await {
+                    return;
+                }
+                if shortcuts::handle(&ctx, &self.db, &self.lookups, &config, &component).await {
+                    return;
+                }
+                if spelling::handle(&ctx, &self.db, &self.lookups, &config, &component).await {
+                    return;
+                }
 
-    // Helper function to clean up formatting markers
-    let clean_text = |text: &str| -> String {
-        text.replace("`", "").replace("~", "")
-    };
+                let Some((entry_id, good)) = itaigi::parse_vote(&component.data.custom_id) else {
+                    return;
+                };
+
+                let Some(token) = &config.itaigi_token else {
+                    return;
+                };
 
-    // Get the main term and clean it up
-    let main_term = json.get("t")
-        .and_then(|v| v.as_str())
-        .map(|s| clean_text(s))
-        .unwrap_or_else(|| keyword.to_string());
-
-    // Parse the heteronyms (h array)
-    if let Some(heteronyms) = json.get("h").and_then(|v| v.as_array()) {
-        for (idx, heteronym) in heteronyms.iter().enumerate() {
-            if idx >= 2 { break; } // Limit to 2 heteronyms to avoid spam
-
-            // Get pronunciation
-            let pronunciation = heteronym.get("T")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-
-            // Get definitions
-            if let Some(definitions) = heteronym.get("d").and_then(|v| v.as_array()) {
-                for (def_idx, definition) in definitions.iter().enumerate() {
-                    if def_idx >= 1 { break; } // Only show first definition per heteronym
-
-                    let part_of_speech = definition.get("type")
-                        .and_then(|v| v.as_str())
-                        .map(|s| clean_text(s))
-                        .unwrap_or_else(|| "".to_string());
-
-                    let definition_text = definition.get("f")
-                        .and_then(|v| v.as_str())
-                        .map(|s| clean_text(s))
-                        .unwrap_or_else(|| "無定義".to_string());
-
-                    // Create user-readable URL
-                    let moedict_url = format!("https://www.moedict.org/#'{}", 
-                        urlencoding::encode(keyword));
-
-                    // Format the result
-                    let formatted_result = if !pronunciation.is_empty() && !part_of_speech.is_empty() {
-                        format!(
-                            "📖 {} [{}] ({}) {} - [萌典]({})",
-                            main_term, 
-                            pronunciation, 
-                            part_of_speech,
-                            definition_text,
-                            moedict_url
-                        )
-                    } else if !pronunciation.is_empty() {
-                        format!(
-                            "📖 {} [{}] {} - [萌典]({})",
-                            main_term, 
-                            pronunciation, 
-                            definition_text,
-                            moedict_url
-                        )
-                    } else {
-                        format!(
-                            "📖 {} {} - [萌典]({})",
-                            main_term, 
-                            definition_text,
-                            moedict_url
-                        )
-                    };
-
-                    results.push(formatted_result);
+                // Every vote goes out from the one configured account, so each
+                // user only gets one per entry
+                let content = async {
+                    let user_id = component.user.id.to_string();
+                    match db::claim_itaigi_vote(&self.db, entry_id, &user_id, good).await {
+                        Ok(true) => {}
+                        Ok(false) => return "You already voted on this iTaigi entry.".to_string(),
+                        Err(why) => {
+                            error!("Error recording iTaigi vote: {why:?}");
+                            return "Could not record your vote.".to_string();
+                        }
+                    }
+                    match itaigi::vote(token, entry_id, good).await {
+                        Ok(()) if good => "👍 Your vote was sent to iTaigi.".to_string(),
+                        Ok(()) => "👎 Your vote was sent to iTaigi.".to_string(),
+                        Err(err) => {
+                            if let Err(why) =
+                                db::release_itaigi_vote(&self.db, entry_id, &user_id).await
+                            {
+                                error!("Error releasing iTaigi vote: {why:?}");
+                            }
+                            format!("Could not vote on iTaigi: {}", err)
+                        }
+                    }
+                };
+                let mode = respond::Mode::Ephemeral;
+                respond::component(&ctx, &self.db, &component, mode, content).await;
+            }
+            Interaction::Modal(modal) => {
+                if self.blocklist.is_blocked(modal.user.id, modal.guild_id) {
+                    return;
                 }
+                if correction::handle_modal(&ctx, &self.db, config.ops_channel, &modal).await {
+                    return;
+                }
+                commands::taigi::run_advanced(&ctx, &modal, &config, &self.lookups, &self.db).await;
             }
+            _ => {}
         }
     }
-
-    Ok(results)
 }
 
 #[async_trait]
 impl EventHandler for Handler {
     async fn message(&self, ctx: Context, msg: Message) {
-        // Ignore messages from bots and messages not in the target channel
-        if msg.author.bot || msg.channel_id.to_string() != "1372944023026794576".to_string() {
+        if !self.leader.is_leader() {
             return;
         }
+        // After taking over from another instance, first answer what was sent
+        // since its last answer. This message is among them.
+        if self.leader.take_catch_up() {
+            self.answer_backlog(&ctx).await;
+        }
+        let span = info_span!("message", guild = msg.guild_id.map(|id| id.get()));
+        logging::timed(span, panics::isolate("message", self.answer_message(&ctx, &msg))).await;
+    }
 
-        let keyword = msg.content.trim();
+    // Servers that just added the bot get the setup wizard. Servers seen at
+    // startup come through here too, with `is_new` false.
+    async fn guild_create(&self, ctx: Context, guild: Guild, is_new: Option<bool>) {
+        if is_new == Some(true)
+            && self.leader.is_leader()
+            && !self.blocklist.is_blocked(guild.owner_id, Some(guild.id))
+        {
+            onboarding::welcome(&context::Context::from(&ctx), &guild).await;
+        }
+    }
 
-        if keyword.is_empty() {
-            if let Err(why) = msg
-                .reply(&ctx.http, "Please provide a keyword to search for.")
-                .await
-            {
-                println!("Error sending empty keyword message: {why:?}");
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        self.dispatch((&ctx).into(), interaction).await;
+    }
+
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        info!("{} is connected!", ready.user.name);
+
+        if let Err(why) = Command::set_global_commands(&ctx.http, slash_commands()).await {
+            error!("Error registering slash commands: {why:?}");
+        }
+
+        // Say which channels the bot can't fully answer in, before anyone asks
+        let config = self.config.get();
+        let lookup = |channel| (channel, Purpose::Lookup);
+        let mut channels: Vec<_> = config.lookup_channels.iter().copied().map(lookup).collect();
+        channels.extend(config.passive_channels.iter().copied().map(lookup));
+        channels.extend(config.digest_channel.map(|channel| (channel, Purpose::Digest)));
+        channels.extend(config.archive_forum.map(|forum| (forum, Purpose::Archive)));
+        match db::guild_lookup_channels(&self.db).await {
+            Ok(extra) => channels.extend(extra.into_iter().map(lookup)),
+            Err(why) => error!("Error loading server lookup channels: {why:?}"),
+        }
+        match db::digest_channels(&self.db).await {
+            Ok(extra) => {
+                channels.extend(extra.into_iter().map(|(_, channel)| (channel, Purpose::Digest)))
             }
-            return;
+            Err(why) => error!("Error loading server digest channels: {why:?}"),
+        }
+        match db::archive_forums(&self.db).await {
+            Ok(extra) => channels.extend(extra.into_iter().map(|forum| (forum, Purpose::Archive))),
+            Err(why) => error!("Error loading server archive forums: {why:?}"),
         }
+        channels.sort();
+        channels.dedup();
+        preflight::check_all(&ctx.http, &channels, ready.user.id).await;
 
-        // Start typing indicator to show the bot is searching
-        let _ = msg.channel_id.broadcast_typing(&ctx.http).await;
+        // A standby answers the backlog when it takes over instead
+        if self.leader.is_leader() {
+            self.answer_backlog(&ctx).await;
+        }
+    }
+}
 
-        // Search all four sources concurrently
-        let (taigitv_result, sutian_result, itaigi_result, moedict_result) = tokio::join!(
-            search_taigitv(keyword),
-            search_sutian(keyword),
-            search_itaigi(keyword),
-            search_moedict(keyword)
-        );
+#[async_trait]
+impl Dispatch for Handler {
+    async fn dispatch(&self, ctx: context::Context, interaction: Interaction) {
+        let span = info_span!("interaction", guild = interaction_guild(&interaction));
+        logging::timed(
+            span,
+            panics::isolate("interaction", self.handle_interaction(ctx, interaction)),
+        )
+        .await;
+    }
+}
 
-        let mut all_results = Vec::new();
-        let mut error_messages = Vec::new();
+#[tokio::main]
+async fn main() {
+    dotenv().ok();
+    logging::init();
+
+    // `init` writes a configuration file interactively, then exits
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).is_some_and(|arg| arg == "init") {
+        if let Err(err) = init::run(slash_commands()).await {
+            error!("Setup stopped: {}", err);
+            process::exit(1);
+        }
+        return;
+    }
 
-        // Collect TaigiTV results
-        match taigitv_result {
-            Ok(mut results) => all_results.append(&mut results),
-            Err(err) => error_messages.push(format!("TaigiTV: {}", err)),
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(errors) => {
+            error!("Cannot start, fix the {} problem(s) above.", errors.len());
+            process::exit(1);
+        }
+    };
+    // `--restore <file>` swaps in a backup from `/admin backup` before starting
+    if let Some(idx) = args.iter().position(|arg| arg == "--restore") {
+        let Some(archive) = args.get(idx + 1) else {
+            error!("--restore needs the backup file to restore");
+            process::exit(1);
+        };
+        match backup::restore(Path::new(archive), &config.database_url) {
+            Ok(path) => info!("Restored {} from {}", path.display(), archive),
+            Err(err) => {
+                error!("Cannot restore the backup: {}", err);
+                process::exit(1);
+            }
         }
+    }
+
+    upstream::init(config.upstream_headers.clone(), config.upstream_max_body);
+    audio::init(config.audio_cache.clone(), config.audio_ffmpeg.clone());
+    config::check_sources(&config.sources).await;
 
-        // Collect Sutian results
-        match sutian_result {
-            Ok(mut results) => all_results.append(&mut results),
-            Err(err) => error_messages.push(format!("Sutian: {}", err)),
+    #[cfg(feature = "plugins")]
+    if let Some(dir) = &config.plugin_dir {
+        match taigi_translate_bot::plugin::load(Path::new(dir)) {
+            Ok(names) => info!("Loaded {} plugin(s): {}", names.len(), names.join(", ")),
+            Err(err) => error!("Error loading plugins: {}", err),
         }
+    }
 
-        // Collect iTaigi results
-        match itaigi_result {
-            Ok(mut results) => all_results.append(&mut results),
-            Err(err) => error_messages.push(format!("iTaigi: {}", err)),
+    if let Some(path) = &config.corpus_path {
+        match corpus::load(path) {
+            Ok(count) => info!("Loaded {} example sentences", count),
+            Err(err) => error!("Error loading the example corpus: {}", err),
         }
+    }
 
-        // Collect Moedict results
-        match moedict_result {
-            Ok(mut results) => all_results.append(&mut results),
-            Err(err) => error_messages.push(format!("Moedict: {}", err)),
+    let db = match db::connect(&config.database_url).await {
+        Ok(db) => db,
+        Err(why) => {
+            error!("Cannot open the database at {}: {why}", config.database_url);
+            error!("Check that DATABASE_URL points to a writable location.");
+            process::exit(1);
         }
+    };
 
-        // Handle results
-        if !all_results.is_empty() {
-            let count = all_results.len();
-            let results_text = all_results.join("\n");
-            let response_message = if count == 1 {
-                format!("Found 1 result for \"{}\":\n{}", keyword, results_text)
-            } else {
-                format!(
-                    "Found {} results for \"{}\":\n{}",
-                    count, keyword, results_text
-                )
-            };
+    let cache = Arc::new(ResultCache::new(config.cache_ttl).persistent(db.clone()));
+    match cache.restore().await {
+        Ok(count) => info!("Restored {} cached lookups", count),
+        Err(why) => error!("Error restoring the cache: {why:?}"),
+    }
+
+    // Replay the most popular past queries from the history as well
+    match cache.warm_up(&db, config.cache_warmup).await {
+        Ok(count) => info!("Warmed up the cache with {} lookups", count),
+        Err(why) => error!("Error warming up the cache: {why:?}"),
+    }
+
+    let lookups = LookupPool::new(cache.clone(), config.batch_concurrency, config.slow_mode);
+    let (jobs, job_receiver) = jobs::queue(db.clone());
+
+    let blocklist = match Blocklist::load(&db).await {
+        Ok(blocklist) => blocklist,
+        Err(why) => {
+            error!("Cannot load the blocklist: {why:?}");
+            process::exit(1);
+        }
+    };
 
-            // Add error info if some sources failed
-            let final_message = if !error_messages.is_empty() {
-                format!(
-                    "{}\n\n⚠️ Some sources had issues: {}",
-                    response_message,
-                    error_messages.join(", ")
-                )
-            } else {
-                response_message
+    // GUILDS delivers guild_create, for the setup wizard
+    let intents = GatewayIntents::GUILDS
+        | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::DIRECT_MESSAGES
+        | GatewayIntents::MESSAGE_CONTENT;
+    // Where members are in voice, for /listen to join them
+    #[cfg(feature = "voice")]
+    let intents = intents | GatewayIntents::GUILD_VOICE_STATES;
+    let token = config.discord_token.clone();
+    #[cfg(feature = "http")]
+    let http_bind = config.http_bind;
+    #[cfg(feature = "grpc")]
+    let grpc_bind = config.grpc_bind;
+    let config = Arc::new(SharedConfig::new(config));
+
+    // `kill -HUP` reloads the configuration like `/admin reload`
+    #[cfg(unix)]
+    {
+        let config = config.clone();
+        let cache = cache.clone();
+        tokio::spawn(async move {
+            use tokio::signal::unix::{SignalKind, signal};
+
+            let mut hangups = match signal(SignalKind::hangup()) {
+                Ok(hangups) => hangups,
+                Err(why) => {
+                    error!("Cannot listen for SIGHUP: {why:?}");
+                    return;
+                }
             };
+            while hangups.recv().await.is_some() {
+                info!("{}", commands::admin::reload(&config, &cache));
+            }
+        });
+    }
 
-            if let Err(why) = msg.reply(&ctx.http, &final_message).await {
-                println!("Error sending message: {why:?}");
+    // Expired lookups are only dropped lazily otherwise, and answered
+    // messages are only remembered for a day
+    {
+        let cache = cache.clone();
+        let db = db.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+            loop {
+                interval.tick().await;
+                match cache.compact().await {
+                    Ok(0) => {}
+                    Ok(count) => info!("Compacted the cache, dropped {} lookups", count),
+                    Err(why) => error!("Error compacting the cache: {why:?}"),
+                }
+                if let Err(why) = db::forget_processed(&db, PROCESSED_MAX_AGE).await {
+                    error!("Error forgetting answered messages: {why:?}");
+                }
             }
-        } else if !error_messages.is_empty() {
-            // All sources failed
-            let error_msg = format!(
-                "Could not search any sources. Errors: {}",
-                error_messages.join(", ")
-            );
-            if let Err(why) = msg.reply(&ctx.http, &error_msg).await {
-                println!("Error sending error message: {why:?}");
+        });
+    }
+
+    let startup = config.get();
+    let leader = Leader::new(startup.leader_election);
+    if startup.leader_election {
+        let instance = startup.instance_name.clone();
+        leader::spawn(leader.clone(), db.clone(), instance, startup.leader_lease).await;
+    }
+
+    let handler = Arc::new(Handler {
+        config: config.clone(),
+        db: db.clone(),
+        cache,
+        lookups: lookups.clone(),
+        jobs: jobs.clone(),
+        blocklist,
+        leader: leader.clone(),
+        circuit: Circuit::default(),
+    });
+    let (client, discord) = if startup.gateway {
+        let builder = Client::builder(&token, intents).event_handler_arc(handler.clone());
+        // Voice connections for /listen
+        #[cfg(feature = "voice")]
+        let builder = songbird::SerenityInit::register_songbird(builder);
+        let client = match builder.await {
+            Ok(client) => client,
+            Err(why) => {
+                error!("Cannot create the Discord client: {why:?}");
+                process::exit(1);
             }
-        } else {
-            // No results found
-            if let Err(why) = msg.react(&ctx.http, '❌').await {
-                println!("Error adding reaction: {why:?}");
+        };
+        // Interactions over HTTP can't reach the gateway's voice connections
+        let discord = context::Context {
+            http: client.http.clone(),
+            cache: client.cache.clone(),
+            gateway: None,
+        };
+        (Some(client), discord)
+    } else {
+        (None, without_gateway(&token).await)
+    };
+    #[cfg(feature = "http")]
+    if let Some(bind) = http_bind {
+        use taigi_translate_bot::http;
+
+        let interactions = startup
+            .public_key
+            .as_deref()
+            .and_then(interactions::verifier)
+            .map(|verifier| http::interactions(verifier, discord.clone(), handler.clone()));
+        http::spawn(bind, db.clone(), config.clone(), lookups.clone(), interactions);
+    }
+    #[cfg(feature = "grpc")]
+    if let Some(bind) = grpc_bind {
+        taigi_translate_bot::grpc::spawn(bind, lookups.clone(), config.clone());
+    }
+    jobs::spawn(
+        &jobs,
+        job_receiver,
+        discord.http.clone(),
+        lookups.clone(),
+        config.clone(),
+        leader.is_leader(),
+    )
+    .await;
+    opslog::spawn(discord.http.clone(), config.clone());
+    alerts::spawn(discord.http.clone(), config.clone());
+    digest::spawn(discord.http.clone(), config, db, lookups, leader);
+
+    match client {
+        Some(mut client) => {
+            if let Err(why) = client.start().await {
+                error!("Client error: {why:?}");
             }
         }
-    }
-
-    async fn ready(&self, _: Context, ready: Ready) {
-        println!("{} is connected!", ready.user.name);
+        // The HTTP server and the jobs run in the background
+        None => std::future::pending().await,
     }
 }
 
-#[tokio::main]
-async fn main() {
-    dotenv().ok();
-    let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
-    let intents = GatewayIntents::GUILD_MESSAGES
-        | GatewayIntents::DIRECT_MESSAGES
-        | GatewayIntents::MESSAGE_CONTENT;
-    let mut client = Client::builder(&token, intents)
-        .event_handler(Handler)
-        .await
-        .expect("Err creating client");
-    if let Err(why) = client.start().await {
-        println!("Client error: {why:?}");
+// Set up to answer interactions over HTTP alone. Commands are registered here,
+// as no ready event comes without the gateway.
+async fn without_gateway(token: &str) -> context::Context {
+    let http = Arc::new(Http::new(token));
+    match http.get_current_application_info().await {
+        Ok(info) => http.set_application_id(info.id),
+        Err(why) => {
+            error!("Cannot fetch the bot's application: {why:?}");
+            process::exit(1);
+        }
+    }
+    if let Err(why) = Command::set_global_commands(&http, slash_commands()).await {
+        error!("Error registering slash commands: {why:?}");
+    }
+    match context::Context::without_gateway(http).await {
+        Ok(ctx) => {
+            info!("Running without the gateway, only HTTP interactions are answered");
+            ctx
+        }
+        Err(err) => {
+            error!("Cannot start without the gateway: {}", err);
+            process::exit(1);
+        }
     }
-}
\ No newline at end of file
+}
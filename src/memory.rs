@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::breakdown::{self, Gloss};
+use crate::config::Config;
+use crate::db;
+use crate::lookup::{self, LookupPool};
+use crate::variant::Accent;
+
+// Sentence breakdowns are kept by normalized sentence, so the same line of
+// teaching material asked again and again is answered from the database
+// instead of a dozen lookups. `/admin memory` shows, fixes and drops them.
+
+// Spaces and the closing punctuation don't change the translation
+pub fn normalize(sentence: &str) -> String {
+    let compact: String = sentence.chars().filter(|c| !c.is_whitespace()).collect();
+    compact
+        .trim_end_matches(['。', '！', '？', '，', '～', '.', '!', '?', ',', '~'])
+        .to_string()
+}
+
+// One line per word: the word, its Hanji and its Tâi-lô, tab-separated and
+// empty where no gloss was found
+pub fn encode(glosses: &[Gloss]) -> String {
+    glosses
+        .iter()
+        .map(|gloss| {
+            format!(
+                "{}\t{}\t{}",
+                gloss.word,
+                gloss.hanji.as_deref().unwrap_or_default(),
+                gloss.lomaji.as_deref().unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn decode(stored: &str) -> Vec<Gloss> {
+    let cell = |cell: Option<&str>| cell.filter(|cell| !cell.is_empty()).map(str::to_string);
+    stored
+        .lines()
+        .map(|line| {
+            let mut cells = line.split('\t');
+            Gloss {
+                word: cells.next().unwrap_or_default().to_string(),
+                hanji: cell(cells.next()),
+                lomaji: cell(cells.next()),
+                complete: true,
+            }
+        })
+        .collect()
+}
+
+// The glosses of a segmented sentence, remembered ones if there are any,
+// and their row in the memory. Fresh ones are only remembered when every
+// source answered for every word.
+pub async fn glosses(
+    db: &SqlitePool,
+    pool: &LookupPool,
+    config: &Arc<Config>,
+    sentence: &str,
+    words: Vec<String>,
+    variant: Option<Accent>,
+) -> (Vec<Gloss>, Option<i64>) {
+    let sentence = normalize(sentence);
+    let key = lookup::cache_key(&sentence, variant);
+    match db::remembered_translation(db, &key).await {
+        Ok(Some((id, stored))) => return (decode(&stored), Some(id)),
+        Ok(None) => {}
+        Err(why) => error!("Error loading the translation memory: {why:?}"),
+    }
+
+    let glosses = breakdown::gloss(words, variant, pool, config).await;
+    if !glosses.iter().all(|gloss| gloss.complete) {
+        return (glosses, None);
+    }
+    match db::remember_translation(db, &key, &sentence, &encode(&glosses)).await {
+        Ok(id) => (glosses, id),
+        Err(why) => {
+            error!("Error storing the translation: {why:?}");
+            (glosses, None)
+        }
+    }
+}
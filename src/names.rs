@@ -0,0 +1,133 @@
+use std::sync::OnceLock;
+
+use tokio::task::JoinSet;
+use tracing::error;
+
+use crate::lookup::LookupPool;
+use crate::reading::{self, Kind};
+use crate::text;
+
+// Chinese personal names read in Taigi. A given name takes the literary
+// (文讀) reading of each character, 明 bîng rather than miâ, while many
+// surnames have a reading of their own, often the colloquial one: 黃 is
+// N̂g, not Hông. Surnames come from `data/surnames.tsv`, the characters of
+// the given name (and of a surname not in the list) from Moedict.
+
+const BUNDLED: &str = include_str!("../data/surnames.tsv");
+
+// Longest names read, with room for a compound surname
+pub const MAX_CHARS: usize = 6;
+
+// A surname or one character of the given name, with its reading if one
+// was found
+pub struct Part {
+    pub hanji: String,
+    pub lomaji: Option<String>,
+}
+
+pub struct Name {
+    pub surname: Part,
+    // Whether the surname is in the list, or was read like the given name
+    pub known_surname: bool,
+    pub given: Vec<Part>,
+}
+
+impl Name {
+    // `Tân Siáu-bîng`: the surname, then the given name's syllables joined
+    // with hyphens. Characters without a reading are written `?`.
+    pub fn lomaji(&self) -> String {
+        let reading = |part: &Part| part.lomaji.clone().unwrap_or_else(|| "?".to_string());
+        let given: Vec<String> = self.given.iter().map(reading).collect();
+        let surname = capitalize(&reading(&self.surname));
+        if given.is_empty() {
+            surname
+        } else {
+            format!("{} {}", surname, capitalize(&given.join("-")))
+        }
+    }
+}
+
+fn capitalize(lomaji: &str) -> String {
+    let mut chars = lomaji.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// (Hanji, reading), compound surnames first so they're matched before the
+// single surname they start with
+fn surnames() -> &'static [(String, String)] {
+    static SURNAMES: OnceLock<Vec<(String, String)>> = OnceLock::new();
+    SURNAMES.get_or_init(|| {
+        let mut surnames: Vec<(String, String)> = BUNDLED
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(hanji, lomaji)| (hanji.to_string(), text::nfc(lomaji)))
+            .collect();
+        surnames.sort_by_key(|(hanji, _)| std::cmp::Reverse(hanji.chars().count()));
+        surnames
+    })
+}
+
+// The listed surname a name starts with
+pub fn surname(name: &str) -> Option<&'static (String, String)> {
+    surnames().iter().find(|(hanji, _)| name.starts_with(hanji.as_str()))
+}
+
+// A character's literary reading, or its first reading when Moedict marks
+// none as literary
+async fn character(pool: LookupPool, hanji: char) -> Result<Option<String>, String> {
+    let readings = reading::moedict(&pool, &hanji.to_string()).await?;
+    let literary = readings.iter().find(|reading| reading.kind() == Some(Kind::Literary));
+    Ok(literary.or(readings.first()).map(|reading| reading.text.clone()))
+}
+
+// Read a name like 陳小明. A surname not in the list is taken to be the
+// first character.
+pub async fn read(pool: &LookupPool, name: &str) -> Result<Name, String> {
+    let name: String = name.chars().filter(|c| !c.is_whitespace()).collect();
+    let is_han = |c: char| ('\u{4E00}'..='\u{9FFF}').contains(&c);
+    if name.is_empty() || !name.chars().all(is_han) {
+        return Err("❌ Give a name in Hanji, e.g. `/name 陳小明`.".to_string());
+    }
+    if name.chars().count() > MAX_CHARS {
+        return Err(format!("❌ Names are read up to {} characters long.", MAX_CHARS));
+    }
+
+    // Every character needing Moedict is looked up at once: the given name,
+    // after the surname when it isn't listed
+    let listed = surname(&name);
+    let unlisted = &name[listed.map_or(0, |(hanji, _)| hanji.len())..];
+    let mut lookups = JoinSet::new();
+    for (idx, hanji) in unlisted.chars().enumerate() {
+        let pool = pool.clone();
+        lookups.spawn(async move { (idx, hanji, character(pool, hanji).await) });
+    }
+    let mut found = lookups.join_all().await;
+    found.sort_by_key(|(idx, _, _)| *idx);
+    let mut parts: Vec<Part> = found
+        .into_iter()
+        .map(|(_, hanji, lomaji)| Part {
+            hanji: hanji.to_string(),
+            lomaji: lomaji.unwrap_or_else(|err| {
+                error!("Error fetching readings of {}: {}", hanji, err);
+                None
+            }),
+        })
+        .collect();
+
+    let surname = match listed {
+        Some((hanji, lomaji)) => Part {
+            hanji: hanji.clone(),
+            lomaji: Some(lomaji.clone()),
+        },
+        None => parts.remove(0),
+    };
+    Ok(Name {
+        surname,
+        known_surname: listed.is_some(),
+        given: parts,
+    })
+}
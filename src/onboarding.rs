@@ -0,0 +1,252 @@
+use serenity::builder::{
+    CreateActionRow, CreateButton, CreateMessage, CreateSelectMenu, CreateSelectMenuKind,
+    CreateSelectMenuOption,
+};
+use serenity::model::application::{
+    ButtonStyle, ComponentInteraction, ComponentInteractionDataKind,
+};
+use serenity::model::channel::ChannelType;
+use serenity::model::guild::Guild;
+use serenity::model::id::{GuildId, UserId};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::context::Context;
+use crate::db::{self, GuildSettings};
+use crate::i18n::Locale;
+use crate::preflight::{self, Purpose};
+use crate::respond::{self, Mode, Reply};
+use crate::romanization::Romanization;
+use crate::source::Source;
+
+const PREFIX: &str = "setup:";
+
+// The wizard's text, showing what has been picked so far
+pub fn summary(settings: &GuildSettings) -> String {
+    let channel = settings
+        .lookup_channel
+        .map_or("not set".to_string(), |id| format!("<#{}>", id));
+    let locale = settings.locale.map_or("bot default", Locale::code);
+    let sources = settings.sources.as_ref().map_or("all".to_string(), |sources| {
+        sources
+            .iter()
+            .map(|source| source.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    let footnotes = if settings.footnotes { "on" } else { "off" };
+    let romanization = settings.romanization.map_or("Tâi-lô", Romanization::label);
+    let announcements = if settings.announcements_muted { "off" } else { "on" };
+    let digest = settings
+        .digest_channel
+        .map_or("off".to_string(), |id| format!("<#{}>", id));
+    let archive = settings
+        .archive_forum
+        .map_or("off".to_string(), |id| format!("<#{}>", id));
+    let english = match settings.english {
+        Some(true) => "on",
+        Some(false) => "off",
+        None => "bot default",
+    };
+    format!(
+        "⚙️ Lookup channel: {}\nReply language: {}\nSources: {}\nRomanization: {}\n\
+         Romanization footnotes: {}\nEnglish meanings: {}\n\
+         Bot announcements in the lookup channel: {}\nDaily digest: {}\nArchive forum: {}",
+        channel, locale, sources, romanization, footnotes, english, announcements, digest, archive
+    )
+}
+
+// Select menus for the lookup channel, language, sources and romanization,
+// footnote and announcement toggles and a done button. Custom IDs carry the server since the
+// wizard may be sent by DM, where the channel menu is left out: it would
+// list the DM's channels rather than the server's.
+pub fn components(guild_id: GuildId, in_server: bool) -> Vec<CreateActionRow> {
+    let id = |setting: &str| format!("{}{}:{}", PREFIX, guild_id, setting);
+
+    // Discord lists and searches the server's text channels itself, however many
+    let channel_kind = CreateSelectMenuKind::Channel {
+        channel_types: Some(vec![ChannelType::Text]),
+        default_channels: None,
+    };
+
+    let locale_options = [(Locale::En, "English"), (Locale::ZhTw, "中文（台灣）")]
+        .into_iter()
+        .map(|(locale, label)| CreateSelectMenuOption::new(label, locale.code()))
+        .collect();
+
+    let source_options: Vec<_> = Source::available()
+        .map(|source| CreateSelectMenuOption::new(source.name(), source.code().to_string()))
+        .collect();
+    let source_count = source_options.len();
+
+    let romanization_options = [Romanization::Tailo, Romanization::Poj]
+        .into_iter()
+        .map(|romanization| {
+            CreateSelectMenuOption::new(romanization.label(), romanization.code())
+        })
+        .collect();
+
+    let select = |setting: &str, options, placeholder: &str| {
+        CreateSelectMenu::new(id(setting), CreateSelectMenuKind::String { options })
+            .placeholder(placeholder)
+    };
+
+    let mut rows = Vec::new();
+    if in_server {
+        rows.push(CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(id("channel"), channel_kind).placeholder("Lookup channel"),
+        ));
+    }
+    rows.extend([
+        CreateActionRow::SelectMenu(select("locale", locale_options, "Reply language")),
+        CreateActionRow::SelectMenu(
+            select("sources", source_options, "Sources to show")
+                .min_values(1)
+                .max_values(source_count as u8),
+        ),
+        CreateActionRow::SelectMenu(select(
+            "romanization",
+            romanization_options,
+            "Romanization for members who haven't picked one",
+        )),
+        CreateActionRow::Buttons(vec![
+            CreateButton::new(id("footnotes"))
+                .style(ButtonStyle::Secondary)
+                .label("Toggle footnotes"),
+            CreateButton::new(id("announcements"))
+                .style(ButtonStyle::Secondary)
+                .label("Toggle announcements"),
+            CreateButton::new(id("done"))
+                .style(ButtonStyle::Success)
+                .label("Done"),
+        ]),
+    ]);
+    rows
+}
+
+// Post the setup wizard in the system channel of a server that just added
+// the bot, or DM it to the owner if there is none
+pub async fn welcome(ctx: &Context, guild: &Guild) {
+    let in_server = guild.system_channel_id.is_some();
+    let channel_note = if in_server {
+        ""
+    } else {
+        " The lookup channel is picked with `/config` in the server."
+    };
+    let message = CreateMessage::new()
+        .content(format!(
+            "👋 Thanks for adding the Taigi bot! Server managers can set it up below, \
+             or later with `/config`.{}\n{}",
+            channel_note,
+            summary(&GuildSettings::default())
+        ))
+        .components(components(guild.id, in_server));
+    let sent = match guild.system_channel_id {
+        Some(channel_id) => channel_id.send_message(&ctx.http, message).await,
+        None => guild.owner_id.direct_message(&ctx.http, message).await,
+    };
+    if let Err(why) = sent {
+        error!("Error sending setup wizard to {}: {why:?}", guild.id);
+    }
+}
+
+const REFUSAL: &str = "Only members who can manage the server may set the bot up.";
+
+// Whether the user owns the server, for wizards sent by DM
+async fn is_owner(ctx: &Context, guild_id: GuildId, user_id: UserId) -> bool {
+    match guild_id.to_partial_guild(&ctx.http).await {
+        Ok(guild) => guild.owner_id == user_id,
+        Err(why) => {
+            error!("Error fetching server {}: {why:?}", guild_id);
+            false
+        }
+    }
+}
+
+// Handle a setup wizard component. Returns false for other components.
+pub async fn handle(ctx: &Context, db: &SqlitePool, component: &ComponentInteraction) -> bool {
+    let Some(rest) = component.data.custom_id.strip_prefix(PREFIX) else {
+        return false;
+    };
+    let Some((guild_id, setting)) = rest.split_once(':') else {
+        return false;
+    };
+    let Ok(guild_id) = guild_id.parse::<u64>().map(GuildId::new) else {
+        return false;
+    };
+
+    // Unlike /config, the wizard can be clicked by anyone who sees it. Members
+    // are refused on their own so the wizard stays in place.
+    if let Some(member) = &component.member
+        && !member
+            .permissions
+            .is_some_and(|permissions| permissions.manage_guild())
+    {
+        let refusal = async { REFUSAL.to_string() };
+        respond::component(ctx, db, component, Mode::Ephemeral, refusal).await;
+        return true;
+    }
+
+    let reply = async {
+        if component.member.is_none() && !is_owner(ctx, guild_id, component.user.id).await {
+            return Reply::from(REFUSAL.to_string());
+        }
+        let mut settings = match db::get_guild_settings(db, &guild_id.to_string()).await {
+            Ok(settings) => settings,
+            Err(why) => {
+                error!("Error loading server settings: {why:?}");
+                return Reply::from("Could not load the server settings.".to_string());
+            }
+        };
+        let values = match &component.data.kind {
+            ComponentInteractionDataKind::StringSelect { values } => values.as_slice(),
+            _ => &[],
+        };
+        match setting {
+            // Only a menu shown in the server lists its channels
+            "channel" if component.guild_id == Some(guild_id) => {
+                if let ComponentInteractionDataKind::ChannelSelect { values } = &component.data.kind {
+                    settings.lookup_channel = values.first().copied();
+                }
+            }
+            "locale" => settings.locale = values.first().and_then(|code| Locale::parse(code)),
+            "sources" => {
+                settings.sources = Some(
+                    values
+                        .iter()
+                        .filter_map(|code| code.chars().next().and_then(Source::from_code))
+                        .collect(),
+                );
+            }
+            "romanization" => {
+                settings.romanization = values.first().and_then(|code| Romanization::parse(code));
+            }
+            "footnotes" => settings.footnotes = !settings.footnotes,
+            "announcements" => settings.announcements_muted = !settings.announcements_muted,
+            _ => {}
+        }
+
+        if setting == "done" {
+            return Reply {
+                content: format!("{}\n✅ All set!", summary(&settings)),
+                components: Some(Vec::new()),
+                files: Vec::new(),
+            };
+        }
+        if let Err(why) = db::save_guild_settings(db, &guild_id.to_string(), &settings).await {
+            error!("Error saving server settings: {why:?}");
+        }
+        // Say right away if the bot can't answer in the channel picked
+        if setting == "channel"
+            && let Some(channel) = settings.lookup_channel
+        {
+            let bot = ctx.cache.current_user().id;
+            if let Some(problem) = preflight::problem(&ctx.http, channel, bot, Purpose::Lookup).await {
+                return Reply::from(format!("{}\n⚠️ {}", summary(&settings), problem));
+            }
+        }
+        Reply::from(summary(&settings))
+    };
+    respond::component(ctx, db, component, Mode::Update, reply).await;
+    true
+}
@@ -0,0 +1,114 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serenity::http::Http;
+use tracing::error;
+
+use crate::config::SharedConfig;
+use crate::respond;
+
+// Problems are collected and posted together at most this often, so a
+// source going down doesn't turn into a message per lookup
+const INTERVAL: Duration = Duration::from_secs(60);
+
+// Distinct problems kept per report, the rest are only counted
+const MAX_DISTINCT: usize = 20;
+
+// What went wrong, for the log channel
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    // A source couldn't be reached or answered with something unreadable
+    SourceFailure,
+    // A page had its result table but none of the expected cells, so the
+    // site's layout probably changed
+    SelectorMismatch,
+    Panic,
+    // Results couldn't be posted in a channel, see `circuit`
+    SendFailure,
+    // The results script failed, see `script`
+    ScriptFailure,
+}
+
+impl Kind {
+    fn label(self) -> &'static str {
+        match self {
+            Kind::SourceFailure => "🌐 Source failure",
+            Kind::SelectorMismatch => "🧩 Selector mismatch",
+            Kind::Panic => "💥 Panic",
+            Kind::SendFailure => "📪 Send failure",
+            Kind::ScriptFailure => "📜 Script failure",
+        }
+    }
+}
+
+// Problems since the last report: (kind, detail, count), and how many
+// more didn't fit
+struct Pending {
+    problems: Vec<(Kind, String, u64)>,
+    dropped: u64,
+}
+
+static PENDING: Mutex<Pending> = Mutex::new(Pending {
+    problems: Vec::new(),
+    dropped: 0,
+});
+
+// Note a problem for the next report. Identical ones are counted together.
+pub fn report(kind: Kind, detail: impl Into<String>) {
+    let detail = detail.into();
+    let mut pending = PENDING.lock().unwrap();
+    let known = pending
+        .problems
+        .iter()
+        .position(|(known_kind, known_detail, _)| *known_kind == kind && *known_detail == detail);
+    match known {
+        Some(idx) => pending.problems[idx].2 += 1,
+        None if pending.problems.len() < MAX_DISTINCT => pending.problems.push((kind, detail, 1)),
+        None => pending.dropped += 1,
+    }
+}
+
+// The report for the problems collected, None if there were none
+fn render(pending: Pending) -> Option<String> {
+    if pending.problems.is_empty() {
+        return None;
+    }
+    let total: u64 = pending.problems.iter().map(|(_, _, count)| count).sum::<u64>()
+        + pending.dropped;
+    let mut lines = vec![format!(
+        "🚨 {} problem(s) in the last {} seconds:",
+        total,
+        INTERVAL.as_secs()
+    )];
+    for (kind, detail, count) in &pending.problems {
+        lines.push(format!("{} ×{} — {}", kind.label(), count, detail));
+    }
+    if pending.dropped > 0 {
+        lines.push(format!("…and {} other(s)", pending.dropped));
+    }
+    Some(respond::fit_message(&lines.join("\n")))
+}
+
+// Post what was collected to the log channel configured at the time. Without
+// one problems are still collected, and dropped.
+pub fn spawn(http: Arc<Http>, config: Arc<SharedConfig>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(INTERVAL);
+        loop {
+            interval.tick().await;
+            let pending = std::mem::replace(
+                &mut *PENDING.lock().unwrap(),
+                Pending {
+                    problems: Vec::new(),
+                    dropped: 0,
+                },
+            );
+            if let Some(channel) = config.get().log_channel
+                && let Some(report) = render(pending)
+                && let Err(why) = channel.say(&http, report).await
+            {
+                error!("Error posting to the log channel: {why:?}");
+            }
+        }
+    });
+}
@@ -0,0 +1,46 @@
+use std::future::{Future, poll_fn};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::Poll;
+
+use tracing::error;
+
+use crate::opslog::{self, Kind};
+
+// Events that panicked since startup, for /metrics
+static PANICS: AtomicU64 = AtomicU64::new(0);
+
+// Run the handling of one event, catching a panic instead of letting it
+// unwind into the gateway task. A query that trips a parsing bug then only
+// loses its own answer, and the panic shows up in the log channel.
+pub async fn isolate(event: &str, work: impl Future<Output = ()>) {
+    let mut work = std::pin::pin!(work);
+    let result = poll_fn(|cx| match catch_unwind(AssertUnwindSafe(|| work.as_mut().poll(cx))) {
+        Ok(Poll::Ready(())) => Poll::Ready(Ok(())),
+        Ok(Poll::Pending) => Poll::Pending,
+        Err(panic) => Poll::Ready(Err(panic)),
+    })
+    .await;
+
+    if let Err(panic) = result {
+        // The panic hook has already printed where it happened
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown cause".to_string());
+        error!("Handling a {} panicked: {}", event, message);
+        PANICS.fetch_add(1, Ordering::Relaxed);
+        opslog::report(Kind::Panic, format!("Handling a {}: {}", event, message));
+    }
+}
+
+// The panic count in the Prometheus text format, for /metrics
+pub fn prometheus() -> String {
+    format!(
+        "# HELP taigi_event_panics_total Events whose handling panicked\n\
+         # TYPE taigi_event_panics_total counter\n\
+         taigi_event_panics_total {}\n",
+        PANICS.load(Ordering::Relaxed)
+    )
+}
@@ -0,0 +1,32 @@
+use crate::direction;
+use crate::romanization;
+use crate::text;
+
+// Reaction left on messages that contain Taigi words
+pub const EMOJI: char = '📖';
+
+// At most this many words are looked up per message
+const MAX_WORDS: usize = 3;
+
+// Taigi words found in an ordinary chat message: Tâi-lô/POJ words and Han
+// words that only Taigi uses
+pub fn detect(content: &str) -> Vec<String> {
+    let content = text::nfc(content);
+    let mut words: Vec<String> = Vec::new();
+
+    for word in content.split(|c: char| c.is_whitespace() || ",.!?;:()\"'".contains(c)) {
+        let word = word.trim_matches('-');
+        if romanization::is_romanized(word) && !words.iter().any(|found| found == word) {
+            words.push(word.to_string());
+        }
+    }
+
+    for hanji in direction::taigi_hanji(&content) {
+        if !words.iter().any(|found| found.contains(hanji)) {
+            words.push(hanji.to_string());
+        }
+    }
+
+    words.truncate(MAX_WORDS);
+    words
+}
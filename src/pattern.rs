@@ -0,0 +1,142 @@
+use regex::{Regex, RegexBuilder};
+
+use crate::ime::{self, Spelling};
+use crate::romanization;
+use crate::text;
+
+// Pattern searches over the local index of spellings (the drill words and
+// the corpus), for looking at which sounds go together: `tsh*` for every
+// word starting with tsh, `?ng` for a letter before ng, like png.
+//
+// Wildcards: `*` is any run of characters, `?` a single one. A pattern
+// without tone marks matches every tone, one with them only the tones
+// written. Regular expressions are matched against the readings as
+// written, tone marks included.
+
+// Limits on what a regular expression may compile to, far above any
+// pattern over a word's reading
+const REGEX_SIZE: usize = 1 << 20;
+
+pub struct Pattern {
+    regex: Regex,
+    // Match the readings without their tone marks
+    toneless: bool,
+}
+
+impl Pattern {
+    // `tsh*-pn̄g`: anchored at both ends, like a file name pattern
+    pub fn wildcard(pattern: &str) -> Result<Pattern, String> {
+        let pattern = text::nfc(pattern.trim()).to_lowercase();
+        if pattern.is_empty() {
+            return Err("The pattern is empty.".to_string());
+        }
+        let toneless = !romanization::has_tone_marks(&pattern);
+        let pattern = if toneless { romanization::toneless(&pattern) } else { pattern };
+        let mut expression = String::from("^");
+        for c in pattern.chars() {
+            match c {
+                '*' => expression.push_str(".*"),
+                '?' => expression.push('.'),
+                c => expression.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        expression.push('$');
+        Ok(Pattern {
+            regex: compile(&expression)?,
+            toneless,
+        })
+    }
+
+    // A regular expression, found anywhere in a reading unless anchored
+    pub fn regex(pattern: &str) -> Result<Pattern, String> {
+        let pattern = text::nfc(pattern.trim());
+        if pattern.is_empty() {
+            return Err("The pattern is empty.".to_string());
+        }
+        Ok(Pattern {
+            regex: compile(&pattern)?,
+            toneless: false,
+        })
+    }
+
+    pub fn matches(&self, spelling: &Spelling) -> bool {
+        let lomaji = if self.toneless {
+            romanization::toneless(&spelling.lomaji)
+        } else {
+            text::nfc(&spelling.lomaji).to_lowercase()
+        };
+        self.regex.is_match(&lomaji) || self.regex.is_match(&spelling.hanji)
+    }
+
+    // Every matching spelling, ordered by reading
+    pub fn search(&self) -> Vec<&'static Spelling> {
+        let mut found: Vec<&Spelling> =
+            ime::spellings().filter(|spelling| self.matches(spelling)).collect();
+        found.sort_by(|a, b| (&a.lomaji, &a.hanji).cmp(&(&b.lomaji, &b.hanji)));
+        found
+    }
+}
+
+fn compile(expression: &str) -> Result<Regex, String> {
+    RegexBuilder::new(expression)
+        .case_insensitive(true)
+        .size_limit(REGEX_SIZE)
+        .dfa_size_limit(REGEX_SIZE)
+        .build()
+        .map_err(|why| format!("Invalid pattern:\n```\n{}\n```", why))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spelling(hanji: &str, lomaji: &str) -> Spelling {
+        Spelling {
+            hanji: hanji.to_string(),
+            lomaji: lomaji.to_string(),
+            tones: Vec::new(),
+            count: 1,
+        }
+    }
+
+    #[test]
+    fn toneless_patterns_match_every_tone() {
+        let pattern = Pattern::wildcard("tsh*").unwrap();
+        assert!(pattern.matches(&spelling("菜", "tshài")));
+        assert!(pattern.matches(&spelling("青", "tshenn")));
+        assert!(!pattern.matches(&spelling("水", "tsuí")));
+        let pattern = Pattern::wildcard("?ng").unwrap();
+        assert!(pattern.matches(&spelling("飯", "pn̄g")));
+        assert!(!pattern.matches(&spelling("湯", "thng")));
+    }
+
+    #[test]
+    fn toned_patterns_match_the_tones_written() {
+        let pattern = Pattern::wildcard("pn̄g").unwrap();
+        assert!(pattern.matches(&spelling("飯", "pn̄g")));
+        assert!(pattern.matches(&spelling("飯", "pn\u{304}g")));
+        assert!(!pattern.matches(&spelling("方", "png")));
+        assert!(!pattern.matches(&spelling("返", "pńg")));
+    }
+
+    #[test]
+    fn wildcards_escape_the_rest() {
+        let pattern = Pattern::wildcard("a.b").unwrap();
+        assert!(pattern.matches(&spelling("", "a.b")));
+        assert!(!pattern.matches(&spelling("", "axb")));
+        assert!(Pattern::wildcard("(tsi[").is_ok());
+        assert!(Pattern::wildcard("  ").is_err());
+    }
+
+    #[test]
+    fn regexes_match_anywhere_unless_anchored() {
+        let pattern = Pattern::regex("ng$").unwrap();
+        assert!(pattern.matches(&spelling("湯", "thng")));
+        assert!(!pattern.matches(&spelling("菜", "tshài")));
+        // Tone marks are part of what's matched
+        assert!(!pattern.matches(&spelling("飯", "pn̄g")));
+        assert!(Pattern::regex("n̄g$").unwrap().matches(&spelling("飯", "pn̄g")));
+        let err = Pattern::regex("(").err().unwrap();
+        assert!(err.starts_with("Invalid pattern"));
+    }
+}
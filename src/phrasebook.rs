@@ -0,0 +1,178 @@
+use std::sync::OnceLock;
+
+use serenity::builder::CreateActionRow;
+
+use crate::db::GuildPhrase;
+use crate::respond::Reply;
+use crate::shortcuts;
+
+// Everyday phrases by situation, from `data/phrasebook.txt`, which
+// moderators can add to for their server with `/phrasebook add`
+
+const BUNDLED: &str = include_str!("../data/phrasebook.txt");
+
+// Discord allows 5 rows of 5 buttons
+const BUTTONS_PER_ROW: usize = 5;
+const MAX_ROWS: usize = 5;
+
+pub struct Phrase {
+    pub hanji: String,
+    pub lomaji: String,
+    pub english: String,
+    // The word whose recording is played, the phrase itself unless set
+    pub audio: String,
+}
+
+pub struct Situation {
+    // Short English name to ask for it by, and its Hanji name
+    pub code: String,
+    pub name: String,
+    pub phrases: Vec<Phrase>,
+}
+
+impl Situation {
+    fn is(&self, wanted: &str) -> bool {
+        self.code.eq_ignore_ascii_case(wanted) || self.name == wanted
+    }
+}
+
+pub fn situations() -> &'static [Situation] {
+    static SITUATIONS: OnceLock<Vec<Situation>> = OnceLock::new();
+    SITUATIONS.get_or_init(|| {
+        let mut situations: Vec<Situation> = Vec::new();
+        for line in BUNDLED.lines().filter(|line| !line.starts_with('#')) {
+            if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                let (code, name) = header.split_once(' ').unwrap_or((header, header));
+                situations.push(Situation {
+                    code: code.to_string(),
+                    name: name.to_string(),
+                    phrases: Vec::new(),
+                });
+                continue;
+            }
+            let mut fields = line.split('\t');
+            let (Some(hanji), Some(lomaji), Some(situation)) =
+                (fields.next(), fields.next(), situations.last_mut())
+            else {
+                continue;
+            };
+            let english = fields.next().unwrap_or_default();
+            let audio = fields.next().filter(|audio| !audio.is_empty()).unwrap_or(hanji);
+            situation.phrases.push(Phrase {
+                hanji: hanji.to_string(),
+                lomaji: lomaji.to_string(),
+                english: english.to_string(),
+                audio: audio.to_string(),
+            });
+        }
+        situations
+    })
+}
+
+// The English of a bundled phrase that is exactly `hanji`
+pub fn english(hanji: &str) -> Option<&'static str> {
+    situations()
+        .iter()
+        .flat_map(|situation| &situation.phrases)
+        .find(|phrase| phrase.hanji == hanji.trim() && !phrase.english.is_empty())
+        .map(|phrase| phrase.english.as_str())
+}
+
+// A bundled situation by its code (any case) or Hanji name
+pub fn find(wanted: &str) -> Option<&'static Situation> {
+    let wanted = wanted.trim();
+    situations().iter().find(|situation| situation.is(wanted))
+}
+
+// The code a server's phrase is filed under: a bundled situation's code, or
+// what the moderator typed, lowercased, for a situation of the server's own
+pub fn situation_code(wanted: &str) -> String {
+    match find(wanted) {
+        Some(situation) => situation.code.clone(),
+        None => wanted.trim().to_lowercase(),
+    }
+}
+
+// The bundled situations and any the server added
+pub fn overview(added: &[GuildPhrase]) -> String {
+    let mut names: Vec<String> = situations()
+        .iter()
+        .map(|situation| format!("{} ({})", situation.name, situation.code))
+        .collect();
+    for phrase in added {
+        if find(&phrase.situation).is_none() && !names.contains(&phrase.situation) {
+            names.push(phrase.situation.clone());
+        }
+    }
+    format!(
+        "💬 Phrasebook situations, e.g. `/phrasebook show greetings`:\n{}",
+        names.join(" · ")
+    )
+}
+
+// The phrases for a situation, bundled ones first and then the server's
+// with their IDs for `/phrasebook remove`, numbered to match their 🔊
+// buttons. The server's phrases only have one when added with an `audio`
+// word. None if neither has any.
+pub fn page(wanted: &str, added: &[GuildPhrase]) -> Option<Reply> {
+    let bundled = find(wanted);
+    let code = situation_code(wanted);
+    let added: Vec<&GuildPhrase> =
+        added.iter().filter(|phrase| phrase.situation == code).collect();
+    if bundled.is_none() && added.is_empty() {
+        return None;
+    }
+
+    let title = match bundled {
+        Some(situation) => format!("💬 **{}** ({})", situation.name, situation.code),
+        None => format!("💬 **{}**", code),
+    };
+    let mut lines = vec![title];
+    let mut audio: Vec<Option<&str>> = Vec::new();
+    for phrase in bundled.map_or(&[][..], |situation| &situation.phrases) {
+        audio.push(Some(&phrase.audio));
+        lines.push(format!(
+            "{}. {} {} — {}",
+            audio.len(),
+            phrase.hanji,
+            phrase.lomaji,
+            phrase.english
+        ));
+    }
+    for phrase in &added {
+        audio.push(phrase.audio.as_deref());
+        lines.push(format!(
+            "{}. {} {} — {} `#{}`",
+            audio.len(),
+            phrase.hanji,
+            phrase.lomaji,
+            phrase.english,
+            phrase.id
+        ));
+    }
+
+    // Phrases sharing a recording share its button, as custom IDs must be unique
+    let mut seen: Vec<&str> = Vec::new();
+    let mut buttons = Vec::new();
+    for (idx, word) in audio.iter().enumerate() {
+        let Some(word) = word else {
+            continue;
+        };
+        if seen.contains(word) {
+            continue;
+        }
+        seen.push(word);
+        buttons.extend(shortcuts::audio_button(word, &(idx + 1).to_string()));
+    }
+    let components: Vec<CreateActionRow> = buttons
+        .chunks(BUTTONS_PER_ROW)
+        .take(MAX_ROWS)
+        .map(|row| CreateActionRow::Buttons(row.to_vec()))
+        .collect();
+
+    Some(Reply {
+        content: lines.join("\n"),
+        components: Some(components),
+        files: Vec::new(),
+    })
+}
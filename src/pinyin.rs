@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+// Bundled Mandarin lexicon, see data/mandarin_lexicon.tsv for the format
+const LEXICON: &str = include_str!("../data/mandarin_lexicon.tsv");
+
+struct LexiconEntry {
+    hanzi: &'static str,
+    tones: Vec<u8>,
+}
+
+// Toneless pinyin spelling (e.g. "pingguo") -> candidate words in file order
+fn lexicon() -> &'static HashMap<String, Vec<LexiconEntry>> {
+    static INDEX: OnceLock<HashMap<String, Vec<LexiconEntry>>> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        let mut index: HashMap<String, Vec<LexiconEntry>> = HashMap::new();
+        for line in LEXICON.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            let Some((hanzi, pinyin)) = line.split_once('\t') else {
+                continue;
+            };
+
+            let mut key = String::new();
+            let mut tones = Vec::new();
+            for syllable in pinyin.split_whitespace() {
+                let letters = syllable.trim_end_matches(|c: char| c.is_ascii_digit());
+                key.push_str(letters);
+                tones.push(syllable[letters.len()..].parse().unwrap_or(5));
+            }
+
+            index.entry(key).or_default().push(LexiconEntry {
+                hanzi: hanzi.trim(),
+                tones,
+            });
+        }
+        index
+    })
+}
+
+// Every word in the bundled lexicon
+pub fn words() -> impl Iterator<Item = &'static str> {
+    lexicon().values().flatten().map(|entry| entry.hanzi)
+}
+
+// Convert a Hanyu Pinyin or Zhuyin query into Han characters using the
+// bundled lexicon. Returns None when the query doesn't look like either
+// or isn't a known word, in which case it should be searched as-is.
+pub fn to_hanzi(query: &str) -> Option<&'static str> {
+    let (key, tones) = if query.chars().any(is_zhuyin) {
+        parse_zhuyin(query)?
+    } else {
+        parse_pinyin(query)?
+    };
+
+    let candidates = lexicon().get(&key)?;
+
+    // Without any tone information a single syllable is too ambiguous
+    // (and too easily confused with English), so only convert words
+    if tones.is_empty() {
+        return candidates
+            .iter()
+            .find(|entry| entry.tones.len() >= 2)
+            .map(|entry| entry.hanzi);
+    }
+
+    // Neutral tones are routinely left unmarked, so compare without them
+    let wanted: Vec<u8> = tones.iter().copied().filter(|&t| t != 5).collect();
+    candidates
+        .iter()
+        .find(|entry| {
+            entry
+                .tones
+                .iter()
+                .copied()
+                .filter(|&t| t != 5)
+                .eq(wanted.iter().copied())
+        })
+        .or_else(|| candidates.first())
+        .map(|entry| entry.hanzi)
+}
+
+fn is_zhuyin(c: char) -> bool {
+    matches!(c, '\u{3105}'..='\u{312F}' | 'ˊ' | 'ˇ' | 'ˋ' | '˙' | 'ˉ')
+}
+
+// Split a pinyin vowel carrying a tone mark into its base letter and tone
+fn strip_tone_mark(c: char) -> Option<(char, u8)> {
+    let marked = [
+        ('a', "āáǎà"),
+        ('e', "ēéěè"),
+        ('i', "īíǐì"),
+        ('o', "ōóǒò"),
+        ('u', "ūúǔù"),
+        ('v', "ǖǘǚǜ"),
+    ];
+    marked.iter().find_map(|(base, forms)| {
+        forms
+            .chars()
+            .position(|form| form == c)
+            .map(|idx| (*base, idx as u8 + 1))
+    })
+}
+
+// Parse `pingguo`, `ping2 guo3` or `píngguǒ` into a toneless key plus
+// whatever tones were given
+fn parse_pinyin(query: &str) -> Option<(String, Vec<u8>)> {
+    let mut key = String::new();
+    let mut tones = Vec::new();
+
+    for c in query.to_lowercase().chars() {
+        match c {
+            'a'..='z' => key.push(c),
+            'ü' => key.push('v'),
+            '1'..='5' => tones.push(c as u8 - b'0'),
+            ' ' | '\'' => {}
+            _ => {
+                let (base, tone) = strip_tone_mark(c)?;
+                key.push(base);
+                tones.push(tone);
+            }
+        }
+    }
+
+    if key.is_empty() {
+        None
+    } else {
+        Some((key, tones))
+    }
+}
+
+#[derive(Default)]
+struct ZhuyinSyllable {
+    initial: Option<char>,
+    medial: Option<char>,
+    rhyme: Option<char>,
+    tone: Option<u8>,
+}
+
+impl ZhuyinSyllable {
+    fn is_empty(&self) -> bool {
+        self.initial.is_none() && self.medial.is_none() && self.rhyme.is_none()
+    }
+
+    fn to_pinyin(&self) -> Option<String> {
+        let initial = match self.initial {
+            None => "",
+            Some(c) => match c {
+                'ㄅ' => "b",
+                'ㄆ' => "p",
+                'ㄇ' => "m",
+                'ㄈ' => "f",
+                'ㄉ' => "d",
+                'ㄊ' => "t",
+                'ㄋ' => "n",
+                'ㄌ' => "l",
+                'ㄍ' => "g",
+                'ㄎ' => "k",
+                'ㄏ' => "h",
+                'ㄐ' => "j",
+                'ㄑ' => "q",
+                'ㄒ' => "x",
+                'ㄓ' => "zh",
+                'ㄔ' => "ch",
+                'ㄕ' => "sh",
+                'ㄖ' => "r",
+                'ㄗ' => "z",
+                'ㄘ' => "c",
+                'ㄙ' => "s",
+                _ => return None,
+            },
+        };
+
+        let rhyme = match self.rhyme {
+            None => "",
+            Some(c) => match c {
+                'ㄚ' => "a",
+                'ㄛ' => "o",
+                'ㄜ' | 'ㄝ' => "e",
+                'ㄞ' => "ai",
+                'ㄟ' => "ei",
+                'ㄠ' => "ao",
+                'ㄡ' => "ou",
+                'ㄢ' => "an",
+                'ㄣ' => "en",
+                'ㄤ' => "ang",
+                'ㄥ' => "eng",
+                'ㄦ' => "er",
+                _ => return None,
+            },
+        };
+
+        let final_part = match self.medial {
+            None if rhyme.is_empty() => {
+                // Syllabic consonants: ㄓ = zhi, ㄙ = si, ...
+                if matches!(initial, "zh" | "ch" | "sh" | "r" | "z" | "c" | "s") {
+                    "i".to_string()
+                } else {
+                    return None;
+                }
+            }
+            None => rhyme.to_string(),
+            Some('ㄧ') => {
+                let spelled = match rhyme {
+                    "" => "i".to_string(),
+                    "ou" => "iu".to_string(),
+                    "en" => "in".to_string(),
+                    "eng" => "ing".to_string(),
+                    _ => format!("i{}", rhyme),
+                };
+                if initial.is_empty() {
+                    match spelled.as_str() {
+                        "i" | "in" | "ing" => format!("y{}", spelled),
+                        "iu" => "you".to_string(),
+                        _ => format!("y{}", &spelled[1..]),
+                    }
+                } else {
+                    spelled
+                }
+            }
+            Some('ㄨ') => {
+                let spelled = match rhyme {
+                    "" => "u".to_string(),
+                    "ei" => "ui".to_string(),
+                    "en" => "un".to_string(),
+                    "eng" => "ong".to_string(),
+                    _ => format!("u{}", rhyme),
+                };
+                if initial.is_empty() {
+                    match spelled.as_str() {
+                        "u" => "wu".to_string(),
+                        "ui" => "wei".to_string(),
+                        "un" => "wen".to_string(),
+                        "ong" => "weng".to_string(),
+                        _ => format!("w{}", &spelled[1..]),
+                    }
+                } else {
+                    spelled
+                }
+            }
+            Some('ㄩ') => {
+                let spelled = match rhyme {
+                    "" => "v",
+                    "e" => "ve",
+                    "an" => "van",
+                    "en" => "vn",
+                    "eng" => "iong",
+                    _ => return None,
+                };
+                match initial {
+                    "" => match spelled {
+                        "iong" => "yong".to_string(),
+                        _ => format!("yu{}", &spelled[1..]),
+                    },
+                    // ü is only written with the umlaut after n and l
+                    "n" | "l" => spelled.to_string(),
+                    _ => spelled.replacen('v', "u", 1),
+                }
+            }
+            Some(_) => return None,
+        };
+
+        Some(format!("{}{}", initial, final_part))
+    }
+}
+
+// Parse `ㄆㄧㄥˊㄍㄨㄛˇ` into a toneless pinyin key plus one tone per syllable
+fn parse_zhuyin(query: &str) -> Option<(String, Vec<u8>)> {
+    let mut syllables = Vec::new();
+    let mut current = ZhuyinSyllable::default();
+    // ˙ is conventionally written before the syllable it marks
+    let mut pending_neutral = false;
+
+    fn flush(current: &mut ZhuyinSyllable, syllables: &mut Vec<ZhuyinSyllable>) {
+        if !current.is_empty() {
+            syllables.push(std::mem::take(current));
+        }
+    }
+
+    for c in query.chars() {
+        match c {
+            'ㄅ'..='ㄙ' => {
+                flush(&mut current, &mut syllables);
+                current.initial = Some(c);
+            }
+            'ㄧ' | 'ㄨ' | 'ㄩ' => {
+                if current.medial.is_some() || current.rhyme.is_some() {
+                    flush(&mut current, &mut syllables);
+                }
+                current.medial = Some(c);
+            }
+            'ㄚ'..='ㄦ' => {
+                if current.rhyme.is_some() {
+                    flush(&mut current, &mut syllables);
+                }
+                current.rhyme = Some(c);
+            }
+            'ˊ' | 'ˇ' | 'ˋ' | 'ˉ' | '˙' => {
+                let tone = match c {
+                    'ˊ' => 2,
+                    'ˇ' => 3,
+                    'ˋ' => 4,
+                    'ˉ' => 1,
+                    _ => 5,
+                };
+                if current.is_empty() {
+                    pending_neutral = tone == 5;
+                } else {
+                    current.tone = Some(tone);
+                    flush(&mut current, &mut syllables);
+                }
+            }
+            ' ' => flush(&mut current, &mut syllables),
+            _ => return None,
+        }
+
+        if pending_neutral && !current.is_empty() {
+            current.tone = Some(5);
+            pending_neutral = false;
+        }
+    }
+    flush(&mut current, &mut syllables);
+
+    if syllables.is_empty() {
+        return None;
+    }
+
+    let mut key = String::new();
+    let mut tones = Vec::new();
+    for syllable in &syllables {
+        key.push_str(&syllable.to_pinyin()?);
+        tones.push(syllable.tone.unwrap_or(1));
+    }
+    Some((key, tones))
+}
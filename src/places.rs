@@ -0,0 +1,71 @@
+use std::sync::OnceLock;
+
+use crate::romanization;
+use crate::text;
+
+// Taiwan's cities, counties and well-known towns with their Taigi names,
+// which the dictionaries either lack or only give the Mandarin-derived
+// reading of: 高雄 is Ko-hiông, 艋舺 is Báng-kah.
+
+const BUNDLED: &str = include_str!("../data/places.tsv");
+
+// Administrative suffixes left off when matching, so 高雄市 finds 高雄
+const SUFFIXES: [char; 5] = ['市', '縣', '區', '鄉', '鎮'];
+
+pub struct Place {
+    pub hanji: String,
+    pub lomaji: String,
+    pub english: String,
+}
+
+pub fn places() -> &'static [Place] {
+    static PLACES: OnceLock<Vec<Place>> = OnceLock::new();
+    PLACES.get_or_init(|| {
+        BUNDLED
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                Some(Place {
+                    hanji: fields.next()?.to_string(),
+                    lomaji: text::nfc(fields.next()?),
+                    english: fields.next().unwrap_or_default().to_string(),
+                })
+            })
+            .collect()
+    })
+}
+
+// Hanji as the list writes it: 台 for 臺, without an administrative suffix
+fn hanji_key(hanji: &str) -> String {
+    let hanji = hanji.replace('臺', "台");
+    match hanji.strip_suffix(SUFFIXES) {
+        Some(stripped) if stripped.chars().count() >= 2 => stripped.to_string(),
+        _ => hanji,
+    }
+}
+
+// The place a query names, by its Hanji, its English name or its reading
+// with or without tone marks
+pub fn find(keyword: &str) -> Option<&'static Place> {
+    let keyword = keyword.trim();
+    if keyword.is_empty() {
+        return None;
+    }
+    let hanji = hanji_key(keyword);
+    let toneless = romanization::toneless(keyword).to_lowercase();
+    places().iter().find(|place| {
+        place.hanji == hanji
+            || place.english.eq_ignore_ascii_case(keyword)
+            || romanization::toneless(&place.lomaji).to_lowercase() == toneless
+    })
+}
+
+// The result line for a known place, e.g. `📍 高雄 [Ko-hiông] Kaohsiung`,
+// and nothing for any other query
+pub async fn search(keyword: &str) -> Result<Vec<String>, String> {
+    Ok(find(keyword)
+        .map(|place| format!("📍 {} [{}] {}", place.hanji, place.lomaji, place.english))
+        .into_iter()
+        .collect())
+}
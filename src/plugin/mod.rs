@@ -0,0 +1,33 @@
+// Community dictionaries shipped as WebAssembly modules, searched together as
+// the Plugins source. A plugin exports:
+//
+// - `memory`
+// - `alloc(len: i32) -> i32`, room for `len` bytes of input
+// - `search_url(ptr: i32, len: i32) -> i64`, the page to fetch for the
+//   keyword at ptr, or an empty string to skip it
+// - `parse(ptr: i32, len: i32, limit: i32) -> i64`, result lines from the
+//   page at ptr, at most `limit`, as `hanji\tlomaji\tdefinition\turl`
+//
+// Strings are UTF-8 and returned as `ptr << 32 | len`. Plugins import
+// nothing: the bot fetches the page itself, with the same limits as the
+// other sources, from the hosts listed in the plugin's `<name>.toml`.
+
+#[cfg(feature = "plugins")]
+mod wasm;
+
+#[cfg(feature = "plugins")]
+pub use wasm::{any, load, search};
+
+// Emoji result lines from plugins start with
+pub const EMOJI: &str = "🧩";
+
+// Whether any plugin is loaded, so the Plugins source is only offered then
+#[cfg(not(feature = "plugins"))]
+pub fn any() -> bool {
+    false
+}
+
+#[cfg(not(feature = "plugins"))]
+pub async fn search(_keyword: &str, _limit: usize) -> Result<Vec<String>, String> {
+    Ok(Vec::new())
+}
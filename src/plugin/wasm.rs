@@ -0,0 +1,307 @@
+// The wasmtime host running the plugins
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use figment::Figment;
+use figment::providers::{Format, Toml};
+use serde::Deserialize;
+use tokio::task::{self, JoinSet};
+use tracing::error;
+use wasmtime::{Engine, Instance, Memory, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::opslog::{self, Kind};
+use crate::upstream;
+
+use super::EMOJI;
+
+// Work each call into a plugin may do, roughly a second's worth, so a plugin
+// stuck in a loop fails its search instead of holding up the reply
+const FUEL: u64 = 1_000_000_000;
+// Memory a plugin may grow to, enough for a few copies of the largest page
+const MAX_MEMORY: usize = 32 << 20;
+const EXPORTS: [&str; 4] = ["memory", "alloc", "search_url", "parse"];
+
+struct Plugin {
+    name: String,
+    module: Module,
+    // Hosts its pages may be fetched from
+    hosts: Vec<String>,
+}
+
+// `<name>.toml` next to a plugin, e.g. `hosts = ["example.org"]`
+#[derive(Deserialize)]
+struct Manifest {
+    hosts: Vec<String>,
+}
+
+static PLUGINS: OnceLock<Vec<Plugin>> = OnceLock::new();
+
+pub fn any() -> bool {
+    PLUGINS.get().is_some_and(|plugins| !plugins.is_empty())
+}
+
+// Compile every `.wasm` file in `dir`, named after the file. Plugins that fail
+// to compile, lack an export or have no manifest listing their hosts are
+// skipped with an error. Returns the names of those loaded.
+pub fn load(dir: &Path) -> Result<Vec<String>, String> {
+    let mut config = wasmtime::Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config).map_err(|why| format!("cannot start wasmtime: {}", why))?;
+
+    let entries = std::fs::read_dir(dir)
+        .map_err(|why| format!("cannot read {}: {}", dir.display(), why))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    paths.sort();
+
+    let mut plugins = Vec::new();
+    for path in paths {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let module = match Module::from_file(&engine, &path) {
+            Ok(module) => module,
+            Err(why) => {
+                error!("Cannot load the plugin {}: {why:#}", path.display());
+                continue;
+            }
+        };
+        let missing: Vec<&str> = EXPORTS
+            .into_iter()
+            .filter(|export| module.get_export(export).is_none())
+            .collect();
+        if !missing.is_empty() {
+            error!("The plugin {} doesn't export {}", name, missing.join(", "));
+            continue;
+        }
+        let manifest = path.with_extension("toml");
+        if !manifest.exists() {
+            error!("The plugin {} has no {}", name, manifest.display());
+            continue;
+        }
+        let hosts = match Figment::from(Toml::file(&manifest)).extract::<Manifest>() {
+            Ok(manifest) => manifest.hosts,
+            Err(why) => {
+                error!("Cannot read {}: {why}", manifest.display());
+                continue;
+            }
+        };
+        plugins.push(Plugin {
+            name,
+            module,
+            hosts,
+        });
+    }
+
+    let names = plugins.iter().map(|plugin| plugin.name.clone()).collect();
+    PLUGINS
+        .set(plugins)
+        .map_err(|_| "plugins are already loaded".to_string())?;
+    Ok(names)
+}
+
+// One plugin instance, fresh for every search so nothing leaks between them
+struct Call {
+    store: Store<StoreLimits>,
+    instance: Instance,
+    memory: Memory,
+}
+
+impl Call {
+    fn new(module: &Module) -> Result<Call, String> {
+        let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+        let mut store = Store::new(module.engine(), limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL).map_err(|why| why.to_string())?;
+        let instance = Instance::new(&mut store, module, &[])
+            .map_err(|why| format!("cannot start: {}", why))?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("exports no memory")?;
+        Ok(Call {
+            store,
+            instance,
+            memory,
+        })
+    }
+
+    // Copy `input` into the plugin, returning where it is
+    fn pass(&mut self, input: &[u8]) -> Result<(i32, i32), String> {
+        let len = i32::try_from(input.len()).map_err(|_| "input too large")?;
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut self.store, "alloc")
+            .map_err(|why| format!("alloc: {}", why))?;
+        let ptr = alloc
+            .call(&mut self.store, len)
+            .map_err(|why| format!("alloc: {}", why))?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, input)
+            .map_err(|why| format!("alloc gave memory out of bounds: {}", why))?;
+        Ok((ptr, len))
+    }
+
+    // The string a function returned as `ptr << 32 | len`
+    fn take(&mut self, packed: i64) -> Result<String, String> {
+        let packed = packed as u64;
+        let (ptr, len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        let mut output = vec![0; len];
+        self.memory
+            .read(&self.store, ptr, &mut output)
+            .map_err(|why| format!("returned memory out of bounds: {}", why))?;
+        String::from_utf8(output).map_err(|_| "returned text that isn't UTF-8".to_string())
+    }
+
+    fn search_url(&mut self, keyword: &str) -> Result<String, String> {
+        let (ptr, len) = self.pass(keyword.as_bytes())?;
+        self.store.set_fuel(FUEL).map_err(|why| why.to_string())?;
+        let packed = self
+            .instance
+            .get_typed_func::<(i32, i32), i64>(&mut self.store, "search_url")
+            .and_then(|search_url| search_url.call(&mut self.store, (ptr, len)))
+            .map_err(|why| format!("search_url: {}", why))?;
+        self.take(packed)
+    }
+
+    fn parse(&mut self, page: &str, limit: usize) -> Result<String, String> {
+        let (ptr, len) = self.pass(page.as_bytes())?;
+        let limit = i32::try_from(limit).unwrap_or(i32::MAX);
+        self.store.set_fuel(FUEL).map_err(|why| why.to_string())?;
+        let packed = self
+            .instance
+            .get_typed_func::<(i32, i32, i32), i64>(&mut self.store, "parse")
+            .and_then(|parse| parse.call(&mut self.store, (ptr, len, limit)))
+            .map_err(|why| format!("parse: {}", why))?;
+        self.take(packed)
+    }
+}
+
+// A result line as the other sources write them, e.g.
+// `🧩 食飯 [tsia̍h-pn̄g] to eat - [kautian](https://…)`
+fn format_line(name: &str, line: &str) -> Option<String> {
+    let mut fields = line.split('\t').map(str::trim);
+    let hanji = fields.next().filter(|hanji| !hanji.is_empty())?;
+    let lomaji = fields.next().unwrap_or_default();
+    let definition = fields.next().unwrap_or_default();
+    let url = fields
+        .next()
+        .filter(|url| url.starts_with("https://") || url.starts_with("http://"));
+
+    let mut result = format!("{} {}", EMOJI, hanji);
+    if !lomaji.is_empty() {
+        result += &format!(" [{}]", lomaji);
+    }
+    if !definition.is_empty() {
+        result += &format!(" {}", definition);
+    }
+    match url {
+        Some(url) => result += &format!(" - [{}]({})", name, url),
+        None => result += &format!(" - {}", name),
+    }
+    Some(result)
+}
+
+// Run plugin code on the blocking pool, since a call may take up to its fuel
+// and would stall every other task on a runtime worker
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    task::spawn_blocking(work)
+        .await
+        .map_err(|why| format!("panicked: {}", why))?
+}
+
+// Whether `url` is a web page on one of the plugin's hosts
+fn allowed(url: &str, hosts: &[String]) -> bool {
+    reqwest::Url::parse(url).is_ok_and(|url| {
+        matches!(url.scheme(), "https" | "http")
+            && url
+                .host_str()
+                .is_some_and(|host| hosts.iter().any(|allowed| allowed.eq_ignore_ascii_case(host)))
+    })
+}
+
+async fn search_one(plugin: &Plugin, keyword: &str, limit: usize) -> Result<Vec<String>, String> {
+    let (module, keyword) = (plugin.module.clone(), keyword.to_string());
+    let (mut call, url) = blocking(move || {
+        let mut call = Call::new(&module)?;
+        let url = call.search_url(&keyword)?;
+        Ok((call, url))
+    })
+    .await?;
+    if url.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !allowed(&url, &plugin.hosts) {
+        return Err(format!("asked for {}, which isn't on its hosts", url));
+    }
+
+    let response = upstream::plugin_client()
+        .get(&url)
+        .send()
+        .await
+        .map_err(|_| format!("Error fetching {}", url))?;
+    // No results, as with Moedict
+    if !response.status().is_success() {
+        return Ok(Vec::new());
+    }
+    let page = upstream::text(response).await?;
+
+    let lines = blocking(move || call.parse(&page, limit)).await?;
+    Ok(lines
+        .lines()
+        .filter_map(|line| format_line(&plugin.name, line))
+        .take(limit)
+        .collect())
+}
+
+// Search every plugin at once, `limit` results from each. A failing plugin is
+// reported on its own, the search only fails when every plugin did.
+pub async fn search(keyword: &str, limit: usize) -> Result<Vec<String>, String> {
+    let Some(plugins) = PLUGINS.get() else {
+        return Ok(Vec::new());
+    };
+    let mut searches = JoinSet::new();
+    for (idx, plugin) in plugins.iter().enumerate() {
+        let keyword = keyword.to_string();
+        searches.spawn(async move { (idx, search_one(plugin, &keyword, limit).await) });
+    }
+    let mut searched = searches.join_all().await;
+    searched.sort_by_key(|(idx, _)| *idx);
+
+    let mut results = Vec::new();
+    let mut errors = Vec::new();
+    for (idx, result) in searched {
+        match result {
+            Ok(mut found) => results.append(&mut found),
+            Err(err) => {
+                let err = format!("{}: {}", plugins[idx].name, err);
+                opslog::report(Kind::SourceFailure, format!("Plugin {}", err));
+                errors.push(err);
+            }
+        }
+    }
+    if !errors.is_empty() && errors.len() == plugins.len() {
+        return Err(errors.join("; "));
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetches_only_from_the_plugin_hosts() {
+        let hosts = vec!["example.org".to_string()];
+        assert!(allowed("https://example.org/search?q=食", &hosts));
+        assert!(allowed("http://EXAMPLE.org/", &hosts));
+        assert!(!allowed("https://example.org.evil.test/", &hosts));
+        assert!(!allowed("http://127.0.0.1/", &hosts));
+        assert!(!allowed("https://user@169.254.169.254/", &hosts));
+        assert!(!allowed("file:///etc/passwd", &hosts));
+    }
+}
@@ -0,0 +1,68 @@
+use crate::lookup::Lookup;
+
+// Moedict's part of speech tags, with the names they can be asked for by
+const TAGS: [(&str, &[&str]); 12] = [
+    ("名", &["名詞", "noun", "n"]),
+    ("動", &["動詞", "verb", "v"]),
+    ("形", &["形容詞", "adjective", "adj"]),
+    ("副", &["副詞", "adverb", "adv"]),
+    ("代", &["代詞", "代名詞", "pronoun", "pron"]),
+    ("數", &["數詞", "numeral", "num"]),
+    ("量", &["量詞", "classifier", "measure"]),
+    ("介", &["介詞", "preposition", "prep"]),
+    ("連", &["連詞", "conjunction", "conj"]),
+    ("助", &["助詞", "particle"]),
+    ("嘆", &["嘆詞", "歎詞", "interjection", "interj"]),
+    ("綴", &["詞綴", "affix"]),
+];
+
+// The tag for a part of speech as given to `--pos`, e.g. 動詞, 動 or verb
+pub fn parse(value: &str) -> Option<&'static str> {
+    let value = value.trim().to_lowercase();
+    TAGS.iter()
+        .find(|(tag, names)| *tag == value || names.contains(&value.as_str()))
+        .map(|(tag, _)| *tag)
+}
+
+pub fn unknown(value: &str) -> String {
+    format!("Unknown part of speech \"{}\", use e.g. 名詞, 動詞 or 形容詞", value)
+}
+
+// Whether a result is of the part of speech asked for, from the tags its
+// lookup carries for it
+pub fn matches(lookup: &Lookup, result: &str, tag: &str) -> bool {
+    lookup.pos.get(result).is_some_and(|tags| tags.iter().any(|known| known == tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source;
+
+    #[test]
+    fn parse_takes_tags_and_names() {
+        assert_eq!(parse("動詞"), Some("動"));
+        assert_eq!(parse(" Verb "), Some("動"));
+        assert_eq!(parse("名"), Some("名"));
+        assert_eq!(parse("歎詞"), Some("嘆"));
+        assert_eq!(parse("飯"), None);
+    }
+
+    #[test]
+    fn matches_every_tag_of_a_result() {
+        let entry = r#"{"t": "`愛~", "h": [
+            {"T": "ài", "d": [{"type": "`動~`、~`名~", "f": "喜歡。"}]},
+            {"T": "ài", "d": [{"f": "無分詞性。"}]}
+        ]}"#;
+        let (results, pos) = source::parse_moedict(entry, "愛", 5).unwrap();
+        let lookup = Lookup {
+            results,
+            pos,
+            ..Lookup::default()
+        };
+        assert_eq!(lookup.pos[&lookup.results[0]], ["動", "名"]);
+        assert!(matches(&lookup, &lookup.results[0], "名"));
+        assert!(!matches(&lookup, &lookup.results[0], "形"));
+        assert!(!matches(&lookup, &lookup.results[1], "動"));
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::OnceLock;
+
+use serenity::builder::{CreateActionRow, CreateButton};
+use serenity::model::application::{ButtonStyle, ComponentInteraction};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::context::Context;
+use crate::db::{self, UserPrefs};
+use crate::respond::{self, Mode, Reply};
+use crate::romanization;
+
+const WORDS: &str = include_str!("../data/tone_words.tsv");
+
+const PAIRS_PREFIX: &str = "pairs:";
+const DRILL_PREFIX: &str = "drill:";
+
+// One word of the bundled drill list
+pub struct Word {
+    pub hanji: &'static str,
+    pub tailo: &'static str,
+    // Spelling without the tone mark, and the tone
+    pub base: String,
+    pub tone: u8,
+}
+
+pub fn words() -> &'static [Word] {
+    static WORD_LIST: OnceLock<Vec<Word>> = OnceLock::new();
+    WORD_LIST.get_or_init(|| {
+        WORDS
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(hanji, tailo)| {
+                let (base, tone) = romanization::tone(tailo);
+                Word {
+                    hanji,
+                    tailo,
+                    base,
+                    tone,
+                }
+            })
+            .collect()
+    })
+}
+
+fn word(hanji: &str) -> Option<&'static Word> {
+    words().iter().find(|word| word.hanji == hanji)
+}
+
+// A number below `bound`, different on every call. Good enough for picking
+// a drill, not for anything that needs to be unpredictable.
+pub fn random(bound: usize) -> usize {
+    (RandomState::new().hash_one(std::time::Instant::now()) % bound.max(1) as u64) as usize
+}
+
+// What tells the two words of a minimal pair apart
+#[derive(Clone, Copy, PartialEq)]
+pub enum Contrast {
+    Tone,
+    // Plain vs aspirated initial: p/ph, t/th, k/kh, ts/tsh
+    Aspiration,
+}
+
+impl Contrast {
+    pub fn parse(value: &str) -> Option<Contrast> {
+        match value {
+            "tone" => Some(Contrast::Tone),
+            "aspiration" => Some(Contrast::Aspiration),
+            _ => None,
+        }
+    }
+}
+
+fn unaspirated(base: &str) -> String {
+    for (aspirated, plain) in [("tsh", "ts"), ("ph", "p"), ("th", "t"), ("kh", "k")] {
+        if let Some(rest) = base.strip_prefix(aspirated) {
+            return format!("{}{}", plain, rest);
+        }
+    }
+    base.to_string()
+}
+
+fn contrast(a: &Word, b: &Word) -> Option<Contrast> {
+    if a.base == b.base && a.tone != b.tone {
+        Some(Contrast::Tone)
+    } else if a.tone == b.tone
+        && a.base != b.base
+        && unaspirated(&a.base) == unaspirated(&b.base)
+    {
+        Some(Contrast::Aspiration)
+    } else {
+        None
+    }
+}
+
+// Every minimal pair of the word list, optionally of one kind
+pub fn pairs(kind: Option<Contrast>) -> Vec<(&'static Word, &'static Word)> {
+    let words = words();
+    let mut pairs = Vec::new();
+    for (i, a) in words.iter().enumerate() {
+        for b in &words[i + 1..] {
+            if let Some(found) = contrast(a, b)
+                && kind.is_none_or(|kind| kind == found)
+            {
+                pairs.push((a, b));
+            }
+        }
+    }
+    pairs
+}
+
+fn moedict_link(word: &Word) -> String {
+    format!(
+        "[{} {}](<https://www.moedict.org/#'{}>)",
+        word.hanji,
+        word.tailo,
+        urlencoding::encode(word.hanji)
+    )
+}
+
+// A button per word of the pair. Custom IDs carry the pair, the guess and
+// the word played, e.g. `pairs:君:滾:0:1`.
+pub fn pair_buttons(pair: (&Word, &Word), played: usize, prefs: &UserPrefs) -> Vec<CreateButton> {
+    [pair.0, pair.1]
+        .into_iter()
+        .enumerate()
+        .map(|(guess, word)| {
+            let custom_id = format!(
+                "{}{}:{}:{}:{}",
+                PAIRS_PREFIX, pair.0.hanji, pair.1.hanji, guess, played
+            );
+            CreateButton::new(custom_id)
+                .style(ButtonStyle::Primary)
+                .label(prefs.render(&format!("{} {}", word.hanji, word.tailo)))
+        })
+        .collect()
+}
+
+// A word for /tonedrill. Tones the user gets wrong come up more often:
+// each tone is weighted by its error rate, smoothed so new users start even.
+pub fn drill_word(stats: &[(u8, i64, i64)]) -> &'static Word {
+    let mut tones: Vec<u8> = words().iter().map(|word| word.tone).collect();
+    tones.sort();
+    tones.dedup();
+    // From 1 (always right) to 5 (always wrong), 3 for untried tones
+    let weights: Vec<usize> = tones
+        .iter()
+        .map(|tone| {
+            let (asked, correct) = stats
+                .iter()
+                .find(|(stat_tone, _, _)| stat_tone == tone)
+                .map_or((0, 0), |&(_, asked, correct)| (asked, correct));
+            1 + (4 * (asked - correct + 1) / (asked + 2)) as usize
+        })
+        .collect();
+
+    let mut pick = random(weights.iter().sum());
+    let mut tone = tones[0];
+    for (candidate, weight) in tones.iter().zip(&weights) {
+        if pick < *weight {
+            tone = *candidate;
+            break;
+        }
+        pick -= weight;
+    }
+    let candidates: Vec<&Word> = words().iter().filter(|word| word.tone == tone).collect();
+    candidates[random(candidates.len())]
+}
+
+// Tone 1-8 buttons in two rows, e.g. custom ID `drill:君:3`
+pub fn drill_buttons(word: &Word) -> Vec<CreateActionRow> {
+    [1..=4, 5..=8]
+        .into_iter()
+        .map(|tones| {
+            let buttons = tones
+                .map(|tone| {
+                    CreateButton::new(format!("{}{}:{}", DRILL_PREFIX, word.hanji, tone))
+                        .style(ButtonStyle::Secondary)
+                        .label(format!("Tone {}", tone))
+                })
+                .collect();
+            CreateActionRow::Buttons(buttons)
+        })
+        .collect()
+}
+
+async fn drill_answer(db: &SqlitePool, component: &ComponentInteraction, rest: &str) -> Reply {
+    let Some((word, guess)) = rest.split_once(':').and_then(|(hanji, guess)| {
+        Some((self::word(hanji)?, guess.parse::<u8>().ok()?))
+    }) else {
+        return Reply::from("This drill is no longer available.".to_string());
+    };
+    let user_id = component.user.id.to_string();
+    let right = guess == word.tone;
+    if let Err(why) = db::record_drill(db, &user_id, word.tone, right).await {
+        error!("Error recording tone drill: {why:?}");
+    }
+
+    let mut content = if right {
+        format!("✅ Right! {} {} is tone {}.", word.hanji, word.tailo, word.tone)
+    } else {
+        format!("❌ {} {} is tone {}, not {}.", word.hanji, word.tailo, word.tone, guess)
+    };
+    match db::drill_stats(db, &user_id).await {
+        Ok(stats) => {
+            let scores: Vec<String> = stats
+                .iter()
+                .map(|(tone, asked, correct)| format!("{}: {}/{}", tone, correct, asked))
+                .collect();
+            content.push_str(&format!("\n📊 Your tones so far: {}", scores.join(" · ")));
+        }
+        Err(why) => error!("Error loading tone drill stats: {why:?}"),
+    }
+    Reply {
+        content,
+        components: Some(Vec::new()),
+        files: Vec::new(),
+    }
+}
+
+// Handle a minimal pair guess or tone drill answer. Returns false for other
+// components.
+pub async fn handle(ctx: &Context, db: &SqlitePool, component: &ComponentInteraction) -> bool {
+    if let Some(rest) = component.data.custom_id.strip_prefix(DRILL_PREFIX) {
+        let answer = drill_answer(db, component, rest);
+        respond::component(ctx, db, component, Mode::Update, answer).await;
+        return true;
+    }
+    let Some(rest) = component.data.custom_id.strip_prefix(PAIRS_PREFIX) else {
+        return false;
+    };
+    let parts: Vec<&str> = rest.split(':').collect();
+    let [a, b, guess, played] = parts[..] else {
+        return false;
+    };
+    let (Some(a), Some(b)) = (word(a), word(b)) else {
+        return false;
+    };
+    let verdict = if guess == played {
+        "✅ Right!"
+    } else {
+        "❌ Not quite."
+    };
+    let played = [a, b][usize::from(played == "1")];
+    let reply = Reply {
+        content: format!(
+            "{} You heard {} {}. Listen to both again: {} · {}",
+            verdict,
+            played.hanji,
+            played.tailo,
+            moedict_link(a),
+            moedict_link(b)
+        ),
+        components: Some(Vec::new()),
+        files: Vec::new(),
+    };
+    respond::component(ctx, db, component, Mode::Update, async { reply }).await;
+    true
+}
@@ -0,0 +1,118 @@
+use serenity::http::Http;
+use serenity::model::Permissions;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use tracing::{info, warn};
+
+// What the bot does in a lookup channel: reply, preview links, react with
+// ❌ and 📖, and attach recordings and cards
+const LOOKUP: [(Permissions, &str); 4] = [
+    (Permissions::SEND_MESSAGES, "Send Messages"),
+    (Permissions::EMBED_LINKS, "Embed Links"),
+    (Permissions::ADD_REACTIONS, "Add Reactions"),
+    (Permissions::ATTACH_FILES, "Attach Files"),
+];
+// The digest is a message with links
+const DIGEST: [(Permissions, &str); 2] = [
+    (Permissions::SEND_MESSAGES, "Send Messages"),
+    (Permissions::EMBED_LINKS, "Embed Links"),
+];
+// In a forum, sending messages is what lets the bot start posts
+const ARCHIVE: [(Permissions, &str); 3] = [
+    (Permissions::SEND_MESSAGES, "Create Posts"),
+    (Permissions::SEND_MESSAGES_IN_THREADS, "Send Messages in Posts"),
+    (Permissions::EMBED_LINKS, "Embed Links"),
+];
+
+// What a channel is configured for, which decides what the bot needs there
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Purpose {
+    Lookup,
+    Digest,
+    Archive,
+}
+
+impl Purpose {
+    fn needed(self) -> &'static [(Permissions, &'static str)] {
+        match self {
+            Purpose::Lookup => &LOOKUP,
+            Purpose::Digest => &DIGEST,
+            Purpose::Archive => &ARCHIVE,
+        }
+    }
+}
+
+// The permissions the bot lacks in `channel` for `purpose`, by name. Fetched
+// over HTTP rather than from the cache, which is still empty at startup.
+pub async fn missing(
+    http: &Http,
+    channel: ChannelId,
+    bot: UserId,
+    purpose: Purpose,
+) -> Result<Vec<&'static str>, String> {
+    let channel = channel
+        .to_channel(http)
+        .await
+        .map_err(|why| format!("cannot see the channel ({})", why))?
+        .guild()
+        .ok_or("not a server channel")?;
+    let guild = channel
+        .guild_id
+        .to_partial_guild(http)
+        .await
+        .map_err(|why| format!("cannot read the server ({})", why))?;
+    let member = guild
+        .id
+        .member(http, bot)
+        .await
+        .map_err(|why| format!("cannot read the bot's roles ({})", why))?;
+
+    let permissions = guild.user_permissions_in(&channel, &member);
+    Ok(purpose
+        .needed()
+        .iter()
+        .filter(|(needed, _)| !permissions.contains(*needed))
+        .map(|&(_, name)| name)
+        .collect())
+}
+
+// The server a configured channel belongs to, None if it can't be seen
+pub async fn guild_of(http: &Http, channel: ChannelId) -> Option<GuildId> {
+    match channel.to_channel(http).await {
+        Ok(channel) => channel.guild().map(|channel| channel.guild_id),
+        Err(why) => {
+            warn!("Cannot see channel {}: {}", channel, why);
+            None
+        }
+    }
+}
+
+// A line saying what's missing in `channel`, None if nothing is
+pub async fn problem(
+    http: &Http,
+    channel: ChannelId,
+    bot: UserId,
+    purpose: Purpose,
+) -> Option<String> {
+    match missing(http, channel, bot, purpose).await {
+        Ok(missing) if missing.is_empty() => None,
+        Ok(missing) => Some(format!("<#{}> is missing {}", channel, missing.join(", "))),
+        Err(err) => Some(format!("<#{}>: {}", channel, err)),
+    }
+}
+
+// Check every channel at startup, logging what each one lacks
+pub async fn check_all(http: &Http, channels: &[(ChannelId, Purpose)], bot: UserId) {
+    let mut ok = 0;
+    for &(channel, purpose) in channels {
+        match missing(http, channel, bot, purpose).await {
+            Ok(missing) if missing.is_empty() => ok += 1,
+            Ok(missing) => warn!("Missing permissions in {}: {}", channel, missing.join(", ")),
+            Err(err) => warn!("Cannot check permissions in {}: {}", channel, err),
+        }
+    }
+    info!(
+        "Permissions checked, {} of {} channels have everything",
+        ok,
+        channels.len()
+    );
+}
@@ -0,0 +1,207 @@
+use crate::pos;
+use crate::source::Source;
+use crate::text;
+use crate::variant::Accent;
+
+#[derive(Default)]
+pub struct SearchOptions {
+    pub variant: Option<Accent>,
+    // Only show the results to the asker: ephemeral replies, DMs. Not counted
+    // in digests.
+    pub private: bool,
+    // Raised source limits and no result limit, for the ➕ shortcut
+    pub more: bool,
+    // Only show these sources' results (the advanced search form)
+    pub sources: Option<Vec<Source>>,
+    // Only show results of this part of speech, as a Moedict tag like 動
+    pub pos: Option<&'static str>,
+}
+
+// Split a message like `睡覺 --variant 漳 --pos 動詞` into the keyword and its options
+pub fn parse(content: &str) -> Result<(String, SearchOptions), String> {
+    let mut words = Vec::new();
+    let mut options = SearchOptions::default();
+
+    let mut tokens = content.split_whitespace();
+    while let Some(token) = tokens.next() {
+        let (flag, inline_value) = match token.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value)),
+            _ => (token, None),
+        };
+
+        match flag {
+            "--variant" => {
+                let value = inline_value
+                    .or_else(|| tokens.next())
+                    .ok_or("`--variant` needs an accent: 漳, 泉 or 廈")?;
+                let accent = Accent::parse(value)
+                    .ok_or_else(|| format!("Unknown accent \"{}\", use 漳, 泉 or 廈", value))?;
+                options.variant = Some(accent);
+            }
+            "--pos" => {
+                let value = inline_value
+                    .or_else(|| tokens.next())
+                    .ok_or("`--pos` needs a part of speech, like 名詞 or 動詞")?;
+                let tag = pos::parse(value).ok_or_else(|| pos::unknown(value))?;
+                options.pos = Some(tag);
+            }
+            "--private" => options.private = true,
+            _ => words.push(token),
+        }
+    }
+
+    Ok((normalize(&words.join(" ")), options))
+}
+
+// Characters that don't render or only join emoji: zero-width spaces and
+// joiners, the word joiner, byte order marks and variation selectors
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' | '\u{FE00}'..='\u{FE0F}')
+}
+
+// Emoji, pictographs, flags and their skin tone and tag modifiers
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c,
+        '\u{2600}'..='\u{27BF}' | '\u{2B00}'..='\u{2BFF}' | '\u{1F000}'..='\u{1FAFF}'
+            | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+// Full-width letters, digits and symbols as their ASCII forms, and the
+// ideographic space as a plain one, so ＡＢＣ and ｔｓｉ̍ｈ search like abc and tsi̍h
+fn half_width(c: char) -> char {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        '\u{3000}' => ' ',
+        _ => c,
+    }
+}
+
+// Punctuation left at the end of a pasted sentence
+const CLOSING: [char; 16] = [
+    '。', '！', '？', '，', '、', '；', '：', '…', '.', '!', '?', ',', ';', ':', '~', '～',
+];
+
+// Markdown and quotes people paste a word in, outermost first where one
+// could be taken for the start of another (``` before `, ** before *)
+const WRAPPERS: [(&str, &str); 14] = [
+    ("```", "```"),
+    ("`", "`"),
+    ("**", "**"),
+    ("__", "__"),
+    ("~~", "~~"),
+    ("||", "||"),
+    ("*", "*"),
+    ("_", "_"),
+    ("\"", "\""),
+    ("'", "'"),
+    ("“", "”"),
+    ("‘", "’"),
+    ("「", "」"),
+    ("『", "』"),
+];
+
+// One layer of Discord formatting around the whole keyword: a `>` or `>>>`
+// quote marker in front, or a pair of wrappers
+fn unwrap(keyword: &str) -> Option<&str> {
+    if let Some(quoted) = keyword
+        .strip_prefix(">>> ")
+        .or_else(|| keyword.strip_prefix("> "))
+    {
+        return Some(quoted);
+    }
+    WRAPPERS.iter().find_map(|(open, close)| {
+        keyword
+            .strip_prefix(open)?
+            .strip_suffix(close)
+            .filter(|inner| !inner.trim().is_empty())
+    })
+}
+
+// Keywords as typed or pasted into a search: invisible characters and emoji
+// dropped, full-width forms narrowed, Lô-jī put in NFC, whitespace collapsed,
+// and Discord formatting and the closing punctuation of a sentence taken off
+pub fn normalize(keyword: &str) -> String {
+    let narrowed: String = keyword
+        .chars()
+        .filter(|&c| !is_invisible(c) && !is_emoji(c))
+        .map(half_width)
+        .collect();
+    let narrowed = text::nfc(&narrowed);
+    let collapsed = narrowed.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    // Punctuation can sit inside the formatting or outside it: **睡覺。**, 「睡覺」。
+    // Wrappers come off first and punctuation one character at a time, so the
+    // tildes of ~~睡覺~~ are taken for strikethrough rather than punctuation.
+    let mut keyword = collapsed.as_str();
+    loop {
+        keyword = keyword.trim();
+        if let Some(inner) = unwrap(keyword) {
+            keyword = inner;
+            continue;
+        }
+        match keyword.strip_suffix(CLOSING) {
+            Some(rest) => keyword = rest,
+            None => return keyword.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize;
+
+    #[test]
+    fn narrows_full_width_forms() {
+        assert_eq!(normalize("ＡＢＣ"), "ABC");
+        assert_eq!(normalize("ｔｓｉ̍ｈ"), "tsi̍h");
+        assert_eq!(normalize("食飽未\u{3000}ｌｉ"), "食飽未 li");
+    }
+
+    #[test]
+    fn drops_invisible_characters() {
+        assert_eq!(normalize("睡\u{200B}覺"), "睡覺");
+        assert_eq!(normalize("\u{FEFF}睡覺"), "睡覺");
+        assert_eq!(normalize("睡覺\u{2060}"), "睡覺");
+        assert_eq!(normalize("睡\u{FE0F}覺"), "睡覺");
+    }
+
+    #[test]
+    fn drops_emoji() {
+        assert_eq!(normalize("睡覺 😴"), "睡覺");
+        assert_eq!(normalize("👨\u{200D}👩\u{200D}👧 家庭"), "家庭");
+        assert_eq!(normalize("🇹🇼台灣"), "台灣");
+        assert_eq!(normalize("👍🏽 讚"), "讚");
+    }
+
+    #[test]
+    fn trims_closing_punctuation() {
+        assert_eq!(normalize("睡覺。"), "睡覺");
+        assert_eq!(normalize("食飽未？"), "食飽未");
+        assert_eq!(normalize("真好！！"), "真好");
+        assert_eq!(normalize("tsia̍h-pá--buē?"), "tsia̍h-pá--buē");
+    }
+
+    #[test]
+    fn unwraps_formatting_before_punctuation() {
+        assert_eq!(normalize("~~睡覺~~"), "睡覺");
+        assert_eq!(normalize("~~睡覺~~。"), "睡覺");
+        assert_eq!(normalize("**睡覺。**"), "睡覺");
+        assert_eq!(normalize("「睡覺」。"), "睡覺");
+        assert_eq!(normalize("> `睡覺`"), "睡覺");
+        assert_eq!(normalize("好～"), "好");
+    }
+
+    #[test]
+    fn collapses_whitespace() {
+        assert_eq!(normalize("  tsia̍h \t pá\n"), "tsia̍h pá");
+        assert_eq!(normalize(""), "");
+        assert_eq!(normalize(" \u{200B} "), "");
+    }
+
+    #[test]
+    fn composes_lomaji() {
+        assert_eq!(normalize("to\u{301}"), "tó");
+    }
+}
@@ -0,0 +1,233 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use serenity::builder::{
+    CreateActionRow, CreateButton, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use serenity::model::application::{ButtonStyle, ComponentInteraction, ComponentInteractionDataKind};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::context::Context;
+use crate::db;
+use crate::respond::{self, Mode, Reply};
+use crate::source::Source;
+use crate::variant::Accent;
+use crate::widget;
+
+const PREFIX: &str = "rate:";
+const PICK_PREFIX: &str = "rate_pick:";
+
+// 👍/👎 under results with an entry ID, custom ID `rate:<good|bad>:<entry id>`.
+// Unlike the iTaigi vote buttons these are kept by the bot, for any source.
+pub fn buttons(entry_id: &str) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{}good:{}", PREFIX, entry_id))
+            .style(ButtonStyle::Secondary)
+            .emoji('👍')
+            .label("Good answer"),
+        CreateButton::new(format!("{}bad:{}", PREFIX, entry_id))
+            .style(ButtonStyle::Secondary)
+            .emoji('👎')
+            .label("Bad answer"),
+    ])
+}
+
+// Results with more 👍 than 👎 first and those with more 👎 last, the rest
+// in the order they were found
+pub fn rank(results: &mut [(String, Source)], scores: &HashMap<String, i64>) {
+    if scores.is_empty() {
+        return;
+    }
+    results.sort_by_key(|(result, _)| Reverse(scores.get(result).copied().unwrap_or(0)));
+}
+
+// The votes on a keyword's results, none if they can't be read
+pub async fn scores(
+    db: &SqlitePool,
+    keyword: &str,
+    variant: Option<Accent>,
+) -> HashMap<String, i64> {
+    match db::result_scores(db, keyword, variant).await {
+        Ok(scores) => scores,
+        Err(why) => {
+            error!("Error loading result votes: {why:?}");
+            HashMap::new()
+        }
+    }
+}
+
+// `good:4f2a` into (true, "4f2a")
+fn parse(rest: &str) -> Option<(bool, &str)> {
+    match rest.split_once(':')? {
+        ("good", entry_id) => Some((true, entry_id)),
+        ("bad", entry_id) => Some((false, entry_id)),
+        _ => None,
+    }
+}
+
+// Handle the vote buttons and the follow-up result picker. Returns false for
+// other components.
+pub async fn handle(ctx: &Context, db: &SqlitePool, component: &ComponentInteraction) -> bool {
+    let custom_id = component.data.custom_id.as_str();
+    if let Some((good, entry_id)) = custom_id.strip_prefix(PREFIX).and_then(parse) {
+        respond::component(ctx, db, component, Mode::Ephemeral, pick(db, component, entry_id, good))
+            .await;
+    } else if let Some((good, entry_id)) = custom_id.strip_prefix(PICK_PREFIX).and_then(parse) {
+        let reply = async {
+            Reply {
+                content: picked(db, component, entry_id, good).await,
+                components: Some(Vec::new()),
+                files: Vec::new(),
+            }
+        };
+        respond::component(ctx, db, component, Mode::Update, reply).await;
+    } else {
+        return false;
+    }
+    true
+}
+
+// The entry's results as stored, those of sources the server hides left out
+async fn results(
+    db: &SqlitePool,
+    component: &ComponentInteraction,
+    entry_id: &str,
+) -> Result<Vec<(String, Source)>, String> {
+    let (_, lookup) = db::entry(db, entry_id)
+        .await
+        .map_err(|why| {
+            error!("Error loading entry {}: {why:?}", entry_id);
+            "Could not load the results.".to_string()
+        })?
+        .ok_or("These results are no longer kept.")?;
+    let guild = answer::load_guild(db, component.guild_id).await;
+    Ok(lookup
+        .results
+        .into_iter()
+        .zip(lookup.sources)
+        .filter(|(_, source)| guild.shows(*source))
+        .collect())
+}
+
+// A picker option's value: the result's position, and a hash to tell
+// whether the entry still has the same result there
+fn option_value(idx: usize, result: &str) -> String {
+    format!("{}:{}", idx, &db::entry_hash(result)[..8])
+}
+
+// Ask which result the vote is for, or vote right away if there's only one
+async fn pick(
+    db: &SqlitePool,
+    component: &ComponentInteraction,
+    entry_id: &str,
+    good: bool,
+) -> Reply {
+    let results = match results(db, component, entry_id).await {
+        Ok(results) => results,
+        Err(err) => return err.into(),
+    };
+    match &results[..] {
+        [] => return "Nothing left to rate here.".to_string().into(),
+        [(result, source)] => {
+            return vote(db, component, entry_id, good, result, *source).await.into();
+        }
+        _ => {}
+    }
+
+    let options: Vec<CreateSelectMenuOption> = results
+        .iter()
+        .enumerate()
+        .take(25) // Discord allows at most 25 options
+        .map(|(idx, (result, source))| {
+            let text = widget::fields(result).text;
+            let label: String =
+                format!("{}: {}", source.name(), text).chars().take(100).collect();
+            CreateSelectMenuOption::new(label, option_value(idx, result))
+        })
+        .collect();
+    let decision = if good { "good" } else { "bad" };
+    let menu = CreateSelectMenu::new(
+        format!("{}{}:{}", PICK_PREFIX, decision, entry_id),
+        CreateSelectMenuKind::String { options },
+    )
+    .placeholder(if good { "Which result is right?" } else { "Which result is wrong?" });
+
+    Reply {
+        content: format!("{} Rate a result", if good { "👍" } else { "👎" }),
+        components: Some(vec![CreateActionRow::SelectMenu(menu)]),
+        files: Vec::new(),
+    }
+}
+
+async fn picked(
+    db: &SqlitePool,
+    component: &ComponentInteraction,
+    entry_id: &str,
+    good: bool,
+) -> String {
+    let ComponentInteractionDataKind::StringSelect { values } = &component.data.kind else {
+        return "No result selected.".to_string();
+    };
+    let Some((idx, _)) = values.first().and_then(|value| value.split_once(':')) else {
+        return "No result selected.".to_string();
+    };
+    let results = match results(db, component, entry_id).await {
+        Ok(results) => results,
+        Err(err) => return err,
+    };
+    let chosen = idx.parse::<usize>().ok().and_then(|idx| {
+        let (result, source) = results.get(idx)?;
+        values.contains(&option_value(idx, result)).then_some((result, *source))
+    });
+    match chosen {
+        Some((result, source)) => vote(db, component, entry_id, good, result, source).await,
+        None => "These results changed since, look the word up again to rate them.".to_string(),
+    }
+}
+
+async fn vote(
+    db: &SqlitePool,
+    component: &ComponentInteraction,
+    entry_id: &str,
+    good: bool,
+    result: &str,
+    source: Source,
+) -> String {
+    let user_id = component.user.id.to_string();
+    match db::vote_result(db, entry_id, result, source, &user_id, good).await {
+        Ok(()) if good => "👍 Thanks, results voted up are shown first.".to_string(),
+        Ok(()) => "👎 Thanks, results voted down are shown last.".to_string(),
+        Err(why) => {
+            error!("Error saving result vote: {why:?}");
+            "Could not save your vote.".to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(words: &[&str]) -> Vec<(String, Source)> {
+        words.iter().map(|word| (word.to_string(), Source::Moedict)).collect()
+    }
+
+    #[test]
+    fn rank_moves_voted_results() {
+        let mut ranked = results(&["a", "b", "c", "d"]);
+        let scores = HashMap::from([("c".to_string(), 2), ("a".to_string(), -1)]);
+        rank(&mut ranked, &scores);
+        assert_eq!(ranked, results(&["c", "b", "d", "a"]));
+    }
+
+    #[test]
+    fn rank_keeps_the_order_without_votes() {
+        let mut ranked = results(&["b", "a", "c"]);
+        rank(&mut ranked, &HashMap::new());
+        assert_eq!(ranked, results(&["b", "a", "c"]));
+        rank(&mut ranked, &HashMap::from([("z".to_string(), 3)]));
+        assert_eq!(ranked, results(&["b", "a", "c"]));
+    }
+}
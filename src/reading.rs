@@ -0,0 +1,131 @@
+use serde_json::Value;
+
+use crate::lookup::LookupPool;
+use crate::variant::Reading;
+
+// 文白異讀: many characters have a literary reading, used in compounds from
+// Classical Chinese, and a colloquial one, used in everyday words
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Literary,
+    Colloquial,
+}
+
+impl Kind {
+    pub fn parse(value: &str) -> Option<Kind> {
+        match value.trim().to_lowercase().as_str() {
+            "wenyan" | "literary" | "文" | "文讀" | "bun" => Some(Kind::Literary),
+            "colloquial" | "vernacular" | "白" | "白讀" | "peh" => Some(Kind::Colloquial),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Kind::Literary => "wenyan",
+            Kind::Colloquial => "colloquial",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Kind::Literary => "文讀",
+            Kind::Colloquial => "白讀",
+        }
+    }
+
+    // Moedict marks readings 文 or 白, and others 替, 俗 or 又 which are
+    // neither. Sutian's 文讀 and 白讀 labels read the same.
+    pub fn from_mark(mark: &str) -> Option<Kind> {
+        if mark.contains('文') {
+            Some(Kind::Literary)
+        } else if mark.contains('白') {
+            Some(Kind::Colloquial)
+        } else {
+            None
+        }
+    }
+}
+
+// The readings of every heteronym of a Moedict Taigi entry, labelled with
+// their marks
+pub fn parse(entry: &Value) -> Vec<Reading> {
+    let clean = |text: &str| text.replace(['`', '~'], "");
+    entry
+        .get("h")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|heteronym| {
+            Some(Reading {
+                text: clean(heteronym.get("T")?.as_str()?),
+                label: heteronym.get("reading").and_then(Value::as_str).map(clean),
+                accent: None,
+            })
+        })
+        .collect()
+}
+
+// Moedict's readings of `word`, fetched through the lookup pool
+pub async fn moedict(pool: &LookupPool, word: &str) -> Result<Vec<Reading>, String> {
+    Ok(pool
+        .moedict_json("t", word)
+        .await?
+        .map(|entry| parse(&entry))
+        .unwrap_or_default())
+}
+
+// The first reading of `word` of the kind asked for, None if Moedict marks
+// none that way
+pub async fn pick(pool: &LookupPool, word: &str, kind: Kind) -> Result<Option<String>, String> {
+    Ok(moedict(pool, word)
+        .await?
+        .into_iter()
+        .find(|reading| reading.kind() == Some(kind))
+        .map(|reading| reading.text))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parses_kinds() {
+        assert_eq!(Kind::parse("wenyan"), Some(Kind::Literary));
+        assert_eq!(Kind::parse(" 白讀 "), Some(Kind::Colloquial));
+        assert_eq!(Kind::parse("Colloquial"), Some(Kind::Colloquial));
+        assert_eq!(Kind::parse("替"), None);
+    }
+
+    #[test]
+    fn parses_moedict_heteronyms() {
+        let entry = json!({
+            "t": "人",
+            "h": [
+                {"T": "lâng", "reading": "白"},
+                {"T": "jîn", "reading": "文"},
+                {"T": "`lâng~", "reading": "替"},
+                {"T": "jîn"},
+                {"reading": "文"}
+            ]
+        });
+        let readings = parse(&entry);
+        let texts: Vec<&str> = readings.iter().map(|reading| reading.text.as_str()).collect();
+        assert_eq!(texts, ["lâng", "jîn", "lâng", "jîn"]);
+        let kinds: Vec<Option<Kind>> = readings.iter().map(Reading::kind).collect();
+        assert_eq!(kinds, [Some(Kind::Colloquial), Some(Kind::Literary), None, None]);
+        assert_eq!(readings[1].mark().as_deref(), Some("文讀"));
+        assert_eq!(readings[2].mark().as_deref(), Some("替"));
+        assert_eq!(readings[3].mark(), None);
+        assert!(parse(&json!({"t": "人"})).is_empty());
+    }
+
+    #[test]
+    fn sutian_labels_have_kinds() {
+        let readings = crate::variant::parse_readings("tsi̍t 文讀 it");
+        let kinds: Vec<Option<Kind>> = readings.iter().map(Reading::kind).collect();
+        assert_eq!(kinds, [None, Some(Kind::Literary)]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+
+use serenity::builder::CreateActionRow;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::browse;
+use crate::db;
+use crate::pinyin;
+use crate::shortcuts;
+
+// Related words fit in a single row of buttons
+const MAX_RELATED: usize = 5;
+// Answered keywords fetched per character of the word
+const PER_CHARACTER: u32 = 20;
+// Buttons kept for words of the same theme, even when more share characters
+const SAME_THEME: usize = 2;
+
+// Words sharing Hanji with `word`, from the bundled lexicon and what the bot
+// answered publicly before, those sharing the most characters first, then
+// the shortest. Words filed under the same theme (the semantic tags of the
+// /browse categories and the corpus) fill the rest of the row.
+pub async fn words(db: &SqlitePool, word: &str) -> Vec<String> {
+    let is_han = |c: &char| ('\u{4E00}'..='\u{9FFF}').contains(c);
+    let mut characters: Vec<char> = Vec::new();
+    for c in word.chars().filter(is_han) {
+        if !characters.contains(&c) {
+            characters.push(c);
+        }
+    }
+    if characters.is_empty() {
+        return Vec::new();
+    }
+
+    let mut candidates: Vec<String> = pinyin::words()
+        .filter(|candidate| candidate.chars().any(|c| characters.contains(&c)))
+        .map(str::to_string)
+        .collect();
+    for &character in &characters {
+        match db::answered_with(db, character, PER_CHARACTER).await {
+            Ok(answered) => candidates.extend(answered),
+            Err(why) => error!("Error loading answered words: {why:?}"),
+        }
+    }
+
+    let shared = |candidate: &str| characters.iter().filter(|c| candidate.contains(**c)).count();
+    candidates.sort_by(|a, b| {
+        shared(b)
+            .cmp(&shared(a))
+            .then(a.chars().count().cmp(&b.chars().count()))
+            .then(a.cmp(b))
+    });
+
+    let themed = same_theme(word);
+    let sharing = MAX_RELATED - themed.len().min(SAME_THEME);
+    let mut seen = HashSet::from([word.to_string()]);
+    let mut related = Vec::new();
+    for candidate in candidates {
+        if related.len() == sharing {
+            break;
+        }
+        if seen.insert(candidate.clone()) {
+            related.push(candidate);
+        }
+    }
+    for candidate in themed {
+        if related.len() == MAX_RELATED {
+            break;
+        }
+        if seen.insert(candidate.clone()) {
+            related.push(candidate);
+        }
+    }
+    related
+}
+
+// The other words of the themes `word` is filed under, in their order
+fn same_theme(word: &str) -> Vec<String> {
+    browse::categories()
+        .iter()
+        .filter(|category| category.entries.iter().any(|entry| entry.hanji == word))
+        .flat_map(|category| category.entries.iter())
+        .filter(|entry| entry.hanji != word)
+        .map(|entry| entry.hanji.clone())
+        .collect()
+}
+
+// One button per related word, each looking it up in a new reply
+pub fn buttons(words: &[String]) -> Option<CreateActionRow> {
+    let buttons: Vec<_> = words
+        .iter()
+        .filter_map(|word| shortcuts::lookup_button(word))
+        .collect();
+    (!buttons.is_empty()).then_some(CreateActionRow::Buttons(buttons))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_theme_lists_the_category() {
+        let themed = same_theme("飯");
+        assert!(themed.contains(&"糜".to_string()));
+        assert!(!themed.contains(&"飯".to_string()));
+        assert!(same_theme("不在分類").is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+use serenity::builder::{
+    CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage,
+    CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use serenity::model::application::{ButtonStyle, ComponentInteraction, ComponentInteractionDataKind};
+use serenity::model::id::{ChannelId, MessageId};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::context::Context;
+use crate::db::{self, Feedback};
+use crate::respond::{self, Mode, Reply};
+use crate::source::Source;
+
+const REPORT_PREFIX: &str = "report:";
+const PICK_PREFIX: &str = "report_pick:";
+
+// 🛑 button under a result message. The custom ID lists the source of each
+// result line in order (e.g. `report:TSIIM`) so the report can be attributed.
+pub fn report_button(sources: &[Source]) -> CreateButton {
+    let codes: String = sources.iter().map(|source| source.code()).collect();
+    CreateButton::new(format!("{}{}", REPORT_PREFIX, codes))
+        .style(ButtonStyle::Secondary)
+        .emoji('🛑')
+        .label("Report")
+}
+
+// Result lines follow the "Found N results" header line
+fn result_line(content: &str, idx: usize) -> Option<&str> {
+    content.lines().nth(idx + 1)
+}
+
+// Handle the report button and the follow-up result picker. Returns false
+// for components that aren't part of the report flow.
+pub async fn handle(
+    ctx: &Context,
+    db: &SqlitePool,
+    ops_channel: Option<ChannelId>,
+    component: &ComponentInteraction,
+) -> bool {
+    let custom_id = component.data.custom_id.as_str();
+
+    if let Some(codes) = custom_id.strip_prefix(REPORT_PREFIX) {
+        let response = pick_menu(component, codes);
+        if let Err(why) = component.create_response(&ctx.http, response).await {
+            error!("Cannot respond to report: {why:?}");
+        }
+    } else if let Some(message_id) = custom_id.strip_prefix(PICK_PREFIX) {
+        // Fetches the result message and notifies the ops channel first
+        let reply = async {
+            let content = match record(ctx, db, ops_channel, component, message_id).await {
+                Ok(()) => "🛑 Thanks, the maintainers have been notified.".to_string(),
+                Err(err) => format!("Could not record the report: {}", err),
+            };
+            Reply {
+                content,
+                components: Some(Vec::new()),
+                files: Vec::new(),
+            }
+        };
+        respond::component(ctx, db, component, Mode::Update, reply).await;
+    } else {
+        return false;
+    }
+    true
+}
+
+// Ask which of the results is wrong
+fn pick_menu(component: &ComponentInteraction, codes: &str) -> CreateInteractionResponse {
+    let options: Vec<CreateSelectMenuOption> = codes
+        .chars()
+        .enumerate()
+        .filter_map(|(idx, code)| {
+            let source = Source::from_code(code)?;
+            let line = result_line(&component.message.content, idx)?;
+            let label: String = format!("{}: {}", source.name(), line).chars().take(100).collect();
+            Some(CreateSelectMenuOption::new(label, format!("{}{}", code, idx)))
+        })
+        .take(25) // Discord allows at most 25 options
+        .collect();
+
+    let menu = CreateSelectMenu::new(
+        format!("{}{}", PICK_PREFIX, component.message.id),
+        CreateSelectMenuKind::String { options },
+    )
+    .placeholder("Which result is wrong?");
+
+    CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content("🛑 Report a bad result")
+            .components(vec![CreateActionRow::SelectMenu(menu)])
+            .ephemeral(true),
+    )
+}
+
+async fn record(
+    ctx: &Context,
+    db: &SqlitePool,
+    ops_channel: Option<ChannelId>,
+    component: &ComponentInteraction,
+    message_id: &str,
+) -> Result<(), String> {
+    let ComponentInteractionDataKind::StringSelect { values } = &component.data.kind else {
+        return Err("No result selected".to_string());
+    };
+    let value = values.first().ok_or("No result selected")?;
+    let source = value
+        .chars()
+        .next()
+        .and_then(Source::from_code)
+        .ok_or("Unknown source")?;
+    let idx: usize = value[1..].parse().map_err(|_| "Unknown result")?;
+
+    let message_id: u64 = message_id.parse().map_err(|_| "Unknown message")?;
+    let message = component
+        .channel_id
+        .message(&ctx.http, MessageId::new(message_id))
+        .await
+        .map_err(|_| "The result message is gone".to_string())?;
+
+    let output = result_line(&message.content, idx).ok_or("Unknown result")?;
+    let query = message
+        .referenced_message
+        .as_ref()
+        .map(|query| query.content.clone())
+        .unwrap_or_default();
+
+    let feedback = Feedback {
+        query,
+        source: source.name().to_string(),
+        output: output.to_string(),
+        reporter_id: component.user.id.to_string(),
+        channel_id: component.channel_id.to_string(),
+        message_id: message.id.to_string(),
+    };
+
+    db::insert_feedback(db, &feedback)
+        .await
+        .map_err(|_| "Error saving the report".to_string())?;
+
+    if let Some(ops) = ops_channel {
+        let notice = format!(
+            "🛑 {} reported a bad {} result in <#{}> for \"{}\":\n```\n{}\n```",
+            component.user.name, feedback.source, feedback.channel_id, feedback.query, feedback.output
+        );
+        if let Err(why) = ops.say(&ctx.http, notice).await {
+            error!("Error notifying ops channel: {why:?}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use std::future::Future;
+use std::time::Duration;
+
+use serenity::builder::{
+    CreateActionRow, CreateAttachment, CreateInteractionResponse,
+    CreateInteractionResponseMessage, EditInteractionResponse,
+};
+use serenity::model::application::{CommandInteraction, ComponentInteraction, ModalInteraction};
+use serenity::model::id::{GuildId, UserId};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer;
+use crate::context::Context;
+
+// Discord gives up on interactions that aren't answered within 3 seconds.
+// Handlers that wait on the network go through here: a reply ready in time
+// is sent directly, otherwise the interaction is deferred and edited later.
+// Deferring at 1.5s leaves the rest for the response to reach Discord.
+const GUARD: Duration = Duration::from_millis(1500);
+
+// Discord messages are limited to 2000 characters
+pub const MAX_MESSAGE: usize = 2000;
+
+// `content` cut to at most `max` characters, at the end of a line where
+// there is one, with … marking the cut
+pub fn fit(content: &str, max: usize) -> String {
+    if content.chars().count() <= max {
+        return content.to_string();
+    }
+    let kept: String = content.chars().take(max.saturating_sub(1)).collect();
+    let kept = match kept.rfind('\n') {
+        Some(end) if end > 0 => &kept[..end],
+        _ => kept.as_str(),
+    };
+    format!("{}…", kept.trim_end())
+}
+
+// `content` cut to what fits in a Discord message
+pub fn fit_message(content: &str) -> String {
+    fit(content, MAX_MESSAGE)
+}
+
+// What a handler answers with, its content rendered in the user's
+// romanization and cut to fit when sent
+pub struct Reply {
+    pub content: String,
+    // None leaves the components of an updated message as they are
+    pub components: Option<Vec<CreateActionRow>>,
+    // Attached to the reply, e.g. an image
+    pub files: Vec<CreateAttachment>,
+}
+
+impl From<String> for Reply {
+    fn from(content: String) -> Reply {
+        Reply {
+            content,
+            components: None,
+            files: Vec::new(),
+        }
+    }
+}
+
+impl Reply {
+    fn message(self) -> CreateInteractionResponseMessage {
+        let message = CreateInteractionResponseMessage::new()
+            .content(fit_message(&self.content))
+            .add_files(self.files);
+        match self.components {
+            Some(components) => message.components(components),
+            None => message,
+        }
+    }
+
+    fn edit(self) -> EditInteractionResponse {
+        let mut edit = EditInteractionResponse::new().content(fit_message(&self.content));
+        for file in self.files {
+            edit = edit.new_attachment(file);
+        }
+        match self.components {
+            Some(components) => edit.components(components),
+            None => edit,
+        }
+    }
+}
+
+// How a component is answered
+#[derive(Clone, Copy, PartialEq)]
+pub enum Mode {
+    // A new message only the clicker sees
+    Ephemeral,
+    // A new message everyone in the channel sees
+    Public,
+    // Replace the message the component is on
+    Update,
+}
+
+enum Target<'a> {
+    Command(&'a CommandInteraction),
+    Component(&'a ComponentInteraction),
+    Modal(&'a ModalInteraction),
+}
+
+impl Target<'_> {
+    fn asker(&self) -> (UserId, Option<GuildId>) {
+        match self {
+            Target::Command(command) => (command.user.id, command.guild_id),
+            Target::Component(component) => (component.user.id, component.guild_id),
+            Target::Modal(modal) => (modal.user.id, modal.guild_id),
+        }
+    }
+
+    async fn create(&self, ctx: &Context, response: CreateInteractionResponse) {
+        let result = match self {
+            Target::Command(command) => command.create_response(&ctx.http, response).await,
+            Target::Component(component) => component.create_response(&ctx.http, response).await,
+            Target::Modal(modal) => modal.create_response(&ctx.http, response).await,
+        };
+        if let Err(why) = result {
+            error!("Cannot respond to interaction: {why:?}");
+        }
+    }
+
+    async fn edit(&self, ctx: &Context, edit: EditInteractionResponse) {
+        let result = match self {
+            Target::Command(command) => command.edit_response(&ctx.http, edit).await.map(|_| ()),
+            Target::Component(component) => {
+                component.edit_response(&ctx.http, edit).await.map(|_| ())
+            }
+            Target::Modal(modal) => modal.edit_response(&ctx.http, edit).await.map(|_| ()),
+        };
+        if let Err(why) = result {
+            error!("Cannot edit interaction response: {why:?}");
+        }
+    }
+}
+
+async fn guarded<R: Into<Reply>>(
+    ctx: &Context,
+    db: &SqlitePool,
+    target: Target<'_>,
+    mode: Mode,
+    work: impl Future<Output = R>,
+) {
+    let (user_id, guild_id) = target.asker();
+    let work = async {
+        let mut reply = work.await.into();
+        let prefs = answer::load_prefs(db, user_id, guild_id).await;
+        reply.content = prefs.render(&reply.content);
+        reply
+    };
+    let mut work = std::pin::pin!(work);
+    match tokio::time::timeout(GUARD, &mut work).await {
+        Ok(reply) => {
+            let message = reply.message();
+            let response = match mode {
+                Mode::Ephemeral => CreateInteractionResponse::Message(message.ephemeral(true)),
+                Mode::Public => CreateInteractionResponse::Message(message),
+                Mode::Update => CreateInteractionResponse::UpdateMessage(message),
+            };
+            target.create(ctx, response).await;
+        }
+        Err(_) => {
+            let defer = match mode {
+                Mode::Ephemeral => CreateInteractionResponse::Defer(
+                    CreateInteractionResponseMessage::new().ephemeral(true),
+                ),
+                Mode::Public => {
+                    CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new())
+                }
+                Mode::Update => CreateInteractionResponse::Acknowledge,
+            };
+            target.create(ctx, defer).await;
+            let reply = work.await;
+            target.edit(ctx, reply.edit()).await;
+        }
+    }
+}
+
+// Answer a slash command with an ephemeral reply
+pub async fn command<R: Into<Reply>>(
+    ctx: &Context,
+    db: &SqlitePool,
+    command: &CommandInteraction,
+    work: impl Future<Output = R>,
+) {
+    guarded(ctx, db, Target::Command(command), Mode::Ephemeral, work).await;
+}
+
+// Answer a slash command with a reply everyone in the channel sees
+pub async fn public<R: Into<Reply>>(
+    ctx: &Context,
+    db: &SqlitePool,
+    command: &CommandInteraction,
+    work: impl Future<Output = R>,
+) {
+    guarded(ctx, db, Target::Command(command), Mode::Public, work).await;
+}
+
+pub async fn component<R: Into<Reply>>(
+    ctx: &Context,
+    db: &SqlitePool,
+    component: &ComponentInteraction,
+    mode: Mode,
+    work: impl Future<Output = R>,
+) {
+    guarded(ctx, db, Target::Component(component), mode, work).await;
+}
+
+// Answer a submitted form with an ephemeral reply
+pub async fn modal<R: Into<Reply>>(
+    ctx: &Context,
+    db: &SqlitePool,
+    modal: &ModalInteraction,
+    work: impl Future<Output = R>,
+) {
+    guarded(ctx, db, Target::Modal(modal), Mode::Ephemeral, work).await;
+}
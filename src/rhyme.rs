@@ -0,0 +1,99 @@
+use crate::ime::{self, Spelling};
+use crate::romanization;
+use crate::text;
+
+// Words rhyme when their last syllables share a final, what's left after the
+// initial: kun, sûn and tsûn all end in -un. Checked finals like -ah only
+// rhyme with each other, and POJ is spelt as Tâi-lô so o͘ rhymes with oo.
+
+pub struct Rhymes {
+    // The final rhymed on and the tone of the syllable asked about
+    pub rime: String,
+    pub tone: u8,
+    // Shortest words first, then the most common
+    pub words: Vec<&'static Spelling>,
+}
+
+// The final and tone of a word's last syllable
+fn ending(lomaji: &str) -> Option<(String, u8)> {
+    let (base, tone) = romanization::syllables(lomaji).pop()?;
+    let base = romanization::toneless(&base);
+    let (_, rime) = romanization::split_initial(&base);
+    Some((rime.to_string(), tone))
+}
+
+// Words of the local index rhyming with the last syllable of `syllable`,
+// ending in the same tone too with `same_tone`
+pub fn find(syllable: &str, same_tone: bool) -> Result<Rhymes, String> {
+    let romanized = text::nfd(syllable).chars().all(|c| {
+        c.is_ascii_alphabetic() || matches!(c, '\u{300}'..='\u{36F}' | 'ⁿ' | '-' | ' ')
+    });
+    let (rime, tone) = ending(syllable)
+        .filter(|_| romanized)
+        .ok_or_else(|| format!("\"{}\" isn't a Tâi-lô syllable, e.g. kûn", syllable.trim()))?;
+    let asked = text::nfc(syllable.trim()).to_lowercase();
+
+    let mut words: Vec<(usize, &Spelling)> = ime::spellings()
+        .filter(|spelling| text::nfc(&spelling.lomaji).to_lowercase() != asked)
+        .filter(|spelling| {
+            ending(&spelling.lomaji).is_some_and(|(found, found_tone)| {
+                found == rime && (!same_tone || found_tone == tone)
+            })
+        })
+        .map(|spelling| (spelling.tones.len(), spelling))
+        .collect();
+    words.sort_by(|(a_len, a), (b_len, b)| {
+        a_len
+            .cmp(b_len)
+            .then(b.count.cmp(&a.count))
+            .then_with(|| a.lomaji.cmp(&b.lomaji))
+    });
+    Ok(Rhymes {
+        rime,
+        tone,
+        words: words.into_iter().map(|(_, spelling)| spelling).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rhymes(syllable: &str, same_tone: bool) -> Vec<&'static str> {
+        let rhymes = find(syllable, same_tone).unwrap();
+        rhymes.words.iter().map(|spelling| spelling.lomaji.as_str()).collect()
+    }
+
+    #[test]
+    fn nasal_finals_without_an_initial() {
+        assert_eq!(romanization::split_initial("ng"), ("", "ng"));
+        assert_eq!(romanization::split_initial("m"), ("", "m"));
+        assert_eq!(romanization::split_initial("png"), ("p", "ng"));
+        assert_eq!(romanization::split_initial("hm"), ("h", "m"));
+        assert_eq!(romanization::split_initial("nga"), ("ng", "a"));
+        assert_eq!(ending("n̂g"), Some(("ng".to_string(), 5)));
+        assert_eq!(ending("m̄"), Some(("m".to_string(), 7)));
+        assert_eq!(find("n̂g", false).unwrap().rime, "ng");
+    }
+
+    #[test]
+    fn checked_finals_rhyme_apart() {
+        assert_eq!(romanization::split_initial("ngh"), ("", "ngh"));
+        assert_eq!(romanization::split_initial("tshiah"), ("tsh", "iah"));
+        assert_eq!(ending("tsia̍h"), Some(("iah".to_string(), 8)));
+        assert_eq!(ending("hngh"), Some(("ngh".to_string(), 4)));
+        assert_eq!(rhymes("kut", false), ["ku̍t"]);
+        assert!(!rhymes("kun", false).contains(&"kut"));
+    }
+
+    #[test]
+    fn finds_rhymes_in_the_index() {
+        let found = rhymes("kûn", false);
+        assert!(found.contains(&"kun"));
+        assert!(found.contains(&"kùn"));
+        assert!(!found.contains(&"kûn"));
+        assert_eq!(rhymes("kûn", true), Vec::<&str>::new());
+        assert_eq!(rhymes("tsûn", true), ["kûn"]);
+        assert!(find("食", false).is_err());
+    }
+}
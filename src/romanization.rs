@@ -0,0 +1,704 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::breakdown;
+use crate::emoji::EmojiSet;
+use crate::text::{self, DOT_ABOVE_RIGHT};
+
+// Taigi romanization systems. All sources return Tâi-lô, which can be
+// rewritten as Pe̍h-ōe-jī (POJ) or transcribed to IPA for display.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Romanization {
+    #[default]
+    Tailo,
+    Poj,
+}
+
+impl Romanization {
+    pub fn parse(value: &str) -> Option<Romanization> {
+        match value.trim().to_lowercase().as_str() {
+            "tailo" | "tâi-lô" | "tai-lo" | "台羅" | "臺羅" => Some(Romanization::Tailo),
+            "poj" | "pe̍h-ōe-jī" | "peh-oe-ji" | "白話字" => Some(Romanization::Poj),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Romanization::Tailo => "tailo",
+            Romanization::Poj => "poj",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Romanization::Tailo => "Tâi-lô",
+            Romanization::Poj => "POJ",
+        }
+    }
+
+    // A bare Tâi-lô reading in this romanization
+    pub fn write(self, tailo: &str) -> String {
+        match self {
+            Romanization::Tailo => tailo.to_string(),
+            Romanization::Poj => to_poj(tailo),
+        }
+    }
+}
+
+const ACUTE: char = '\u{301}';
+const GRAVE: char = '\u{300}';
+const CIRCUMFLEX: char = '\u{302}';
+const CARON: char = '\u{30C}';
+const MACRON: char = '\u{304}';
+const VERTICAL_LINE: char = '\u{30D}';
+const DOUBLE_ACUTE: char = '\u{30B}';
+
+// The tone marks and o͘'s dot. Other marks, like the ¨ of naïve or the ¸ of
+// façade, aren't Lô-jī.
+fn is_mark(c: char) -> bool {
+    matches!(
+        c,
+        ACUTE | GRAVE | CIRCUMFLEX | CARON | MACRON | VERTICAL_LINE | DOUBLE_ACUTE | DOT_ABOVE_RIGHT
+    )
+}
+
+// A precomposed letter with a tone mark, like á or ǹ
+fn is_precomposed(c: char) -> bool {
+    let decomposed = text::nfd(&c.to_string());
+    let mut chars = decomposed.chars();
+    chars.next().is_some_and(|base| base != c && base.is_ascii_alphabetic()) && chars.all(is_mark)
+}
+
+fn is_letter(c: char) -> bool {
+    c.is_ascii_alphabetic() || is_mark(c) || c == 'ⁿ' || is_precomposed(c)
+}
+
+// The letters Tâi-lô and POJ are written with: no d, f, q, v, w, x, y or z
+fn is_lomaji_letter(c: char) -> bool {
+    "abceghijklmnoprstu".contains(c.to_ascii_lowercase())
+}
+
+// Whether a word of ordinary chat looks like Tâi-lô or POJ: Lô-jī letters
+// and hyphens with at least one tone mark, ⁿ or o͘, and no more than one tone
+// per syllable, so café, naïve and résumé don't
+pub fn is_romanized(word: &str) -> bool {
+    let word = text::nfd(word);
+    let tones = |part: &str| part.chars().filter(|&c| is_mark(c) && c != DOT_ABOVE_RIGHT).count();
+    word.chars().all(|c| is_lomaji_letter(c) || is_mark(c) || c == 'ⁿ' || c == '-')
+        && word.chars().any(|c| is_mark(c) || c == 'ⁿ')
+        && word.split('-').all(|part| tones(part) <= 1)
+}
+
+// A syllable without its tone mark, lowercased, plus the mark and whether
+// it was capitalized
+struct Syllable {
+    base: String,
+    mark: Option<char>,
+    capitalized: bool,
+}
+
+impl Syllable {
+    fn parse(text: &str) -> Syllable {
+        let mut base = String::new();
+        let mut mark = None;
+        for c in text::nfd(text).chars() {
+            if is_mark(c) && c != DOT_ABOVE_RIGHT {
+                mark = Some(c);
+            } else {
+                base.push(c);
+            }
+        }
+        Syllable {
+            capitalized: base.starts_with(|c: char| c.is_uppercase()),
+            base: base.to_lowercase(),
+            mark,
+        }
+    }
+
+    // Tones 1-9, with unmarked checked syllables (-p/-t/-k/-h) being tone 4
+    fn tone(&self) -> u8 {
+        match self.mark {
+            Some(ACUTE) => 2,
+            Some(GRAVE) => 3,
+            Some(CIRCUMFLEX) => 5,
+            Some(CARON) => 6,
+            Some(MACRON) => 7,
+            Some(VERTICAL_LINE) => 8,
+            Some(DOUBLE_ACUTE) => 9,
+            _ if self.base.ends_with(['p', 't', 'k', 'h']) => 4,
+            _ => 1,
+        }
+    }
+}
+
+fn recompose(base: char, mark: char) -> String {
+    text::nfc(&format!("{}{}", base, mark))
+}
+
+// Rewrite the romanized syllables of `text` with `convert`, keeping hyphens,
+// spaces and any other characters as they are (in NFC)
+fn map_syllables(text: &str, mut convert: impl FnMut(&Syllable) -> String) -> String {
+    let mut output = String::new();
+    let mut word = String::new();
+    for c in text::nfc(text).chars().chain(std::iter::once('\0')) {
+        if is_letter(c) {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            output.push_str(&convert(&Syllable::parse(&word)));
+            word.clear();
+        }
+        if c != '\0' {
+            output.push(c);
+        }
+    }
+    output
+}
+
+fn poj_syllable(syllable: &Syllable) -> String {
+    let mut text = syllable.base.clone();
+    if let Some(rest) = text.strip_prefix("tsh") {
+        text = format!("chh{}", rest);
+    } else if let Some(rest) = text.strip_prefix("ts") {
+        text = format!("ch{}", rest);
+    }
+    text = text.replace("ua", "oa").replace("ue", "oe");
+    if let Some(stem) = text.strip_suffix("ing") {
+        text = format!("{}eng", stem);
+    } else if let Some(stem) = text.strip_suffix("ik") {
+        text = format!("{}ek", stem);
+    }
+    text = text.replace("nn", "ⁿ").replace("oo", "o\u{358}");
+
+    let mut chars: Vec<char> = text.chars().collect();
+    if syllable.capitalized
+        && let Some(first) = chars.first_mut()
+    {
+        *first = first.to_ascii_uppercase();
+    }
+
+    let Some(mark) = syllable.mark else {
+        return chars.into_iter().collect();
+    };
+
+    // POJ puts the tone on a, then o͘, e, o, u, i, and finally syllabic n/m,
+    // except that open oa and oe put it on the o: kòa, ōe, óaⁿ but oa̍h, koài
+    let position = |wanted: &dyn Fn(usize, char) -> bool| {
+        chars.iter().enumerate().position(|(idx, &c)| wanted(idx, c.to_ascii_lowercase()))
+    };
+    let open_o = |idx: usize, c: char| {
+        c == 'o'
+            && matches!(chars.get(idx + 1).map(char::to_ascii_lowercase), Some('a' | 'e'))
+            && chars[idx + 2..].iter().all(|&c| c == 'ⁿ')
+    };
+    let target = position(&open_o)
+        .or_else(|| position(&|_, c| c == 'a'))
+        .or_else(|| position(&|idx, c| c == 'o' && chars.get(idx + 1) == Some(&DOT_ABOVE_RIGHT)))
+        .or_else(|| position(&|_, c| c == 'e'))
+        .or_else(|| position(&|_, c| c == 'o'))
+        .or_else(|| position(&|_, c| c == 'u'))
+        .or_else(|| position(&|_, c| c == 'i'))
+        .or_else(|| position(&|_, c| c == 'n' || c == 'm'));
+
+    chars
+        .iter()
+        .enumerate()
+        .map(|(idx, &c)| {
+            if Some(idx) == target {
+                recompose(c, mark)
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+// Tâi-lô → Pe̍h-ōe-jī, e.g. `tsia̍h-pn̄g` → `chia̍h-pn̄g`, `ue̍h-lâng` → `oe̍h-lâng`
+pub fn to_poj(tailo: &str) -> String {
+    map_syllables(tailo, poj_syllable)
+}
+
+// What follows the initial of a Tâi-lô syllable: a vowel, or syllabic m or
+// ng, then any nasal, stop or nasalized ending
+const NUCLEI: &[&str] = &[
+    "a", "ai", "au", "e", "i", "ia", "iau", "io", "iu", "o", "oo", "u", "ua", "uai", "ue", "ui",
+    "m", "ng",
+];
+const ENDINGS: &[&str] = &["", "m", "n", "ng", "p", "t", "k", "h", "nn", "nnh"];
+
+fn is_tailo_syllable(syllable: &Syllable) -> bool {
+    let (_, rest) = split_initial(&syllable.base);
+    NUCLEI.iter().any(|nucleus| {
+        rest.strip_prefix(nucleus)
+            .is_some_and(|ending| ENDINGS.contains(&ending))
+    })
+}
+
+// Whether a word of rendered text is Tâi-lô: Tâi-lô syllables joined by
+// hyphens, with a tone mark, more than one syllable or a spelling only
+// Tâi-lô uses. Bare syllables like hue count too `after` Hanji or other
+// Tâi-lô, while English words like king or sue elsewhere are left alone.
+fn is_tailo_word(word: &str, after: bool) -> bool {
+    let parts: Vec<&str> = word.split('-').filter(|part| !part.is_empty()).collect();
+    let tones = |part: &str| {
+        text::nfd(part)
+            .chars()
+            .filter(|&c| is_mark(c) && c != DOT_ABOVE_RIGHT)
+            .count()
+    };
+    let lowercase = word.to_lowercase();
+    !parts.is_empty()
+        && parts
+            .iter()
+            .all(|part| tones(part) <= 1 && is_tailo_syllable(&Syllable::parse(part)))
+        && (after
+            || parts.len() > 1
+            || has_tone_marks(word)
+            || ["ts", "oo", "nn"].iter().any(|spelling| lowercase.contains(spelling)))
+}
+
+// Inline code like `/search tsi*`, mentions, custom emoji, URLs and the
+// ` /IPA/` added in pronunciations, which are never rewritten. Code blocks
+// are, as they hold laid out text like the /kinship tree.
+fn protected() -> &'static Regex {
+    static PROTECTED: OnceLock<Regex> = OnceLock::new();
+    PROTECTED.get_or_init(|| {
+        Regex::new(r"`[^`\n]+`|<[^<>\s]*>|https?://[^\s)>]*| /[^/\]\n]*/\]").unwrap()
+    })
+}
+
+// Every piece of text the bot sends goes through here: the Tâi-lô words of
+// rendered text, wherever they came from, in the wanted romanization
+pub fn render(text: &str, romanization: Romanization) -> String {
+    if romanization == Romanization::Tailo {
+        return text.to_string();
+    }
+    let text = text::nfc(text);
+    let rewrite = |segment: &str| {
+        let mut output = String::new();
+        let mut word = String::new();
+        // Whether Hanji or Tâi-lô came last, past spaces and markup
+        let mut after = false;
+        for c in segment.chars().chain(std::iter::once('\0')) {
+            if is_letter(c) || (c == '-' && !word.is_empty()) {
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty() {
+                after = is_tailo_word(&word, after);
+                if after {
+                    output.push_str(&romanization.write(&word));
+                } else {
+                    output.push_str(&word);
+                }
+                word.clear();
+            }
+            if breakdown::is_han(c) {
+                after = true;
+            } else if !c.is_whitespace() && !"*_([（「".contains(c) {
+                after = false;
+            }
+            if c != '\0' {
+                output.push(c);
+            }
+        }
+        output
+    };
+    let mut output = String::new();
+    let mut last = 0;
+    for kept in protected().find_iter(&text) {
+        output.push_str(&rewrite(&text[last..kept.start()]));
+        output.push_str(kept.as_str());
+        last = kept.end();
+    }
+    output.push_str(&rewrite(&text[last..]));
+    output
+}
+
+const INITIALS: &[(&str, &str)] = &[
+    ("tsh", "tsʰ"), ("ts", "ts"), ("ph", "pʰ"), ("th", "tʰ"), ("kh", "kʰ"), ("ng", "ŋ"),
+    ("p", "p"), ("b", "b"), ("m", "m"), ("t", "t"), ("n", "n"), ("l", "l"),
+    ("k", "k"), ("g", "ɡ"), ("h", "h"), ("s", "s"), ("j", "dz"),
+];
+
+const CODAS: &[(&str, &str)] = &[
+    ("ng", "ŋ"), ("m", "m"), ("n", "n"), ("p", "p̚"), ("t", "t̚"), ("k", "k̚"), ("h", "ʔ"),
+];
+
+// Taiwanese citation tones as Chao tone letters
+fn tone_letters(tone: u8) -> &'static str {
+    match tone {
+        1 => "˥",
+        2 => "˥˩",
+        3 => "˨˩",
+        4 => "˧˨",
+        5 | 6 => "˨˦",
+        7 => "˧",
+        8 => "˥",
+        _ => "˧˥",
+    }
+}
+
+// A toneless syllable as its initial and its final, e.g. `tsiah` → (`ts`,
+// `iah`) and `png` → (`p`, `ng`). Syllables like `ang` or `ng` have no initial.
+pub fn split_initial(base: &str) -> (&str, &str) {
+    INITIALS
+        .iter()
+        .find_map(|&(tailo, _)| base.strip_prefix(tailo).map(|rest| (tailo, rest)))
+        .filter(|(_, rest)| !rest.is_empty() && !rest.starts_with('h'))
+        .unwrap_or(("", base))
+}
+
+fn ipa_syllable(syllable: &Syllable) -> String {
+    let tone = tone_letters(syllable.tone());
+    let base = syllable.base.as_str();
+
+    let (initial, rest) = split_initial(base);
+    let initial = INITIALS
+        .iter()
+        .find(|(tailo, _)| *tailo == initial)
+        .map_or("", |(_, ipa)| ipa);
+
+    // Syllabic nasals: m̄, n̂g, pn̄g, hngh
+    match rest {
+        "m" => return format!("{}m̩{}", initial, tone),
+        "mh" => return format!("{}m̩ʔ{}", initial, tone),
+        "ng" => return format!("{}ŋ̍{}", initial, tone),
+        "ngh" => return format!("{}ŋ̍ʔ{}", initial, tone),
+        _ => {}
+    }
+
+    let nasal = rest.contains("nn");
+    let rest = rest.replace("nn", "");
+    let (nucleus, coda) = CODAS
+        .iter()
+        .find_map(|&(tailo, ipa)| rest.strip_suffix(tailo).map(|nucleus| (nucleus, ipa)))
+        .filter(|(nucleus, _)| !nucleus.is_empty())
+        .unwrap_or((rest.as_str(), ""));
+
+    // ts/tsh/s/j are palatalized before i
+    let initial = match (initial, nucleus.starts_with('i')) {
+        ("ts", true) => "tɕ",
+        ("tsʰ", true) => "tɕʰ",
+        ("s", true) => "ɕ",
+        ("dz", true) => "dʑ",
+        (initial, _) => initial,
+    };
+
+    let closed = !coda.is_empty() && coda != "ʔ";
+    let vowels = match nucleus {
+        "i" if coda == "ŋ" || coda == "k̚" => "iə".to_string(),
+        _ => nucleus
+            .replace("oo", "ɔ")
+            .replace("ir", "ɨ")
+            .replace("er", "ə")
+            .replace('o', if closed { "ɔ" } else { "ə" }),
+    };
+    let vowels: String = if nasal {
+        vowels.chars().flat_map(|c| [c, '\u{303}']).collect()
+    } else {
+        vowels
+    };
+
+    format!("{}{}{}{}", initial, vowels, coda, tone)
+}
+
+// A single syllable as its toneless spelling and tone number, e.g.
+// `kûn` → (`kun`, 5)
+pub fn tone(syllable: &str) -> (String, u8) {
+    let syllable = Syllable::parse(syllable);
+    let tone = syllable.tone();
+    (syllable.base, tone)
+}
+
+// Every syllable of `text` as its toneless spelling and tone, e.g.
+// `tsia̍h-pn̄g` → [(`tsiah`, 8), (`png`, 7)]
+pub fn syllables(text: &str) -> Vec<(String, u8)> {
+    let mut syllables = Vec::new();
+    map_syllables(text, |syllable| {
+        syllables.push((syllable.base.clone(), syllable.tone()));
+        String::new()
+    });
+    syllables
+}
+
+// `text` with the tone marks dropped and POJ's o͘ and ⁿ spelt oo and nn, so
+// searches can ignore them: `phōng`, `phòng` and `phong` are all `phong`
+pub fn toneless(text: &str) -> String {
+    map_syllables(text, |syllable| {
+        syllable.base.replace(DOT_ABOVE_RIGHT, "o").replace('ⁿ', "nn")
+    })
+}
+
+// Whether `text` has any tone mark. Without them the tones of `syllables`
+// are only guesses, since people often leave the marks out.
+pub fn has_tone_marks(text: &str) -> bool {
+    text::nfd(text).chars().any(|c| is_mark(c) && c != DOT_ABOVE_RIGHT)
+}
+
+// Tâi-lô → IPA in citation tones, e.g. `tsia̍h-pn̄g` → `tɕiaʔ˥ pŋ̍˧`
+pub fn to_ipa(tailo: &str) -> String {
+    let mut syllables = Vec::new();
+    map_syllables(tailo, |syllable| {
+        syllables.push(ipa_syllable(syllable));
+        String::new()
+    });
+    syllables.join(" ")
+}
+
+// Short guide to reading the tone marks and spelling of a system
+pub fn legend(romanization: Romanization) -> &'static str {
+    match romanization {
+        Romanization::Tailo => {
+            "❓ **Tâi-lô** tones, shown on the vowel:\n\
+             1 a (high) · 2 á (falling) · 3 à (low) · 4 ah (low checked)\n\
+             5 â (rising) · 7 ā (mid) · 8 a̍h (high checked)\n\
+             Tone 6 merged into tone 7. Checked syllables end in -h, -p, -t or -k.\n\
+             Spelling: ts/tsh like English j/ch, p/t/k are unaspirated and ph/th/kh aspirated, \
+             oo is the open o, nn marks a nasal vowel and a hyphen joins the syllables of one word."
+        }
+        Romanization::Poj => {
+            "❓ **Pe̍h-ōe-jī** tones, shown on the vowel:\n\
+             1 a (high) · 2 á (falling) · 3 à (low) · 4 ah (low checked)\n\
+             5 â (rising) · 7 ā (mid) · 8 a̍h (high checked)\n\
+             Tone 6 merged into tone 7. Checked syllables end in -h, -p, -t or -k.\n\
+             Spelling: ch/chh like English j/ch, p/t/k are unaspirated and ph/th/kh aspirated, \
+             o͘ is the open o, ⁿ marks a nasal vowel and a hyphen joins the syllables of one word."
+        }
+    }
+}
+
+// Sections of the full /legend cheat sheet as (title, text), comparing
+// both systems
+pub const CHEAT_SHEET: [(&str, &str); 5] = [
+    (
+        "Tones",
+        "1 a high level · 2 á high falling · 3 à low falling · 4 ah low checked\n\
+         5 â rising · 6 ǎ (merged into 7 in most accents) · 7 ā mid level\n\
+         8 a̍h high checked · 9 ő high rising (Lok-kang only)\n\
+         The mark goes on the main vowel; tones 1 and 4 have none.",
+    ),
+    (
+        "Tone sandhi",
+        "Every syllable but the last of a word changes tone: 1→7, 2→1, 3→2, 5→7 (3 in the \
+         south), 7→3, 4→8 and 8→4 (4→2 and 8→3 for -h). Dictionaries write the citation \
+         tone.\nA double hyphen `--` means the syllable after it is neutral.",
+    ),
+    (
+        "Consonants (Tâi-lô / POJ)",
+        "ts / ch and tsh / chh sound like English *j* / *ch*\n\
+         p t k unaspirated, ph th kh aspirated, b g voiced like Spanish\n\
+         j is a voiced ts, mostly l in the south\n\
+         Finals -p -t -k -h stop the syllable without releasing it.",
+    ),
+    (
+        "Vowels (Tâi-lô / POJ)",
+        "oo / o͘ open o as in *law*, o is a mid o as in *go*\n\
+         nn / ⁿ after the vowel makes it nasal, e.g. sann / saⁿ 三\n\
+         ua / oa, ue / oe, ing / eng, ik / ek are the same sounds spelt differently\n\
+         m and ng can be syllables by themselves, e.g. m̄ 毋, n̂g 黃",
+    ),
+    (
+        "Hyphens",
+        "Syllables of one word are joined with hyphens, e.g. tsia̍h-pn̄g 食飯.",
+    ),
+];
+
+// The `[pronunciation]`s of formatted results, without the `[name](url)`
+// links or any IPA added after them
+fn pronunciations(results: &[String]) -> Vec<&str> {
+    let mut found = Vec::new();
+    for result in results {
+        let mut rest = result.as_str();
+        while let Some(open) = rest.find('[') {
+            let Some(close) = rest[open..].find(']').map(|close| open + close) else {
+                break;
+            };
+            if !rest[close + 1..].starts_with('(') {
+                found.extend(rest[open + 1..close].split(" /").next());
+            }
+            rest = &rest[close + 1..];
+        }
+    }
+    found
+}
+
+// One line explaining the tones and symbols that appear in some formatted
+// results, e.g. `ℹ️ á tone 2 (falling) · nn nasal vowel`
+pub fn footnote(results: &[String], emoji: &EmojiSet) -> Option<String> {
+    let mut tones = [false; 10];
+    let mut nasal = None;
+    let mut open_o = None;
+    let mut neutral = false;
+    for pronunciation in pronunciations(results) {
+        map_syllables(pronunciation, |syllable| {
+            tones[syllable.tone() as usize] = true;
+            if syllable.base.contains('ⁿ') {
+                nasal = Some("ⁿ");
+            } else if syllable.base.ends_with("nn") || syllable.base.contains("nnh") {
+                nasal = Some("nn");
+            }
+            if syllable.base.contains(DOT_ABOVE_RIGHT) {
+                open_o = Some("o͘");
+            } else if syllable.base.contains("oo") {
+                open_o = Some("oo");
+            }
+            String::new()
+        });
+        neutral |= pronunciation.contains("--");
+    }
+
+    let mut notes: Vec<String> = [
+        (2, "á", "falling"),
+        (3, "à", "low"),
+        (4, "ah", "low checked"),
+        (5, "â", "rising"),
+        (6, "ǎ", "rising, rare"),
+        (7, "ā", "mid"),
+        (8, "a̍h", "high checked"),
+        (9, "ő", "high rising"),
+    ]
+    .into_iter()
+    .filter(|(tone, _, _)| tones[*tone])
+    .map(|(tone, example, contour)| {
+        let note = format!("{} tone {} ({})", example, tone, contour);
+        match emoji.tone(tone as u8) {
+            Some(emoji) => format!("{} {}", emoji, note),
+            None => note,
+        }
+    })
+    .collect();
+    if let Some(symbol) = nasal {
+        notes.push(format!("{} nasal vowel", symbol));
+    }
+    if let Some(symbol) = open_o {
+        notes.push(format!("{} open o", symbol));
+    }
+    if neutral {
+        notes.push("-- neutral tone after it".to_string());
+    }
+
+    if notes.is_empty() {
+        None
+    } else {
+        Some(format!("ℹ️ {} · `/legend` for more", notes.join(" · ")))
+    }
+}
+
+// Rewrite every `[pronunciation]` in a formatted result (but not the
+// `[name](url)` links) for the wanted romanization, optionally adding IPA.
+// Results come back in NFC whatever form the source used.
+pub fn apply(result: &str, romanization: Romanization, ipa: bool) -> String {
+    let result = text::nfc(result);
+    if romanization == Romanization::Tailo && !ipa {
+        return result;
+    }
+
+    let mut output = String::new();
+    let mut rest = result.as_str();
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find(']').map(|close| open + close) else {
+            break;
+        };
+        output.push_str(&rest[..=open]);
+        let inner = &rest[open + 1..close];
+        if rest[close + 1..].starts_with('(') {
+            output.push_str(inner);
+        } else {
+            match romanization {
+                Romanization::Tailo => output.push_str(inner),
+                Romanization::Poj => output.push_str(&to_poj(inner)),
+            }
+            let transcription = to_ipa(inner);
+            if ipa && !transcription.is_empty() {
+                output.push_str(&format!(" /{}/", transcription));
+            }
+        }
+        output.push(']');
+        rest = &rest[close + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Romanization, is_romanized, render, to_poj};
+
+    #[test]
+    fn recognizes_lomaji_in_chat() {
+        assert!(is_romanized("tsia̍h-pn̄g"));
+        assert!(is_romanized("Pe̍h-ōe-jī"));
+        assert!(is_romanized("kó͘"));
+        assert!(is_romanized("o͘"));
+        assert!(is_romanized("siⁿ"));
+        assert!(is_romanized("lí"));
+        assert!(!is_romanized("café"));
+        assert!(!is_romanized("naïve"));
+        assert!(!is_romanized("façade"));
+        assert!(!is_romanized("résumé"));
+        assert!(!is_romanized("hello"));
+        assert!(!is_romanized("tsiah-png"));
+    }
+
+    #[test]
+    fn marks_the_o_of_open_oa_and_oe() {
+        assert_eq!(to_poj("kuà"), "kòa");
+        assert_eq!(to_poj("uē"), "ōe");
+        assert_eq!(to_poj("Pe̍h-uē-jī"), "Pe̍h-ōe-jī");
+        assert_eq!(to_poj("hué"), "hóe");
+        assert_eq!(to_poj("uánn"), "óaⁿ");
+        assert_eq!(to_poj("Kuâ"), "Kôa");
+    }
+
+    #[test]
+    fn marks_the_second_vowel_before_a_final() {
+        assert_eq!(to_poj("ua̍h"), "oa̍h");
+        assert_eq!(to_poj("ue̍h-lâng"), "oe̍h-lâng");
+        assert_eq!(to_poj("kuài"), "koài");
+        assert_eq!(to_poj("kuân"), "koân");
+    }
+
+    #[test]
+    fn keeps_the_usual_order_elsewhere() {
+        assert_eq!(to_poj("tsia̍h-pn̄g"), "chia̍h-pn̄g");
+        assert_eq!(to_poj("tshù"), "chhù");
+        assert_eq!(to_poj("kóo"), "kó͘");
+        assert_eq!(to_poj("sing-lí"), "seng-lí");
+        assert_eq!(to_poj("hōo"), "hō͘");
+        assert_eq!(to_poj("tsiu"), "chiu");
+    }
+
+    #[test]
+    fn renders_tailo_in_text() {
+        let poj = |text| render(text, Romanization::Poj);
+        assert_eq!(poj("🍚 食飯 tsia̍h-pn̄g — to eat"), "🍚 食飯 chia̍h-pn̄g — to eat");
+        assert_eq!(poj("1. 多謝 to-siā, 2. 歹勢 pháinn-sè"), "1. 多謝 to-siā, 2. 歹勢 pháiⁿ-sè");
+        assert_eq!(poj("Tsiah. 講 kóng"), "Chiah. 講 kóng");
+        assert_eq!(poj("是 sī · 花 hue — flower"), "是 sī · 花 hoe — flower");
+        assert_eq!(poj("**寒** kuânn"), "**寒** kôaⁿ");
+        assert_eq!(poj("王 isn't in the list"), "王 isn't in the list");
+        assert_eq!(
+            poj("[tsia̍h](https://sutian.moe.edu.tw/tsiah)"),
+            "[chia̍h](https://sutian.moe.edu.tw/tsiah)"
+        );
+        assert_eq!(poj("<:tsiah:123> <#456>"), "<:tsiah:123> <#456>");
+        assert_eq!(poj("`tsi*` ```\ntsia̍h\n```"), "`tsi*` ```\nchia̍h\n```");
+        assert_eq!(poj("[tsu /tsu˥/]"), "[chu /tsu˥/]");
+    }
+
+    #[test]
+    fn leaves_english_and_poj_alone() {
+        let poj = |text| render(text, Romanization::Poj);
+        assert_eq!(poj("king sing sue ok"), "king sing sue ok");
+        assert_eq!(poj("Pe̍h-ōe-jī chia̍h kòa"), "Pe̍h-ōe-jī chia̍h kòa");
+        assert_eq!(poj("café naïve résumé"), "café naïve résumé");
+        // Text already written in POJ comes through unchanged
+        let written = "1. 烏仔 o͘-á 2. 花 hoe 3. 歹勢 pháiⁿ-sè 4. 食 chia̍h";
+        assert_eq!(poj(written), written);
+        assert_eq!(render("tsia̍h", Romanization::Tailo), "tsia̍h");
+    }
+}
@@ -0,0 +1,153 @@
+// The operator's script run over the results of each lookup before they are
+// shown, to hide, reorder or annotate them per deployment. It defines
+//
+//     fn results(keyword, results) { … }
+//
+// which gets the results as maps with `index`, `source`, `hanji`, `lomaji`,
+// `text`, `url`, `contributor` (iTaigi's) and `note`, and returns the ones to
+// show in order. Results are matched back by `index`, and a `note` set on one
+// is added to its line.
+#[cfg(feature = "scripting")]
+use rhai::{AST, Array, Dynamic, Engine, Map, Scope};
+use tracing::error;
+
+use crate::opslog::{self, Kind};
+use crate::source::Source;
+#[cfg(feature = "scripting")]
+use crate::widget;
+
+// Work a script may do per lookup, far more than filtering a page of results
+// takes, so a runaway loop fails instead of holding up the reply
+#[cfg(feature = "scripting")]
+const MAX_OPERATIONS: u64 = 200_000;
+
+pub struct Hook {
+    // The script as read, to tell whether a reload changed it
+    source: String,
+    #[cfg(feature = "scripting")]
+    engine: Engine,
+    #[cfg(feature = "scripting")]
+    ast: AST,
+}
+
+impl Hook {
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    // Read and compile the script at `path`
+    #[cfg(feature = "scripting")]
+    pub fn load(path: &str) -> Result<Hook, String> {
+        let source =
+            std::fs::read_to_string(path).map_err(|why| format!("cannot read {}: {}", path, why))?;
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(32);
+        let ast = engine
+            .compile(&source)
+            .map_err(|why| format!("{}: {}", path, why))?;
+        if !ast.iter_functions().any(|function| function.name == "results") {
+            return Err(format!("{} doesn't define fn results(keyword, results)", path));
+        }
+        Ok(Hook {
+            source,
+            engine,
+            ast,
+        })
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    pub fn load(_path: &str) -> Result<Hook, String> {
+        Err("the bot was built without the scripting feature".to_string())
+    }
+
+    // The results to show, as the script has them. Left as they were when the
+    // script fails.
+    pub fn process(&self, keyword: &str, results: Vec<(String, Source)>) -> Vec<(String, Source)> {
+        match self.run(keyword, &results) {
+            Ok(processed) => processed,
+            Err(err) => {
+                error!("Error running the results script: {}", err);
+                opslog::report(Kind::ScriptFailure, err);
+                results
+            }
+        }
+    }
+
+    #[cfg(feature = "scripting")]
+    fn run(
+        &self,
+        keyword: &str,
+        results: &[(String, Source)],
+    ) -> Result<Vec<(String, Source)>, String> {
+        let given: Array = results
+            .iter()
+            .enumerate()
+            .map(|(idx, (line, source))| Dynamic::from_map(structured(idx, line, *source)))
+            .collect();
+        let returned: Array = self
+            .engine
+            .call_fn(&mut Scope::new(), &self.ast, "results", (keyword.to_string(), given))
+            .map_err(|why| why.to_string())?;
+
+        let mut shown = Vec::new();
+        for item in returned {
+            let Some(map) = item.try_cast::<Map>() else {
+                return Err("results() must return the result maps it was given".to_string());
+            };
+            let Some((line, source)) = map
+                .get("index")
+                .and_then(|index| index.as_int().ok())
+                .and_then(|index| results.get(usize::try_from(index).ok()?))
+            else {
+                continue;
+            };
+            let note = map
+                .get("note")
+                .and_then(|note| note.clone().into_string().ok())
+                .filter(|note| !note.trim().is_empty());
+            match note {
+                Some(note) => shown.push((format!("{} 📝 {}", line, note.trim()), *source)),
+                None => shown.push((line.clone(), *source)),
+            }
+        }
+        Ok(shown)
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn run(
+        &self,
+        _keyword: &str,
+        results: &[(String, Source)],
+    ) -> Result<Vec<(String, Source)>, String> {
+        Ok(results.to_vec())
+    }
+}
+
+// What the script sees of a result line
+#[cfg(feature = "scripting")]
+fn structured(index: usize, line: &str, source: Source) -> Map {
+    let fields = widget::fields(line);
+    let text = |value: Option<&str>| value.map_or(Dynamic::UNIT, |value| value.into());
+    let mut map = Map::new();
+    map.insert("index".into(), (index as i64).into());
+    map.insert("source".into(), source.name().into());
+    map.insert("hanji".into(), text(fields.hanji));
+    map.insert("lomaji".into(), text(fields.lomaji));
+    map.insert("text".into(), fields.text.into());
+    map.insert("url".into(), text(fields.url));
+    map.insert("contributor".into(), text(contributor(line, source)));
+    map.insert("note".into(), "".into());
+    map
+}
+
+// Who added an iTaigi entry, from its `by <name> - ` part
+#[cfg(feature = "scripting")]
+fn contributor(line: &str, source: Source) -> Option<&str> {
+    if source != Source::ITaigi {
+        return None;
+    }
+    let (_, rest) = line.rsplit_once(" by ")?;
+    let (name, _) = rest.split_once(" - ")?;
+    Some(name.trim())
+}
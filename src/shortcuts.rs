@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use serenity::builder::{
+    CreateAttachment, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage,
+    EditInteractionResponse,
+};
+use serenity::model::application::{ButtonStyle, ComponentInteraction};
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer::{self, Answer, Asker};
+use crate::audio;
+use crate::config::Config;
+use crate::context::Context;
+use crate::db;
+use crate::lookup::LookupPool;
+use crate::query::SearchOptions;
+use crate::respond;
+use crate::romanization;
+use crate::variant::Accent;
+
+const PREFIX: &str = "shortcut:";
+
+// Discord limits custom IDs to 100 characters
+const MAX_CUSTOM_ID: usize = 100;
+
+// 🔊 audio, 💾 save, ➕ more results and ❓ romanization guide under every
+// result. The custom ID carries the looked up word, e.g.
+// `shortcut:more:漳州:睡覺`, since the reply may be ephemeral.
+pub fn buttons(keyword: &str, variant: Option<Accent>) -> Vec<CreateButton> {
+    let variant = variant.map_or("", Accent::label);
+    let mut buttons = Vec::new();
+    for (action, emoji, label) in [
+        ("audio", '🔊', "Audio"),
+        ("save", '💾', "Save"),
+        ("more", '➕', "More"),
+    ] {
+        let custom_id = format!("{}{}:{}:{}", PREFIX, action, variant, keyword);
+        if custom_id.len() <= MAX_CUSTOM_ID {
+            buttons.push(
+                CreateButton::new(custom_id)
+                    .style(ButtonStyle::Secondary)
+                    .emoji(emoji)
+                    .label(label),
+            );
+        }
+    }
+    buttons.push(
+        CreateButton::new(format!("{}legend::", PREFIX))
+            .style(ButtonStyle::Secondary)
+            .emoji('❓')
+            .label("Romanization"),
+    );
+    buttons
+}
+
+// A button looking `word` up in a new reply, for related words
+pub fn lookup_button(word: &str) -> Option<CreateButton> {
+    let custom_id = format!("{}lookup::{}", PREFIX, word);
+    (custom_id.len() <= MAX_CUSTOM_ID).then(|| {
+        CreateButton::new(custom_id)
+            .style(ButtonStyle::Primary)
+            .label(word)
+    })
+}
+
+// A button playing the recording of `word`, for lists of phrases
+pub fn audio_button(word: &str, label: &str) -> Option<CreateButton> {
+    let custom_id = format!("{}audio::{}", PREFIX, word);
+    (custom_id.len() <= MAX_CUSTOM_ID).then(|| {
+        CreateButton::new(custom_id)
+            .style(ButtonStyle::Secondary)
+            .emoji('🔊')
+            .label(label)
+    })
+}
+
+// Handle a shortcut button. Returns false for other components.
+pub async fn handle(
+    ctx: &Context,
+    db: &SqlitePool,
+    pool: &LookupPool,
+    config: &Arc<Config>,
+    component: &ComponentInteraction,
+) -> bool {
+    let Some(rest) = component.data.custom_id.strip_prefix(PREFIX) else {
+        return false;
+    };
+    let mut parts = rest.splitn(3, ':');
+    let (Some(action), Some(variant), Some(keyword)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    let variant = Accent::parse(variant);
+
+    match action {
+        "save" => {
+            let content = match db::save_word(db, &component.user.id.to_string(), keyword).await {
+                Ok(true) => format!("💾 Saved {} to your word list, see `/saved`.", keyword),
+                Ok(false) => format!("💾 {} is already in your word list.", keyword),
+                Err(why) => {
+                    error!("Error saving word: {why:?}");
+                    "Could not save the word.".to_string()
+                }
+            };
+            reply(ctx, component, content).await;
+        }
+        "legend" => {
+            let prefs = answer::load_prefs(db, component.user.id, component.guild_id).await;
+            let legend = romanization::legend(prefs.romanization.unwrap_or_default());
+            reply(ctx, component, legend.to_string()).await;
+        }
+        "audio" | "more" | "lookup" => {
+            // Both need another request, which may take longer than Discord waits
+            let defer = CreateInteractionResponseMessage::new().ephemeral(true);
+            if let Err(why) = component
+                .create_response(&ctx.http, CreateInteractionResponse::Defer(defer))
+                .await
+            {
+                error!("Cannot defer shortcut: {why:?}");
+                return true;
+            }
+
+            let edit = if action == "audio" {
+                match audio::moedict(keyword).await {
+                    Ok(Some(audio)) => EditInteractionResponse::new()
+                        .content(format!("🔊 {}", keyword))
+                        .new_attachment(CreateAttachment::bytes(audio.bytes, audio.filename)),
+                    Ok(None) => EditInteractionResponse::new()
+                        .content(format!("🔇 No recording found for {}.", keyword)),
+                    Err(err) => EditInteractionResponse::new()
+                        .content(format!("Could not fetch the recording: {}", err)),
+                }
+            } else {
+                let asker = Asker {
+                    user_id: component.user.id,
+                    channel_id: component.channel_id,
+                    guild_id: component.guild_id,
+                    prefs: answer::load_prefs(db, component.user.id, component.guild_id).await,
+                    guild: answer::load_guild(db, component.guild_id).await,
+                };
+                // Answered ephemerally
+                let options = SearchOptions {
+                    variant,
+                    private: true,
+                    more: action == "more",
+                    ..SearchOptions::default()
+                };
+                let content = match answer::answer(db, pool, config, &asker, keyword, &options).await {
+                    Answer::Breakdown { content, .. }
+                    | Answer::Found { content, .. }
+                    | Answer::Failed(content)
+                    | Answer::NotFound { content, .. } => content,
+                };
+                EditInteractionResponse::new().content(respond::fit_message(&content))
+            };
+
+            if let Err(why) = component.edit_response(&ctx.http, edit).await {
+                error!("Cannot respond to shortcut: {why:?}");
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+async fn reply(ctx: &Context, component: &ComponentInteraction, content: String) {
+    let data = CreateInteractionResponseMessage::new()
+        .content(content)
+        .ephemeral(true);
+    let builder = CreateInteractionResponse::Message(data);
+    if let Err(why) = component.create_response(&ctx.http, builder).await {
+        error!("Cannot respond to shortcut: {why:?}");
+    }
+}
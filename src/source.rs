@@ -0,0 +1,654 @@
+use std::collections::HashMap;
+
+use scraper::{Html, Selector};
+use serde_json::Value;
+
+use crate::corpus::Sentence;
+use crate::direction::Direction;
+use crate::itaigi::VoteTarget;
+use crate::opslog::{self, Kind};
+use crate::plugin;
+use crate::upstream;
+use crate::variant::{self, Accent};
+
+// The dictionaries every query is searched in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Source {
+    TaigiTv,
+    Sutian,
+    ITaigi,
+    Moedict,
+    // Every loaded plugin, see `plugin`
+    Plugins,
+    // The bundled Taiwan place names, see `places`
+    Places,
+}
+
+impl Source {
+    pub const ALL: [Source; 6] = [
+        Source::TaigiTv,
+        Source::Sutian,
+        Source::ITaigi,
+        Source::Moedict,
+        Source::Plugins,
+        Source::Places,
+    ];
+
+    // The sources on this bot, leaving out Plugins when none are loaded
+    pub fn available() -> impl Iterator<Item = Source> {
+        Source::ALL
+            .into_iter()
+            .filter(|source| *source != Source::Plugins || plugin::any())
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Source::TaigiTv => "TaigiTV",
+            Source::Sutian => "Sutian",
+            Source::ITaigi => "iTaigi",
+            Source::Moedict => "Moedict",
+            Source::Plugins => "Plugins",
+            Source::Places => "Places",
+        }
+    }
+
+    // Single-letter code used to fit per-result sources into custom IDs
+    pub fn code(self) -> char {
+        match self {
+            Source::TaigiTv => 'T',
+            Source::Sutian => 'S',
+            Source::ITaigi => 'I',
+            Source::Moedict => 'M',
+            Source::Plugins => 'P',
+            Source::Places => 'L',
+        }
+    }
+
+    // None for plugins, which each have their own site, and the bundled
+    // place names
+    pub fn home_url(self) -> Option<&'static str> {
+        match self {
+            Source::TaigiTv => Some("https://www.taigitv.org.tw"),
+            Source::Sutian => Some("https://sutian.moe.edu.tw"),
+            Source::ITaigi => Some("https://itaigi.tw"),
+            Source::Moedict => Some("https://www.moedict.tw"),
+            Source::Plugins | Source::Places => None,
+        }
+    }
+
+    pub fn from_code(code: char) -> Option<Source> {
+        match code {
+            'T' => Some(Source::TaigiTv),
+            'S' => Some(Source::Sutian),
+            'I' => Some(Source::ITaigi),
+            'M' => Some(Source::Moedict),
+            'P' => Some(Source::Plugins),
+            'L' => Some(Source::Places),
+            _ => None,
+        }
+    }
+}
+
+// Whether a page has an element the result selectors start from, telling a
+// page without results from one whose layout changed
+fn has_match(document: &Html, selector: &str) -> bool {
+    Selector::parse(selector).is_ok_and(|selector| document.select(&selector).next().is_some())
+}
+
+pub async fn search_taigitv(keyword: &str, limit: usize) -> Result<Vec<String>, String> {
+    let search_url = format!(
+        "https://www.taigitv.org.tw/taigi-words?keyword={}",
+        urlencoding::encode(keyword)
+    );
+
+    let response_text = match upstream::client().get(&search_url).send().await {
+        Ok(response) => match upstream::text(response).await {
+            Ok(text) => text,
+            Err(_) => return Err("Error reading response from TaigiTV".to_string()),
+        },
+        Err(_) => return Err("Error fetching from TaigiTV".to_string()),
+    };
+    parse_taigitv(&response_text, limit)
+}
+
+// Result lines from a TaigiTV search page
+pub fn parse_taigitv(page: &str, limit: usize) -> Result<Vec<String>, String> {
+    let document = Html::parse_document(page);
+
+    // Fixed selectors for TaigiTV
+    let link_selector = Selector::parse(".btngaa .h3 a")
+        .map_err(|_| "Could not parse TaigiTV selector".to_string())?;
+
+    // Extract results
+    let results: Vec<String> = document
+        .select(&link_selector)
+        .filter_map(|element| {
+            let text = element.text().collect::<String>().trim().to_string();
+            let url = element.value().attr("href").map(|href| {
+                if href.starts_with("http") {
+                    href.to_string()
+                } else if href.starts_with("/") {
+                    format!("https://www.taigitv.org.tw{}", href)
+                } else {
+                    format!("https://www.taigitv.org.tw/{}", href)
+                }
+            });
+
+            url.map(|u| format!("📺 {} - [台語新詞辭庫]({})", text, u))
+        })
+        .take(limit)
+        .collect();
+
+    if results.is_empty() && has_match(&document, ".btngaa") {
+        opslog::report(Kind::SelectorMismatch, "TaigiTV: result cards without \".h3 a\" links");
+    }
+
+    Ok(results)
+}
+
+// Format one Sutian row, surfacing 又唸作/俗唸作 and regional readings
+fn format_sutian_result(
+    link_element: scraper::ElementRef,
+    pronunciation_element: scraper::ElementRef,
+    explanation: Option<String>,
+    variant: Option<Accent>,
+) -> Option<String> {
+    let word = link_element.text().collect::<String>().trim().to_string();
+    let href = link_element.value().attr("href").unwrap_or("");
+    let mut readings =
+        variant::parse_readings(&pronunciation_element.text().collect::<String>());
+
+    if word.is_empty() || readings.is_empty() {
+        return None;
+    }
+
+    if let Some(accent) = variant {
+        variant::prefer(&mut readings, accent);
+    }
+
+    let full_url = if href.starts_with("http") {
+        href.to_string()
+    } else if href.starts_with("/") {
+        format!("https://sutian.moe.edu.tw{}", href)
+    } else {
+        format!("https://sutian.moe.edu.tw/{}", href)
+    };
+
+    let variants: Vec<String> = readings[1..]
+        .iter()
+        .map(|reading| match &reading.label {
+            Some(label) => format!("{} {}", label, reading.display()),
+            None => reading.display(),
+        })
+        .collect();
+
+    // The Mandarin explanation, only listed when searching Taigi words
+    let explanation = explanation
+        .map(|text| format!(" {}", text))
+        .unwrap_or_default();
+
+    if variants.is_empty() {
+        Some(format!(
+            "📚 {} [{}]{} - [教育部臺灣台語常用詞辭典]({})",
+            word,
+            readings[0].display(),
+            explanation,
+            full_url
+        ))
+    } else {
+        Some(format!(
+            "📚 {} [{}] ({}){} - [教育部臺灣台語常用詞辭典]({})",
+            word,
+            readings[0].display(),
+            variants.join("；"),
+            explanation,
+            full_url
+        ))
+    }
+}
+
+pub async fn search_sutian(
+    keyword: &str,
+    variant: Option<Accent>,
+    direction: Direction,
+    limit: usize,
+) -> Result<Vec<String>, String> {
+    // Mandarin words are searched among the 華語詞, Taigi ones among the 台語詞目
+    let lui = match direction {
+        Direction::MandarinToTaigi => "hua_su",
+        Direction::TaigiToMandarin => "tai_su",
+    };
+    let search_url = format!(
+        "https://sutian.moe.edu.tw/zh-hant/tshiau/?lui={}&tsha={}",
+        lui,
+        urlencoding::encode(keyword)
+    );
+
+    let response_text = match upstream::client().get(&search_url).send().await {
+        Ok(response) => match upstream::text(response).await {
+            Ok(text) => text,
+            Err(_) => return Err("Error reading response from Sutian".to_string()),
+        },
+        Err(_) => return Err("Error fetching from Sutian".to_string()),
+    };
+    parse_sutian(&response_text, variant, direction, limit)
+}
+
+// Result lines from a Sutian search page
+pub fn parse_sutian(
+    page: &str,
+    variant: Option<Accent>,
+    direction: Direction,
+    limit: usize,
+) -> Result<Vec<String>, String> {
+    let document = Html::parse_document(page);
+
+    // Selectors for Sutian - extracting from both mobile and desktop tables
+    let mobile_link_selector = Selector::parse("table.d-md-none tbody tr:nth-child(2) td a")
+        .map_err(|_| "Could not parse Sutian mobile selector".to_string())?;
+    let desktop_link_selector =
+        Selector::parse("table.d-none.d-md-table tbody tr td:nth-child(2) a")
+            .map_err(|_| "Could not parse Sutian desktop selector".to_string())?;
+
+    let mobile_pronunciation_selector = Selector::parse("table.d-md-none tbody tr:nth-child(3) td")
+        .map_err(|_| "Could not parse Sutian mobile pronunciation selector".to_string())?;
+    let desktop_pronunciation_selector =
+        Selector::parse("table.d-none.d-md-table tbody tr td:nth-child(3)")
+            .map_err(|_| "Could not parse Sutian desktop pronunciation selector".to_string())?;
+    let mobile_explanation_selector = Selector::parse("table.d-md-none tbody tr:nth-child(4) td")
+        .map_err(|_| "Could not parse Sutian mobile explanation selector".to_string())?;
+    let desktop_explanation_selector =
+        Selector::parse("table.d-none.d-md-table tbody tr td:nth-child(4)")
+            .map_err(|_| "Could not parse Sutian desktop explanation selector".to_string())?;
+
+    // Rows don't always have an explanation, so they're matched by position
+    let explanations = |selector: &Selector| -> Vec<String> {
+        if direction == Direction::MandarinToTaigi {
+            return Vec::new();
+        }
+        document
+            .select(selector)
+            .map(|cell| {
+                let text = cell.text().collect::<String>();
+                text.split_whitespace().collect::<Vec<_>>().join(" ")
+            })
+            .collect()
+    };
+
+    // Try mobile table first
+    let mobile_explanations = explanations(&mobile_explanation_selector);
+    let mut results: Vec<String> = document
+        .select(&mobile_link_selector)
+        .zip(document.select(&mobile_pronunciation_selector))
+        .enumerate()
+        .filter_map(|(idx, (link_element, pronunciation_element))| {
+            let explanation =
+                mobile_explanations.get(idx).filter(|text| !text.is_empty()).cloned();
+            format_sutian_result(link_element, pronunciation_element, explanation, variant)
+        })
+        .take(limit)
+        .collect();
+
+    // If no mobile results, try desktop table
+    if results.is_empty() {
+        let desktop_explanations = explanations(&desktop_explanation_selector);
+        results = document
+            .select(&desktop_link_selector)
+            .zip(document.select(&desktop_pronunciation_selector))
+            .enumerate()
+            .filter_map(|(idx, (link_element, pronunciation_element))| {
+                let explanation =
+                    desktop_explanations.get(idx).filter(|text| !text.is_empty()).cloned();
+                format_sutian_result(link_element, pronunciation_element, explanation, variant)
+            })
+            .take(limit)
+            .collect();
+    }
+
+    let rows = "table.d-md-none tbody tr, table.d-none.d-md-table tbody tr";
+    if results.is_empty() && has_match(&document, rows) {
+        opslog::report(
+            Kind::SelectorMismatch,
+            "Sutian: result tables without the expected word and pronunciation cells",
+        );
+    }
+
+    Ok(results)
+}
+
+pub async fn search_itaigi(
+    keyword: &str,
+    limit: usize,
+) -> Result<(Vec<String>, Vec<VoteTarget>), String> {
+    let search_url = format!(
+        "https://itaigi.tw/平臺項目列表/揣列表?關鍵字={}",
+        urlencoding::encode(keyword)
+    );
+
+    let response_text = match upstream::client().get(&search_url).send().await {
+        Ok(response) => match upstream::text(response).await {
+            Ok(text) => text,
+            Err(_) => return Err("Error reading response from iTaigi".to_string()),
+        },
+        Err(_) => return Err("Error fetching from iTaigi".to_string()),
+    };
+    parse_itaigi(&response_text, keyword, limit)
+}
+
+// Result lines and votable entries from an iTaigi search response
+pub fn parse_itaigi(
+    response: &str,
+    keyword: &str,
+    limit: usize,
+) -> Result<(Vec<String>, Vec<VoteTarget>), String> {
+    let json: Value = match serde_json::from_str(response) {
+        Ok(json) => json,
+        Err(_) => return Err("Error parsing JSON from iTaigi".to_string()),
+    };
+
+    let mut results = Vec::new();
+    let mut vote_targets = Vec::new();
+
+    // Parse the 列表 array
+    if let Some(list) = json.get("列表").and_then(|v| v.as_array()) {
+        for item in list.iter().take(limit) {
+            // Get 外語資料 (foreign word)
+            let foreign_word = item
+                .get("外語資料")
+                .and_then(|v| v.as_str())
+                .unwrap_or("N/A");
+
+            // Get the first 新詞文本 entry if available
+            if let Some(new_word_list) = item.get("新詞文本").and_then(|v| v.as_array())
+                && let Some(first_entry) = new_word_list.first()
+            {
+                let taigi_text = first_entry
+                    .get("文本資料")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("N/A");
+                let pronunciation = first_entry
+                    .get("音標資料")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("N/A");
+                let contributor = first_entry
+                    .get("貢獻者")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("匿名");
+                let good_votes = first_entry
+                    .get("按呢講好")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(0);
+                let bad_votes = first_entry
+                    .get("按呢無好")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(0);
+
+                // Create iTaigi URL
+                let itaigi_url = format!("https://itaigi.tw/k/{}", foreign_word);
+
+                // Remember the entry so it can be voted on from Discord
+                if let Some(entry_id) = first_entry.get("新詞文本項目編號").map(|v| match v {
+                    Value::String(id) => id.clone(),
+                    other => other.to_string(),
+                }) {
+                    vote_targets.push(VoteTarget {
+                        entry_id,
+                        label: format!("{} → {}", foreign_word, taigi_text),
+                    });
+                }
+
+                results.push(format!(
+                    "🏷️ {} → {} [{}] (👍{} 👎{}) by {} - [iTaigi 愛台語]({})",
+                    foreign_word,
+                    taigi_text,
+                    pronunciation,
+                    good_votes,
+                    bad_votes,
+                    contributor,
+                    itaigi_url
+                ));
+            }
+        }
+    }
+
+    // If no results from 列表, check 其他建議
+    if results.is_empty()
+        && let Some(suggestions) = json.get("其他建議").and_then(|v| v.as_array())
+    {
+        for suggestion in suggestions.iter().take(limit) {
+            let taigi_text = suggestion
+                .get("文本資料")
+                .and_then(|v| v.as_str())
+                .unwrap_or("N/A");
+            let pronunciation = suggestion
+                .get("音標資料")
+                .and_then(|v| v.as_str())
+                .unwrap_or("N/A");
+
+            // Get associated foreign words if available
+            let mut foreign_words = Vec::new();
+            if let Some(foreign_list) = suggestion
+                .get("按呢講的外語列表")
+                .and_then(|v| v.as_array())
+            {
+                for foreign_item in foreign_list.iter().take(2) {
+                    if let Some(foreign_word) =
+                        foreign_item.get("外語資料").and_then(|v| v.as_str())
+                    {
+                        foreign_words.push(foreign_word);
+                    }
+                }
+            }
+
+            let foreign_display = if foreign_words.is_empty() {
+                keyword.to_string()
+            } else {
+                foreign_words.join(", ")
+            };
+
+            results.push(format!(
+                "🏷️ {} → {} [{}] (建議) - [iTaigi 愛台語](https://itaigi.tw)",
+                foreign_display, taigi_text, pronunciation
+            ));
+        }
+    }
+
+    Ok((results, vote_targets))
+}
+
+// Result lines, with the part of speech tags of those Moedict gives them for
+pub async fn search_moedict(keyword: &str, limit: usize) -> Result<Moedicted, String> {
+    let search_url = format!(
+        "https://www.moedict.tw/t/{}.json",
+        urlencoding::encode(keyword)
+    );
+
+    let response_text = match upstream::client().get(&search_url).send().await {
+        Ok(response) => {
+            if response.status().is_success() {
+                match upstream::text(response).await {
+                    Ok(text) => text,
+                    Err(_) => return Err("Error reading response from Moedict".to_string()),
+                }
+            } else {
+                return Ok(Default::default()); // No results found, but not an error
+            }
+        },
+        Err(_) => return Err("Error fetching from Moedict".to_string()),
+    };
+    parse_moedict(&response_text, keyword, limit)
+}
+
+// Result lines from a Moedict entry, and the tags of each line that has some
+pub type Moedicted = (Vec<String>, HashMap<String, Vec<String>>);
+
+pub fn parse_moedict(entry: &str, keyword: &str, limit: usize) -> Result<Moedicted, String> {
+    let json: Value = match serde_json::from_str(entry) {
+        Ok(json) => json,
+        Err(_) => return Err("Error parsing JSON from Moedict".to_string()),
+    };
+
+    let mut results = Vec::new();
+    let mut tags = HashMap::new();
+
+    // Helper function to clean up formatting markers
+    let clean_text = |text: &str| -> String {
+        text.replace("`", "").replace("~", "")
+    };
+
+    // Get the main term and clean it up
+    let main_term = json.get("t")
+        .and_then(|v| v.as_str())
+        .map(clean_text)
+        .unwrap_or_else(|| keyword.to_string());
+
+    // Parse the heteronyms (h array)
+    if let Some(heteronyms) = json.get("h").and_then(|v| v.as_array()) {
+        for (idx, heteronym) in heteronyms.iter().enumerate() {
+            if idx >= limit { break; } // Limit heteronyms to avoid spam
+
+            // Get pronunciation
+            let pronunciation = heteronym.get("T")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+
+            // Get definitions
+            if let Some(definitions) = heteronym.get("d").and_then(|v| v.as_array()) {
+                for (def_idx, definition) in definitions.iter().enumerate() {
+                    if def_idx >= 1 { break; } // Only show first definition per heteronym
+
+                    let part_of_speech = definition.get("type")
+                        .and_then(|v| v.as_str())
+                        .map(clean_text)
+                        .unwrap_or_else(|| "".to_string());
+
+                    let definition_text = definition.get("f")
+                        .and_then(|v| v.as_str())
+                        .map(clean_text)
+                        .unwrap_or_else(|| "無定義".to_string());
+
+                    // Create user-readable URL
+                    let moedict_url = format!("https://www.moedict.org/#'{}", 
+                        urlencoding::encode(keyword));
+
+                    // Format the result
+                    let formatted_result = if !pronunciation.is_empty() && !part_of_speech.is_empty() {
+                        format!(
+                            "📖 {} [{}] ({}) {} - [萌典]({})",
+                            main_term, 
+                            pronunciation, 
+                            part_of_speech,
+                            definition_text,
+                            moedict_url
+                        )
+                    } else if !pronunciation.is_empty() {
+                        format!(
+                            "📖 {} [{}] {} - [萌典]({})",
+                            main_term, 
+                            pronunciation, 
+                            definition_text,
+                            moedict_url
+                        )
+                    } else {
+                        format!(
+                            "📖 {} {} - [萌典]({})",
+                            main_term, 
+                            definition_text,
+                            moedict_url
+                        )
+                    };
+
+                    // Some words have several, like `動、名`
+                    let line_tags: Vec<String> = part_of_speech
+                        .split(['、', ',', '，'])
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                    if !line_tags.is_empty() {
+                        tags.insert(formatted_result.clone(), line_tags);
+                    }
+                    results.push(formatted_result);
+                }
+            }
+        }
+    }
+
+    Ok((results, tags))
+}
+
+// A Moedict entry as JSON, from its Taigi (`t`) or Mandarin (`a`)
+// dictionary. None when it has no entry for `word`.
+pub async fn moedict_json(dictionary: &str, word: &str) -> Result<Option<Value>, String> {
+    let url = format!(
+        "https://www.moedict.tw/{}/{}.json",
+        dictionary,
+        urlencoding::encode(word)
+    );
+    let response = upstream::client()
+        .get(&url)
+        .send()
+        .await
+        .map_err(|_| "Error fetching from Moedict".to_string())?;
+    if !response.status().is_success() {
+        return Ok(None);
+    }
+    let text = upstream::text(response)
+        .await
+        .map_err(|_| "Error reading response from Moedict".to_string())?;
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|_| "Error parsing JSON from Moedict".to_string())
+}
+
+// Example sentences given with Moedict's definitions. They are stored as
+// interlinear annotations: \u{FFF9}Hanji\u{FFFA}Lô-jī\u{FFFB}Mandarin.
+pub async fn moedict_examples(keyword: &str, limit: usize) -> Result<Vec<Sentence>, String> {
+    let url = format!("https://www.moedict.tw/t/{}.json", urlencoding::encode(keyword));
+    let response = upstream::client()
+        .get(&url)
+        .send()
+        .await
+        .map_err(|_| "Error fetching from Moedict".to_string())?;
+    if !response.status().is_success() {
+        return Ok(Vec::new());
+    }
+    let text = upstream::text(response)
+        .await
+        .map_err(|_| "Error reading response from Moedict".to_string())?;
+    let json: Value = serde_json::from_str(&text)
+        .map_err(|_| "Error parsing JSON from Moedict".to_string())?;
+
+    let examples = json
+        .get("h")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|heteronym| heteronym.get("d")?.as_array())
+        .flatten()
+        .filter_map(|definition| definition.get("e")?.as_array())
+        .flatten()
+        .filter_map(Value::as_str);
+
+    let mut sentences = Vec::new();
+    for example in examples {
+        let example = example.replace(['`', '~'], "");
+        let example = example.trim_start_matches('\u{FFF9}');
+        let (hanji, rest) = example.split_once('\u{FFFA}').unwrap_or((example, ""));
+        let (lomaji, mandarin) = rest.split_once('\u{FFFB}').unwrap_or((rest, ""));
+        if hanji.trim().is_empty() {
+            continue;
+        }
+        sentences.push(Sentence {
+            hanji: hanji.trim().to_string(),
+            lomaji: lomaji.trim().to_string(),
+            mandarin: mandarin.trim().to_string(),
+            english: String::new(),
+            category: String::new(),
+        });
+        if sentences.len() >= limit {
+            break;
+        }
+    }
+    Ok(sentences)
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use serenity::builder::{
+    CreateActionRow, CreateButton, CreateInteractionResponse, EditInteractionResponse,
+};
+use serenity::model::application::{ButtonStyle, ComponentInteraction};
+use serenity::model::channel::MessageFlags;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::answer::{self, Answer, Asker};
+use crate::config::Config;
+use crate::context::Context;
+use crate::lookup::LookupPool;
+use crate::pinyin;
+use crate::query::SearchOptions;
+use crate::variant::Accent;
+
+const PREFIX: &str = "didyoumean:";
+
+// Suggestions fit in a single row of buttons
+const MAX_SUGGESTIONS: usize = 5;
+
+// Number of single-character insertions, deletions or substitutions
+// between two words
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// Lexicon words one edit away from a Mandarin query that found nothing,
+// e.g. 蘋菓 → 蘋果
+pub fn suggest(query: &str) -> Vec<&'static str> {
+    let query: Vec<char> = query.chars().collect();
+    // Single characters have too many neighbours to be useful
+    let is_han = |c: &char| ('\u{4E00}'..='\u{9FFF}').contains(c);
+    if query.len() < 2 || !query.iter().all(is_han) {
+        return Vec::new();
+    }
+
+    let mut suggestions: Vec<&'static str> = pinyin::words()
+        .filter(|word| {
+            let word: Vec<char> = word.chars().collect();
+            word.len().abs_diff(query.len()) <= 1 && edit_distance(&query, &word) == 1
+        })
+        .collect();
+    suggestions.sort_unstable();
+    suggestions.dedup();
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+// One button per suggestion, custom ID `didyoumean:<accent>:<word>`
+pub fn buttons(suggestions: &[&str], variant: Option<Accent>) -> Vec<CreateActionRow> {
+    if suggestions.is_empty() {
+        return Vec::new();
+    }
+    let variant = variant.map_or("", Accent::label);
+    vec![CreateActionRow::Buttons(
+        suggestions
+            .iter()
+            .map(|word| {
+                CreateButton::new(format!("{}{}:{}", PREFIX, variant, word))
+                    .style(ButtonStyle::Primary)
+                    .label(*word)
+            })
+            .collect(),
+    )]
+}
+
+// Re-run the lookup with the picked suggestion, replacing the not found
+// message. Returns false for other components.
+pub async fn handle(
+    ctx: &Context,
+    db: &SqlitePool,
+    pool: &LookupPool,
+    config: &Arc<Config>,
+    component: &ComponentInteraction,
+) -> bool {
+    let Some(rest) = component.data.custom_id.strip_prefix(PREFIX) else {
+        return false;
+    };
+    let Some((variant, word)) = rest.split_once(':') else {
+        return false;
+    };
+
+    if let Err(why) = component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+    {
+        error!("Cannot acknowledge suggestion: {why:?}");
+        return true;
+    }
+
+    let asker = Asker {
+        user_id: component.user.id,
+        channel_id: component.channel_id,
+        guild_id: component.guild_id,
+        prefs: answer::load_prefs(db, component.user.id, component.guild_id).await,
+        guild: answer::load_guild(db, component.guild_id).await,
+    };
+    // The answer replaces the not found message, ephemeral or not
+    let ephemeral = component
+        .message
+        .flags
+        .is_some_and(|flags| flags.contains(MessageFlags::EPHEMERAL));
+    let options = SearchOptions {
+        variant: Accent::parse(variant),
+        private: ephemeral,
+        ..SearchOptions::default()
+    };
+    let answer = answer::answer(db, pool, config, &asker, word, &options).await;
+
+    // Ephemeral messages can't be reported, see Answer::components
+    let components = answer.components(config, !ephemeral);
+    let content = match answer {
+        Answer::Breakdown { content, .. }
+        | Answer::Found { content, .. }
+        | Answer::Failed(content)
+        | Answer::NotFound { content, .. } => content,
+    };
+
+    let edit = EditInteractionResponse::new()
+        .content(content)
+        .components(components);
+    if let Err(why) = component.edit_response(&ctx.http, edit).await {
+        error!("Cannot respond to suggestion: {why:?}");
+    }
+    true
+}
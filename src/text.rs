@@ -0,0 +1,110 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+// Lô-jī comes precomposed (á), with combining marks (a + ◌́) or a mix of
+// both, depending on the keyboard or the site it was copied from. Searches,
+// conversions and answers all go through `nfc` so the forms compare equal.
+
+// The dot of POJ's o͘, which has no precomposed form
+pub const DOT_ABOVE_RIGHT: char = '\u{358}';
+
+// What people type for that dot when their keyboard has none: a middle dot
+// (o·), the Greek ano teleia that looks the same, or a dot above (ȯ)
+const DOT_LOOKALIKES: [char; 3] = ['\u{B7}', '\u{387}', '\u{307}'];
+
+// Text in NFC, with the stand-ins for o͘'s dot replaced by the real one. Only
+// h and ⁿ follow o͘ in a syllable, so a dot before other letters (Foo·bar) is
+// left as it is.
+pub fn nfc(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    let mut chars: Vec<char> = text.nfd().collect();
+    for idx in 1..chars.len() {
+        if !DOT_LOOKALIKES.contains(&chars[idx]) {
+            continue;
+        }
+        // The tone mark of ó͘ can come between the o and its dot
+        let letter = chars[..idx].iter().rev().find(|c| !is_combining_mark(**c));
+        let marks_only = chars[..idx]
+            .iter()
+            .rev()
+            .take_while(|c| is_combining_mark(**c))
+            .all(|c| *c != DOT_ABOVE_RIGHT);
+        let next = chars[idx + 1..].iter().find(|c| !is_combining_mark(**c));
+        let ends_syllable =
+            next.is_none_or(|c| !c.is_alphabetic() || matches!(c, 'h' | 'H' | 'ⁿ'));
+        if matches!(letter, Some('o' | 'O')) && marks_only && ends_syllable {
+            chars[idx] = DOT_ABOVE_RIGHT;
+        }
+    }
+    // Recomposing also puts the marks back in canonical order, tone mark first
+    chars.into_iter().nfc().collect()
+}
+
+// Text with every mark split from its letter, e.g. `á` → `a` + `◌́`
+pub fn nfd(text: &str) -> String {
+    nfc(text).nfd().collect()
+}
+
+// The letter under a precomposed letter's marks, e.g. `ǹ` → `n`
+pub fn base(c: char) -> char {
+    c.nfd().next().unwrap_or(c)
+}
+
+// Escape text for HTML and XML content and attributes
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nfc_composes_and_orders_marks() {
+        assert_eq!(nfc("a\u{301}"), "á");
+        assert_eq!(nfc("tsia\u{30D}h"), "tsia̍h");
+        // The tone mark goes before the dot whichever was typed first
+        assert_eq!(nfc("o\u{358}\u{301}"), "ó\u{358}");
+        assert_eq!(nfc("ó\u{358}"), "ó\u{358}");
+        assert_eq!(nfc("ascii"), "ascii");
+    }
+
+    #[test]
+    fn nfc_replaces_dot_lookalikes_after_o() {
+        assert_eq!(nfc("o·"), "o\u{358}");
+        assert_eq!(nfc("O\u{387}"), "O\u{358}");
+        assert_eq!(nfc("ȯ"), "o\u{358}");
+        assert_eq!(nfc("ó·"), "ó\u{358}");
+        assert_eq!(nfc("ho·-tô"), "ho\u{358}-tô");
+        assert_eq!(nfc("o·h"), "o\u{358}h");
+        assert_eq!(nfc("o·ⁿ"), "o\u{358}ⁿ");
+    }
+
+    #[test]
+    fn nfc_leaves_other_dots() {
+        assert_eq!(nfc("Foo·bar"), "Foo·bar");
+        assert_eq!(nfc("a·b"), "a·b");
+        assert_eq!(nfc("tâi · gí"), "tâi · gí");
+        // A second dot after o͘ isn't another o͘
+        assert_eq!(nfc("o\u{358}·"), "o\u{358}·");
+    }
+
+    #[test]
+    fn nfd_splits_marks() {
+        assert_eq!(nfd("á"), "a\u{301}");
+        assert_eq!(nfd("o·"), "o\u{358}");
+    }
+
+    #[test]
+    fn base_strips_marks() {
+        assert_eq!(base('ǹ'), 'n');
+        assert_eq!(base('â'), 'a');
+        assert_eq!(base('a'), 'a');
+        assert_eq!(base('食'), '食');
+    }
+}
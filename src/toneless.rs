@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use crate::ime;
+use crate::romanization;
+use crate::source::Source;
+use crate::text;
+use crate::widget;
+
+// Searching `phong` finds phong in every tone: results read like the query,
+// tone marks aside, come first and grouped by tone, and a line sums up the
+// tones found, from the results and the local index of spellings.
+
+// Spellings taken from the local index, and Hanji listed per tone
+const MAX_LOCAL: usize = 10;
+const MAX_HANJI: usize = 3;
+
+// A romanized query without tone marks, like `phong` or `tsiah png`
+pub fn is_toneless(query: &str) -> bool {
+    query.chars().any(|c| c.is_ascii_alphabetic())
+        && query
+            .chars()
+            .all(|c| c.is_ascii_alphabetic() || matches!(c, '-' | ' ' | 'ⁿ' | '\u{358}'))
+}
+
+// The reading of a result that is read like `key` and its tones, None for
+// other results. Some sources list several readings, split by / or ,
+fn reading<'a>(result: &'a str, key: &str) -> Option<(&'a str, Vec<u8>)> {
+    let lomaji = widget::fields(result).lomaji?;
+    lomaji.split(['/', ',', '、']).find_map(|reading| {
+        let syllables = romanization::syllables(reading);
+        let tones = syllables.iter().map(|(_, tone)| *tone).collect();
+        (ime::key(&syllables) == key).then_some((reading.trim(), tones))
+    })
+}
+
+// The index key of a toneless query, None for any other query
+fn query_key(query: &str) -> Option<String> {
+    if !is_toneless(query) {
+        return None;
+    }
+    Some(ime::key(&romanization::syllables(query))).filter(|key| !key.is_empty())
+}
+
+// Put the results read like a toneless query first, by tone. Stable, so the
+// votes still order each group and the other results keep their order.
+pub fn order(query: &str, results: &mut [(String, Source)]) {
+    let Some(key) = query_key(query) else {
+        return;
+    };
+    results.sort_by_cached_key(|(result, _)| match reading(result, &key) {
+        Some((_, tones)) => (false, tones),
+        None => (true, Vec::new()),
+    });
+}
+
+// The tones a toneless query is read in, e.g. `phóng (2) 紡 · phòng (3) 放`,
+// None for other queries or if no reading is known
+pub fn summary(query: &str, results: &[(String, Source)]) -> Option<String> {
+    let key = query_key(query)?;
+    // Tones -> the reading as first seen and its Hanji
+    let mut groups: BTreeMap<Vec<u8>, (String, Vec<String>)> = BTreeMap::new();
+    let mut add = |tones: Vec<u8>, reading: &str, hanji: Option<&str>| {
+        let (_, spellings) = groups
+            .entry(tones)
+            .or_insert_with(|| (text::nfc(reading), Vec::new()));
+        if let Some(hanji) = hanji
+            && spellings.len() < MAX_HANJI
+            && !spellings.iter().any(|known| known == hanji)
+        {
+            spellings.push(hanji.to_string());
+        }
+    };
+    for (result, _) in results {
+        if let Some((found, tones)) = reading(result, &key) {
+            add(tones, found, widget::fields(result).hanji);
+        }
+    }
+    for spelling in ime::candidates(query, MAX_LOCAL) {
+        add(spelling.tones.clone(), &spelling.lomaji, Some(&spelling.hanji));
+    }
+    if groups.is_empty() {
+        return None;
+    }
+
+    let groups: Vec<String> = groups
+        .into_iter()
+        .map(|(tones, (reading, hanji))| {
+            let tones: Vec<String> = tones.iter().map(u8::to_string).collect();
+            let reading = format!("{} ({})", reading, tones.join("-"));
+            if hanji.is_empty() {
+                reading
+            } else {
+                format!("{} {}", reading, hanji.join("、"))
+            }
+        })
+        .collect();
+    Some(groups.join(" · "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(lines: &[&str]) -> Vec<(String, Source)> {
+        lines.iter().map(|line| (line.to_string(), Source::Sutian)).collect()
+    }
+
+    #[test]
+    fn toneless_queries() {
+        assert!(is_toneless("phong"));
+        assert!(is_toneless("tsiah-png"));
+        assert!(is_toneless("o\u{358}"));
+        assert!(!is_toneless("phòng"));
+        assert!(!is_toneless("放"));
+        assert!(!is_toneless("-"));
+    }
+
+    #[test]
+    fn order_groups_by_tone() {
+        let mut found = results(&[
+            "📚 放 [pàng] put",
+            "📚 芳 [phang] fragrant",
+            "📚 紡 [phóng] spin",
+            "📚 膨 [phòng] swell",
+            "📚 蜂 [phang] bee",
+        ]);
+        order("phong", &mut found);
+        let hanji: Vec<&str> =
+            found.iter().filter_map(|(result, _)| widget::fields(result).hanji).collect();
+        assert_eq!(hanji, ["紡", "膨", "放", "芳", "蜂"]);
+    }
+
+    #[test]
+    fn order_leaves_other_queries() {
+        let mut found = results(&["📚 膨 [phòng] swell", "📚 紡 [phóng] spin"]);
+        order("phòng", &mut found);
+        assert_eq!(found, results(&["📚 膨 [phòng] swell", "📚 紡 [phóng] spin"]));
+    }
+
+    #[test]
+    fn summary_lists_the_tones() {
+        let found = results(&[
+            "📚 膨 [phòng] swell",
+            "📚 紡 [phóng] spin",
+            "📚 捧 [phóng] hold",
+            "📚 芳 [phang] fragrant",
+        ]);
+        assert_eq!(
+            summary("phong", &found).as_deref(),
+            Some("phóng (2) 紡、捧 · phòng (3) 膨")
+        );
+        assert_eq!(summary("phòng", &found), None);
+        assert_eq!(summary("phong", &results(&["📚 芳 [phang] fragrant"])), None);
+    }
+}
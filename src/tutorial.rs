@@ -0,0 +1,238 @@
+use serenity::builder::{CreateActionRow, CreateButton};
+use serenity::model::application::{ButtonStyle, ComponentInteraction};
+use serenity::model::id::UserId;
+use sqlx::SqlitePool;
+use tracing::error;
+
+use crate::context::Context;
+use crate::db;
+use crate::respond::{self, Mode, Reply};
+
+const PREFIX: &str = "tutorial:";
+
+// A question checking the step was understood, answered with tone buttons
+struct Quiz {
+    hanji: &'static str,
+    tailo: &'static str,
+    tone: u8,
+    // Why, shown with a wrong answer
+    hint: &'static str,
+}
+
+struct Step {
+    title: &'static str,
+    text: &'static str,
+    quiz: Option<Quiz>,
+}
+
+const STEPS: [Step; 8] = [
+    Step {
+        title: "Welcome",
+        text: "This bot looks words up in Taiwanese (Taigi) dictionaries: TaigiTV, Sutian, \
+               iTaigi and Moedict. This walkthrough shows how to read the romanization in the \
+               results, how to ask the bot and how to keep words to study.\n\
+               Your place is kept, so `/tutorial` later carries on from here.",
+        quiz: None,
+    },
+    Step {
+        title: "Tâi-lô and its tones",
+        text: "Results spell words in Tâi-lô, the romanization taught in Taiwan's schools. \
+               Taigi has 7 tones in most accents, numbered 1 to 8 (6 has merged into 7), \
+               and the mark over the main vowel tells them apart:\n\
+               1 **a** high level · 2 **á** high falling · 3 **à** low falling\n\
+               5 **â** rising · 7 **ā** mid level\n\
+               Tones 1 and 4 have no mark, so *kun* 君 is tone 1.",
+        quiz: Some(Quiz {
+            hanji: "滾",
+            tailo: "kún",
+            tone: 2,
+            hint: "the ´ mark is the high falling tone 2",
+        }),
+    },
+    Step {
+        title: "Checked tones",
+        text: "Syllables ending in **-p -t -k -h** are cut short without releasing the \
+               consonant. They only come in two tones: 4 is low and unmarked (*kut* 骨), \
+               8 is high and marked with a vertical line (*ku̍t* 滑).",
+        quiz: Some(Quiz {
+            hanji: "滑",
+            tailo: "ku̍t",
+            tone: 8,
+            hint: "a checked syllable with the vertical line is the high tone 8",
+        }),
+    },
+    Step {
+        title: "Tone sandhi",
+        text: "In speech every syllable but the last of a word changes tone, e.g. 1→7, \
+               2→1, 7→3. Dictionaries still write each syllable's own tone, so *tsia̍h-pn̄g* \
+               食飯 is said with tsia̍h changed. Syllables of one word are joined with hyphens. \
+               `/legend` has the full table whenever you need it.",
+        quiz: None,
+    },
+    Step {
+        title: "Asking the bot",
+        text: "Type a word in a lookup channel, or use `/taigi search` anywhere. Mandarin \
+               (吃飯), Taigi Hanji (食飯), Tâi-lô (tsia̍h-pn̄g) and even pinyin all work, and \
+               whole sentences are glossed word by word. On someone's message, Apps → \
+               Explain Taigi words explains its words to you alone.\n\
+               `/taigi search` has options for an accent or a part of speech, \
+               `/taigi advanced` lets you pick the sources, \
+               and `/prefs` sets your defaults, like POJ instead of Tâi-lô.",
+        quiz: None,
+    },
+    Step {
+        title: "Reading the results",
+        text: "Each line is one dictionary's answer, marked 📺 TaigiTV, 📚 Sutian, \
+               🏷️ iTaigi or 📖 Moedict, with the pronunciation in [brackets].\n\
+               Under them, 🔊 plays a recording, 💾 saves the word, ➕ shows more results, \
+               👍/👎 rate the answers and 🛑 reports a wrong one. The `tg#…` ID brings the \
+               same results back later with `/entry`.",
+        quiz: None,
+    },
+    Step {
+        title: "Flashcards and practice",
+        text: "Words saved with 💾 become your flashcards: `/saved list` shows them and \
+               `/saved export` sends them by DM as a deck for Anki, one card per word with \
+               the Mandarin, the Taigi, its Lô-jī and a gloss. `/vocab week` makes a list of \
+               what a channel looked up lately.\n\
+               `/tonedrill` and `/minimal-pairs` train your ear; the tones you miss come up \
+               more often.",
+        quiz: None,
+    },
+    Step {
+        title: "That's it",
+        text: "You're ready to go. Try looking up a word you'd like to say today!",
+        quiz: None,
+    },
+];
+
+// Save where the user is, logging failures: losing a step isn't worth an error
+async fn save(db: &SqlitePool, user_id: UserId, step: usize, finished: bool) {
+    let user_id = user_id.to_string();
+    if let Err(why) = db::save_tutorial_progress(db, &user_id, step, finished).await {
+        error!("Error saving tutorial progress: {why:?}");
+    }
+}
+
+// Where /tutorial starts: the step the user left off at, or the beginning
+// if they finished or `restart` is set
+pub async fn start(db: &SqlitePool, user_id: UserId, restart: bool) -> Reply {
+    let progress = match db::tutorial_progress(db, &user_id.to_string()).await {
+        Ok(progress) => progress,
+        Err(why) => {
+            error!("Error loading tutorial progress: {why:?}");
+            None
+        }
+    };
+    let (step, note) = match progress {
+        Some((step, false)) if !restart && step > 0 => {
+            (step.min(STEPS.len() - 1), Some("↪️ Carrying on where you left off."))
+        }
+        Some((_, true)) if !restart => {
+            (0, Some("🎓 You finished the tutorial before, here it is again."))
+        }
+        _ => (0, None),
+    };
+    save(db, user_id, step, false).await;
+    page(step, note)
+}
+
+// One step's text, with its quiz and the buttons to move on. Custom IDs are
+// `tutorial:step:<n>` and `tutorial:answer:<n>:<tone>`.
+fn page(step: usize, note: Option<&str>) -> Reply {
+    let current = &STEPS[step];
+    let mut content = format!(
+        "📘 **{}** ({}/{})\n{}",
+        current.title,
+        step + 1,
+        STEPS.len(),
+        current.text
+    );
+    let mut components = Vec::new();
+    if let Some(quiz) = &current.quiz {
+        content.push_str(&format!("\n\n❓ Which tone is {} *{}*?", quiz.hanji, quiz.tailo));
+        for tones in [1..=4, 5..=8] {
+            let buttons = tones
+                .map(|tone| {
+                    CreateButton::new(format!("{}answer:{}:{}", PREFIX, step, tone))
+                        .style(ButtonStyle::Secondary)
+                        .label(format!("Tone {}", tone))
+                })
+                .collect();
+            components.push(CreateActionRow::Buttons(buttons));
+        }
+    }
+    if let Some(note) = note {
+        content.push_str("\n\n");
+        content.push_str(note);
+    }
+
+    let last = step + 1 == STEPS.len();
+    let mut navigation = vec![
+        CreateButton::new(format!("{}step:{}", PREFIX, step.saturating_sub(1)))
+            .style(ButtonStyle::Secondary)
+            .label("◀ Back")
+            .disabled(step == 0),
+    ];
+    navigation.push(if last {
+        CreateButton::new(format!("{}step:{}", PREFIX, STEPS.len()))
+            .style(ButtonStyle::Success)
+            .label("Finish")
+    } else {
+        CreateButton::new(format!("{}step:{}", PREFIX, step + 1))
+            .style(ButtonStyle::Primary)
+            .label("Next ▶")
+    });
+    components.push(CreateActionRow::Buttons(navigation));
+
+    Reply {
+        content,
+        components: Some(components),
+        files: Vec::new(),
+    }
+}
+
+async fn click(db: &SqlitePool, component: &ComponentInteraction, rest: &str) -> Reply {
+    let user_id = component.user.id;
+    if let Some(step) = rest.strip_prefix("step:").and_then(|step| step.parse::<usize>().ok()) {
+        if step >= STEPS.len() {
+            save(db, user_id, STEPS.len() - 1, true).await;
+            return Reply {
+                content: "🎓 Tutorial done! `/legend` keeps the romanization at hand, and \
+                          `/tutorial restart:True` goes through it again."
+                    .to_string(),
+                components: Some(Vec::new()),
+                files: Vec::new(),
+            };
+        }
+        save(db, user_id, step, false).await;
+        return page(step, None);
+    }
+
+    let answer = rest.strip_prefix("answer:").and_then(|answer| {
+        let (step, tone) = answer.split_once(':')?;
+        let step = step.parse::<usize>().ok().filter(|step| *step < STEPS.len())?;
+        Some((step, tone.parse::<u8>().ok()?))
+    });
+    let Some((step, tone)) = answer else {
+        return Reply::from("This tutorial step is no longer available.".to_string());
+    };
+    let Some(quiz) = &STEPS[step].quiz else {
+        return page(step, None);
+    };
+    let note = if tone == quiz.tone {
+        format!("✅ Right, {} *{}* is tone {}.", quiz.hanji, quiz.tailo, quiz.tone)
+    } else {
+        format!("❌ Not quite: {}, so *{}* is tone {}.", quiz.hint, quiz.tailo, quiz.tone)
+    };
+    page(step, Some(&note))
+}
+
+// Handle the tutorial's buttons. Returns false for other components.
+pub async fn handle(ctx: &Context, db: &SqlitePool, component: &ComponentInteraction) -> bool {
+    let Some(rest) = component.data.custom_id.strip_prefix(PREFIX) else {
+        return false;
+    };
+    respond::component(ctx, db, component, Mode::Update, click(db, component, rest)).await;
+    true
+}
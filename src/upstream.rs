@@ -0,0 +1,171 @@
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use encoding_rs::{Encoding, UTF_8};
+use reqwest::header::{ACCEPT_LANGUAGE, CONTENT_TYPE, HeaderMap, HeaderValue, USER_AGENT};
+use reqwest::redirect::Policy;
+use tracing::warn;
+
+// Who is asking, so site operators can tell the bot apart and reach its
+// maintainers instead of blocking it
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    "taigi-translate-bot/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/MagicTeaMC/taigi-translate-bot)"
+);
+// Some sources answer in Simplified Chinese or English otherwise
+pub const DEFAULT_ACCEPT_LANGUAGE: &str = "zh-TW,zh-Hant;q=0.9,zh;q=0.8,en;q=0.5";
+
+// Larger responses are given up on rather than read into memory; no
+// dictionary page or recording comes close
+pub const DEFAULT_MAX_BODY: usize = 2 * 1024 * 1024;
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static PLUGIN_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static MAX_BODY: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_BODY);
+
+// The headers sent with every request, or why a value can't be sent
+pub fn headers(user_agent: &str, accept_language: &str) -> Result<HeaderMap, String> {
+    let value = |name: &str, value: &str| {
+        HeaderValue::from_str(value.trim())
+            .map_err(|_| format!("{}: {:?} can't be sent as a header", name, value))
+    };
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, value("upstream.user_agent", user_agent)?);
+    headers.insert(
+        ACCEPT_LANGUAGE,
+        value("upstream.accept_language", accept_language)?,
+    );
+    Ok(headers)
+}
+
+// Compressed responses (gzip, Brotli) are asked for and unpacked by reqwest
+fn build(headers: HeaderMap, redirect: Policy) -> reqwest::Client {
+    reqwest::Client::builder()
+        .default_headers(headers)
+        .gzip(true)
+        .brotli(true)
+        .redirect(redirect)
+        .build()
+        .unwrap_or_default()
+}
+
+// Set up the client with the configured headers and body size limit, once
+// at startup
+pub fn init(headers: HeaderMap, max_body: usize) {
+    let already = PLUGIN_CLIENT.set(build(headers.clone(), Policy::none())).is_err();
+    if CLIENT.set(build(headers, Policy::default())).is_err() || already {
+        warn!("The HTTP client was already set up, keeping its headers");
+    }
+    MAX_BODY.store(max_body, Ordering::Relaxed);
+}
+
+// The client every request to a source goes through, sharing connections
+pub fn client() -> &'static reqwest::Client {
+    CLIENT.get_or_init(|| {
+        let headers = headers(DEFAULT_USER_AGENT, DEFAULT_ACCEPT_LANGUAGE).unwrap_or_default();
+        build(headers, Policy::default())
+    })
+}
+
+// The client plugins' pages are fetched with. It doesn't follow redirects,
+// which could lead off the hosts a plugin is allowed.
+pub fn plugin_client() -> &'static reqwest::Client {
+    PLUGIN_CLIENT.get_or_init(|| {
+        let headers = headers(DEFAULT_USER_AGENT, DEFAULT_ACCEPT_LANGUAGE).unwrap_or_default();
+        build(headers, Policy::none())
+    })
+}
+
+// How far into a page a <meta charset> is looked for, as browsers do
+const SNIFF_BYTES: usize = 1024;
+
+// The `charset=` label in a Content-Type value or the start of a page
+fn charset_label(text: &str) -> Option<&str> {
+    let start = text.to_ascii_lowercase().find("charset=")? + "charset=".len();
+    let label = text[start..].trim_start_matches(['"', '\'']);
+    let end = label
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        .unwrap_or(label.len());
+    Some(&label[..end]).filter(|label| !label.is_empty())
+}
+
+// The body of a response, read chunk by chunk and abandoned as soon as it
+// grows past the size limit, so a huge or endless response can't exhaust
+// memory. A Content-Length over the limit is refused before reading.
+pub async fn bytes(mut response: reqwest::Response) -> Result<Vec<u8>, String> {
+    let max = MAX_BODY.load(Ordering::Relaxed);
+    let too_large = |response: &reqwest::Response| {
+        warn!("Gave up on {}: the response is over {} bytes", response.url(), max);
+        format!("response over {} bytes", max)
+    };
+    if response.content_length().is_some_and(|length| length > max as u64) {
+        return Err(too_large(&response));
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|why| why.to_string())? {
+        if body.len() + chunk.len() > max {
+            return Err(too_large(&response));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+// The body of a response as text. Like `Response::text`, but pages that
+// only declare their encoding in a <meta> tag (Big5 ones, mostly) are
+// decoded with it instead of being read as UTF-8 mojibake. A byte order
+// mark wins over both.
+pub async fn text(response: reqwest::Response) -> Result<String, String> {
+    let header = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(charset_label)
+        .map(str::to_string);
+    let bytes = bytes(response).await?;
+    Ok(decode(&bytes, header.as_deref()))
+}
+
+// A body decoded with the charset of its Content-Type `header`, or else of
+// its <meta> tag, or else as UTF-8
+fn decode(bytes: &[u8], header: Option<&str>) -> String {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(SNIFF_BYTES)]);
+    let encoding = header
+        .or_else(|| charset_label(&head))
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .unwrap_or(UTF_8);
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::BIG5;
+
+    #[test]
+    fn charset_label_reads_headers_and_meta_tags() {
+        assert_eq!(charset_label("text/html; charset=Big5"), Some("Big5"));
+        assert_eq!(charset_label("<meta charset=\"utf-8\">"), Some("utf-8"));
+        assert_eq!(charset_label("text/html; CHARSET='big5-hkscs'"), Some("big5-hkscs"));
+        assert_eq!(charset_label("text/html"), None);
+        assert_eq!(charset_label("text/html; charset="), None);
+    }
+
+    #[test]
+    fn decode_follows_the_declared_charset() {
+        let page = "<html><head><meta charset=\"big5\"></head><body>食飯</body></html>";
+        let (big5, _, _) = BIG5.encode(page);
+        assert!(decode(&big5, None).contains("食飯"));
+        assert!(decode(&big5, Some("big5")).contains("食飯"));
+
+        // The header wins over the page, and a byte order mark over both
+        let (mislabeled, _, _) = BIG5.encode("<meta charset=\"utf-8\">食飯");
+        assert!(decode(&mislabeled, Some("big5")).ends_with("食飯"));
+        assert_eq!(decode("食飯".as_bytes(), Some("no-such-charset")), "食飯");
+        let bom = [&[0xEF, 0xBB, 0xBF][..], "食飯".as_bytes()].concat();
+        assert_eq!(decode(&bom, Some("big5")), "食飯");
+    }
+}
@@ -0,0 +1,133 @@
+use crate::reading::Kind;
+
+// Regional accents distinguished by Sutian's pronunciation notes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Accent {
+    Tsiang, // 漳州
+    Tsuan,  // 泉州
+    E,      // 廈門
+}
+
+impl Accent {
+    // Accepts the Han character, the city name or common romanizations
+    pub fn parse(value: &str) -> Option<Accent> {
+        match value.trim().to_lowercase().as_str() {
+            "漳" | "漳州" | "漳腔" | "tsiang" | "chiang" | "zhang" | "zhangzhou" => {
+                Some(Accent::Tsiang)
+            }
+            "泉" | "泉州" | "泉腔" | "tsuan" | "choan" | "quan" | "quanzhou" => {
+                Some(Accent::Tsuan)
+            }
+            "廈" | "廈門" | "厦门" | "e" | "ē" | "amoy" | "xiamen" => Some(Accent::E),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Accent::Tsiang => "漳州",
+            Accent::Tsuan => "泉州",
+            Accent::E => "廈門",
+        }
+    }
+
+    // Find an accent marker (偏漳, 泉腔, ...) inside a note
+    fn from_note(note: &str) -> Option<Accent> {
+        if note.contains('漳') {
+            Some(Accent::Tsiang)
+        } else if note.contains('泉') {
+            Some(Accent::Tsuan)
+        } else if note.contains('廈') {
+            Some(Accent::E)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Reading {
+    pub text: String,
+    // Sutian's own wording, e.g. 又唸作 / 俗唸作 / 文讀, or Moedict's mark
+    // (文, 白, 替…)
+    pub label: Option<String>,
+    pub accent: Option<Accent>,
+}
+
+impl Reading {
+    pub fn display(&self) -> String {
+        match self.accent {
+            Some(accent) => format!("{}（{}）", self.text, accent.label()),
+            None => self.text.clone(),
+        }
+    }
+
+    // Whether it's a literary or colloquial reading
+    pub fn kind(&self) -> Option<Kind> {
+        self.label.as_deref().and_then(Kind::from_mark)
+    }
+
+    // The label with a kind spelled out, e.g. 文讀 for Moedict's 文
+    pub fn mark(&self) -> Option<String> {
+        match self.kind() {
+            Some(kind) => Some(kind.label().to_string()),
+            None => self.label.clone(),
+        }
+    }
+}
+
+const READING_LABELS: [&str; 7] =
+    ["合音唸作", "又唸作", "俗唸作", "又音", "俗音", "文讀", "白讀"];
+
+// Split the text of a Sutian 音讀 cell into its readings. The first reading
+// without a label is the main one; the rest carry the label that introduced
+// them. Parenthesised accent notes describe the reading before them, bare
+// ones the reading after them.
+pub fn parse_readings(cell_text: &str) -> Vec<Reading> {
+    let mut normalized = cell_text.to_string();
+    for (from, to) in [("（", " ("), ("）", ") "), ("：", " "), (":", " "), ("/", " "), ("、", " ")] {
+        normalized = normalized.replace(from, to);
+    }
+    for label in READING_LABELS {
+        normalized = normalized.replace(label, &format!(" {} ", label));
+    }
+
+    let mut readings: Vec<Reading> = Vec::new();
+    let mut pending_label: Option<String> = None;
+    let mut pending_accent: Option<Accent> = None;
+
+    for token in normalized.split_whitespace() {
+        if READING_LABELS.contains(&token) {
+            pending_label = Some(token.to_string());
+            continue;
+        }
+
+        let is_romanized = token.chars().any(|c| c.is_ascii_alphabetic());
+        if is_romanized {
+            readings.push(Reading {
+                text: token.trim_matches(|c| c == '(' || c == ')').to_string(),
+                label: pending_label.take(),
+                accent: pending_accent.take(),
+            });
+            continue;
+        }
+
+        let Some(accent) = Accent::from_note(token) else {
+            continue;
+        };
+        let describes_previous = token.starts_with('(');
+        match readings.last_mut() {
+            Some(last) if describes_previous && last.accent.is_none() => last.accent = Some(accent),
+            _ => pending_accent = Some(accent),
+        }
+    }
+
+    readings
+}
+
+// Move the reading matching the preferred accent to the front, if any
+pub fn prefer(readings: &mut [Reading], accent: Accent) {
+    if let Some(idx) = readings.iter().position(|r| r.accent == Some(accent)) {
+        readings[..=idx].rotate_right(1);
+    }
+}
@@ -0,0 +1,88 @@
+use serde_json::{Value, json};
+
+use crate::breakdown;
+use crate::lookup::Lookup;
+
+// Queries longer than this aren't words, and would only cost lookups
+pub const MAX_QUERY: usize = 50;
+
+// A formatted result split into plain fields
+pub struct Fields<'a> {
+    pub hanji: Option<&'a str>,
+    pub lomaji: Option<&'a str>,
+    // The whole entry, without the source's emoji and link
+    pub text: &'a str,
+    pub url: Option<&'a str>,
+}
+
+// `📚 食飯 [tsia̍h-pn̄g] 吃飯 - [Sutian](url)` becomes its text without the
+// emoji and link, the link, and the headword
+pub fn fields(line: &str) -> Fields<'_> {
+    let (entry, link) = line.rsplit_once(" - [").unwrap_or((line, ""));
+    let headword = breakdown::headword(line);
+    Fields {
+        hanji: headword.map(|(hanji, _)| hanji),
+        lomaji: headword.and_then(|(_, lomaji)| lomaji),
+        text: entry.split_once(' ').map_or(entry, |(_, text)| text),
+        url: link
+            .split_once("](")
+            .map(|(_, url)| url.trim_end_matches(')').trim_matches(['<', '>'])),
+    }
+}
+
+// One result as the widget shows it
+pub fn result(line: &str, source: &str) -> Value {
+    let fields = fields(line);
+    json!({
+        "source": source,
+        "hanji": fields.hanji,
+        "lomaji": fields.lomaji,
+        "text": fields.text,
+        "url": fields.url,
+    })
+}
+
+// The widget's answer to `query`, described by SCHEMA
+pub fn response(query: &str, lookup: &Lookup) -> Value {
+    let results: Vec<Value> = lookup
+        .results
+        .iter()
+        .zip(&lookup.sources)
+        .map(|(line, source)| result(line, source.name()))
+        .collect();
+    json!({
+        "query": query,
+        "complete": lookup.is_complete(),
+        "results": results,
+    })
+}
+
+// JSON Schema of the widget's answers, served at /widget/schema.json
+pub const SCHEMA: &str = r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "Taigi lookup",
+  "type": "object",
+  "required": ["query", "complete", "results"],
+  "properties": {
+    "query": { "type": "string", "description": "The query as searched" },
+    "complete": {
+      "type": "boolean",
+      "description": "False when a dictionary failed, so results may be missing"
+    },
+    "results": {
+      "type": "array",
+      "items": {
+        "type": "object",
+        "required": ["source", "text"],
+        "properties": {
+          "source": { "type": "string", "description": "Dictionary the result is from" },
+          "hanji": { "type": ["string", "null"], "description": "Taigi headword" },
+          "lomaji": { "type": ["string", "null"], "description": "Its Tâi-lô reading" },
+          "text": { "type": "string", "description": "The whole entry as one line" },
+          "url": { "type": ["string", "null"], "description": "The entry in the dictionary" }
+        }
+      }
+    }
+  }
+}
+"#;